@@ -4,7 +4,10 @@
 use aptos_api_types::{Error, LedgerInfo, TransactionOnChainData};
 use aptos_config::config::ApiConfig;
 use aptos_crypto::HashValue;
-use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
+use aptos_mempool::{
+    MempoolClientRequest, MempoolClientSender, MempoolTransactionInfo, PeerBroadcastState,
+    SubmissionStatus,
+};
 use aptos_types::{
     account_address::AccountAddress,
     account_state::AccountState,
@@ -208,6 +211,52 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    pub async fn get_mempool_transaction_info(
+        &self,
+        hash: HashValue,
+    ) -> Result<Option<MempoolTransactionInfo>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetTransactionInfoByHash(
+                hash, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_mempool_account_transactions_info(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<MempoolTransactionInfo>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetAccountTransactionsInfo(
+                address, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    pub async fn get_mempool_peer_broadcast_states(&self) -> Result<Vec<PeerBroadcastState>> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetPeerBroadcastStates(req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
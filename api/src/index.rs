@@ -6,7 +6,7 @@ use crate::{
     context::Context,
     events,
     failpoint::fail_point,
-    log,
+    log, mempool,
     metrics::{metrics, status_metrics},
     state, transactions,
 };
@@ -42,6 +42,9 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
         .or(state::get_account_resource(context.clone()))
         .or(state::get_account_module(context.clone()))
         .or(state::get_table_item(context.clone()))
+        .or(mempool::get_mempool_account_transactions(context.clone()))
+        .or(mempool::get_mempool_transaction(context.clone()))
+        .or(mempool::get_mempool_peers(context.clone()))
         .or(context.health_check_route().with(metrics("health_check")))
         .with(
             warp::cors()
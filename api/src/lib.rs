@@ -7,6 +7,7 @@ mod events;
 mod health_check;
 mod index;
 pub(crate) mod log;
+mod mempool;
 mod metrics;
 mod page;
 pub mod param;
@@ -0,0 +1,146 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{AddressParam, HashValueParam},
+};
+
+use aptos_api_types::{
+    Error, LedgerInfo, MempoolPeerBroadcastState, MempoolTransaction, MempoolTransactionStatus,
+    Response,
+};
+use aptos_mempool::{MempoolTransactionInfo, PeerBroadcastState};
+
+use anyhow::Result;
+use warp::{filters::BoxedFilter, Filter, Rejection, Reply};
+
+// GET /mempool/accounts/<address>/transactions
+pub fn get_mempool_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "accounts" / AddressParam / "transactions")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_mempool_account_transactions)
+        .with(metrics("get_mempool_account_transactions"))
+        .boxed()
+}
+
+// GET /mempool/transactions/<txn_hash>
+pub fn get_mempool_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "transactions" / HashValueParam)
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_mempool_transaction)
+        .with(metrics("get_mempool_transaction"))
+        .boxed()
+}
+
+// GET /mempool/peers
+pub fn get_mempool_peers(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("mempool" / "peers")
+        .and(warp::get())
+        .and(context.filter())
+        .and_then(handle_get_mempool_peers)
+        .with(metrics("get_mempool_peers"))
+        .boxed()
+}
+
+async fn handle_get_mempool_account_transactions(
+    address: AddressParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_account_transactions")?;
+    Ok(Mempool::new(context)?.list_by_account(address).await?)
+}
+
+async fn handle_get_mempool_transaction(
+    hash: HashValueParam,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_transaction")?;
+    Ok(Mempool::new(context)?.get_transaction(hash).await?)
+}
+
+async fn handle_get_mempool_peers(context: Context) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_get_mempool_peers")?;
+    Ok(Mempool::new(context)?.list_peer_broadcast_states().await?)
+}
+
+struct Mempool {
+    ledger_info: LedgerInfo,
+    context: Context,
+}
+
+impl Mempool {
+    fn new(context: Context) -> Result<Self, Error> {
+        let ledger_info = context.get_latest_ledger_info()?;
+        Ok(Self {
+            ledger_info,
+            context,
+        })
+    }
+
+    pub async fn list_by_account(self, address: AddressParam) -> Result<impl Reply, Error> {
+        let txns: Vec<MempoolTransaction> = self
+            .context
+            .get_mempool_account_transactions_info(address.parse("account address")?.into())
+            .await?
+            .into_iter()
+            .map(render_transaction)
+            .collect();
+        Response::new(self.ledger_info, &txns)
+    }
+
+    pub async fn get_transaction(self, hash: HashValueParam) -> Result<impl Reply, Error> {
+        let hash = hash.parse("transaction hash")?;
+        let txn = self
+            .context
+            .get_mempool_transaction_info(hash.into())
+            .await?
+            .map(render_transaction)
+            .ok_or_else(|| {
+                Error::not_found("mempool transaction", hash, self.ledger_info.version())
+            })?;
+        Response::new(self.ledger_info, &txn)
+    }
+
+    pub async fn list_peer_broadcast_states(self) -> Result<impl Reply, Error> {
+        let peers: Vec<MempoolPeerBroadcastState> = self
+            .context
+            .get_mempool_peer_broadcast_states()
+            .await?
+            .into_iter()
+            .map(render_peer_broadcast_state)
+            .collect();
+        Response::new(self.ledger_info, &peers)
+    }
+}
+
+fn render_transaction(info: MempoolTransactionInfo) -> MempoolTransaction {
+    let status = match info.status {
+        aptos_mempool::MempoolTransactionStatus::Ready => MempoolTransactionStatus::Ready,
+        aptos_mempool::MempoolTransactionStatus::Parked => MempoolTransactionStatus::Parked,
+    };
+    MempoolTransaction {
+        hash: info.hash.into(),
+        sender: info.sender.into(),
+        sequence_number: info.sequence_number.into(),
+        max_gas_amount: info.max_gas_amount.into(),
+        gas_unit_price: info.gas_unit_price.into(),
+        expiration_timestamp_secs: info.expiration_timestamp_secs.into(),
+        status,
+    }
+}
+
+fn render_peer_broadcast_state(state: PeerBroadcastState) -> MempoolPeerBroadcastState {
+    MempoolPeerBroadcastState {
+        network: state.peer.network_id().to_string(),
+        peer_id: state.peer.peer_id().into(),
+        timeline_id: state.timeline_id.into(),
+        backoff_mode: state.backoff_mode,
+        pending_acks: (state.pending_acks as u64).into(),
+        pending_retries: (state.pending_retries as u64).into(),
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{current_function_name, tests::new_test_context};
+
+#[tokio::test]
+async fn test_get_mempool_transaction_by_hash() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let body = bcs::to_bytes(&txn).unwrap();
+    let pending_txn = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", body)
+        .await;
+
+    let txn_hash = pending_txn["hash"].as_str().unwrap();
+    let resp = context
+        .get(&format!("/mempool/transactions/{}", txn_hash))
+        .await;
+    assert_eq!(resp["hash"], pending_txn["hash"]);
+    assert_eq!(resp["sender"], pending_txn["sender"]);
+    assert_eq!(resp["sequence_number"], pending_txn["sequence_number"]);
+    assert_eq!(resp["status"], "ready");
+
+    context
+        .expect_status_code(404)
+        .get("/mempool/transactions/0xdadfeddcca7cb6396c735e9094c76c6e4e9cb3e3ef814730693aed59bd87b31d")
+        .await;
+}
+
+#[tokio::test]
+async fn test_get_mempool_account_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let body = bcs::to_bytes(&txn).unwrap();
+    let pending_txn = context
+        .expect_status_code(202)
+        .post_bcs_txn("/transactions", body)
+        .await;
+
+    let sender = pending_txn["sender"].as_str().unwrap();
+    let resp = context
+        .get(&format!("/mempool/accounts/{}/transactions", sender))
+        .await;
+    let txns = resp.as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(txns[0]["hash"], pending_txn["hash"]);
+
+    let resp = context
+        .get(&format!(
+            "/mempool/accounts/{}/transactions",
+            account.address()
+        ))
+        .await;
+    assert!(resp.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_mempool_peers() {
+    let context = new_test_context(current_function_name!());
+
+    // The test node has no upstream peers to broadcast to
+    let resp = context.get("/mempool/peers").await;
+    assert!(resp.as_array().unwrap().is_empty());
+}
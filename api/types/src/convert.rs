@@ -99,6 +99,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                 (info, payload, events).into()
             }
             BlockMetadata(txn) => (&txn, info).into(),
+            BlockMetadataV2(txn) => (txn.metadata(), info).into(),
             StateCheckpoint => {
                 Transaction::StateCheckpointTransaction(StateCheckpointTransaction {
                     info,
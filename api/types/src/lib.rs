@@ -9,6 +9,7 @@ mod error;
 mod event_key;
 mod hash;
 mod ledger_info;
+mod mempool;
 pub mod mime_types;
 mod move_types;
 mod response;
@@ -23,6 +24,7 @@ pub use error::Error;
 pub use event_key::EventKey;
 pub use hash::HashValue;
 pub use ledger_info::LedgerInfo;
+pub use mempool::{MempoolPeerBroadcastState, MempoolTransaction, MempoolTransactionStatus};
 pub use move_types::{
     HexEncodedBytes, MoveFunction, MoveModule, MoveModuleBytecode, MoveModuleId, MoveResource,
     MoveScriptBytecode, MoveStructTag, MoveStructValue, MoveType, MoveValue, ScriptFunctionId,
@@ -0,0 +1,37 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, HashValue, U64};
+
+use serde::{Deserialize, Serialize};
+
+/// Whether a transaction in mempool can be included in the next block, or is waiting for a
+/// transaction with a lower sequence number from the same account.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolTransactionStatus {
+    Ready,
+    Parked,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolTransaction {
+    pub hash: HashValue,
+    pub sender: Address,
+    pub sequence_number: U64,
+    pub max_gas_amount: U64,
+    pub gas_unit_price: U64,
+    pub expiration_timestamp_secs: U64,
+    pub status: MempoolTransactionStatus,
+}
+
+/// The progress of broadcasting this node's mempool transactions to an upstream peer.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MempoolPeerBroadcastState {
+    pub network: String,
+    pub peer_id: Address,
+    pub timeline_id: U64,
+    pub backoff_mode: bool,
+    pub pending_acks: U64,
+    pub pending_retries: U64,
+}
@@ -26,7 +26,7 @@ use aptos_logger::prelude::*;
 use aptos_state_view::StateView;
 use aptos_types::{
     account_config,
-    block_metadata::BlockMetadata,
+    block_metadata::{BlockMetadata, BlockMetadataV2},
     on_chain_config::{VMConfig, VMPublishingOption, Version},
     transaction::{
         ChangeSet, ExecutionStatus, ModuleBundle, SignatureCheckedTransaction, SignedTransaction,
@@ -602,6 +602,33 @@ impl AptosVM {
         storage: &S,
         block_metadata: BlockMetadata,
         log_context: &AdapterLogSchema,
+    ) -> Result<(VMStatus, TransactionOutput), VMStatus> {
+        self.run_block_prologue(storage, block_metadata, None, log_context)
+    }
+
+    pub(crate) fn process_block_prologue_v2<S: MoveResolverExt>(
+        &self,
+        storage: &S,
+        block_metadata: BlockMetadataV2,
+        log_context: &AdapterLogSchema,
+    ) -> Result<(VMStatus, TransactionOutput), VMStatus> {
+        let (block_metadata, failed_proposer_indices) = block_metadata.into_inner();
+        self.run_block_prologue(
+            storage,
+            block_metadata,
+            Some(failed_proposer_indices),
+            log_context,
+        )
+    }
+
+    /// Runs `block_prologue`, or `block_prologue_v2` if the block records the indices of the
+    /// proposers that failed since the previous block.
+    fn run_block_prologue<S: MoveResolverExt>(
+        &self,
+        storage: &S,
+        block_metadata: BlockMetadata,
+        failed_proposer_indices: Option<Vec<u32>>,
+        log_context: &AdapterLogSchema,
     ) -> Result<(VMStatus, TransactionOutput), VMStatus> {
         fail_point!("move_adapter::process_block_prologue", |_| {
             Err(VMStatus::Error(
@@ -634,26 +661,38 @@ impl AptosVM {
             })
             .collect::<Vec<_>>();
 
-        let args = serialize_values(&vec![
+        let mut args = vec![
             MoveValue::Signer(txn_data.sender),
             MoveValue::U64(epoch),
             MoveValue::U64(round),
             MoveValue::Vector(previous_vote.into_iter().map(MoveValue::Bool).collect()),
             MoveValue::Vector(missed_votes.into_iter().map(MoveValue::U64).collect()),
             MoveValue::Address(proposer),
-            MoveValue::U64(timestamp),
-        ]);
+        ];
+        let function_name = match failed_proposer_indices {
+            Some(failed_proposer_indices) => {
+                args.push(MoveValue::Vector(
+                    failed_proposer_indices
+                        .into_iter()
+                        .map(|index| MoveValue::U64(index as u64))
+                        .collect(),
+                ));
+                BLOCK_PROLOGUE_V2
+            }
+            None => BLOCK_PROLOGUE,
+        };
+        args.push(MoveValue::U64(timestamp));
         session
             .execute_function_bypass_visibility(
                 &BLOCK_MODULE,
-                BLOCK_PROLOGUE,
+                function_name,
                 vec![],
-                args,
+                serialize_values(&args),
                 &mut gas_status,
             )
             .map(|_return_vals| ())
             .or_else(|e| {
-                expect_only_successful_execution(e, BLOCK_PROLOGUE.as_str(), log_context)
+                expect_only_successful_execution(e, function_name.as_str(), log_context)
             })?;
         SYSTEM_TRANSACTIONS_EXECUTED.inc();
 
@@ -947,6 +986,14 @@ impl VMAdapter for AptosVM {
                     self.process_block_prologue(data_cache, block_metadata.clone(), log_context)?;
                 (vm_status, output, Some("block_prologue".to_string()))
             }
+            PreprocessedTransaction::BlockMetadataV2(block_metadata) => {
+                let (vm_status, output) = self.process_block_prologue_v2(
+                    data_cache,
+                    block_metadata.clone(),
+                    log_context,
+                )?;
+                (vm_status, output, Some("block_prologue_v2".to_string()))
+            }
             PreprocessedTransaction::WaypointWriteSet(write_set_payload) => {
                 let (vm_status, output) =
                     self.process_waypoint_change_set(data_cache, write_set_payload.clone())?;
@@ -5,11 +5,14 @@ use crate::{
     adapter_common::PreprocessedTransaction,
     move_vm_ext::MoveResolverExt,
     script_to_script_function::remapping,
-    system_module_names::{BLOCK_MODULE, BLOCK_PROLOGUE, SCRIPT_PROLOGUE_NAME, USER_EPILOGUE_NAME},
+    system_module_names::{
+        BLOCK_MODULE, BLOCK_PROLOGUE, BLOCK_PROLOGUE_V2, SCRIPT_PROLOGUE_NAME, USER_EPILOGUE_NAME,
+    },
 };
 use anyhow::{anyhow, bail, Result};
 use aptos_types::{
     account_config,
+    block_metadata::BlockMetadata,
     transaction::{SignedTransaction, TransactionPayload},
 };
 use move_deps::{
@@ -37,6 +40,7 @@ const TRANSACTION_FEES_NAME: &IdentStr = ident_str!("TransactionFee");
 pub fn add_on_functions_list() -> Vec<(ModuleId, Identifier)> {
     vec![
         (BLOCK_MODULE.clone(), BLOCK_PROLOGUE.to_owned()),
+        (BLOCK_MODULE.clone(), BLOCK_PROLOGUE_V2.to_owned()),
         (
             account_config::constants::APTOS_ACCOUNT_MODULE.clone(),
             SCRIPT_PROLOGUE_NAME.to_owned(),
@@ -177,25 +181,15 @@ impl<'a, R: MoveResolverExt> ReadWriteSetAnalysis<'a, R> {
                 self.get_keys_user_transaction_impl(tx, concretize)
             }
             PreprocessedTransaction::BlockMetadata(block_metadata) => {
-                let (epoch, round, timestamp, previous_vote, proposer) =
-                    block_metadata.clone().into_inner();
-                let args = serialize_values(&vec![
-                    MoveValue::Signer(account_config::reserved_vm_address()),
-                    MoveValue::U64(epoch),
-                    MoveValue::U64(round),
-                    MoveValue::Vector(previous_vote.into_iter().map(MoveValue::Bool).collect()),
-                    MoveValue::Address(proposer),
-                    MoveValue::U64(timestamp),
-                ]);
-                let metadata_access = self.get_partially_concretized_summary(
-                    &BLOCK_MODULE,
-                    BLOCK_PROLOGUE,
-                    &[],
-                    &args,
-                    &[],
-                    &self.module_cache,
-                )?;
-                self.concretize_secondary_indexes(metadata_access, concretize)
+                self.get_keys_block_prologue(block_metadata.clone(), None, concretize)
+            }
+            PreprocessedTransaction::BlockMetadataV2(block_metadata) => {
+                let (block_metadata, failed_proposer_indices) = block_metadata.clone().into_inner();
+                self.get_keys_block_prologue(
+                    block_metadata,
+                    Some(failed_proposer_indices),
+                    concretize,
+                )
             }
             PreprocessedTransaction::InvalidSignature => Ok((vec![], vec![])),
             PreprocessedTransaction::WriteSet(_) | PreprocessedTransaction::WaypointWriteSet(_) => {
@@ -205,6 +199,51 @@ impl<'a, R: MoveResolverExt> ReadWriteSetAnalysis<'a, R> {
         }
     }
 
+    fn get_keys_block_prologue(
+        &self,
+        block_metadata: BlockMetadata,
+        failed_proposer_indices: Option<Vec<u32>>,
+        concretize: bool,
+    ) -> Result<(Vec<ResourceKey>, Vec<ResourceKey>)> {
+        let (epoch, round, timestamp, previous_vote, proposer) = block_metadata.into_inner();
+        let missed_votes = previous_vote
+            .iter()
+            .enumerate()
+            .filter(|(_, &validator_voted)| !validator_voted)
+            .map(|(idx, _)| MoveValue::U64(idx as u64))
+            .collect();
+        let mut args = vec![
+            MoveValue::Signer(account_config::reserved_vm_address()),
+            MoveValue::U64(epoch),
+            MoveValue::U64(round),
+            MoveValue::Vector(previous_vote.into_iter().map(MoveValue::Bool).collect()),
+            MoveValue::Vector(missed_votes),
+            MoveValue::Address(proposer),
+        ];
+        let function_name = match failed_proposer_indices {
+            Some(failed_proposer_indices) => {
+                args.push(MoveValue::Vector(
+                    failed_proposer_indices
+                        .into_iter()
+                        .map(|index| MoveValue::U64(index as u64))
+                        .collect(),
+                ));
+                BLOCK_PROLOGUE_V2
+            }
+            None => BLOCK_PROLOGUE,
+        };
+        args.push(MoveValue::U64(timestamp));
+        let metadata_access = self.get_partially_concretized_summary(
+            &BLOCK_MODULE,
+            function_name,
+            &[],
+            &serialize_values(&args),
+            &[],
+            &self.module_cache,
+        )?;
+        self.concretize_secondary_indexes(metadata_access, concretize)
+    }
+
     fn get_concretized_keys_script_function(
         &self,
         tx: &SignedTransaction,
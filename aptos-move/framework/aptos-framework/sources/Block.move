@@ -25,6 +25,25 @@ module AptosFramework::Block {
         time_microseconds: u64,
     }
 
+    /// Holds the handle where `NewBlockEventV2`s are emitted
+    struct NewBlockEventsV2 has key {
+        new_block_events: Event::EventHandle<Self::NewBlockEventV2>,
+    }
+
+    /// A `NewBlockEvent` that also records the proposers of the rounds that failed since the
+    /// previous block. Emitted by `block_prologue_v2`, in addition to the `NewBlockEvent`.
+    struct NewBlockEventV2 has drop, store {
+        epoch: u64,
+        round: u64,
+        previous_block_votes: vector<bool>,
+        proposer: address,
+        /// On-chain time during the block at the given height
+        time_microseconds: u64,
+        /// Indices (in the validator set) of the proposers of the rounds that failed
+        /// since the previous block
+        failed_proposer_indices: vector<u64>,
+    }
+
     /// The `BlockMetadata` resource is in an invalid state
     const EBLOCK_METADATA: u64 = 0;
     /// An invalid signer was provided. Expected the signer to be the VM or a Validator.
@@ -46,6 +65,26 @@ module AptosFramework::Block {
                 new_block_events: Event::new_event_handle<Self::NewBlockEvent>(account),
             }
         );
+        publish_new_block_events_v2(account);
+    }
+
+    /// Publishes the `NewBlockEventsV2` handle on chains whose genesis predates it. This must be
+    /// called by the Association before the on-chain consensus config is switched to record
+    /// failed proposers, otherwise no `NewBlockEventV2`s are emitted.
+    public(script) fun initialize_new_block_events_v2(account: &signer) {
+        SystemAddresses::assert_core_resource(account);
+        assert!(is_initialized(), Errors::not_published(EBLOCK_METADATA));
+        assert!(!exists<NewBlockEventsV2>(@CoreResources), Errors::already_published(EBLOCK_METADATA));
+        publish_new_block_events_v2(account);
+    }
+
+    fun publish_new_block_events_v2(account: &signer) {
+        move_to<NewBlockEventsV2>(
+            account,
+            NewBlockEventsV2 {
+                new_block_events: Event::new_event_handle<Self::NewBlockEventV2>(account),
+            }
+        );
     }
 
     /// Helper function to determine whether this module has been initialized.
@@ -96,9 +135,53 @@ module AptosFramework::Block {
         Stake::update_performance_statistics(missed_votes);
     }
 
+    /// Same as `block_prologue`, but also records the proposers of the rounds that failed since
+    /// the previous block. The runtime runs this instead of `block_prologue` for blocks whose
+    /// proposal records failed proposers. The `NewBlockEventV2` is only emitted once the handle
+    /// has been published (see `initialize_new_block_events_v2`), so that switching an existing
+    /// chain to record failed proposers cannot halt it.
+    fun block_prologue_v2(
+        vm: signer,
+        epoch: u64,
+        round: u64,
+        previous_block_votes: vector<bool>,
+        missed_votes: vector<u64>,
+        proposer: address,
+        failed_proposer_indices: vector<u64>,
+        timestamp: u64
+    ) acquires BlockMetadata, NewBlockEventsV2 {
+        if (exists<NewBlockEventsV2>(@CoreResources)) {
+            Event::emit_event<NewBlockEventV2>(
+                &mut borrow_global_mut<NewBlockEventsV2>(@CoreResources).new_block_events,
+                NewBlockEventV2 {
+                    epoch,
+                    round,
+                    previous_block_votes: copy previous_block_votes,
+                    proposer,
+                    time_microseconds: timestamp,
+                    failed_proposer_indices,
+                }
+            );
+        };
+        block_prologue(vm, epoch, round, previous_block_votes, missed_votes, proposer, timestamp);
+    }
+
     /// Get the current block height
     public fun get_current_block_height(): u64 acquires BlockMetadata {
         assert!(is_initialized(), Errors::not_published(EBLOCK_METADATA));
         borrow_global<BlockMetadata>(@CoreResources).height
     }
+
+    #[test(account = @CoreResources)]
+    #[expected_failure(abort_code = 6)]
+    public(script) fun fail_initialize_new_block_events_v2_twice(account: signer) {
+        initialize_block_metadata(&account, 1);
+        initialize_new_block_events_v2(&account);
+    }
+
+    #[test(account = @0x123)]
+    #[expected_failure(abort_code = 2)]
+    public(script) fun fail_initialize_new_block_events_v2_not_core_resource(account: signer) {
+        initialize_new_block_events_v2(&account);
+    }
 }
@@ -13,6 +13,7 @@ use aptos_config::{
 use aptos_data_client::aptosnet::AptosNetDataClient;
 use aptos_infallible::RwLock;
 use aptos_logger::{prelude::*, Logger};
+use aptos_mempool::MempoolClientSender;
 use aptos_metrics::metric_server;
 use aptos_state_view::account_with_state_view::AsAccountWithStateView;
 use aptos_time_service::TimeService;
@@ -221,7 +222,12 @@ fn fetch_chain_id(db: &DbReaderWriter) -> ChainId {
         .chain_id()
 }
 
-fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> NodeDebugService {
+fn setup_debug_interface(
+    config: &NodeConfig,
+    logger: Option<Arc<Logger>>,
+    mempool_client: MempoolClientSender,
+    db: DbReaderWriter,
+) -> NodeDebugService {
     let addr = format!(
         "{}:{}",
         config.debug_interface.address, config.debug_interface.admission_control_node_debug_port,
@@ -231,12 +237,12 @@ fn setup_debug_interface(config: &NodeConfig, logger: Option<Arc<Logger>>) -> No
     .next()
     .unwrap();
 
-    NodeDebugService::new(addr, logger, config)
+    NodeDebugService::new(addr, logger, config, mempool_client, db)
 }
 
 fn create_state_sync_runtimes<M: MempoolNotificationSender + 'static>(
     node_config: &NodeConfig,
-    storage_service_server_network_handles: Vec<StorageServiceNetworkEvents>,
+    storage_service_server_network_handles: Vec<(NetworkId, StorageServiceNetworkEvents)>,
     storage_service_client_network_handles: HashMap<
         NetworkId,
         storage_service_client::StorageServiceNetworkSender,
@@ -356,7 +362,7 @@ fn setup_aptos_data_client(
 
 fn setup_state_sync_storage_service(
     config: StorageServiceConfig,
-    network_handles: Vec<StorageServiceNetworkEvents>,
+    network_handles: Vec<(NetworkId, StorageServiceNetworkEvents)>,
     db_rw: &DbReaderWriter,
 ) -> Runtime {
     // Create a new state sync storage service runtime
@@ -368,12 +374,13 @@ fn setup_state_sync_storage_service(
 
     // Spawn all state sync storage service servers on the same runtime
     let storage_reader = StorageReader::new(config, Arc::clone(&db_rw.reader));
-    for events in network_handles {
+    for (network_id, events) in network_handles {
         let service = StorageServiceServer::new(
             config,
             storage_service_runtime.handle().clone(),
             storage_reader.clone(),
             TimeService::real(),
+            network_id,
             events,
         );
         storage_service_runtime.spawn(service.start());
@@ -425,7 +432,7 @@ async fn periodic_state_dump(node_config: NodeConfig, db: DbReaderWriter) {
 }
 
 pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>) -> AptosHandle {
-    let debug_if = setup_debug_interface(node_config, logger);
+    let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
 
     let metrics_port = node_config.debug_interface.metrics_server_port;
     let metric_host = node_config.debug_interface.address.clone();
@@ -433,18 +440,15 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
 
     let mut instant = Instant::now();
     let (aptos_db, db_rw) = DbReaderWriter::wrap(
-        AptosDB::open(
-            &node_config.storage.dir(),
-            false, /* readonly */
-            node_config.storage.storage_pruner_config,
-            node_config.storage.rocksdb_config,
-        )
-        .expect("DB should open."),
+        AptosDB::open_with_storage_config(&node_config.storage, false /* readonly */)
+            .expect("DB should open."),
     );
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&aptos_db),
     );
+    let debug_if =
+        setup_debug_interface(node_config, logger, mp_client_sender.clone(), db_rw.clone());
 
     let genesis_waypoint = node_config.base.waypoint.genesis_waypoint();
     // if there's genesis txn and waypoint, commit it if the result matches.
@@ -552,7 +556,7 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
             network_builder.add_service(&storage_service_server::network::network_endpoint_config(
                 node_config.state_sync.storage_service,
             ));
-        storage_service_server_network_handles.push(storage_service_events);
+        storage_service_server_network_handles.push((network_id, storage_service_events));
 
         // Register the storage-service clients with Network
         let storage_service_sender =
@@ -611,8 +615,6 @@ pub fn setup_environment(node_config: &NodeConfig, logger: Option<Arc<Logger>>)
         db_rw.clone(),
     );
 
-    let (mp_client_sender, mp_client_events) = channel(AC_SMP_CHANNEL_BUFFER_SIZE);
-
     let api_runtime = bootstrap_api(node_config, chain_id, aptos_db, mp_client_sender).unwrap();
 
     let mut consensus_runtime = None;
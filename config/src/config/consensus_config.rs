@@ -11,6 +11,8 @@ use std::{collections::HashMap, path::PathBuf};
 pub struct ConsensusConfig {
     pub contiguous_rounds: u32,
     pub max_block_size: u64,
+    // Max sum of the max_gas_amount of the transactions in a proposed block
+    pub max_block_gas: Option<u64>,
     pub max_pruned_blocks_in_mem: usize,
     // Timeout for consensus to get an ack from mempool for executed transactions (in milliseconds)
     pub mempool_executed_txn_timeout_ms: u64,
@@ -30,6 +32,9 @@ pub struct ConsensusConfig {
     // the period = (poll_count - 1) * 30ms
     pub quorum_store_poll_count: u64,
     pub intra_consensus_channel_buffer_size: usize,
+    // Lowers the on-chain back pressure limit: the max number of rounds that can be ordered
+    // ahead of the last committed round before the node stops proposing and voting
+    pub back_pressure_limit: Option<Round>,
 }
 
 impl Default for ConsensusConfig {
@@ -37,6 +42,7 @@ impl Default for ConsensusConfig {
         ConsensusConfig {
             contiguous_rounds: 2,
             max_block_size: 3000,
+            max_block_gas: None,
             max_pruned_blocks_in_mem: 100,
             mempool_executed_txn_timeout_ms: 1000,
             mempool_txn_pull_timeout_ms: 1000,
@@ -52,6 +58,7 @@ impl Default for ConsensusConfig {
             quorum_store_pull_timeout_ms: 1000,
             quorum_store_poll_count: 20,
             intra_consensus_channel_buffer_size: 10,
+            back_pressure_limit: None,
         }
     }
 }
@@ -71,6 +78,9 @@ pub enum ConsensusProposerType {
     RotatingProposer,
     // Committed history based proposer election
     LeaderReputation(LeaderReputationConfig),
+    // Committed history based proposer election that also accounts for failed
+    // proposals and weights proposers by voting power
+    LeaderReputationV2(LeaderReputationV2Config),
     // Pre-specified proposers for each round,
     // or default proposer if round proposer not
     // specified
@@ -83,3 +93,33 @@ pub struct LeaderReputationConfig {
     pub active_weights: u64,
     pub inactive_weights: u64,
 }
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LeaderReputationV2Config {
+    pub active_weight: u64,
+    pub inactive_weight: u64,
+    pub failed_weight: u64,
+    // A proposer whose failed proposals exceed this percentage of its rounds in the
+    // proposer window is assigned failed_weight
+    pub failure_threshold_percent: u32,
+    // Window sizes, as multiples of the number of validators in the epoch
+    pub proposer_window_num_validators_multiplier: usize,
+    pub voter_window_num_validators_multiplier: usize,
+    // Scale each proposer's reputation weight by its voting power
+    pub weight_by_voting_power: bool,
+}
+
+impl Default for LeaderReputationV2Config {
+    fn default() -> LeaderReputationV2Config {
+        LeaderReputationV2Config {
+            active_weight: 1000,
+            inactive_weight: 10,
+            failed_weight: 1,
+            failure_threshold_percent: 10,
+            proposer_window_num_validators_multiplier: 10,
+            voter_window_num_validators_multiplier: 1,
+            weight_by_voting_power: true,
+        }
+    }
+}
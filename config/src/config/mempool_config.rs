@@ -20,6 +20,21 @@ pub struct MempoolConfig {
     pub system_transaction_timeout_secs: u64,
     pub system_transaction_gc_interval_ms: u64,
     pub shared_mempool_validator_broadcast: bool,
+    // if enabled, transactions are journaled on disk and reloaded on restart
+    pub persist_transactions: bool,
+    // length of the window over which the per-peer admission quota is counted
+    pub shared_mempool_admission_interval_ms: u64,
+    // max number of broadcast transactions accepted from a single peer per admission interval
+    pub max_txns_per_peer_per_interval: usize,
+    // max number of transactions a single sender may hold in mempool for new transactions
+    // broadcast by public peers to still be accepted (validator and VFN broadcasts are exempt)
+    pub max_broadcast_txns_per_user: usize,
+    // if enabled, broadcasts are compressed for peers that support the compressed mempool protocol
+    pub shared_mempool_compress_broadcasts: bool,
+    // how long transactions received from a peer are remembered so they aren't broadcast back to it
+    pub shared_mempool_recently_seen_ttl_ms: u64,
+    // max number of recently received transactions remembered per peer
+    pub shared_mempool_recently_seen_capacity: usize,
 }
 
 impl Default for MempoolConfig {
@@ -38,6 +53,13 @@ impl Default for MempoolConfig {
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
             shared_mempool_validator_broadcast: true,
+            persist_transactions: false,
+            shared_mempool_admission_interval_ms: 1_000,
+            max_txns_per_peer_per_interval: 10_000,
+            max_broadcast_txns_per_user: 100,
+            shared_mempool_compress_broadcasts: true,
+            shared_mempool_recently_seen_ttl_ms: 60_000,
+            shared_mempool_recently_seen_capacity: 10_000,
         }
     }
 }
@@ -5,7 +5,7 @@ use crate::{
     config::{IdentityBlob, LoggerConfig, SecureBackend, WaypointConfig},
     keys::ConfigKey,
 };
-use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, x25519, Uniform};
 use aptos_types::{network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
@@ -48,8 +48,10 @@ impl Default for SafetyRulesConfig {
 
 impl SafetyRulesConfig {
     pub fn set_data_dir(&mut self, data_dir: PathBuf) {
-        if let SecureBackend::OnDiskStorage(backend) = &mut self.backend {
-            backend.set_data_dir(data_dir);
+        match &mut self.backend {
+            SecureBackend::OnDiskStorage(backend) => backend.set_data_dir(data_dir),
+            SecureBackend::EncryptedOnDiskStorage(backend) => backend.set_data_dir(data_dir),
+            _ => {}
         }
     }
 }
@@ -109,6 +111,10 @@ pub enum SafetyRulesService {
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    // When set, the connection between consensus and safety rules is mutually authenticated and
+    // encrypted with a Noise IK handshake
+    #[serde(default)]
+    pub noise: Option<RemoteServiceNoise>,
 }
 
 impl RemoteService {
@@ -121,6 +127,15 @@ impl RemoteService {
     }
 }
 
+/// The x25519 keys of one end of a remote safety rules connection: consensus configures its own
+/// key and the safety rules public key, and the safety rules process the reverse.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteServiceNoise {
+    pub private_key: ConfigKey<x25519::PrivateKey>,
+    pub remote_public_key: x25519::PublicKey,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
@@ -3,7 +3,8 @@
 
 use crate::config::Error;
 use aptos_secure_storage::{
-    GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, Storage, VaultStorage,
+    EncryptedOnDiskStorage, GitHubStorage, InMemoryStorage, Namespaced, OnDiskStorage, Storage,
+    VaultStorage,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -19,6 +20,7 @@ pub enum SecureBackend {
     InMemoryStorage,
     Vault(VaultConfig),
     OnDiskStorage(OnDiskStorageConfig),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig),
 }
 
 impl SecureBackend {
@@ -26,9 +28,11 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
-                namespace.as_deref()
-            }
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => namespace.as_deref(),
             SecureBackend::InMemoryStorage => None,
         }
     }
@@ -37,7 +41,11 @@ impl SecureBackend {
         match self {
             SecureBackend::GitHub(GitHubConfig { namespace, .. })
             | SecureBackend::Vault(VaultConfig { namespace, .. })
-            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. }) => {
+            | SecureBackend::OnDiskStorage(OnDiskStorageConfig { namespace, .. })
+            | SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+                namespace,
+                ..
+            }) => {
                 *namespace = None;
             }
             SecureBackend::InMemoryStorage => {}
@@ -110,6 +118,21 @@ pub struct OnDiskStorageConfig {
     data_dir: PathBuf,
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptedOnDiskStorageConfig {
+    // Required path for encrypted on disk storage
+    pub path: PathBuf,
+    /// A namespace is an optional portion of the path to a key stored within
+    /// EncryptedOnDiskStorage. For example, a key, S, without a namespace would be available in S,
+    /// with a namespace, N, it would be in N/S.
+    pub namespace: Option<String>,
+    /// The passphrase used to derive the file encryption key, either inline or from a key file
+    pub passphrase: Token,
+    #[serde(skip)]
+    data_dir: PathBuf,
+}
+
 /// Tokens can either be directly within this config or stored somewhere on disk.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -164,6 +187,29 @@ impl OnDiskStorageConfig {
     }
 }
 
+impl EncryptedOnDiskStorageConfig {
+    pub fn new(path: PathBuf, passphrase: Token) -> Self {
+        Self {
+            path,
+            namespace: None,
+            passphrase,
+            data_dir: PathBuf::from("/opt/aptos/data"),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        if self.path.is_relative() {
+            self.data_dir.join(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    pub fn set_data_dir(&mut self, data_dir: PathBuf) {
+        self.data_dir = data_dir;
+    }
+}
+
 fn read_file(path: &Path) -> Result<String, Error> {
     let mut file =
         File::open(path).map_err(|e| Error::IO(path.to_str().unwrap().to_string(), e))?;
@@ -202,6 +248,21 @@ impl From<&SecureBackend> for Storage {
                     storage
                 }
             }
+            SecureBackend::EncryptedOnDiskStorage(config) => {
+                let passphrase = config
+                    .passphrase
+                    .read_token()
+                    .expect("Unable to read passphrase");
+                let storage = Storage::from(
+                    EncryptedOnDiskStorage::new(config.path(), passphrase.as_bytes())
+                        .expect("Unable to open encrypted storage"),
+                );
+                if let Some(namespace) = &config.namespace {
+                    Storage::from(Namespaced::new(namespace, Box::new(storage)))
+                } else {
+                    storage
+                }
+            }
             SecureBackend::Vault(config) => {
                 let storage = Storage::from(VaultStorage::new(
                     config.server.clone(),
@@ -320,6 +381,29 @@ vault:
         serde_yaml::to_string(&from_disk).unwrap();
     }
 
+    #[test]
+    fn test_encrypted_on_disk_parsing() {
+        let expected = SecureBackend::EncryptedOnDiskStorage(EncryptedOnDiskStorageConfig {
+            path: PathBuf::from("secure_storage.enc"),
+            namespace: Some("consensus".to_string()),
+            passphrase: Token::FromDisk(PathBuf::from("/passphrase")),
+            data_dir: PathBuf::new(),
+        });
+
+        let text = r#"
+type: "encrypted_on_disk_storage"
+path: "secure_storage.enc"
+namespace: "consensus"
+passphrase:
+    from_disk: "/passphrase"
+        "#;
+
+        let backend: SecureBackend = serde_yaml::from_str(text).unwrap();
+        assert_eq!(backend, expected);
+        // Just assert that it can be serialized, no need to do string comparison
+        serde_yaml::to_string(&expected).unwrap();
+    }
+
     #[test]
     fn test_token_reading() {
         let temppath = aptos_temppath::TempPath::new();
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::MAX_FRAME_SIZE;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -60,8 +61,9 @@ impl Default for StateSyncConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BootstrappingMode {
     ApplyTransactionOutputsFromGenesis, // Applies transaction outputs (starting at genesis)
-    DownloadLatestAccountStates,        // Downloads the account states (at the latest version)
-    ExecuteTransactionsFromGenesis,     // Executes transactions (starting at genesis)
+    ApplyTransactionOutputsFromWaypoint, // Downloads the account states at the waypoint and applies transaction outputs from there
+    DownloadLatestAccountStates,         // Downloads the account states (at the latest version)
+    ExecuteTransactionsFromGenesis,      // Executes transactions (starting at genesis)
 }
 
 /// The continuous syncing mode determines how the node will stay up-to-date
@@ -84,6 +86,7 @@ pub struct StateSyncDriverConfig {
     pub max_consecutive_stream_notifications: u64, // The max number of notifications to process per driver loop
     pub max_pending_data_chunks: u64, // The max number of data chunks pending execution or commit
     pub max_stream_wait_time_ms: u64, // The max time (ms) to wait for a data stream notification
+    pub waypoint_epoch: u64, // The epoch ended by the waypoint (epoch ending ledger infos are fetched from here when bootstrapping from the waypoint)
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -99,6 +102,7 @@ impl Default for StateSyncDriverConfig {
             max_consecutive_stream_notifications: 10,
             max_pending_data_chunks: 100,
             max_stream_wait_time_ms: 5000,
+            waypoint_epoch: 0,
         }
     }
 }
@@ -106,13 +110,21 @@ impl Default for StateSyncDriverConfig {
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
+    pub enable_request_rate_limiting: bool, // Whether to rate limit the requests of each peer
     pub max_account_states_chunk_sizes: u64, // Max num of accounts per chunk
-    pub max_concurrent_requests: u64,        // Max num of concurrent storage server tasks
-    pub max_epoch_chunk_size: u64,           // Max num of epoch ending ledger infos per chunk
-    pub max_lru_cache_size: u64,             // Max num of items in the lru cache before eviction
-    pub max_network_channel_size: u64,       // Max num of pending network messages
-    pub max_subscription_period_ms: u64,     // Max period (ms) of pending subscription requests
-    pub max_transaction_chunk_size: u64,     // Max num of transactions per chunk
+    pub max_concurrent_requests: u64,       // Max num of concurrent storage server tasks
+    pub max_epoch_chunk_size: u64,          // Max num of epoch ending ledger infos per chunk
+    pub max_lru_cache_size: u64,            // Max num of items in the lru cache before eviction
+    pub max_network_channel_size: u64,      // Max num of pending network messages
+    pub max_network_chunk_bytes: u64,       // Max num of (compressed) bytes per response
+    pub max_priority_peer_requests_per_sec: u64, // Max num of requests per second for each validator/VFN peer
+    pub max_priority_peer_response_bytes_per_sec: u64, // Max num of response bytes per second for each validator/VFN peer
+    pub max_public_network_requests_per_sec: u64, // Max num of requests per second for all public peers combined
+    pub max_public_network_response_bytes_per_sec: u64, // Max num of response bytes per second for all public peers combined
+    pub max_public_peer_requests_per_sec: u64, // Max num of requests per second for each public peer
+    pub max_public_peer_response_bytes_per_sec: u64, // Max num of response bytes per second for each public peer
+    pub max_subscription_period_ms: u64, // Max period (ms) of pending subscription requests
+    pub max_transaction_chunk_size: u64, // Max num of transactions per chunk
     pub max_transaction_output_chunk_size: u64, // Max num of transaction outputs per chunk
     pub storage_summary_refresh_interval_ms: u64, // The interval (ms) to refresh the storage summary
 }
@@ -120,11 +132,19 @@ pub struct StorageServiceConfig {
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
+            enable_request_rate_limiting: true,
             max_account_states_chunk_sizes: 1000,
             max_concurrent_requests: 4000,
             max_epoch_chunk_size: 100,
             max_lru_cache_size: 100,
             max_network_channel_size: 4000,
+            max_network_chunk_bytes: MAX_FRAME_SIZE as u64,
+            max_priority_peer_requests_per_sec: 2000,
+            max_priority_peer_response_bytes_per_sec: 500 * 1024 * 1024, // 500 MiB
+            max_public_network_requests_per_sec: 4000, // 20 public peers at their own limit
+            max_public_network_response_bytes_per_sec: 1024 * 1024 * 1024, // 1 GiB (20 public peers at their own limit)
+            max_public_peer_requests_per_sec: 200,
+            max_public_peer_response_bytes_per_sec: 50 * 1024 * 1024, // 50 MiB
             max_subscription_period_ms: 10000,
             max_transaction_chunk_size: 1000,
             max_transaction_output_chunk_size: 1000,
@@ -177,8 +197,13 @@ impl Default for DataStreamingServiceConfig {
 pub struct AptosDataClientConfig {
     pub max_num_in_flight_priority_polls: u64, // Max num of in-flight polls for priority peers
     pub max_num_in_flight_regular_polls: u64,  // Max num of in-flight polls for regular peers
-    pub response_timeout_ms: u64, // Timeout (in milliseconds) when waiting for a response
+    pub max_parallel_range_requests: u64, // Max num of peers to split a single range request across (1 disables splitting)
+    pub min_parallel_range_chunk_size: u64, // Min num of items requested from each peer when splitting a range request
+    pub peer_exploration_ratio: f64, // Fraction of requests sent to random peers (instead of the fastest ones)
+    pub response_timeout_ms: u64,    // Timeout (in milliseconds) when waiting for a response
     pub summary_poll_interval_ms: u64, // Interval (in milliseconds) between data summary polls
+    pub throttled_peer_backoff_ms: u64, // Time (in milliseconds) to avoid peers that rate limited our requests
+    pub use_compression: bool,          // Whether to request compressed responses (if supported)
 }
 
 impl Default for AptosDataClientConfig {
@@ -186,8 +211,13 @@ impl Default for AptosDataClientConfig {
         Self {
             max_num_in_flight_priority_polls: 10,
             max_num_in_flight_regular_polls: 10,
+            max_parallel_range_requests: 1,
+            min_parallel_range_chunk_size: 100,
+            peer_exploration_ratio: 0.1,
             response_timeout_ms: 5000,
             summary_poll_interval_ms: 200,
+            throttled_peer_backoff_ms: 1000,
+            use_compression: false,
         }
     }
 }
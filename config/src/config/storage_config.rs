@@ -46,8 +46,13 @@ pub struct StorageConfig {
     pub timeout_ms: u64,
     /// Rocksdb-specific configurations
     pub rocksdb_config: RocksdbConfig,
+    /// Maximum number of Jellyfish Merkle nodes kept in the in-memory node cache, on top of the
+    /// pinned top levels of the tree. 0 disables it.
+    pub state_merkle_node_cache_size: usize,
 }
 
+pub const DEFAULT_STATE_MERKLE_NODE_CACHE_SIZE: usize = 131_072;
+
 pub const NO_OP_STORAGE_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
     state_store_prune_window: None,
     ledger_prune_window: None,
@@ -105,6 +110,7 @@ impl Default for StorageConfig {
             // Default read/write/connection timeout, in milliseconds
             timeout_ms: 30_000,
             rocksdb_config: RocksdbConfig::default(),
+            state_merkle_node_cache_size: DEFAULT_STATE_MERKLE_NODE_CACHE_SIZE,
         }
     }
 }
@@ -0,0 +1,95 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Context, Result};
+use aptos_config::config::RocksdbConfig;
+use aptos_temppath::TempPath;
+use aptos_types::validator_verifier::ValidatorVerifier;
+use aptosdb::{AptosDB, LEDGER_DB_NAME, STATE_MERKLE_DB_NAME};
+use consensus::db_tool::{latest_validator_verifier, ConsensusDbInspector};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "consensus-db-tool",
+    about = "Inspect the consensus storage of a stopped validator."
+)]
+struct Opt {
+    /// The node's storage directory, containing the consensusdb directory.
+    #[structopt(long, parse(from_os_str))]
+    db_dir: PathBuf,
+
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Print the last vote, highest timeout certificate, blocks and quorum certs.
+    Dump,
+    /// Print the block tree formed by the stored blocks.
+    Tree,
+    /// Verify the signatures of every stored quorum cert.
+    VerifyQcs {
+        /// BCS-serialized ValidatorVerifier to verify against. Defaults to the verifier of the
+        /// latest epoch in the AptosDB under --db-dir.
+        #[structopt(long, parse(from_os_str))]
+        validator_verifier_file: Option<PathBuf>,
+    },
+    /// Replay the stored block tree to reproduce the state a restarting node would recover to.
+    Replay,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    // The validator must be stopped first, so that the ConsensusDB and AptosDB are consistent
+    // with each other. AptosDB is still opened as secondary so that the tool never writes to it.
+    // Using a TempPath since it won't run for long.
+    let tmpdir = TempPath::new();
+    let inspector = ConsensusDbInspector::open(&opt.db_dir)?;
+
+    match opt.cmd {
+        Command::Dump => print!("{}", inspector.dump()?),
+        Command::Tree => print!("{}", inspector.block_tree()?),
+        Command::VerifyQcs {
+            validator_verifier_file,
+        } => {
+            let verifier = match validator_verifier_file {
+                Some(path) => bcs::from_bytes::<ValidatorVerifier>(&fs::read(&path)?)
+                    .with_context(|| format_err!("Failed to load {:?}.", path))?,
+                None => latest_validator_verifier(&open_aptos_db(&opt.db_dir, &tmpdir)?)?,
+            };
+            let failures = inspector.verify_quorum_certs(&verifier)?;
+            for failure in &failures {
+                println!("{}", failure);
+            }
+            if !failures.is_empty() {
+                bail!("{} quorum certs failed verification.", failures.len());
+            }
+            println!("All quorum certs verified.");
+        }
+        Command::Replay => print!(
+            "{}",
+            inspector.replay(open_aptos_db(&opt.db_dir, &tmpdir)?)?
+        ),
+    }
+
+    Ok(())
+}
+
+fn open_aptos_db(db_dir: &Path, tmpdir: &TempPath) -> Result<Arc<dyn DbReader>> {
+    let db = AptosDB::open_as_secondary(
+        db_dir,
+        &tmpdir.as_ref().to_path_buf().join(LEDGER_DB_NAME),
+        &tmpdir.as_ref().to_path_buf().join(STATE_MERKLE_DB_NAME),
+        RocksdbConfig::default(),
+    )
+    .with_context(|| format_err!("Failed to open DB."))?;
+    Ok(Arc::new(db))
+}
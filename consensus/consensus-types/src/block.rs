@@ -12,7 +12,7 @@ use aptos_infallible::duration_since_epoch;
 use aptos_types::{
     account_address::AccountAddress,
     block_info::BlockInfo,
-    block_metadata::BlockMetadata,
+    block_metadata::{BlockMetadata, BlockMetadataV2},
     epoch_state::EpochState,
     ledger_info::LedgerInfo,
     transaction::{Transaction, Version},
@@ -101,6 +101,10 @@ impl Block {
         self.block_data.payload()
     }
 
+    pub fn failed_authors(&self) -> Option<&Vec<(Round, Author)>> {
+        self.block_data.failed_authors()
+    }
+
     pub fn quorum_cert(&self) -> &QuorumCert {
         self.block_data.quorum_cert()
     }
@@ -206,6 +210,26 @@ impl Block {
         Self::new_proposal_from_block_data(block_data, validator_signer)
     }
 
+    pub fn new_proposal_with_failed_authors(
+        payload: Payload,
+        failed_authors: Vec<(Round, Author)>,
+        round: Round,
+        timestamp_usecs: u64,
+        quorum_cert: QuorumCert,
+        validator_signer: &ValidatorSigner,
+    ) -> Self {
+        let block_data = BlockData::new_proposal_with_failed_authors(
+            payload,
+            validator_signer.author(),
+            failed_authors,
+            round,
+            timestamp_usecs,
+            quorum_cert,
+        );
+
+        Self::new_proposal_from_block_data(block_data, validator_signer)
+    }
+
     pub fn new_proposal_from_block_data(
         block_data: BlockData,
         validator_signer: &ValidatorSigner,
@@ -231,7 +255,7 @@ impl Block {
         match self.block_data.block_type() {
             BlockType::Genesis => bail!("We should not accept genesis from others"),
             BlockType::NilBlock => self.quorum_cert().verify(validator),
-            BlockType::Proposal { author, .. } => {
+            BlockType::Proposal { author, .. } | BlockType::ProposalV2 { author, .. } => {
                 let signature = self
                     .signature
                     .as_ref()
@@ -264,6 +288,18 @@ impl Block {
                 "Reconfiguration suffix should not carry payload"
             );
         }
+        if let Some(failed_authors) = self.failed_authors() {
+            ensure!(
+                failed_authors
+                    .iter()
+                    .all(|(round, _)| parent.round() < *round && *round < self.round()),
+                "Failed authors must be from rounds between the parent and the block"
+            );
+            ensure!(
+                failed_authors.windows(2).all(|w| w[0].0 < w[1].0),
+                "Failed authors must be in strictly increasing round order"
+            );
+        }
         if self.is_nil_block() || parent.has_reconfiguration() {
             ensure!(
                 self.timestamp_usecs() == parent.timestamp_usecs(),
@@ -297,18 +333,25 @@ impl Block {
     }
 
     pub fn transactions_to_execute(&self, validators: &[AccountAddress]) -> Vec<Transaction> {
-        std::iter::once(Transaction::BlockMetadata(
-            self.new_block_metadata(validators),
-        ))
-        .chain(
-            self.payload()
-                .unwrap_or(&Payload::new_empty())
-                .clone()
-                .into_iter()
-                .map(Transaction::UserTransaction),
-        )
-        .chain(once(Transaction::StateCheckpoint))
-        .collect()
+        let block_metadata = self.new_block_metadata(validators);
+        // Only blocks that record failed authors use the newer block metadata format
+        let block_metadata_txn = match self.failed_authors() {
+            Some(failed_authors) => Transaction::BlockMetadataV2(BlockMetadataV2::new(
+                block_metadata,
+                Self::failed_authors_to_indices(validators, failed_authors),
+            )),
+            None => Transaction::BlockMetadata(block_metadata),
+        };
+        once(block_metadata_txn)
+            .chain(
+                self.payload()
+                    .unwrap_or(&Payload::new_empty())
+                    .clone()
+                    .into_iter()
+                    .map(Transaction::UserTransaction),
+            )
+            .chain(once(Transaction::StateCheckpoint))
+            .collect()
     }
 
     fn new_block_metadata(&self, validators: &[AccountAddress]) -> BlockMetadata {
@@ -324,6 +367,21 @@ impl Block {
         )
     }
 
+    fn failed_authors_to_indices(
+        validators: &[AccountAddress],
+        failed_authors: &[(Round, Author)],
+    ) -> Vec<u32> {
+        failed_authors
+            .iter()
+            .filter_map(|(_round, failed_author)| {
+                validators
+                    .iter()
+                    .position(|address| address == failed_author)
+                    .map(|index| index as u32)
+            })
+            .collect()
+    }
+
     fn voters_to_bitmap<T>(
         validators: &[AccountAddress],
         voters: &BTreeMap<AccountAddress, T>,
@@ -12,6 +12,8 @@ pub enum ConsensusRequest {
     GetBlockRequest(
         // max block size
         u64,
+        // max sum of the max_gas_amount of the transactions in the block
+        u64,
         // block payloads to exclude from the requested block
         PayloadFilter,
         // callback to respond to
@@ -31,11 +33,11 @@ pub enum ConsensusRequest {
 impl fmt::Display for ConsensusRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            ConsensusRequest::GetBlockRequest(block_size, excluded, _) => {
+            ConsensusRequest::GetBlockRequest(block_size, block_gas, excluded, _) => {
                 write!(
                     f,
-                    "GetBlockRequest [block_size: {}, excluded: {}]",
-                    block_size, excluded
+                    "GetBlockRequest [block_size: {}, block_gas: {}, excluded: {}]",
+                    block_size, block_gas, excluded
                 )
             }
             ConsensusRequest::CleanRequest(epoch, round, _) => {
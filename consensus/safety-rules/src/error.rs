@@ -31,6 +31,8 @@ pub enum Error {
     NotInitialized(String),
     #[error("Data not found in secure storage: {0}")]
     SecureStorageMissingDataError(String),
+    #[error("Key cannot be exported from secure storage: {0}")]
+    SecureStorageKeyNotExportable(String),
     #[error("Unexpected error returned by secure storage: {0}")]
     SecureStorageUnexpectedError(String),
     #[error("Serialization error: {0}")]
@@ -87,6 +89,9 @@ impl From<aptos_secure_storage::Error> for Error {
             | aptos_secure_storage::Error::KeyNotSet(_) => {
                 Self::SecureStorageMissingDataError(error.to_string())
             }
+            aptos_secure_storage::Error::KeyNotExportable(_) => {
+                Self::SecureStorageKeyNotExportable(error.to_string())
+            }
             _ => Self::SecureStorageUnexpectedError(error.to_string()),
         }
     }
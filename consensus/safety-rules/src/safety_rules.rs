@@ -303,7 +303,7 @@ impl SafetyRules {
                     // Try to export the consensus key directly from storage.
                     match self
                         .persistent_storage
-                        .consensus_key_for_version(expected_key)
+                        .consensus_key_for_version(expected_key.clone())
                     {
                         Ok(consensus_key) => {
                             self.validator_signer = Some(ConfigurableValidatorSigner::new_signer(
@@ -315,18 +315,14 @@ impl SafetyRules {
                         Err(Error::SecureStorageMissingDataError(error)) => {
                             Err(Error::ValidatorKeyNotFound(error))
                         }
+                        // Keys held in a hardware module never leave it, sign through storage.
+                        Err(Error::SecureStorageKeyNotExportable(_)) => {
+                            self.initialize_validator_handle(author, expected_key, ledger_info)
+                        }
                         Err(error) => Err(error),
                     }
                 } else {
-                    // Try to generate a signature over a test message to ensure the expected key
-                    // is actually held in storage.
-                    self.validator_signer = Some(ConfigurableValidatorSigner::new_handle(
-                        author,
-                        expected_key,
-                    ));
-                    self.sign(ledger_info)
-                        .map(|_signature| ())
-                        .map_err(|error| Error::ValidatorKeyNotFound(error.to_string()))
+                    self.initialize_validator_handle(author, expected_key, ledger_info)
                 }
             }
         };
@@ -339,6 +335,23 @@ impl SafetyRules {
         })
     }
 
+    /// Sets up a handle to the consensus key held in storage and generates a signature over a
+    /// test message to ensure the expected key is actually there.
+    fn initialize_validator_handle(
+        &mut self,
+        author: Author,
+        expected_key: Ed25519PublicKey,
+        ledger_info: &LedgerInfo,
+    ) -> Result<(), Error> {
+        self.validator_signer = Some(ConfigurableValidatorSigner::new_handle(
+            author,
+            expected_key,
+        ));
+        self.sign(ledger_info)
+            .map(|_signature| ())
+            .map_err(|error| Error::ValidatorKeyNotFound(error.to_string()))
+    }
+
     fn guarded_sign_proposal(&mut self, block_data: &BlockData) -> Result<Ed25519Signature, Error> {
         self.signer()?;
         self.verify_author(block_data.author())?;
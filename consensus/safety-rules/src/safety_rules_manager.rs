@@ -10,7 +10,9 @@ use crate::{
     thread::ThreadService,
     SafetyRules, TSafetyRules,
 };
-use aptos_config::config::{InitialSafetyRulesConfig, SafetyRulesConfig, SafetyRulesService};
+use aptos_config::config::{
+    InitialSafetyRulesConfig, RemoteServiceNoise, SafetyRulesConfig, SafetyRulesService,
+};
 use aptos_infallible::RwLock;
 use aptos_secure_storage::{KVStorage, Storage};
 use std::{convert::TryInto, net::SocketAddr, sync::Arc};
@@ -96,7 +98,11 @@ pub struct SafetyRulesManager {
 impl SafetyRulesManager {
     pub fn new(config: &SafetyRulesConfig) -> Self {
         if let SafetyRulesService::Process(conf) = &config.service {
-            return Self::new_process(conf.server_address(), config.network_timeout_ms);
+            return Self::new_process(
+                conf.server_address(),
+                config.network_timeout_ms,
+                conf.noise.clone(),
+            );
         }
 
         let storage = storage(config);
@@ -138,8 +144,12 @@ impl SafetyRulesManager {
         }
     }
 
-    pub fn new_process(server_addr: SocketAddr, timeout_ms: u64) -> Self {
-        let process_service = ProcessService::new(server_addr, timeout_ms);
+    pub fn new_process(
+        server_addr: SocketAddr,
+        timeout_ms: u64,
+        noise: Option<RemoteServiceNoise>,
+    ) -> Self {
+        let process_service = ProcessService::new(server_addr, timeout_ms, noise);
         Self {
             internal_safety_rules: SafetyRulesWrapper::Process(process_service),
         }
@@ -0,0 +1,58 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{test_utils, tests::suite, PersistentSafetyStorage, SafetyRulesManager};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use aptos_secure_storage::{HardwareStorage, InMemoryHardwareModule, InMemoryStorage, Storage};
+use aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test() {
+    let boolean_values = [false, true];
+    for verify_vote_proposal_signature in &boolean_values {
+        // Exporting is refused by the module, so both settings must sign through storage
+        for export_consensus_key in &boolean_values {
+            suite::run_test_suite(&safety_rules(
+                *verify_vote_proposal_signature,
+                *export_consensus_key,
+            ));
+        }
+    }
+}
+
+fn safety_rules(
+    verify_vote_proposal_signature: bool,
+    export_consensus_key: bool,
+) -> suite::Callback {
+    Box::new(move || {
+        let signer = ValidatorSigner::from_int(0);
+        let waypoint = test_utils::validator_signers_to_waypoint(&[&signer]);
+        let storage = Storage::from(HardwareStorage::new(
+            Box::new(InMemoryHardwareModule::new()),
+            Box::new(Storage::from(InMemoryStorage::new())),
+        ));
+        let storage = PersistentSafetyStorage::initialize(
+            storage,
+            signer.author(),
+            signer.private_key().clone(),
+            Ed25519PrivateKey::generate_for_testing(),
+            waypoint,
+            true,
+        );
+        let safety_rules_manager = SafetyRulesManager::new_local(
+            storage,
+            verify_vote_proposal_signature,
+            export_consensus_key,
+        );
+        let safety_rules = safety_rules_manager.client();
+        (
+            safety_rules,
+            signer,
+            if verify_vote_proposal_signature {
+                Some(Ed25519PrivateKey::generate_for_testing())
+            } else {
+                None
+            },
+        )
+    })
+}
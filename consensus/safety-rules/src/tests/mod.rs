@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod hardware_module;
 mod local;
 mod networking;
 mod safety_rules;
@@ -440,6 +440,21 @@ impl BlockStore {
         Ok(())
     }
 
+    /// Number of rounds between the ordered root and the commit root, i.e. how far the execution
+    /// pipeline lags behind ordering.
+    pub fn pending_commit_rounds(&self) -> Round {
+        let inner = self.inner.read();
+        inner
+            .ordered_root()
+            .round()
+            .saturating_sub(inner.commit_root().round())
+    }
+
+    /// Returns true if more than `back_pressure_limit` rounds are ordered but not committed yet.
+    pub fn back_pressure(&self) -> bool {
+        self.pending_commit_rounds() > self.back_pressure_limit
+    }
+
     /// Prune the tree up to next_root_id (keep next_root_id's block).  Any branches not part of
     /// the next_root_id's tree should be removed as well.
     ///
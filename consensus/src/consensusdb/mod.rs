@@ -51,6 +51,20 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Opens an existing ConsensusDB without write access, e.g. for offline inspection.
+    pub fn open_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let column_families = vec![
+            /* UNUSED CF = */ DEFAULT_COLUMN_FAMILY_NAME,
+            BLOCK_CF_NAME,
+            QC_CF_NAME,
+            SINGLE_ENTRY_CF_NAME,
+        ];
+
+        let path = db_root_path.as_ref().join("consensusdb");
+        let db = DB::open_cf_readonly(&Options::default(), path, "consensus", column_families)?;
+        Ok(Self { db })
+    }
+
     pub fn get_data(
         &self,
     ) -> Result<(
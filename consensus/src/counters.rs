@@ -99,6 +99,15 @@ pub static COMMITTED_VOTES_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Failed proposals from this validator when using LeaderReputationV2 as the ProposerElection
+pub static FAILED_PROPOSALS_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_failed_proposals_in_window",
+        "Total number of this validator's failed proposals in the current reputation window"
+    )
+    .unwrap()
+});
+
 /// The number of block events the LeaderReputation uses
 pub static LEADER_REPUTATION_WINDOW_SIZE: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -196,6 +205,46 @@ pub static NUM_BLOCKS_IN_TREE: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+//////////////////////
+// BACK PRESSURE COUNTERS
+//////////////////////
+/// Number of rounds that are ordered but not committed yet by the execution pipeline.
+pub static PENDING_COMMIT_ROUNDS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_pending_commit_rounds",
+        "Number of rounds that are ordered but not committed yet by the execution pipeline."
+    )
+    .unwrap()
+});
+
+/// 1 if the node stopped proposing and voting because execution lags too far behind ordering.
+pub static BACK_PRESSURE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_back_pressure",
+        "1 if the node stopped proposing and voting because execution lags too far behind ordering."
+    )
+    .unwrap()
+});
+
+/// Count of proposals, votes and timeout votes skipped due to back pressure.
+pub static BACK_PRESSURE_SKIPPED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_back_pressure_skipped_count",
+        "Count of proposals, votes and timeout votes skipped due to back pressure.",
+        &["action"]
+    )
+    .unwrap()
+});
+
+/// Number of ordered blocks held by the buffer manager until they are committed.
+pub static BUFFER_MANAGER_PENDING_BLOCKS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_buffer_manager_pending_blocks",
+        "Number of ordered blocks held by the buffer manager until they are committed."
+    )
+    .unwrap()
+});
+
 //////////////////////
 // PERFORMANCE COUNTERS
 //////////////////////
@@ -0,0 +1,334 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline inspection of a validator's ConsensusDB.
+//!
+//! Backs the `consensus-db-tool` binary: it dumps the stored blocks and quorum certificates,
+//! rebuilds the block tree they form, verifies QC signatures, and replays the stored tree through
+//! [`BlockStore`] to show the state a restarting node would recover to. Nothing is written to
+//! either ConsensusDB or AptosDB.
+
+use crate::{
+    block_storage::{BlockReader, BlockStore},
+    consensusdb::ConsensusDB,
+    epoch_manager::LivenessStorageData,
+    error::{DbError, StateSyncError},
+    persistent_liveness_storage::{
+        read_recovery_data, LedgerRecoveryData, PersistentLivenessStorage,
+    },
+    state_replication::{StateComputer, StateComputerCommitCallBackType},
+    util::time_service::ClockTimeService,
+};
+use anyhow::{format_err, Context, Result};
+use aptos_config::config::ConsensusConfig;
+use aptos_crypto::HashValue;
+use aptos_types::{
+    epoch_change::EpochChangeProof, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    validator_verifier::ValidatorVerifier,
+};
+use consensus_types::{
+    block::Block, executed_block::ExecutedBlock, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+};
+use executor_types::{Error as ExecutionError, StateComputeResult};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::Path,
+    sync::Arc,
+};
+use storage_interface::DbReader;
+
+#[cfg(test)]
+#[path = "db_tool_test.rs"]
+mod db_tool_test;
+
+/// Read-only view over the ConsensusDB of a stopped validator.
+pub struct ConsensusDbInspector {
+    db: Arc<ConsensusDB>,
+}
+
+impl ConsensusDbInspector {
+    /// Opens the ConsensusDB under `db_root_path` (the node's storage directory) read-only.
+    pub fn open<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let db = ConsensusDB::open_readonly(db_root_path).context("Failed to open ConsensusDB")?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Returns the stored blocks and quorum certs, sorted by (epoch, round).
+    fn read_tree(&self) -> Result<(Vec<Block>, Vec<QuorumCert>)> {
+        let (_, _, mut blocks, mut quorum_certs) = self.db.get_data()?;
+        blocks.sort_by_key(|b| (b.epoch(), b.round()));
+        quorum_certs.sort_by_key(|qc| (qc.certified_block().epoch(), qc.certified_block().round()));
+        Ok((blocks, quorum_certs))
+    }
+
+    /// Renders every record in the ConsensusDB: last vote, highest timeout certificate, blocks
+    /// and quorum certs.
+    pub fn dump(&self) -> Result<String> {
+        let (last_vote, highest_tc, _, _) = self.db.get_data()?;
+        let last_vote = last_vote
+            .map(|bytes| bcs::from_bytes::<Vote>(&bytes))
+            .transpose()
+            .context("unable to deserialize last vote")?;
+        let highest_tc = highest_tc
+            .map(|bytes| bcs::from_bytes::<TwoChainTimeoutCertificate>(&bytes))
+            .transpose()
+            .context("unable to deserialize highest 2-chain timeout cert")?;
+        let (blocks, quorum_certs) = self.read_tree()?;
+
+        let mut output = String::new();
+        writeln!(output, "Last vote: {}", display_option(&last_vote))?;
+        writeln!(
+            output,
+            "Highest 2-chain timeout certificate: {}",
+            display_option(&highest_tc)
+        )?;
+        writeln!(output, "Blocks ({}):", blocks.len())?;
+        for block in &blocks {
+            writeln!(output, "\t{}", block)?;
+        }
+        writeln!(output, "Quorum certs ({}):", quorum_certs.len())?;
+        for qc in &quorum_certs {
+            writeln!(output, "\t{}", qc)?;
+        }
+        Ok(output)
+    }
+
+    /// Renders the block tree formed by the stored blocks' parent links. Blocks whose parent is
+    /// not stored are shown as roots, and certified blocks are marked.
+    pub fn block_tree(&self) -> Result<String> {
+        let (blocks, quorum_certs) = self.read_tree()?;
+        let ids: HashSet<HashValue> = blocks.iter().map(|b| b.id()).collect();
+        let certified: HashSet<HashValue> = quorum_certs
+            .iter()
+            .map(|qc| qc.certified_block().id())
+            .collect();
+        let mut children: HashMap<HashValue, Vec<&Block>> = HashMap::new();
+        let mut roots = vec![];
+        for block in &blocks {
+            if ids.contains(&block.parent_id()) {
+                children.entry(block.parent_id()).or_default().push(block);
+            } else {
+                roots.push(block);
+            }
+        }
+
+        let mut output = String::new();
+        // blocks are sorted, so every list of children is ordered by round
+        let mut stack: Vec<(&Block, usize)> = roots.into_iter().rev().map(|b| (b, 0)).collect();
+        while let Some((block, depth)) = stack.pop() {
+            writeln!(
+                output,
+                "{}{}{}",
+                "  ".repeat(depth),
+                block,
+                if certified.contains(&block.id()) {
+                    " (certified)"
+                } else {
+                    ""
+                }
+            )?;
+            if let Some(block_children) = children.get(&block.id()) {
+                stack.extend(block_children.iter().rev().map(|b| (*b, depth + 1)));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Verifies every stored quorum cert against `verifier`, returning a description of each
+    /// failure. QCs from other epochs are expected to fail against a single epoch's verifier.
+    pub fn verify_quorum_certs(&self, verifier: &ValidatorVerifier) -> Result<Vec<String>> {
+        let (_, quorum_certs) = self.read_tree()?;
+        Ok(quorum_certs
+            .iter()
+            .filter_map(|qc| {
+                qc.verify(verifier)
+                    .err()
+                    .map(|e| format!("{}: {:#}", qc, e))
+            })
+            .collect())
+    }
+
+    /// Recomputes the RecoveryData a restarting node would build from this ConsensusDB and the
+    /// committed state in `aptos_db`, replays it through a [`BlockStore`] without executing any
+    /// transactions, and renders the resulting consensus state.
+    pub fn replay(&self, aptos_db: Arc<dyn DbReader>) -> Result<String> {
+        let (_, recovery_data) = read_recovery_data(&self.db, &aptos_db)?;
+        let mut output = String::new();
+        let mut recovery_data = match recovery_data {
+            Ok(recovery_data) => recovery_data,
+            Err(e) => {
+                writeln!(
+                    output,
+                    "Failed to construct recovery data, the node would start from the ledger \
+                     info and sync: {:#}",
+                    e
+                )?;
+                return Ok(output);
+            }
+        };
+
+        let blocks_to_prune = recovery_data.take_blocks_to_prune();
+        writeln!(output, "Recovery root: {}", recovery_data.root_block())?;
+        writeln!(
+            output,
+            "Last vote: {}",
+            display_option(&recovery_data.last_vote())
+        )?;
+        writeln!(
+            output,
+            "Highest 2-chain timeout certificate: {}",
+            display_option(&recovery_data.highest_2chain_timeout_certificate())
+        )?;
+        writeln!(
+            output,
+            "Blocks pruned on restart ({}):",
+            blocks_to_prune.len()
+        )?;
+        for id in &blocks_to_prune {
+            writeln!(output, "\t{}", id)?;
+        }
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        let storage = Arc::new(ReplayStorage {
+            db: self.db.clone(),
+            aptos_db,
+        });
+        let block_store = BlockStore::new(
+            storage,
+            recovery_data,
+            Arc::new(ReplayStateComputer),
+            ConsensusConfig::default().max_pruned_blocks_in_mem,
+            Arc::new(ClockTimeService::new(runtime.handle().clone())),
+            u64::max_value(),
+        );
+        writeln!(output, "Ordered root: {}", block_store.ordered_root())?;
+        writeln!(output, "Commit root: {}", block_store.commit_root())?;
+        writeln!(
+            output,
+            "Highest certified block: {}",
+            block_store.highest_certified_block()
+        )?;
+        writeln!(
+            output,
+            "Highest quorum cert: {}",
+            block_store.highest_quorum_cert()
+        )?;
+        writeln!(
+            output,
+            "Highest ordered cert: {}",
+            block_store.highest_ordered_cert()
+        )?;
+        writeln!(
+            output,
+            "Highest ledger info: {}",
+            block_store.highest_ledger_info()
+        )?;
+        Ok(output)
+    }
+}
+
+fn display_option<T: std::fmt::Display>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "None".to_string(), |v| v.to_string())
+}
+
+/// Liveness storage for replays: reads come from the inspected databases, writes are dropped.
+struct ReplayStorage {
+    db: Arc<ConsensusDB>,
+    aptos_db: Arc<dyn DbReader>,
+}
+
+impl PersistentLivenessStorage for ReplayStorage {
+    fn save_tree(&self, _blocks: Vec<Block>, _quorum_certs: Vec<QuorumCert>) -> Result<()> {
+        Ok(())
+    }
+
+    fn prune_tree(&self, _block_ids: Vec<HashValue>) -> Result<()> {
+        Ok(())
+    }
+
+    fn save_vote(&self, _vote: &Vote) -> Result<()> {
+        Ok(())
+    }
+
+    fn recover_from_ledger(&self) -> LedgerRecoveryData {
+        let startup_info = self
+            .aptos_db
+            .get_startup_info()
+            .expect("unable to read ledger info from storage")
+            .expect("startup info is None");
+
+        LedgerRecoveryData::new(startup_info.latest_ledger_info)
+    }
+
+    fn start(&self) -> LivenessStorageData {
+        let (ledger_recovery_data, recovery_data) =
+            read_recovery_data(&self.db, &self.aptos_db).expect("unable to recover consensus data");
+        match recovery_data {
+            Ok(recovery_data) => LivenessStorageData::RecoveryData(recovery_data),
+            Err(_) => LivenessStorageData::LedgerRecoveryData(ledger_recovery_data),
+        }
+    }
+
+    fn save_highest_2chain_timeout_cert(
+        &self,
+        _highest_timeout_cert: &TwoChainTimeoutCertificate,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof> {
+        let (_, proofs) = self
+            .aptos_db
+            .get_state_proof(version)
+            .map_err(DbError::from)?
+            .into_inner();
+        Ok(proofs)
+    }
+
+    fn aptos_db(&self) -> Arc<dyn DbReader> {
+        self.aptos_db.clone()
+    }
+}
+
+/// Orders blocks without executing them, the same way the ordering phase of decoupled execution
+/// does, so that replays only depend on the stored blocks and certificates.
+struct ReplayStateComputer;
+
+#[async_trait::async_trait]
+impl StateComputer for ReplayStateComputer {
+    async fn compute(
+        &self,
+        _block: &Block,
+        _parent_block_id: HashValue,
+    ) -> Result<StateComputeResult, ExecutionError> {
+        Ok(StateComputeResult::new_dummy())
+    }
+
+    async fn commit(
+        &self,
+        blocks: &[Arc<ExecutedBlock>],
+        finality_proof: LedgerInfoWithSignatures,
+        callback: StateComputerCommitCallBackType,
+    ) -> Result<(), ExecutionError> {
+        callback(blocks, finality_proof);
+        Ok(())
+    }
+
+    async fn sync_to(&self, _target: LedgerInfoWithSignatures) -> Result<(), StateSyncError> {
+        Ok(())
+    }
+
+    fn new_epoch(&self, _epoch_state: &EpochState) {}
+}
+
+/// Loads the validator verifier of the latest epoch known to `aptos_db`.
+pub fn latest_validator_verifier(aptos_db: &Arc<dyn DbReader>) -> Result<ValidatorVerifier> {
+    let startup_info = aptos_db
+        .get_startup_info()?
+        .ok_or_else(|| format_err!("startup info is None"))?;
+    Ok(startup_info.get_epoch_state().verifier.clone())
+}
@@ -0,0 +1,115 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{consensusdb::ConsensusDB, db_tool::ConsensusDbInspector};
+use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures, on_chain_config::ValidatorSet,
+    validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
+};
+use consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+    quorum_cert::QuorumCert,
+};
+use std::sync::Arc;
+use storage_interface::{DbReader, StartupInfo, TreeState};
+
+/// An AptosDB that has only committed the given epoch ending ledger info.
+struct MockLedger {
+    ledger_info: LedgerInfoWithSignatures,
+}
+
+impl DbReader for MockLedger {
+    fn get_startup_info(&self) -> anyhow::Result<Option<StartupInfo>> {
+        Ok(Some(StartupInfo::new(
+            self.ledger_info.clone(),
+            None,
+            TreeState::new_empty(),
+            None,
+        )))
+    }
+}
+
+#[test]
+fn test_block_tree_and_qc_verification() {
+    let tmp_dir = TempPath::new();
+    let signer = ValidatorSigner::random(None);
+    let genesis = Block::make_genesis_block();
+    let genesis_qc = certificate_for_genesis();
+    let a1 = Block::new_proposal(Payload::new_empty(), 1, 1, genesis_qc.clone(), &signer);
+    let b1 = Block::new_proposal(Payload::new_empty(), 2, 2, genesis_qc.clone(), &signer);
+    {
+        let db = ConsensusDB::new(&tmp_dir);
+        db.save_blocks_and_quorum_certificates(
+            vec![b1.clone(), genesis.clone(), a1.clone()],
+            vec![genesis_qc],
+        )
+        .unwrap();
+    }
+
+    let inspector = ConsensusDbInspector::open(&tmp_dir).unwrap();
+    let tree = inspector.block_tree().unwrap();
+    assert_eq!(
+        tree.lines().collect::<Vec<_>>(),
+        vec![
+            format!("{} (certified)", genesis),
+            format!("  {}", a1),
+            format!("  {}", b1),
+        ]
+    );
+
+    // The genesis QC doesn't carry signatures, so it verifies against any validator set
+    let (_, verifier) = random_validator_verifier(4, None, false);
+    assert!(inspector.verify_quorum_certs(&verifier).unwrap().is_empty());
+
+    let dump = inspector.dump().unwrap();
+    assert!(dump.contains("Blocks (3):"));
+    assert!(dump.contains("Quorum certs (1):"));
+}
+
+#[test]
+fn test_replay() {
+    let tmp_dir = TempPath::new();
+    let signer = ValidatorSigner::random(None);
+    let ledger_info = LedgerInfoWithSignatures::genesis(HashValue::zero(), ValidatorSet::empty());
+
+    // The node recovers to the virtual genesis block of the epoch started by the ledger info
+    let genesis = Block::make_genesis_block_from_ledger_info(ledger_info.ledger_info());
+    let genesis_qc = QuorumCert::certificate_for_genesis_from_ledger_info(
+        ledger_info.ledger_info(),
+        genesis.id(),
+    );
+    let a1 = Block::new_proposal(Payload::new_empty(), 1, 1, genesis_qc.clone(), &signer);
+    let a2 = Block::new_proposal(Payload::new_empty(), 2, 2, genesis_qc.clone(), &signer);
+
+    // A block built on another genesis doesn't descend from the root, so it's pruned on restart
+    let stale_block = Block::new_proposal(
+        Payload::new_empty(),
+        1,
+        1,
+        certificate_for_genesis(),
+        &signer,
+    );
+    {
+        let db = ConsensusDB::new(&tmp_dir);
+        db.save_blocks_and_quorum_certificates(vec![a1, a2, stale_block.clone()], vec![])
+            .unwrap();
+    }
+
+    let inspector = ConsensusDbInspector::open(&tmp_dir).unwrap();
+    let replay = inspector
+        .replay(Arc::new(MockLedger { ledger_info }))
+        .unwrap();
+    let lines: Vec<_> = replay.lines().collect();
+    assert!(lines.contains(&format!("Recovery root: {}", genesis).as_str()));
+    assert!(lines.contains(&"Blocks pruned on restart (1):"));
+    assert!(lines.contains(&format!("\t{}", stale_block.id()).as_str()));
+    assert!(lines.contains(&format!("Ordered root: {}", genesis).as_str()));
+    assert!(lines.contains(&format!("Highest certified block: {}", genesis).as_str()));
+
+    // Replays don't write to the ConsensusDB
+    let dump = inspector.dump().unwrap();
+    assert!(dump.contains("Blocks (3):"));
+}
@@ -12,7 +12,9 @@ use crate::{
         ordering_state_computer::OrderingStateComputer,
     },
     liveness::{
-        leader_reputation::{ActiveInactiveHeuristic, AptosDBBackend, LeaderReputation},
+        leader_reputation::{
+            ActiveInactiveHeuristic, AptosDBBackend, LeaderReputation, ProposerAndVoterHeuristic,
+        },
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
         rotating_proposer_election::{choose_leader, RotatingProposer},
@@ -197,18 +199,65 @@ impl EpochManager {
                     proposers.len(),
                     onchain_config.leader_reputation_exclude_round() + 10,
                     self.storage.aptos_db(),
+                    onchain_config.max_failed_authors_to_store().is_some(),
                 ));
                 let heuristic = Box::new(ActiveInactiveHeuristic::new(
                     self.author,
                     heuristic_config.active_weights,
                     heuristic_config.inactive_weights,
                 ));
+                let voting_powers = vec![1; proposers.len()];
                 Box::new(LeaderReputation::new(
                     epoch_state.epoch,
                     proposers,
+                    voting_powers,
                     backend,
                     heuristic,
                     onchain_config.leader_reputation_exclude_round(),
+                    false,
+                ))
+            }
+            ConsensusProposerType::LeaderReputationV2(leader_reputation_config) => {
+                let proposer_window_size = proposers.len()
+                    * leader_reputation_config.proposer_window_num_validators_multiplier;
+                let voter_window_size = proposers.len()
+                    * leader_reputation_config.voter_window_num_validators_multiplier;
+                let backend = Box::new(AptosDBBackend::new(
+                    std::cmp::max(proposer_window_size, voter_window_size),
+                    onchain_config.leader_reputation_exclude_round() + 10,
+                    self.storage.aptos_db(),
+                    onchain_config.max_failed_authors_to_store().is_some(),
+                ));
+                let heuristic = Box::new(ProposerAndVoterHeuristic::new(
+                    self.author,
+                    leader_reputation_config.active_weight,
+                    leader_reputation_config.inactive_weight,
+                    leader_reputation_config.failed_weight,
+                    leader_reputation_config.failure_threshold_percent,
+                    voter_window_size,
+                    proposer_window_size,
+                ));
+                let voting_powers = if leader_reputation_config.weight_by_voting_power {
+                    proposers
+                        .iter()
+                        .map(|p| {
+                            epoch_state
+                                .verifier
+                                .get_voting_power(p)
+                                .expect("proposers come from the validator verifier")
+                        })
+                        .collect()
+                } else {
+                    vec![1; proposers.len()]
+                };
+                Box::new(LeaderReputation::new(
+                    epoch_state.epoch,
+                    proposers,
+                    voting_powers,
+                    backend,
+                    heuristic,
+                    onchain_config.leader_reputation_exclude_round(),
+                    true,
                 ))
             }
             ConsensusProposerType::RoundProposer(round_proposers) => {
@@ -461,6 +510,13 @@ impl EpochManager {
             self.commit_state_computer.clone()
         };
 
+        // the local config can only tighten the on-chain back pressure limit
+        let back_pressure_limit = self
+            .config
+            .back_pressure_limit
+            .map_or(onchain_config.back_pressure_limit(), |limit| {
+                limit.min(onchain_config.back_pressure_limit())
+            });
         info!(epoch = epoch, "Create BlockStore");
         let block_store = Arc::new(BlockStore::new(
             Arc::clone(&self.storage),
@@ -468,7 +524,7 @@ impl EpochManager {
             state_computer,
             self.config.max_pruned_blocks_in_mem,
             Arc::clone(&self.time_service),
-            onchain_config.back_pressure_limit(),
+            back_pressure_limit,
         ));
 
         info!(epoch = epoch, "Create ProposalGenerator");
@@ -480,6 +536,8 @@ impl EpochManager {
             Arc::new(payload_manager),
             self.time_service.clone(),
             self.config.max_block_size,
+            self.config.max_block_gas,
+            onchain_config.max_failed_authors_to_store(),
         );
 
         let mut round_manager = RoundManager::new(
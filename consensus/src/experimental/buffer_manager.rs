@@ -23,6 +23,7 @@ use aptos_types::{
 use consensus_types::{common::Author, executed_block::ExecutedBlock};
 
 use crate::{
+    counters,
     experimental::{
         buffer::{Buffer, Cursor},
         buffer_item::BufferItem,
@@ -150,6 +151,7 @@ impl BufferManager {
             callback,
         } = ordered_blocks;
         debug!("Receive ordered block {}", ordered_proof.commit_info());
+        counters::BUFFER_MANAGER_PENDING_BLOCKS.add(ordered_blocks.len() as i64);
 
         let item = BufferItem::new_ordered(ordered_blocks, ordered_proof, callback);
         self.buffer.push_back(item);
@@ -203,6 +205,7 @@ impl BufferManager {
         let mut blocks_to_persist: Vec<Arc<ExecutedBlock>> = vec![];
 
         while let Some(item) = self.buffer.pop_front() {
+            counters::BUFFER_MANAGER_PENDING_BLOCKS.sub(item.get_blocks().len() as i64);
             blocks_to_persist.extend(
                 item.get_blocks()
                     .iter()
@@ -253,6 +256,7 @@ impl BufferManager {
 
         self.stop = stop;
         self.buffer = Buffer::new();
+        counters::BUFFER_MANAGER_PENDING_BLOCKS.set(0);
         self.execution_root = None;
         self.signing_root = None;
         // Wait for ongoing tasks to finish before sending back ack.
@@ -42,6 +42,8 @@ mod util;
 pub mod consensus_provider;
 /// Required by the telemetry service
 pub mod counters;
+/// Offline inspection of ConsensusDB
+pub mod db_tool;
 /// AptosNet interface.
 pub mod network_interface;
 
@@ -3,52 +3,109 @@
 
 use crate::{
     counters::{
-        COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW, LEADER_REPUTATION_WINDOW_SIZE,
+        COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW, FAILED_PROPOSALS_IN_WINDOW,
+        LEADER_REPUTATION_WINDOW_SIZE,
     },
     liveness::proposer_election::{next, ProposerElection},
 };
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
-use aptos_types::block_metadata::{new_block_event_key, NewBlockEvent};
+use aptos_types::{
+    block_metadata::{
+        new_block_event_key, NewBlockEvent, NewBlockEventV2, NewBlockEventsV2Resource,
+    },
+    event::EventKey,
+    state_store::state_key::StateKey,
+};
 use consensus_types::common::{Author, Round};
-use std::{cmp::Ordering, collections::HashSet, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
 use storage_interface::{DbReader, Order};
 
 /// Interface to query committed BlockMetadata.
 pub trait MetadataBackend: Send + Sync {
     /// Return a contiguous BlockMetadata window in which last one is at target_round or
     /// latest committed, return all previous one if not enough.
-    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent>;
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEventV2>;
 }
 
+/// Reads the committed NewBlockEvents from AptosDB. When `read_failed_proposers` is set (the
+/// on-chain consensus config has blocks record failed proposers), the V2 event stream is read,
+/// otherwise the V1 stream is read and its events carry no failed proposers.
 pub struct AptosDBBackend {
     window_size: usize,
     seek_len: u64,
     aptos_db: Arc<dyn DbReader>,
-    window: Mutex<Vec<(u64, NewBlockEvent)>>,
+    read_failed_proposers: bool,
+    new_block_event_v2_key: Mutex<Option<EventKey>>,
+    window: Mutex<Vec<(u64, NewBlockEventV2)>>,
 }
 
 impl AptosDBBackend {
-    pub fn new(window_size: usize, seek_len: u64, aptos_db: Arc<dyn DbReader>) -> Self {
+    pub fn new(
+        window_size: usize,
+        seek_len: u64,
+        aptos_db: Arc<dyn DbReader>,
+        read_failed_proposers: bool,
+    ) -> Self {
         Self {
             window_size,
             seek_len,
             aptos_db,
+            read_failed_proposers,
+            new_block_event_v2_key: Mutex::new(None),
             window: Mutex::new(vec![]),
         }
     }
 
+    /// Returns the key of the `NewBlockEventV2` handle, or `None` if the handle hasn't been
+    /// published yet (in which case no such events have been emitted).
+    fn get_new_block_event_v2_key(&self) -> anyhow::Result<Option<EventKey>> {
+        if let Some(event_key) = *self.new_block_event_v2_key.lock() {
+            return Ok(Some(event_key));
+        }
+
+        let event_key = self
+            .aptos_db
+            .get_latest_state_value(StateKey::AccessPath(NewBlockEventsV2Resource::access_path()))?
+            .and_then(|state_value| state_value.maybe_bytes)
+            .map(|bytes| bcs::from_bytes::<NewBlockEventsV2Resource>(&bytes))
+            .transpose()?
+            .map(|resource| *resource.new_block_events().key());
+        *self.new_block_event_v2_key.lock() = event_key;
+        Ok(event_key)
+    }
+
     fn refresh_window(&self, target_round: Round) -> anyhow::Result<()> {
+        let event_key = if self.read_failed_proposers {
+            match self.get_new_block_event_v2_key()? {
+                Some(event_key) => event_key,
+                None => {
+                    *self.window.lock() = vec![];
+                    return Ok(());
+                }
+            }
+        } else {
+            new_block_event_key()
+        };
         // assumes target round is not too far from latest commit
         let events = self.aptos_db.get_events(
-            &new_block_event_key(),
+            &event_key,
             u64::max_value(),
             Order::Descending,
             self.window_size as u64 + self.seek_len,
         )?;
         let mut result = vec![];
         for event in events {
-            let e = bcs::from_bytes::<NewBlockEvent>(event.event.event_data())?;
+            let e = if self.read_failed_proposers {
+                bcs::from_bytes::<NewBlockEventV2>(event.event.event_data())?
+            } else {
+                bcs::from_bytes::<NewBlockEvent>(event.event.event_data())?.into()
+            };
             if e.round() <= target_round && result.len() < self.window_size {
                 result.push((event.transaction_version, e));
             }
@@ -60,7 +117,7 @@ impl AptosDBBackend {
 
 impl MetadataBackend for AptosDBBackend {
     // assume the target_round only increases
-    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEventV2> {
         let (known_version, known_round) = self
             .window
             .lock()
@@ -89,8 +146,12 @@ impl MetadataBackend for AptosDBBackend {
 /// Interface to calculate weights for proposers based on history.
 pub trait ReputationHeuristic: Send + Sync {
     /// Return the weights of all candidates based on the history.
-    fn get_weights(&self, epoch: u64, candidates: &[Author], history: &[NewBlockEvent])
-        -> Vec<u64>;
+    fn get_weights(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEventV2],
+    ) -> Vec<u64>;
 }
 
 /// If candidate appear in the history, it's assigned active_weight otherwise inactive weight.
@@ -167,7 +228,7 @@ impl ReputationHeuristic for ActiveInactiveHeuristic {
         &self,
         epoch: u64,
         candidates: &[Author],
-        history: &[NewBlockEvent],
+        history: &[NewBlockEventV2],
     ) -> Vec<u64> {
         let mut committed_proposals: usize = 0;
         let mut committed_votes: usize = 0;
@@ -223,23 +284,175 @@ impl ReputationHeuristic for ActiveInactiveHeuristic {
     }
 }
 
+/// Extends the active/inactive split with the failed proposals recorded in block metadata.
+///
+/// Proposals (successful and failed) are counted over the most recent `proposer_window_size`
+/// events, and votes over the most recent `voter_window_size` events, of the current epoch.
+/// A candidate whose failed proposals exceed `failure_threshold_percent` of its proposal rounds
+/// gets `failed_weight`; otherwise it gets `active_weight` if it proposed or voted in the
+/// windows, and `inactive_weight` if it did neither.
+pub struct ProposerAndVoterHeuristic {
+    author: Author,
+    active_weight: u64,
+    inactive_weight: u64,
+    failed_weight: u64,
+    failure_threshold_percent: u32,
+    voter_window_size: usize,
+    proposer_window_size: usize,
+}
+
+impl ProposerAndVoterHeuristic {
+    pub fn new(
+        author: Author,
+        active_weight: u64,
+        inactive_weight: u64,
+        failed_weight: u64,
+        failure_threshold_percent: u32,
+        voter_window_size: usize,
+        proposer_window_size: usize,
+    ) -> Self {
+        Self {
+            author,
+            active_weight,
+            inactive_weight,
+            failed_weight,
+            failure_threshold_percent,
+            voter_window_size,
+            proposer_window_size,
+        }
+    }
+
+    fn count_votes(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEventV2],
+    ) -> HashMap<Author, u32> {
+        let mut votes = HashMap::new();
+        for meta in history
+            .iter()
+            .take(self.voter_window_size)
+            .filter(|meta| meta.epoch() == epoch)
+        {
+            match ActiveInactiveHeuristic::bitmap_to_voters(candidates, meta.previous_block_votes())
+            {
+                Ok(voters) => {
+                    for &voter in voters {
+                        *votes.entry(voter).or_insert(0) += 1;
+                    }
+                }
+                Err(msg) => {
+                    warn!(
+                        "Voter conversion from bitmap failed at epoch {}, round {}: {}",
+                        meta.epoch(),
+                        meta.round(),
+                        msg
+                    )
+                }
+            }
+        }
+        votes
+    }
+
+    fn count_proposals(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEventV2],
+    ) -> (HashMap<Author, u32>, HashMap<Author, u32>) {
+        let mut proposals = HashMap::new();
+        let mut failed_proposals = HashMap::new();
+        for meta in history
+            .iter()
+            .take(self.proposer_window_size)
+            .filter(|meta| meta.epoch() == epoch)
+        {
+            *proposals.entry(meta.proposer()).or_insert(0) += 1;
+            for &index in meta.failed_proposer_indices() {
+                match usize::try_from(index)
+                    .ok()
+                    .and_then(|index| candidates.get(index))
+                {
+                    Some(&failed_author) => {
+                        *failed_proposals.entry(failed_author).or_insert(0) += 1
+                    }
+                    None => warn!(
+                        "Failed proposer index {} out of bounds at epoch {}, round {}",
+                        index,
+                        meta.epoch(),
+                        meta.round()
+                    ),
+                }
+            }
+        }
+        (proposals, failed_proposals)
+    }
+}
+
+impl ReputationHeuristic for ProposerAndVoterHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEventV2],
+    ) -> Vec<u64> {
+        let votes = self.count_votes(epoch, candidates, history);
+        let (proposals, failed_proposals) = self.count_proposals(epoch, candidates, history);
+
+        COMMITTED_PROPOSALS_IN_WINDOW.set(*proposals.get(&self.author).unwrap_or(&0) as i64);
+        FAILED_PROPOSALS_IN_WINDOW.set(*failed_proposals.get(&self.author).unwrap_or(&0) as i64);
+        COMMITTED_VOTES_IN_WINDOW.set(*votes.get(&self.author).unwrap_or(&0) as i64);
+        LEADER_REPUTATION_WINDOW_SIZE.set(std::cmp::min(
+            history.len(),
+            std::cmp::max(self.proposer_window_size, self.voter_window_size),
+        ) as i64);
+
+        candidates
+            .iter()
+            .map(|author| {
+                let cur_votes = *votes.get(author).unwrap_or(&0);
+                let cur_proposals = *proposals.get(author).unwrap_or(&0);
+                let cur_failed_proposals = *failed_proposals.get(author).unwrap_or(&0);
+
+                if u64::from(cur_failed_proposals) * 100
+                    > u64::from(cur_proposals + cur_failed_proposals)
+                        * u64::from(self.failure_threshold_percent)
+                {
+                    self.failed_weight
+                } else if cur_proposals > 0 || cur_votes > 0 {
+                    self.active_weight
+                } else {
+                    self.inactive_weight
+                }
+            })
+            .collect()
+    }
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
+///
+/// Each proposer's reputation weight is multiplied by its entry in `voting_powers`, and when
+/// `use_epoch_in_seed` is set the election is seeded by (epoch, round) instead of round alone.
 pub struct LeaderReputation {
     epoch: u64,
     proposers: Vec<Author>,
+    voting_powers: Vec<u64>,
     backend: Box<dyn MetadataBackend>,
     heuristic: Box<dyn ReputationHeuristic>,
     exclude_round: u64,
+    use_epoch_in_seed: bool,
 }
 
 impl LeaderReputation {
     pub fn new(
         epoch: u64,
         proposers: Vec<Author>,
+        voting_powers: Vec<u64>,
         backend: Box<dyn MetadataBackend>,
         heuristic: Box<dyn ReputationHeuristic>,
         exclude_round: u64,
+        use_epoch_in_seed: bool,
     ) -> Self {
         // assert!(proposers.is_sorted()) implementation from new api
         assert!(proposers.windows(2).all(|w| {
@@ -247,33 +460,58 @@ impl LeaderReputation {
                 .map(|o| o != Ordering::Greater)
                 .unwrap_or(false)
         }));
+        assert_eq!(proposers.len(), voting_powers.len());
 
         Self {
             epoch,
             proposers,
+            voting_powers,
             backend,
             heuristic,
             exclude_round,
+            use_epoch_in_seed,
         }
     }
+
+    fn seed(&self, round: Round) -> Vec<u8> {
+        let mut state = vec![];
+        if self.use_epoch_in_seed {
+            state.extend_from_slice(&self.epoch.to_le_bytes());
+        }
+        state.extend_from_slice(&round.to_le_bytes());
+        state
+    }
 }
 
 impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
         let target_round = round.saturating_sub(self.exclude_round);
         let sliding_window = self.backend.get_block_metadata(target_round);
-        let mut weights = self
+        let weights = self
             .heuristic
             .get_weights(self.epoch, &self.proposers, &sliding_window);
         assert_eq!(weights.len(), self.proposers.len());
-        let mut total_weight = 0;
-        for w in &mut weights {
-            total_weight += *w;
-            *w = total_weight;
+        let mut total_weight: u128 = 0;
+        let cumulative_weights: Vec<u128> = weights
+            .iter()
+            .zip(self.voting_powers.iter())
+            .map(|(w, voting_power)| {
+                total_weight += u128::from(*w) * u128::from(*voting_power);
+                total_weight
+            })
+            .collect();
+        let mut state = self.seed(round);
+        // with every candidate weighted 0 there is nothing to bias towards, so fall back to a
+        // uniform choice instead of dividing by zero
+        if total_weight == 0 {
+            warn!(
+                "[leader reputation] All proposer weights are 0 at epoch {}, round {}",
+                self.epoch, round
+            );
+            return self.proposers[next(&mut state) as usize % self.proposers.len()];
         }
-        let mut state = round.to_le_bytes().to_vec();
-        let chosen_weight = next(&mut state) % total_weight;
-        let chosen_index = weights
+        let chosen_weight = u128::from(next(&mut state)) % total_weight;
+        let chosen_index = cumulative_weights
             .binary_search_by(|w| {
                 if *w <= chosen_weight {
                     Ordering::Less
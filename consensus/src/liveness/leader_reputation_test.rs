@@ -3,13 +3,14 @@
 
 use crate::liveness::{
     leader_reputation::{
-        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ReputationHeuristic,
+        ActiveInactiveHeuristic, LeaderReputation, MetadataBackend, ProposerAndVoterHeuristic,
+        ReputationHeuristic,
     },
     proposer_election::{next, ProposerElection},
 };
 
 use aptos_types::{
-    account_address::AccountAddress, block_metadata::NewBlockEvent,
+    account_address::AccountAddress, block_metadata::NewBlockEventV2,
     validator_signer::ValidatorSigner,
 };
 use consensus_types::common::{Author, Round};
@@ -17,17 +18,17 @@ use itertools::Itertools;
 
 struct MockHistory {
     window_size: usize,
-    data: Vec<NewBlockEvent>,
+    data: Vec<NewBlockEventV2>,
 }
 
 impl MockHistory {
-    fn new(window_size: usize, data: Vec<NewBlockEvent>) -> Self {
+    fn new(window_size: usize, data: Vec<NewBlockEventV2>) -> Self {
         Self { window_size, data }
     }
 }
 
 impl MetadataBackend for MockHistory {
-    fn get_block_metadata(&self, _target_round: Round) -> Vec<NewBlockEvent> {
+    fn get_block_metadata(&self, _target_round: Round) -> Vec<NewBlockEventV2> {
         let start = if self.data.len() > self.window_size {
             self.data.len() - self.window_size
         } else {
@@ -37,8 +38,17 @@ impl MetadataBackend for MockHistory {
     }
 }
 
-fn create_block(epoch: u64, proposer: Author, voters: Vec<bool>) -> NewBlockEvent {
-    NewBlockEvent::new(epoch, 0, voters, proposer, 0)
+fn create_block(epoch: u64, proposer: Author, voters: Vec<bool>) -> NewBlockEventV2 {
+    create_block_with_failed_proposers(epoch, proposer, voters, vec![])
+}
+
+fn create_block_with_failed_proposers(
+    epoch: u64,
+    proposer: Author,
+    voters: Vec<bool>,
+    failed_proposer_indices: Vec<u64>,
+) -> NewBlockEventV2 {
+    NewBlockEventV2::new(epoch, 0, voters, proposer, failed_proposer_indices, 0)
 }
 
 #[test]
@@ -150,6 +160,7 @@ fn test_api() {
     let leader_reputation = LeaderReputation::new(
         0,
         proposers.clone(),
+        vec![1; proposers.len()],
         Box::new(MockHistory::new(1, history)),
         Box::new(ActiveInactiveHeuristic::new(
             proposers[0],
@@ -157,6 +168,7 @@ fn test_api() {
             inactive_weight,
         )),
         4,
+        false,
     );
     let round = 42u64;
     // first metadata is ignored because of window size 1
@@ -187,3 +199,153 @@ fn test_api() {
     assert!(leader_reputation.is_valid_proposer(proposers[expected_index], 42));
     assert!(!leader_reputation.is_valid_proposer(proposers[unexpected_index], 42));
 }
+
+#[test]
+fn test_proposer_and_voter_heuristic() {
+    let active_weight = 9;
+    let inactive_weight = 2;
+    let failed_weight = 1;
+    let mut proposers = vec![];
+    for i in 0..8 {
+        proposers.push(ValidatorSigner::random([i; 32]).author());
+    }
+    // proposer window of 3, voter window of 1, failures above 10% are penalized
+    let heuristic = ProposerAndVoterHeuristic::new(
+        proposers[0],
+        active_weight,
+        inactive_weight,
+        failed_weight,
+        10,
+        1,
+        3,
+    );
+    // History (most recent first):
+    // [proposer 0, voters 1, failed 5], [proposer 2, voters 3, failed 6], [proposer 5],
+    // [proposer 7, failed 4] which falls out of the proposer window
+    let weights = heuristic.get_weights(
+        0,
+        &proposers,
+        &[
+            create_block_with_failed_proposers(
+                0,
+                proposers[0],
+                vec![false, true, false, false, false, false, false, false],
+                vec![5],
+            ),
+            create_block_with_failed_proposers(
+                0,
+                proposers[2],
+                vec![false, false, false, true, false, false, false, false],
+                vec![6],
+            ),
+            create_block(0, proposers[5], vec![false; 8]),
+            create_block_with_failed_proposers(0, proposers[7], vec![false; 8], vec![4]),
+        ],
+    );
+    assert_eq!(
+        weights,
+        vec![
+            active_weight,   // proposed
+            active_weight,   // voted in the voter window
+            active_weight,   // proposed
+            inactive_weight, // vote is outside of the voter window
+            inactive_weight, // failure is outside of the proposer window
+            failed_weight,   // failed 1 out of 2 rounds
+            failed_weight,   // failed its only round
+            inactive_weight, // proposal is outside of the proposer window
+        ]
+    );
+}
+
+#[test]
+fn test_proposer_and_voter_heuristic_epoch_change() {
+    let mut proposers = vec![];
+    for i in 0..4 {
+        proposers.push(ValidatorSigner::random([i; 32]).author());
+    }
+    let heuristic = ProposerAndVoterHeuristic::new(proposers[0], 9, 2, 1, 10, 10, 10);
+    // Failures recorded in the previous epoch don't count
+    let weights = heuristic.get_weights(
+        1,
+        &proposers,
+        &[
+            create_block(1, proposers[0], vec![false, true, false, false]),
+            create_block_with_failed_proposers(0, proposers[3], vec![false; 4], vec![1, 2]),
+        ],
+    );
+    assert_eq!(weights, vec![9, 9, 2, 2]);
+}
+
+#[test]
+fn test_voting_power_weighting() {
+    let proposers: Vec<AccountAddress> =
+        (0..3).map(|_| AccountAddress::random()).sorted().collect();
+    // Only the last proposer has any voting power, so it is the only one that can be elected
+    // no matter what its reputation weight is.
+    let leader_reputation = LeaderReputation::new(
+        5,
+        proposers.clone(),
+        vec![0, 0, 1],
+        Box::new(MockHistory::new(1, vec![])),
+        Box::new(ActiveInactiveHeuristic::new(proposers[0], 100, 1)),
+        4,
+        true,
+    );
+    for round in 0..20 {
+        assert_eq!(leader_reputation.get_valid_proposer(round), proposers[2]);
+    }
+}
+
+#[test]
+fn test_zero_total_weight() {
+    let proposers: Vec<AccountAddress> =
+        (0..4).map(|_| AccountAddress::random()).sorted().collect();
+    // No proposer has any voting power, so the election falls back to a uniform choice
+    let leader_reputation = LeaderReputation::new(
+        0,
+        proposers.clone(),
+        vec![0; proposers.len()],
+        Box::new(MockHistory::new(1, vec![])),
+        Box::new(ActiveInactiveHeuristic::new(proposers[0], 1, 1)),
+        4,
+        false,
+    );
+    for round in 0..20u64 {
+        let mut state = round.to_le_bytes().to_vec();
+        let expected_index = next(&mut state) as usize % proposers.len();
+        assert_eq!(
+            leader_reputation.get_valid_proposer(round),
+            proposers[expected_index]
+        );
+    }
+}
+
+#[test]
+fn test_epoch_in_seed() {
+    let proposers: Vec<AccountAddress> =
+        (0..10).map(|_| AccountAddress::random()).sorted().collect();
+    let create_election = |epoch| {
+        LeaderReputation::new(
+            epoch,
+            proposers.clone(),
+            vec![1; proposers.len()],
+            Box::new(MockHistory::new(1, vec![])),
+            Box::new(ActiveInactiveHeuristic::new(proposers[0], 1, 1)),
+            4,
+            true,
+        )
+    };
+    let epoch_1 = create_election(1);
+    let epoch_2 = create_election(2);
+    // Same epoch and round always elect the same proposer
+    for round in 0..20 {
+        assert_eq!(
+            epoch_1.get_valid_proposer(round),
+            create_election(1).get_valid_proposer(round)
+        );
+    }
+    // Different epochs produce different schedules
+    assert!(
+        (0..20).any(|round| epoch_1.get_valid_proposer(round) != epoch_2.get_valid_proposer(round))
+    );
+}
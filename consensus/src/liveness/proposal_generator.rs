@@ -2,7 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    block_storage::BlockReader, state_replication::PayloadManager, util::time_service::TimeService,
+    block_storage::BlockReader,
+    liveness::{
+        proposer_election::ProposerElection,
+        unequivocal_proposer_election::UnequivocalProposerElection,
+    },
+    state_replication::PayloadManager,
+    util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
 use consensus_types::{
@@ -43,6 +49,11 @@ pub struct ProposalGenerator {
     time_service: Arc<dyn TimeService>,
     // Max number of transactions to be added to a proposed block.
     max_block_size: u64,
+    // Max sum of the max_gas_amount of the transactions in a proposed block.
+    max_block_gas: Option<u64>,
+    // Max number of failed authors to be added to a proposed block, None if the on-chain
+    // consensus config doesn't have proposals record failed authors.
+    max_failed_authors_to_store: Option<usize>,
     // Last round that a proposal was generated
     last_round_generated: Mutex<Round>,
 }
@@ -54,6 +65,8 @@ impl ProposalGenerator {
         payload_manager: Arc<dyn PayloadManager>,
         time_service: Arc<dyn TimeService>,
         max_block_size: u64,
+        max_block_gas: Option<u64>,
+        max_failed_authors_to_store: Option<usize>,
     ) -> Self {
         Self {
             author,
@@ -61,6 +74,8 @@ impl ProposalGenerator {
             payload_manager,
             time_service,
             max_block_size,
+            max_block_gas,
+            max_failed_authors_to_store,
             last_round_generated: Mutex::new(0),
         }
     }
@@ -85,9 +100,12 @@ impl ProposalGenerator {
     /// 2. The round is provided by the caller.
     /// 3. In case a given round is not greater than the calculated parent, return an OldRound
     /// error.
+    /// 4. The rounds between the parent and the proposal had no certified block, so their
+    /// expected proposers are recorded as failed authors, if the on-chain config enables it.
     pub async fn generate_proposal(
         &mut self,
         round: Round,
+        proposer_election: &UnequivocalProposerElection,
         wait_callback: BoxFuture<'static, ()>,
     ) -> anyhow::Result<BlockData> {
         {
@@ -143,6 +161,7 @@ impl ProposalGenerator {
                 .payload_manager
                 .pull_payload(
                     self.max_block_size,
+                    self.max_block_gas.unwrap_or(u64::MAX),
                     payload_filter,
                     wait_callback,
                     pending_ordering,
@@ -153,14 +172,48 @@ impl ProposalGenerator {
             (payload, timestamp.as_micros() as u64)
         };
 
+        let failed_authors =
+            self.compute_failed_authors(round, hqc.certified_block().round(), proposer_election);
+
         // create block proposal
-        Ok(BlockData::new_proposal(
-            payload,
-            self.author,
-            round,
-            timestamp,
-            hqc.as_ref().clone(),
-        ))
+        Ok(match failed_authors {
+            Some(failed_authors) => BlockData::new_proposal_with_failed_authors(
+                payload,
+                self.author,
+                failed_authors,
+                round,
+                timestamp,
+                hqc.as_ref().clone(),
+            ),
+            None => BlockData::new_proposal(
+                payload,
+                self.author,
+                round,
+                timestamp,
+                hqc.as_ref().clone(),
+            ),
+        })
+    }
+
+    /// Returns the expected proposers of the rounds strictly between `previous_round` and
+    /// `round`, keeping only the most recent `max_failed_authors_to_store` of them, or None if
+    /// proposals don't record failed authors.
+    pub fn compute_failed_authors(
+        &self,
+        round: Round,
+        previous_round: Round,
+        proposer_election: &UnequivocalProposerElection,
+    ) -> Option<Vec<(Round, Author)>> {
+        let max_failed_authors_to_store = self.max_failed_authors_to_store?;
+        let start = std::cmp::max(
+            previous_round + 1,
+            round.saturating_sub(max_failed_authors_to_store as u64),
+        );
+        Some(
+            (start..round)
+                .map(|i| (i, proposer_election.get_valid_proposer(i)))
+                .collect(),
+        )
     }
 
     fn ensure_highest_quorum_cert(&self, round: Round) -> anyhow::Result<Arc<QuorumCert>> {
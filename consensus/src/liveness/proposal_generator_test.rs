@@ -3,12 +3,18 @@
 
 use crate::{
     block_storage::BlockReader,
-    liveness::proposal_generator::ProposalGenerator,
+    liveness::{
+        proposal_generator::ProposalGenerator, rotating_proposer_election::RotatingProposer,
+        unequivocal_proposer_election::UnequivocalProposerElection,
+    },
     test_utils::{build_empty_tree, MockPayloadManager, TreeInserter},
     util::mock_time_service::SimulatedTimeService,
 };
 use aptos_types::validator_signer::ValidatorSigner;
-use consensus_types::block::{block_test_utils::certificate_for_genesis, Block};
+use consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Author,
+};
 use futures::{future::BoxFuture, FutureExt};
 use std::sync::Arc;
 
@@ -16,6 +22,10 @@ fn empty_callback() -> BoxFuture<'static, ()> {
     async move {}.boxed()
 }
 
+fn single_proposer_election(author: Author) -> UnequivocalProposerElection {
+    UnequivocalProposerElection::new(Box::new(RotatingProposer::new(vec![author], 1)))
+}
+
 #[tokio::test]
 async fn test_proposal_generation_empty_tree() {
     let signer = ValidatorSigner::random(None);
@@ -26,22 +36,26 @@ async fn test_proposal_generation_empty_tree() {
         Arc::new(MockPayloadManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        None,
+        Some(10),
     );
+    let proposer_election = single_proposer_election(signer.author());
     let genesis = block_store.ordered_root();
 
     // Generate proposals for an empty tree.
     let proposal_data = proposal_generator
-        .generate_proposal(1, empty_callback())
+        .generate_proposal(1, &proposer_election, empty_callback())
         .await
         .unwrap();
     let proposal = Block::new_proposal_from_block_data(proposal_data, &signer);
     assert_eq!(proposal.parent_id(), genesis.id());
     assert_eq!(proposal.round(), 1);
     assert_eq!(proposal.quorum_cert().certified_block().id(), genesis.id());
+    assert_eq!(proposal.failed_authors(), Some(&vec![]));
 
     // Duplicate proposals on the same round are not allowed
     let proposal_err = proposal_generator
-        .generate_proposal(1, empty_callback())
+        .generate_proposal(1, &proposer_election, empty_callback())
         .await
         .err();
     assert!(proposal_err.is_some());
@@ -57,7 +71,10 @@ async fn test_proposal_generation_parent() {
         Arc::new(MockPayloadManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        None,
+        Some(10),
     );
+    let proposer_election = single_proposer_election(inserter.signer().author());
     let genesis = block_store.ordered_root();
     let a1 = inserter
         .insert_block_with_qc(certificate_for_genesis(), &genesis, 1)
@@ -70,7 +87,7 @@ async fn test_proposal_generation_parent() {
     // generate proposals for an empty tree.
     assert_eq!(
         proposal_generator
-            .generate_proposal(10, empty_callback())
+            .generate_proposal(10, &proposer_election, empty_callback())
             .await
             .unwrap()
             .parent_id(),
@@ -80,7 +97,7 @@ async fn test_proposal_generation_parent() {
     // Once a1 is certified, it should be the one to choose from
     inserter.insert_qc_for_block(a1.as_ref(), None);
     let a1_child_res = proposal_generator
-        .generate_proposal(11, empty_callback())
+        .generate_proposal(11, &proposer_election, empty_callback())
         .await
         .unwrap();
     assert_eq!(a1_child_res.parent_id(), a1.id());
@@ -90,7 +107,7 @@ async fn test_proposal_generation_parent() {
     // Once b1 is certified, it should be the one to choose from
     inserter.insert_qc_for_block(b1.as_ref(), None);
     let b1_child_res = proposal_generator
-        .generate_proposal(12, empty_callback())
+        .generate_proposal(12, &proposer_election, empty_callback())
         .await
         .unwrap();
     assert_eq!(b1_child_res.parent_id(), b1.id());
@@ -108,7 +125,10 @@ async fn test_old_proposal_generation() {
         Arc::new(MockPayloadManager::new(None)),
         Arc::new(SimulatedTimeService::new()),
         1,
+        None,
+        Some(10),
     );
+    let proposer_election = single_proposer_election(inserter.signer().author());
     let genesis = block_store.ordered_root();
     let a1 = inserter
         .insert_block_with_qc(certificate_for_genesis(), &genesis, 1)
@@ -116,8 +136,61 @@ async fn test_old_proposal_generation() {
     inserter.insert_qc_for_block(a1.as_ref(), None);
 
     let proposal_err = proposal_generator
-        .generate_proposal(1, empty_callback())
+        .generate_proposal(1, &proposer_election, empty_callback())
         .await
         .err();
     assert!(proposal_err.is_some());
 }
+
+#[tokio::test]
+async fn test_proposal_generation_failed_authors() {
+    let signer = ValidatorSigner::random(None);
+    let block_store = build_empty_tree();
+    let mut proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store.clone(),
+        Arc::new(MockPayloadManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        1,
+        None,
+        Some(3),
+    );
+    let proposer_election = single_proposer_election(signer.author());
+
+    // Rounds 1 to 5 had no certified block, only the most recent 3 are recorded.
+    let proposal_data = proposal_generator
+        .generate_proposal(6, &proposer_election, empty_callback())
+        .await
+        .unwrap();
+    assert_eq!(
+        proposal_data.failed_authors(),
+        Some(&vec![
+            (3, signer.author()),
+            (4, signer.author()),
+            (5, signer.author())
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_proposal_generation_without_failed_authors() {
+    let signer = ValidatorSigner::random(None);
+    let block_store = build_empty_tree();
+    let mut proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        block_store.clone(),
+        Arc::new(MockPayloadManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        1,
+        None,
+        None,
+    );
+    let proposer_election = single_proposer_election(signer.author());
+
+    // The on-chain config doesn't record failed authors, so the V1 proposal format is used.
+    let proposal_data = proposal_generator
+        .generate_proposal(6, &proposer_election, empty_callback())
+        .await
+        .unwrap();
+    assert_eq!(proposal_data.failed_authors(), None);
+}
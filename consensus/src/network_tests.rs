@@ -41,6 +41,23 @@ use std::{
 };
 use tokio::runtime::Handle;
 
+/// What the playground does with a direct-send message, as decided by a `MessageInterceptor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryAction {
+    /// Deliver the message right away
+    Deliver,
+    /// Drop the message
+    Drop,
+    /// Hold the message back until a message of the given round (or a later one) goes through
+    /// the playground, then deliver it
+    HoldUntilRound(u64),
+}
+
+/// Decides how a message from the first `TwinId` to the second one is handled. The last argument
+/// is the round of the message, for proposals and votes.
+pub type MessageInterceptor =
+    Arc<dyn Fn(&TwinId, &TwinId, &ConsensusMsg, Option<u64>) -> DeliveryAction + Send + Sync>;
+
 /// `TwinId` is used by the NetworkPlayground to uniquely identify
 /// nodes, even if they have the same `AccountAddress` (e.g. for Twins)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -78,6 +95,10 @@ pub struct NetworkPlayground {
     drop_config: Arc<RwLock<DropConfig>>,
     /// Allow test code to drop direct-send messages between peers per round.
     drop_config_round: DropConfigRound,
+    /// Allow test code to drop or delay individual direct-send messages.
+    message_interceptor: Option<MessageInterceptor>,
+    /// Messages held back by the `message_interceptor`, with the round that releases them.
+    held_msgs: Vec<(u64, TwinId, TwinId, PeerManagerNotification)>,
     /// An executor for spawning node outbound network event handlers
     executor: Handle,
     /// Maps authors to twins IDs
@@ -97,6 +118,8 @@ impl NetworkPlayground {
             outbound_msgs_rx,
             drop_config: Arc::new(RwLock::new(DropConfig::default())),
             drop_config_round: DropConfigRound::default(),
+            message_interceptor: None,
+            held_msgs: vec![],
             executor,
             author_to_twin_ids: Arc::new(RwLock::new(AuthorToTwinIds::default())),
             peer_metadata_storage: PeerMetadataStorage::new(&[NetworkId::Validator]),
@@ -237,10 +260,11 @@ impl NetworkPlayground {
                 msg_notif
             ),
         };
-        let _ = node_consensus_tx.push(
-            (src_twin_id.author, ProtocolId::ConsensusDirectSendBcs),
-            msg_notif,
-        );
+        let protocol_id = match &msg_notif {
+            PeerManagerNotification::RecvMessage(_, msg) => msg.protocol_id,
+            _ => unreachable!(),
+        };
+        let _ = node_consensus_tx.push((src_twin_id.author, protocol_id), msg_notif);
         msg_copy
     }
 
@@ -372,6 +396,33 @@ impl NetworkPlayground {
         ret
     }
 
+    /// Installs `interceptor` to decide the fate of every direct-send message that isn't already
+    /// dropped by a partition. Only applies to messages delivered by `start`.
+    pub fn set_message_interceptor(&mut self, interceptor: MessageInterceptor) {
+        self.message_interceptor = Some(interceptor);
+    }
+
+    fn intercept(&self, src: &TwinId, dst: &TwinId, msg: &ConsensusMsg) -> DeliveryAction {
+        self.message_interceptor
+            .as_ref()
+            .map_or(DeliveryAction::Deliver, |interceptor| {
+                interceptor(src, dst, msg, Self::get_message_round(msg.clone()))
+            })
+    }
+
+    /// Delivers the held back messages released by a message of `round`, in the order they were
+    /// sent.
+    async fn release_held_messages(&mut self, round: u64) {
+        let (released, held) = std::mem::take(&mut self.held_msgs)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(release_round, ..)| *release_round <= round);
+        self.held_msgs = held;
+        for (_, src_twin_id, dst_twin_id, msg_notif) in released {
+            self.deliver_message(src_twin_id, dst_twin_id, msg_notif)
+                .await;
+        }
+    }
+
     pub async fn start(mut self) {
         // Take the next queued message
         while let Some((src_twin_id, net_req)) = self.outbound_msgs_rx.next().await {
@@ -388,16 +439,29 @@ impl NetworkPlayground {
             };
 
             let dst_twin_ids = self.get_twin_ids(dst);
+            let consensus_msg: ConsensusMsg = msg.to_message().unwrap();
+            if let Some(round) = Self::get_message_round(consensus_msg.clone()) {
+                self.release_held_messages(round).await;
+            }
 
             for dst_twin_id in dst_twin_ids.iter() {
                 let msg_notif =
                     PeerManagerNotification::RecvMessage(src_twin_id.author, msg.clone());
-                let consensus_msg = msg.to_message().unwrap();
 
                 // Deliver and copy message it if it's not dropped
-                if !self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg) {
-                    self.deliver_message(src_twin_id, *dst_twin_id, msg_notif)
-                        .await;
+                if self.is_message_dropped(&src_twin_id, dst_twin_id, consensus_msg.clone()) {
+                    continue;
+                }
+                match self.intercept(&src_twin_id, dst_twin_id, &consensus_msg) {
+                    DeliveryAction::Deliver => {
+                        self.deliver_message(src_twin_id, *dst_twin_id, msg_notif)
+                            .await;
+                    }
+                    DeliveryAction::Drop => (),
+                    DeliveryAction::HoldUntilRound(round) => {
+                        self.held_msgs
+                            .push((round, src_twin_id, *dst_twin_id, msg_notif));
+                    }
                 }
             }
         }
@@ -48,10 +48,16 @@ impl QuorumStoreClient {
     async fn pull_internal(
         &self,
         max_size: u64,
+        max_gas: u64,
         exclude_payloads: PayloadFilter,
     ) -> Result<Payload, QuorumStoreError> {
         let (callback, callback_rcv) = oneshot::channel();
-        let req = ConsensusRequest::GetBlockRequest(max_size, exclude_payloads.clone(), callback);
+        let req = ConsensusRequest::GetBlockRequest(
+            max_size,
+            max_gas,
+            exclude_payloads.clone(),
+            callback,
+        );
         // send to shared mempool
         self.consensus_to_quorum_store_sender
             .clone()
@@ -80,6 +86,7 @@ impl PayloadManager for QuorumStoreClient {
     async fn pull_payload(
         &self,
         max_size: u64,
+        max_gas: u64,
         exclude_payloads: PayloadFilter,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
@@ -93,7 +100,7 @@ impl PayloadManager for QuorumStoreClient {
         let payload = loop {
             count -= 1;
             let payload = self
-                .pull_internal(max_size, exclude_payloads.clone())
+                .pull_internal(max_size, max_gas, exclude_payloads.clone())
                 .await?;
             if payload.is_empty() && !pending_ordering && count > 0 {
                 if let Some(callback) = callback_wrapper.take() {
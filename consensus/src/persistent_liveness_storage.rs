@@ -285,6 +285,63 @@ impl RecoveryData {
     }
 }
 
+/// Reads the raw ConsensusDB contents and the latest committed ledger state and computes the data
+/// consensus starts from, without modifying either store. The inner result is an error if the
+/// consensus block tree can't be reconstructed on top of the committed ledger info.
+pub(crate) fn read_recovery_data(
+    db: &ConsensusDB,
+    aptos_db: &Arc<dyn DbReader>,
+) -> Result<(LedgerRecoveryData, Result<RecoveryData>)> {
+    let (last_vote, highest_2chain_timeout_cert, blocks, quorum_certs) = db.get_data()?;
+
+    let last_vote = last_vote
+        .map(|bytes| bcs::from_bytes(&bytes[..]).context("unable to deserialize last vote"))
+        .transpose()?;
+    let highest_2chain_timeout_cert = highest_2chain_timeout_cert
+        .map(|b| bcs::from_bytes(&b).context("unable to deserialize highest 2-chain timeout cert"))
+        .transpose()?;
+    let blocks_repr: Vec<String> = blocks.iter().map(|b| format!("\n\t{}", b)).collect();
+    info!(
+        "The following blocks were restored from ConsensusDB : {}",
+        blocks_repr.concat()
+    );
+    let qc_repr: Vec<String> = quorum_certs
+        .iter()
+        .map(|qc| format!("\n\t{}", qc))
+        .collect();
+    info!(
+        "The following quorum certs were restored from ConsensusDB: {}",
+        qc_repr.concat()
+    );
+
+    // find the block corresponding to storage latest ledger info
+    let startup_info = aptos_db
+        .get_startup_info()?
+        .ok_or_else(|| format_err!("startup info is None"))?;
+    let ledger_recovery_data = LedgerRecoveryData::new(startup_info.latest_ledger_info.clone());
+    let frozen_root_hashes = startup_info
+        .committed_tree_state
+        .ledger_frozen_subtree_hashes
+        .clone();
+    let root_executed_trees = startup_info
+        .committed_tree_state
+        .into_ledger_view(aptos_db)
+        .context("Failed to construct committed ledger view.")?;
+    let recovery_data = RecoveryData::new(
+        last_vote,
+        ledger_recovery_data.clone(),
+        blocks,
+        RootMetadata::new(
+            root_executed_trees.txn_accumulator().num_leaves(),
+            root_executed_trees.state_id(),
+            frozen_root_hashes,
+        ),
+        quorum_certs,
+        highest_2chain_timeout_cert,
+    );
+    Ok((ledger_recovery_data, recovery_data))
+}
+
 /// The proxy we use to persist data in db storage service via grpc.
 pub struct StorageWriteProxy {
     db: Arc<ConsensusDB>,
@@ -329,61 +386,9 @@ impl PersistentLivenessStorage for StorageWriteProxy {
 
     fn start(&self) -> LivenessStorageData {
         info!("Start consensus recovery.");
-        let raw_data = self
-            .db
-            .get_data()
-            .expect("unable to recover consensus data");
-
-        let last_vote = raw_data
-            .0
-            .map(|bytes| bcs::from_bytes(&bytes[..]).expect("unable to deserialize last vote"));
-
-        let highest_2chain_timeout_cert = raw_data.1.map(|b| {
-            bcs::from_bytes(&b).expect("unable to deserialize highest 2-chain timeout cert")
-        });
-        let blocks = raw_data.2;
-        let quorum_certs: Vec<_> = raw_data.3;
-        let blocks_repr: Vec<String> = blocks.iter().map(|b| format!("\n\t{}", b)).collect();
-        info!(
-            "The following blocks were restored from ConsensusDB : {}",
-            blocks_repr.concat()
-        );
-        let qc_repr: Vec<String> = quorum_certs
-            .iter()
-            .map(|qc| format!("\n\t{}", qc))
-            .collect();
-        info!(
-            "The following quorum certs were restored from ConsensusDB: {}",
-            qc_repr.concat()
-        );
-
-        // find the block corresponding to storage latest ledger info
-        let startup_info = self
-            .aptos_db
-            .get_startup_info()
-            .expect("unable to read ledger info from storage")
-            .expect("startup info is None");
-        let ledger_recovery_data = LedgerRecoveryData::new(startup_info.latest_ledger_info.clone());
-        let frozen_root_hashes = startup_info
-            .committed_tree_state
-            .ledger_frozen_subtree_hashes
-            .clone();
-        let root_executed_trees = startup_info
-            .committed_tree_state
-            .into_ledger_view(&self.aptos_db)
-            .expect("Failed to construct committed ledger view.");
-        match RecoveryData::new(
-            last_vote,
-            ledger_recovery_data.clone(),
-            blocks,
-            RootMetadata::new(
-                root_executed_trees.txn_accumulator().num_leaves(),
-                root_executed_trees.state_id(),
-                frozen_root_hashes,
-            ),
-            quorum_certs,
-            highest_2chain_timeout_cert,
-        ) {
+        let (ledger_recovery_data, recovery_data) =
+            read_recovery_data(&self.db, &self.aptos_db).expect("unable to recover consensus data");
+        match recovery_data {
             Ok(mut initial_data) => {
                 (self as &dyn PersistentLivenessStorage)
                     .prune_tree(initial_data.take_blocks_to_prune())
@@ -43,10 +43,11 @@ impl DirectMempoolQuorumStore {
     async fn pull_internal(
         &self,
         max_size: u64,
+        max_gas: u64,
         exclude_txns: Vec<TransactionSummary>,
     ) -> Result<Vec<SignedTransaction>, anyhow::Error> {
         let (callback, callback_rcv) = oneshot::channel();
-        let msg = QuorumStoreRequest::GetBatchRequest(max_size, exclude_txns, callback);
+        let msg = QuorumStoreRequest::GetBatchRequest(max_size, max_gas, exclude_txns, callback);
         self.mempool_sender
             .clone()
             .try_send(msg)
@@ -75,13 +76,14 @@ impl DirectMempoolQuorumStore {
     async fn handle_block_request(
         &self,
         max_size: u64,
+        max_gas: u64,
         payload_filter: PayloadFilter,
         callback: oneshot::Sender<Result<ConsensusResponse>>,
     ) {
         let get_batch_start_time = Instant::now();
         let (txns, result) = match payload_filter {
             PayloadFilter::DirectMempool(exclude_txns) => {
-                match self.pull_internal(max_size, exclude_txns).await {
+                match self.pull_internal(max_size, max_gas, exclude_txns).await {
                     Err(_) => {
                         error!("GetBatch failed");
                         (vec![], counters::REQUEST_FAIL_LABEL)
@@ -127,8 +129,8 @@ impl DirectMempoolQuorumStore {
 
     async fn handle_consensus_request(&self, req: ConsensusRequest) {
         match req {
-            ConsensusRequest::GetBlockRequest(max_size, payload_filter, callback) => {
-                self.handle_block_request(max_size, payload_filter, callback)
+            ConsensusRequest::GetBlockRequest(max_size, max_gas, payload_filter, callback) => {
+                self.handle_block_request(max_size, max_gas, payload_filter, callback)
                     .await;
             }
             ConsensusRequest::CleanRequest(_, _, callback) => {
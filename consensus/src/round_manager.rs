@@ -187,10 +187,6 @@ impl RoundManager {
         self.onchain_config.decoupled_execution()
     }
 
-    fn back_pressure_limit(&self) -> u64 {
-        self.onchain_config.back_pressure_limit()
-    }
-
     fn create_block_retriever(&self, author: Author) -> BlockRetriever {
         BlockRetriever::new(self.network.clone(), author)
     }
@@ -229,6 +225,17 @@ impl RoundManager {
             .proposer_election
             .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
         {
+            if self.back_pressure() {
+                counters::BACK_PRESSURE_SKIPPED_COUNT
+                    .with_label_values(&["proposal"])
+                    .inc();
+                warn!(
+                    self.new_log(LogEvent::Propose),
+                    "Skip proposing: {} rounds are ordered but not committed yet",
+                    self.block_store.pending_commit_rounds()
+                );
+                return Ok(());
+            }
             let proposal_msg = Box::new(self.generate_proposal(new_round_event).await?);
             let mut network = self.network.clone();
             #[cfg(feature = "failpoints")]
@@ -259,7 +266,7 @@ impl RoundManager {
         .boxed();
         let proposal = self
             .proposal_generator
-            .generate_proposal(new_round_event.round, callback)
+            .generate_proposal(new_round_event.round, &self.proposer_election, callback)
             .await?;
         let signature = self.safety_rules.lock().sign_proposal(&proposal)?;
         let signed_proposal =
@@ -413,25 +420,26 @@ impl RoundManager {
         Ok(())
     }
 
-    fn sync_only(&self) -> bool {
-        if self.decoupled_execution() {
-            let commit_round = self.block_store.commit_root().round();
-            let ordered_round = self.block_store.ordered_root().round();
-            let sync_or_not =
-                self.sync_only || ordered_round > self.back_pressure_limit() + commit_round;
-
-            counters::OP_COUNTERS
-                .gauge("sync_only")
-                .set(sync_or_not as i64);
+    /// With decoupled execution, ordering can run ahead of the execution pipeline. Once too many
+    /// rounds are ordered but not committed, the node stops proposing and voting until execution
+    /// catches up, which bounds the blocks buffered in the pipeline.
+    fn back_pressure(&self) -> bool {
+        if !self.decoupled_execution() {
+            return false;
+        }
+        let pending_commit_rounds = self.block_store.pending_commit_rounds();
+        let back_pressure = self.block_store.back_pressure();
 
-            counters::OP_COUNTERS
-                .gauge("back_pressure")
-                .set((ordered_round - commit_round) as i64);
+        counters::OP_COUNTERS
+            .gauge("sync_only")
+            .set((self.sync_only || back_pressure) as i64);
+        counters::OP_COUNTERS
+            .gauge("back_pressure")
+            .set(pending_commit_rounds as i64);
+        counters::PENDING_COMMIT_ROUNDS.set(pending_commit_rounds as i64);
+        counters::BACK_PRESSURE.set(back_pressure as i64);
 
-            sync_or_not
-        } else {
-            self.sync_only
-        }
+        back_pressure
     }
 
     /// The replica broadcasts a "timeout vote message", which includes the round signature, which
@@ -447,13 +455,19 @@ impl RoundManager {
             return Ok(());
         }
 
-        if self.sync_only() {
+        let back_pressure = self.back_pressure();
+        if self.sync_only || back_pressure {
+            if back_pressure {
+                counters::BACK_PRESSURE_SKIPPED_COUNT
+                    .with_label_values(&["timeout"])
+                    .inc();
+            }
             self.network
                 .broadcast(ConsensusMsg::SyncInfo(Box::new(
                     self.block_store.sync_info(),
                 )))
                 .await;
-            bail!("[RoundManager] sync_only flag is set, broadcasting SyncInfo");
+            bail!("[RoundManager] sync_only flag is set or back pressured, broadcasting SyncInfo");
         }
 
         let (is_nil_vote, mut timeout_vote) = match self.round_state.vote_sent() {
@@ -529,6 +543,19 @@ impl RoundManager {
             proposal,
         );
 
+        let expected_failed_authors = self.proposal_generator.compute_failed_authors(
+            proposal.round(),
+            proposal.quorum_cert().certified_block().round(),
+            &self.proposer_election,
+        );
+        ensure!(
+            proposal.failed_authors() == expected_failed_authors.as_ref(),
+            "[RoundManager] Proposal for block {} has incorrect failed authors {:?}, expected {:?}",
+            proposal.round(),
+            proposal.failed_authors(),
+            expected_failed_authors,
+        );
+
         let block_time_since_epoch = Duration::from_micros(proposal.timestamp_usecs());
 
         ensure!(
@@ -580,9 +607,15 @@ impl RoundManager {
             self.round_state.current_round()
         );
 
+        let back_pressure = self.back_pressure();
+        if back_pressure {
+            counters::BACK_PRESSURE_SKIPPED_COUNT
+                .with_label_values(&["vote"])
+                .inc();
+        }
         ensure!(
-            !self.sync_only(),
-            "[RoundManager] sync_only flag is set, stop voting"
+            !self.sync_only && !back_pressure,
+            "[RoundManager] sync_only flag is set or back pressured, stop voting"
         );
 
         let maybe_signed_vote_proposal =
@@ -145,6 +145,8 @@ fn create_node_for_fuzzing() -> RoundManager {
         Arc::new(MockPayloadManager::new(None)),
         time_service,
         1,
+        None,
+        None,
     );
 
     //
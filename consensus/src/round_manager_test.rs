@@ -15,9 +15,10 @@ use crate::{
     network_tests::{NetworkPlayground, TwinId},
     persistent_liveness_storage::RecoveryData,
     round_manager::RoundManager,
+    state_replication::StateComputer,
     test_utils::{
-        consensus_runtime, timed_block_on, MockPayloadManager, MockStateComputer, MockStorage,
-        TreeInserter,
+        consensus_runtime, timed_block_on, EmptyStateComputer, MockPayloadManager,
+        MockStateComputer, MockStorage, TreeInserter,
     },
     util::time_service::{ClockTimeService, TimeService},
 };
@@ -41,7 +42,7 @@ use consensus_types::{
         Block,
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
-    common::{Author, Payload},
+    common::{Author, Payload, Round},
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
     timeout_2chain::{TwoChainTimeout, TwoChainTimeoutCertificate},
@@ -96,6 +97,17 @@ impl NodeSetup {
         playground: &mut NetworkPlayground,
         executor: Handle,
         num_nodes: usize,
+    ) -> Vec<Self> {
+        Self::create_nodes_with_back_pressure_limit(playground, executor, num_nodes, None)
+    }
+
+    /// With a `back_pressure_limit`, the nodes never commit the blocks they order, so ordering
+    /// runs ahead of commit until the limit is hit.
+    fn create_nodes_with_back_pressure_limit(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+        back_pressure_limit: Option<Round>,
     ) -> Vec<Self> {
         let (signers, validators) = random_validator_verifier(num_nodes, None, false);
         let proposer_author = signers[0].author();
@@ -137,6 +149,7 @@ impl NodeSetup {
                 initial_data,
                 safety_rules_manager,
                 id,
+                back_pressure_limit,
             ));
         }
         nodes
@@ -151,6 +164,7 @@ impl NodeSetup {
         initial_data: RecoveryData,
         safety_rules_manager: SafetyRulesManager,
         id: usize,
+        back_pressure_limit: Option<Round>,
     ) -> Self {
         let epoch_state = EpochState {
             epoch: 1,
@@ -182,11 +196,18 @@ impl NodeSetup {
         let last_vote_sent = initial_data.last_vote();
         let (commit_cb_sender, commit_cb_receiver) = mpsc::unbounded::<LedgerInfoWithSignatures>();
         let (state_sync_client, _state_sync_receiver) = mpsc::unbounded();
-        let state_computer = Arc::new(MockStateComputer::new(
-            state_sync_client,
-            commit_cb_sender,
-            Arc::clone(&storage),
-        ));
+        let (state_computer, back_pressure_limit): (Arc<dyn StateComputer>, Round) =
+            match back_pressure_limit {
+                Some(limit) => (Arc::new(EmptyStateComputer), limit),
+                None => (
+                    Arc::new(MockStateComputer::new(
+                        state_sync_client,
+                        commit_cb_sender,
+                        Arc::clone(&storage),
+                    )),
+                    10,
+                ),
+            };
         let time_service = Arc::new(ClockTimeService::new(executor));
 
         let block_store = Arc::new(BlockStore::new(
@@ -195,7 +216,7 @@ impl NodeSetup {
             state_computer,
             10, // max pruned blocks in mem
             time_service.clone(),
-            10,
+            back_pressure_limit,
         ));
 
         let proposal_generator = ProposalGenerator::new(
@@ -204,6 +225,8 @@ impl NodeSetup {
             Arc::new(MockPayloadManager::new(None)),
             time_service.clone(),
             1,
+            None,
+            None,
         );
 
         let round_state = Self::create_round_state(time_service);
@@ -253,6 +276,7 @@ impl NodeSetup {
             recover_data,
             self.safety_rules_manager,
             self.id,
+            None,
         )
     }
 
@@ -954,3 +978,46 @@ fn safety_rules_crash() {
         node.next_proposal().await;
     });
 }
+
+#[test]
+/// Once more rounds are ordered than the back pressure limit allows ahead of commit, the leader
+/// stops proposing and the node only broadcasts SyncInfo on timeout.
+fn no_proposal_or_timeout_vote_under_back_pressure() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_back_pressure_limit(
+        &mut playground,
+        runtime.handle().clone(),
+        1,
+        Some(1),
+    );
+    let node = &mut nodes[0];
+    let genesis = node.block_store.commit_root();
+    timed_block_on(&mut runtime, async {
+        // order blocks of rounds 1 and 2, which are never committed
+        for round in 1..=3 {
+            let proposal_msg = node.next_proposal().await;
+            assert_eq!(proposal_msg.proposal().round(), round);
+            node.round_manager
+                .process_proposal_msg(proposal_msg)
+                .await
+                .unwrap();
+            let vote_msg = node.next_vote().await;
+            node.round_manager.process_vote_msg(vote_msg).await.unwrap();
+        }
+        assert_eq!(node.block_store.ordered_root().round(), 2);
+        assert_eq!(node.block_store.commit_root().id(), genesis.id());
+        assert!(node.block_store.back_pressure());
+
+        // round 4 started without a proposal, so the first message is the SyncInfo sent instead
+        // of a timeout vote
+        node.round_manager
+            .process_local_timeout(4)
+            .await
+            .unwrap_err();
+        match node.next_message().await {
+            ConsensusMsg::SyncInfo(sync_info) => assert_eq!(sync_info.highest_round(), 3),
+            msg => panic!("Unexpected Consensus Message: {:?}", msg),
+        }
+    });
+}
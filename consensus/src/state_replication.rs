@@ -22,6 +22,7 @@ pub trait PayloadManager: Send + Sync {
     async fn pull_payload(
         &self,
         max_size: u64,
+        max_gas: u64,
         exclude: PayloadFilter,
         wait_callback: BoxFuture<'static, ()>,
         pending_ordering: bool,
@@ -0,0 +1,83 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::twins::scenario::Scenario;
+use std::time::Duration;
+
+const SCENARIO_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn assert_safe_and_live(scenario: &Scenario) {
+    let outcome = scenario.run(SCENARIO_TIMEOUT);
+    if let Err(e) = outcome.check_safety() {
+        panic!("[TwinsTest] Safety violation in {:?}: {}", scenario, e);
+    }
+    if let Err(e) = outcome.check_liveness() {
+        panic!("[TwinsTest] Liveness violation in {:?}: {}", scenario, e);
+    }
+}
+
+#[test]
+/// This test checks that an equivocating leader can't get two blocks committed
+///
+/// Setup:
+///
+/// 4 nodes (n0, n1, n2, n3), and 1 twin (twin0)
+/// n0 and twin0 lead rounds 1 to 3 from two partitions, p1=[n0, n1, n2], p2=[n3, twin0]
+///
+/// Test:
+///
+/// No conflicting commits happen, and all nodes commit once the partitions heal
+///
+/// Run the test:
+/// cargo xtest -p consensus equivocating_leader_test -- --nocapture
+fn equivocating_leader_test() {
+    let mut scenario = Scenario::new(4, 1);
+    for round in 1..=3 {
+        scenario = scenario
+            .leader(round, 0)
+            .partition(round, vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+    assert_safe_and_live(&scenario);
+}
+
+#[test]
+/// This test checks that consensus makes progress after rounds without a quorum of votes
+///
+/// Setup:
+///
+/// 4 nodes (n0, n1, n2, n3), and 1 twin (twin0)
+/// n1 and n2 withhold their votes in round 1, n3's messages of round 2 are held back until
+/// round 4, and n0 equivocates in round 3 while twin0 withholds its votes
+///
+/// Test:
+///
+/// No conflicting commits happen, and all nodes commit once the faults stop
+///
+/// Run the test:
+/// cargo xtest -p consensus withheld_votes_and_delays_test -- --nocapture
+fn withheld_votes_and_delays_test() {
+    let scenario = Scenario::new(4, 1)
+        .leader(1, 3)
+        .withhold_votes(1, 1)
+        .withhold_votes(1, 2)
+        .leader(2, 1)
+        .delay(2, 3, 2)
+        .leader(3, 0)
+        .withhold_votes(3, 4);
+    assert_safe_and_live(&scenario);
+}
+
+#[test]
+/// This test runs randomly generated scenarios and checks safety and liveness for each of them
+///
+/// Setup:
+///
+/// 4 nodes and 1 twin, with 5 rounds of random leaders, partitions, withheld votes and delays
+///
+/// Run the test:
+/// cargo xtest -p consensus randomized_twins_test -- --nocapture
+fn randomized_twins_test() {
+    for seed in 0..3 {
+        assert_safe_and_live(&Scenario::random(seed, 4, 1, 5));
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small DSL for describing Byzantine behaviour in twins tests, and a runner that executes a
+//! scenario on `SMRNode`s connected through the `NetworkPlayground`.
+//!
+//! Nodes are referred to by their index in the list returned by
+//! `SMRNode::start_num_nodes_with_twins`: `0..num_nodes` are the validators (ordered by author)
+//! and `num_nodes + i` is the twin of validator `i`, for `i < num_twins`. A twin shares the keys of
+//! its validator, so a twinned leader equivocates and a twinned voter can vote for two blocks.
+//!
+//! The faulty rounds of a scenario are followed by `STABLE_ROUNDS` fault-free rounds led by
+//! validators without twins, so that liveness can be checked once the network stabilizes.
+
+use crate::{
+    liveness::proposer_election::next,
+    network_interface::ConsensusMsg,
+    network_tests::{DeliveryAction, NetworkPlayground, TwinId},
+    test_utils::consensus_runtime,
+    twins::twins_node::SMRNode,
+};
+use aptos_config::config::ConsensusProposerType::RoundProposer;
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use consensus_types::common::Round;
+use futures::{stream::select_all, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// Index of a node (validator or twin) in a scenario.
+pub type NodeIdx = usize;
+
+/// Initial round timeout of the nodes, low enough for them to recover from faulty rounds.
+const ROUND_TIMEOUT_MS: u64 = 1_000;
+/// Number of fault-free rounds with honest leaders following the faulty ones.
+const STABLE_ROUNDS: Round = 20;
+
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    num_nodes: usize,
+    num_twins: usize,
+    leaders: HashMap<Round, NodeIdx>,
+    partitions: HashMap<Round, Vec<Vec<NodeIdx>>>,
+    withheld_votes: HashSet<(Round, NodeIdx)>,
+    delays: HashMap<(Round, NodeIdx), Round>,
+}
+
+impl Scenario {
+    pub fn new(num_nodes: usize, num_twins: usize) -> Self {
+        assert!(
+            num_nodes > num_twins,
+            "at least one validator must be without a twin to lead the stable rounds"
+        );
+        Self {
+            num_nodes,
+            num_twins,
+            leaders: HashMap::new(),
+            partitions: HashMap::new(),
+            withheld_votes: HashSet::new(),
+            delays: HashMap::new(),
+        }
+    }
+
+    /// Generates a scenario with `num_faulty_rounds` rounds of random leaders, partitions,
+    /// withheld votes and delays, deterministically from `seed`.
+    pub fn random(seed: u64, num_nodes: usize, num_twins: usize, num_faulty_rounds: Round) -> Self {
+        let mut state = seed.to_le_bytes().to_vec();
+        let mut pick = |bound: usize| (next(&mut state) % bound as u64) as usize;
+        let num_all_nodes = num_nodes + num_twins;
+
+        let mut scenario = Self::new(num_nodes, num_twins);
+        for round in 1..=num_faulty_rounds {
+            scenario = scenario.leader(round, pick(num_nodes));
+            scenario = match pick(4) {
+                0 => scenario,
+                1 => {
+                    let mut partitions = vec![vec![], vec![]];
+                    for node in 0..num_all_nodes {
+                        partitions[pick(2)].push(node);
+                    }
+                    partitions.retain(|p| !p.is_empty());
+                    scenario.partition(round, partitions)
+                }
+                2 => scenario.withhold_votes(round, pick(num_all_nodes)),
+                _ => {
+                    let delay = pick(3) as Round + 1;
+                    scenario.delay(round, pick(num_all_nodes), delay)
+                }
+            };
+        }
+        scenario
+    }
+
+    /// Validator `leader` proposes in `round`. If it has a twin, the twin proposes a block of
+    /// its own in the same round, so picking a validator below `num_twins` makes the leader
+    /// equivocate.
+    pub fn leader(mut self, round: Round, leader: NodeIdx) -> Self {
+        assert!(leader < self.num_nodes, "leaders are validators, not twins");
+        self.leaders.insert(round, leader);
+        self
+    }
+
+    /// Proposals and votes of `round` are only delivered within each partition. Nodes missing
+    /// from `partitions` stay connected to everyone.
+    pub fn partition(mut self, round: Round, partitions: Vec<Vec<NodeIdx>>) -> Self {
+        self.partitions.insert(round, partitions);
+        self
+    }
+
+    /// `node` doesn't send its votes for `round`.
+    pub fn withhold_votes(mut self, round: Round, node: NodeIdx) -> Self {
+        self.withheld_votes.insert((round, node));
+        self
+    }
+
+    /// The proposals and votes of `round` sent by `node` are held back until a proposal or
+    /// vote of `round + delay` is sent.
+    pub fn delay(mut self, round: Round, node: NodeIdx, delay: Round) -> Self {
+        assert!(
+            delay > 0,
+            "messages can only be held back until a later round"
+        );
+        self.delays.insert((round, node), delay);
+        self
+    }
+
+    /// The last round in which any fault is injected.
+    pub fn last_faulty_round(&self) -> Round {
+        self.leaders
+            .keys()
+            .chain(self.partitions.keys())
+            .chain(self.withheld_votes.iter().map(|(round, _)| round))
+            .chain(self.delays.keys().map(|(round, _)| round))
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Leaders of the faulty rounds, followed by validators without a twin in rotation.
+    fn round_leaders(&self) -> HashMap<Round, NodeIdx> {
+        let num_honest_leaders = (self.num_nodes - self.num_twins) as u64;
+        (1..=self.last_faulty_round() + STABLE_ROUNDS)
+            .map(|round| {
+                let honest_leader = self.num_twins + (round % num_honest_leaders) as usize;
+                (round, *self.leaders.get(&round).unwrap_or(&honest_leader))
+            })
+            .collect()
+    }
+
+    /// Runs the scenario until every node commits a block past the last faulty round, or until
+    /// `timeout` elapses, and returns the commits observed by each node along with the parent
+    /// links of the blocks voted on during the run.
+    pub fn run(&self, timeout: Duration) -> ScenarioOutcome {
+        let runtime = consensus_runtime();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let mut nodes = SMRNode::start_num_nodes_with_twins_and_round_timeout(
+            self.num_nodes,
+            self.num_twins,
+            &mut playground,
+            RoundProposer(HashMap::new()),
+            Some(self.round_leaders()),
+            ROUND_TIMEOUT_MS,
+        );
+        let twin_ids: Vec<TwinId> = nodes.iter().map(|node| node.id).collect();
+
+        let round_partitions: HashMap<u64, Vec<Vec<TwinId>>> = self
+            .partitions
+            .iter()
+            .map(|(round, partitions)| {
+                let partitions = partitions
+                    .iter()
+                    .map(|p| p.iter().map(|node| twin_ids[*node]).collect())
+                    .collect();
+                (*round, partitions)
+            })
+            .collect();
+        assert!(playground.split_network_round(&round_partitions));
+
+        let withheld_votes: HashSet<(Round, TwinId)> = self
+            .withheld_votes
+            .iter()
+            .map(|(round, node)| (*round, twin_ids[*node]))
+            .collect();
+        let delays: HashMap<(Round, TwinId), Round> = self
+            .delays
+            .iter()
+            .map(|((round, node), delay)| ((*round, twin_ids[*node]), *delay))
+            .collect();
+        // Every committed block (NIL blocks included) was voted on by a quorum, so the votes and
+        // quorum certs going through the playground are enough to link each committed block to
+        // its parent.
+        let blocks = Arc::new(Mutex::new(HashMap::new()));
+        let voted_blocks = blocks.clone();
+        playground.set_message_interceptor(Arc::new(move |src, _dst, msg, round| {
+            let vote_data = match msg {
+                ConsensusMsg::ProposalMsg(proposal_msg) => {
+                    Some(proposal_msg.proposal().quorum_cert().vote_data())
+                }
+                ConsensusMsg::VoteMsg(vote_msg) => Some(vote_msg.vote().vote_data()),
+                _ => None,
+            };
+            if let Some(vote_data) = vote_data {
+                voted_blocks.lock().insert(
+                    vote_data.proposed().id(),
+                    (vote_data.parent().round(), vote_data.parent().id()),
+                );
+            }
+            let round = match round {
+                Some(round) => round,
+                None => return DeliveryAction::Deliver,
+            };
+            if matches!(msg, ConsensusMsg::VoteMsg(_)) && withheld_votes.contains(&(round, *src)) {
+                return DeliveryAction::Drop;
+            }
+            delays
+                .get(&(round, *src))
+                .map_or(DeliveryAction::Deliver, |delay| {
+                    DeliveryAction::HoldUntilRound(round + *delay)
+                })
+        }));
+        runtime.spawn(playground.start());
+
+        let last_faulty_round = self.last_faulty_round();
+        let mut commits = vec![vec![]; nodes.len()];
+        let mut commit_receivers =
+            select_all(nodes.iter_mut().enumerate().map(|(node, smr_node)| {
+                (&mut smr_node.commit_cb_receiver).map(move |li| (node, li))
+            }));
+        let all_committed = |commits: &[Vec<LedgerInfoWithSignatures>]| {
+            commits.iter().all(|node_commits| {
+                node_commits
+                    .last()
+                    .map_or(false, |li| li.ledger_info().round() > last_faulty_round)
+            })
+        };
+        runtime.block_on(async {
+            // the timeout only bounds a scenario that fails to make progress, commits are
+            // collected as soon as the nodes make them
+            let _ = tokio::time::timeout(timeout, async {
+                while !all_committed(&commits) {
+                    match commit_receivers.next().await {
+                        Some((node, li)) => commits[node].push(li),
+                        None => break,
+                    }
+                }
+            })
+            .await;
+        });
+
+        let blocks = blocks.lock().clone();
+        ScenarioOutcome {
+            commits,
+            blocks,
+            last_faulty_round,
+        }
+    }
+}
+
+/// Commits observed by each node of a scenario, in the order they were made.
+pub struct ScenarioOutcome {
+    pub commits: Vec<Vec<LedgerInfoWithSignatures>>,
+    /// Parent round and id of every block voted on during the scenario
+    blocks: HashMap<HashValue, (Round, HashValue)>,
+    last_faulty_round: Round,
+}
+
+impl ScenarioOutcome {
+    /// Every node commits blocks in increasing (epoch, round) order, and the committed chains of
+    /// all nodes are prefixes of one another: of any two committed blocks, the one with the lower
+    /// round is an ancestor of (or the same as) the other.
+    pub fn check_safety(&self) -> Result<(), String> {
+        let mut committed = vec![];
+        for (node, node_commits) in self.commits.iter().enumerate() {
+            let mut last_committed = None;
+            for li in node_commits {
+                let info = li.ledger_info().commit_info();
+                let key = (info.epoch(), info.round());
+                if last_committed.map_or(false, |last| key <= last) {
+                    return Err(format!(
+                        "node {} committed {} after (epoch, round) {:?}",
+                        node,
+                        info,
+                        last_committed.unwrap()
+                    ));
+                }
+                last_committed = Some(key);
+                committed.push((key, info.id(), node));
+            }
+        }
+
+        // Checking each committed block against the next higher one covers every pair, since
+        // ancestry is transitive
+        committed.sort();
+        committed.dedup_by_key(|(key, id, _)| (*key, *id));
+        for pair in committed.windows(2) {
+            let ((low_key, low_id, low_node), (high_key, high_id, high_node)) = (pair[0], pair[1]);
+            if !self.is_ancestor(low_key.1, low_id, high_key.1, high_id)? {
+                return Err(format!(
+                    "conflicting commits: node {} committed {} at (epoch, round) {:?}, which \
+                     doesn't extend {} committed by node {} at {:?}",
+                    high_node, high_id, high_key, low_id, low_node, low_key
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the block `ancestor_id` of `ancestor_round` is on the chain of `id`, walking the
+    /// parents of `id` down to `ancestor_round`.
+    fn is_ancestor(
+        &self,
+        ancestor_round: Round,
+        ancestor_id: HashValue,
+        mut round: Round,
+        mut id: HashValue,
+    ) -> Result<bool, String> {
+        while round > ancestor_round {
+            let (parent_round, parent_id) = self
+                .blocks
+                .get(&id)
+                .ok_or_else(|| format!("committed block {} was never voted on", id))?;
+            round = *parent_round;
+            id = *parent_id;
+        }
+        Ok(round == ancestor_round && id == ancestor_id)
+    }
+
+    /// Every node committed a block past the last faulty round.
+    pub fn check_liveness(&self) -> Result<(), String> {
+        for (node, node_commits) in self.commits.iter().enumerate() {
+            let last_round = node_commits.last().map_or(0, |li| li.ledger_info().round());
+            if last_round <= self.last_faulty_round {
+                return Err(format!(
+                    "node {} only committed up to round {} after the faults ended in round {}",
+                    node, last_round, self.last_faulty_round
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -157,6 +157,27 @@ impl SMRNode {
         playground: &mut NetworkPlayground,
         proposer_type: ConsensusProposerType,
         round_proposers_idx: Option<HashMap<Round, usize>>,
+    ) -> Vec<Self> {
+        // Disable timeout in twins test to avoid flakiness
+        Self::start_num_nodes_with_twins_and_round_timeout(
+            num_nodes,
+            num_twins,
+            playground,
+            proposer_type,
+            round_proposers_idx,
+            2_000_000,
+        )
+    }
+
+    /// Starts a given number of nodes and their twins, with the given initial round timeout.
+    /// Nodes are ordered by author, followed by the twins of the first `num_twins` nodes.
+    pub fn start_num_nodes_with_twins_and_round_timeout(
+        num_nodes: usize,
+        num_twins: usize,
+        playground: &mut NetworkPlayground,
+        proposer_type: ConsensusProposerType,
+        round_proposers_idx: Option<HashMap<Round, usize>>,
+        round_initial_timeout_ms: u64,
     ) -> Vec<Self> {
         assert!(num_nodes >= num_twins);
         let ValidatorSwarm {
@@ -228,8 +249,7 @@ impl SMRNode {
             config.base.waypoint = WaypointConfig::FromConfig(waypoint);
             config.consensus.proposer_type = proposer_type.clone();
             config.consensus.safety_rules.verify_vote_proposal_signature = false;
-            // Disable timeout in twins test to avoid flakiness
-            config.consensus.round_initial_timeout_ms = 2_000_000;
+            config.consensus.round_initial_timeout_ms = round_initial_timeout_ms;
 
             let author = author_from_config(&config);
 
@@ -0,0 +1,85 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! LZ4 block compression for payloads exchanged between nodes.
+//!
+//! Compressed blocks are prefixed with their uncompressed length (as a little-endian `u32`),
+//! which lets [`decompress`] reject oversized payloads before allocating for them.
+
+use std::convert::TryInto;
+use thiserror::Error;
+
+/// Number of bytes used by the uncompressed length prefix
+const SIZE_PREFIX_LEN: usize = 4;
+
+pub type CompressedData = Vec<u8>;
+
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("Compression failed: {0}")]
+    CompressionError(String),
+    #[error("Decompression failed: {0}")]
+    DecompressionError(String),
+}
+
+/// Compresses `raw_data`, failing if the input is larger than `max_bytes`
+pub fn compress(raw_data: &[u8], max_bytes: usize) -> Result<CompressedData, Error> {
+    if raw_data.len() > max_bytes {
+        return Err(Error::CompressionError(format!(
+            "Raw data size {} exceeds the maximum of {} bytes",
+            raw_data.len(),
+            max_bytes
+        )));
+    }
+    lz4::block::compress(raw_data, None, true)
+        .map_err(|error| Error::CompressionError(error.to_string()))
+}
+
+/// Decompresses `compressed_data`, failing if the uncompressed size would exceed `max_bytes`
+pub fn decompress(compressed_data: &[u8], max_bytes: usize) -> Result<Vec<u8>, Error> {
+    let size_prefix: [u8; SIZE_PREFIX_LEN] = compressed_data
+        .get(..SIZE_PREFIX_LEN)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or_else(|| {
+            Error::DecompressionError("Compressed data is missing the size prefix".into())
+        })?;
+    let uncompressed_size = u32::from_le_bytes(size_prefix) as usize;
+    if uncompressed_size > max_bytes {
+        return Err(Error::DecompressionError(format!(
+            "Uncompressed size {} exceeds the maximum of {} bytes",
+            uncompressed_size, max_bytes
+        )));
+    }
+    lz4::block::decompress(compressed_data, None)
+        .map_err(|error| Error::DecompressionError(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let raw_data: Vec<u8> = (0..10_000u32)
+            .flat_map(|i| (i % 17).to_le_bytes())
+            .collect();
+        let compressed_data = compress(&raw_data, raw_data.len()).unwrap();
+        assert!(compressed_data.len() < raw_data.len());
+        assert_eq!(
+            decompress(&compressed_data, raw_data.len()).unwrap(),
+            raw_data
+        );
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let raw_data = vec![7u8; 1024];
+        compress(&raw_data, 1023).unwrap_err();
+
+        let compressed_data = compress(&raw_data, 1024).unwrap();
+        decompress(&compressed_data, 1023).unwrap_err();
+        decompress(&compressed_data[..2], 1024).unwrap_err();
+    }
+}
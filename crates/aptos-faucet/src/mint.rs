@@ -195,10 +195,7 @@ pub async fn process(service: &Service, params: MintParams) -> Result<Response>
     if params.return_txns.unwrap_or(false) {
         Ok(Response::SubmittedTxns(txns))
     } else {
-        let hashes = txns
-            .iter()
-            .map(|txn| txn.clone().committed_hash())
-            .collect();
+        let hashes = txns.iter().map(|txn| txn.committed_hash()).collect();
         Ok(Response::SubmittedTxnsHashes(hashes))
     }
 }
@@ -127,11 +127,8 @@ impl Client {
         transaction: &SignedTransaction,
     ) -> Result<Response<Transaction>> {
         let expiration_timestamp = transaction.expiration_timestamp_secs();
-        self.wait_for_transaction_by_hash(
-            transaction.clone().committed_hash(),
-            expiration_timestamp,
-        )
-        .await
+        self.wait_for_transaction_by_hash(transaction.committed_hash(), expiration_timestamp)
+            .await
     }
 
     pub async fn wait_for_transaction_by_hash(
@@ -2,11 +2,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_mempool::{MempoolTransactionInfo, PeerBroadcastState};
+use aptos_types::account_address::AccountAddress;
+use aptos_types::transaction::Version;
 use reqwest::{blocking, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub mod node_debug_service;
 
+/// The pruner names accepted by the debug interface.
+pub const STATE_STORE_PRUNER: &str = "state_store";
+pub const LEDGER_PRUNER: &str = "ledger";
+
+/// The prune window and progress of each pruner, both are `None` when pruning is disabled.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PrunerStatus {
+    pub state_store: StorePrunerStatus,
+    pub ledger: StorePrunerStatus,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StorePrunerStatus {
+    pub prune_window: Option<usize>,
+    pub min_readable_version: Option<Version>,
+}
+
 /// Implement default utility client for NodeDebugInterface
 pub struct NodeDebugClient {
     client: blocking::Client,
@@ -78,6 +100,61 @@ impl NodeDebugClient {
             })
             .collect()
     }
+
+    /// Retrieves all transactions of `address` in the node's mempool, both ready and parked.
+    pub fn get_mempool_account_transactions(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<MempoolTransactionInfo>> {
+        self.get_json(&format!("mempool/accounts/{}/transactions", address))
+    }
+
+    /// Retrieves a transaction in the node's mempool by its hash.
+    pub fn get_mempool_transaction(
+        &self,
+        hash: HashValue,
+    ) -> Result<Option<MempoolTransactionInfo>> {
+        self.get_json(&format!("mempool/transactions/{}", hash))
+    }
+
+    /// Retrieves the mempool broadcast state for each upstream peer of the node.
+    pub fn get_mempool_peer_broadcast_states(&self) -> Result<Vec<PeerBroadcastState>> {
+        self.get_json("mempool/peers")
+    }
+
+    /// Retrieves the prune windows and the min readable versions of the node's storage.
+    pub fn get_pruner_status(&self) -> Result<PrunerStatus> {
+        self.get_json("storage/pruner")
+    }
+
+    /// Changes the prune window of `pruner` (one of `STATE_STORE_PRUNER` or `LEDGER_PRUNER`)
+    /// without restarting the node. The node only accepts this from its own host.
+    pub fn set_prune_window(&self, pruner: &str, window: usize) -> Result<()> {
+        let mut url = self.url.clone();
+        url.set_path(&format!("storage/pruner/{}/window", pruner));
+        let response = self.client.post(url).body(window.to_string()).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Error setting prune window: {}: {}",
+                response.status(),
+                response.text()?
+            );
+        }
+        Ok(())
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let mut url = self.url.clone();
+        url.set_path(path);
+        let response = self.client.get(url).send()?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Error querying {}: {}", path, response.status());
+        }
+
+        Ok(response.json::<T>()?)
+    }
 }
 
 /// Implement default utility client for AsyncNodeDebugInterface
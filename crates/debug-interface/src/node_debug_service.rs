@@ -3,13 +3,20 @@
 
 //! Debug interface to access information in a specific node.
 
+use crate::{PrunerStatus, StorePrunerStatus, LEDGER_PRUNER, STATE_STORE_PRUNER};
+use anyhow::{format_err, Result};
 use aptos_config::config::NodeConfig;
+use aptos_crypto::HashValue;
 use aptos_logger::{info, Filter, Logger};
+use aptos_mempool::{MempoolClientRequest, MempoolClientSender};
 use aptos_metrics::metric_server;
+use aptos_types::account_address::AccountAddress;
+use futures::{channel::oneshot, SinkExt};
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use storage_interface::DbReaderWriter;
 use tokio::runtime::{Builder, Runtime};
-use warp::Filter as _;
+use warp::{http::StatusCode, reply::Response, Filter as _, Reply};
 
 #[derive(Debug)]
 pub struct NodeDebugService {
@@ -23,7 +30,13 @@ struct NodeInfo {
 }
 
 impl NodeDebugService {
-    pub fn new(address: SocketAddr, logger: Option<Arc<Logger>>, node_config: &NodeConfig) -> Self {
+    pub fn new(
+        address: SocketAddr,
+        logger: Option<Arc<Logger>>,
+        node_config: &NodeConfig,
+        mempool_client: MempoolClientSender,
+        db: DbReaderWriter,
+    ) -> Self {
         let runtime = Builder::new_multi_thread()
             .thread_name("nodedebug")
             .enable_all()
@@ -77,7 +90,105 @@ impl NodeDebugService {
         };
         let node_info_route = warp::path("node-info").map(move || warp::reply::json(&node_info));
 
-        let routes = log.or(warp::get().and(metrics.or(node_info_route)));
+        // Get /mempool/accounts/<address>/transactions
+        let mempool_account_txns = {
+            let mempool_client = mempool_client.clone();
+            warp::path!("mempool" / "accounts" / String / "transactions").and_then(
+                move |address: String| {
+                    let mempool_client = mempool_client.clone();
+                    async move {
+                        let response = match parse_address(&address) {
+                            Ok(address) => {
+                                query_mempool(mempool_client, |callback| {
+                                    MempoolClientRequest::GetAccountTransactionsInfo(
+                                        address, callback,
+                                    )
+                                })
+                                .await
+                            }
+                            Err(e) => bad_request(e),
+                        };
+                        Ok::<_, Infallible>(response)
+                    }
+                },
+            )
+        };
+
+        // Get /mempool/transactions/<hash>
+        let mempool_txn = {
+            let mempool_client = mempool_client.clone();
+            warp::path!("mempool" / "transactions" / String).and_then(move |hash: String| {
+                let mempool_client = mempool_client.clone();
+                async move {
+                    let response = match HashValue::from_hex(hash.trim_start_matches("0x")) {
+                        Ok(hash) => {
+                            query_mempool(mempool_client, |callback| {
+                                MempoolClientRequest::GetTransactionInfoByHash(hash, callback)
+                            })
+                            .await
+                        }
+                        Err(e) => bad_request(e.into()),
+                    };
+                    Ok::<_, Infallible>(response)
+                }
+            })
+        };
+
+        // Get /mempool/peers (broadcast state of upstream peers)
+        let mempool_peers = warp::path!("mempool" / "peers").and_then(move || {
+            let mempool_client = mempool_client.clone();
+            async move {
+                Ok::<_, Infallible>(
+                    query_mempool(mempool_client, MempoolClientRequest::GetPeerBroadcastStates)
+                        .await,
+                )
+            }
+        });
+
+        // Get /storage/pruner
+        let pruner_status_route = {
+            let db = db.clone();
+            warp::path!("storage" / "pruner").map(move || match pruner_status(&db) {
+                Ok(status) => warp::reply::json(&status).into_response(),
+                Err(e) => warp::reply::with_status(
+                    format!("Failed to query pruner: {}", e),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                )
+                .into_response(),
+            })
+        };
+
+        // Post /storage/pruner/<pruner>/window (only from the node's own host, as pruning cannot
+        // be undone)
+        let pruner_window = warp::post()
+            .and(warp::path!("storage" / "pruner" / String / "window"))
+            .and(warp::addr::remote())
+            .and(warp::body::content_length_limit(64))
+            .and(warp::body::bytes())
+            .map(
+                move |pruner: String, remote: Option<SocketAddr>, bytes: bytes::Bytes| {
+                    if !remote.map_or(false, |addr| addr.ip().is_loopback()) {
+                        return warp::reply::with_status(
+                            "Prune windows can only be changed from localhost",
+                            StatusCode::FORBIDDEN,
+                        )
+                        .into_response();
+                    }
+                    match set_prune_window(&db, &pruner, &bytes) {
+                        Ok(()) => warp::reply().into_response(),
+                        Err(e) => bad_request(e),
+                    }
+                },
+            );
+
+        let routes = log.or(pruner_window).or(warp::get().and(
+            metrics
+                .or(node_info_route)
+                .or(mempool_account_txns)
+                .or(mempool_txn)
+                .or(mempool_peers)
+                .or(pruner_status_route),
+        ));
 
         runtime
             .handle()
@@ -90,3 +201,58 @@ impl NodeDebugService {
         &self.runtime
     }
 }
+
+/// Accepts both the short (e.g. `0x1`) and the full hex form of an address.
+fn parse_address(address: &str) -> Result<AccountAddress> {
+    Ok(AccountAddress::from_hex_literal(&format!(
+        "0x{}",
+        address.trim_start_matches("0x")
+    ))?)
+}
+
+/// Sends a read-only request to mempool and renders its response as JSON.
+async fn query_mempool<T: Serialize>(
+    mut mempool_client: MempoolClientSender,
+    request: impl FnOnce(oneshot::Sender<T>) -> MempoolClientRequest,
+) -> Response {
+    let (callback, receiver) = oneshot::channel();
+    let result = match mempool_client.send(request(callback)).await {
+        Ok(()) => receiver.await.map_err(|e| format_err!("{}", e)),
+        Err(e) => Err(format_err!("{}", e)),
+    };
+    match result {
+        Ok(response) => warp::reply::json(&response).into_response(),
+        Err(e) => warp::reply::with_status(
+            format!("Failed to query mempool: {}", e),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+        .into_response(),
+    }
+}
+
+fn pruner_status(db: &DbReaderWriter) -> Result<PrunerStatus> {
+    Ok(PrunerStatus {
+        state_store: StorePrunerStatus {
+            prune_window: db.reader.get_state_prune_window()?,
+            min_readable_version: db.reader.get_state_min_readable_version()?,
+        },
+        ledger: StorePrunerStatus {
+            prune_window: db.reader.get_ledger_prune_window()?,
+            min_readable_version: db.reader.get_ledger_min_readable_version()?,
+        },
+    })
+}
+
+fn set_prune_window(db: &DbReaderWriter, pruner: &str, body: &[u8]) -> Result<()> {
+    let window = std::str::from_utf8(body)?.trim().parse::<usize>()?;
+    info!(pruner = pruner, window = window, "Updating prune window");
+    match pruner {
+        STATE_STORE_PRUNER => db.writer.set_state_prune_window(window),
+        LEDGER_PRUNER => db.writer.set_ledger_prune_window(window),
+        _ => Err(format_err!("Unknown pruner: {}", pruner)),
+    }
+}
+
+fn bad_request(error: anyhow::Error) -> Response {
+    warp::reply::with_status(error.to_string(), StatusCode::BAD_REQUEST).into_response()
+}
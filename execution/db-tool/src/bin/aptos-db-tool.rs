@@ -0,0 +1,291 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Context, Result};
+use aptos_config::config::{RocksdbConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_logger::{Level, Logger};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix},
+    transaction::Version,
+    waypoint::Waypoint,
+};
+use aptosdb::{
+    db_verifier::IntegrityVerifierProgress, AptosDB, LEDGER_DB_NAME, STATE_MERKLE_DB_NAME,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use storage_interface::DbReader;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "aptos-db-tool",
+    about = "Inspect, truncate, prune and compact an AptosDB offline."
+)]
+enum Command {
+    /// Prints the latest versions, pruner progress and column family sizes.
+    Summary {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+    },
+    /// Prints the transaction at a version together with its info, events and write set.
+    DumpTxn {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+
+        #[structopt(long)]
+        version: Version,
+    },
+    /// Prints state values at a version, either a single key or all keys of an account.
+    DumpState {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+
+        #[structopt(long)]
+        version: Version,
+
+        /// Dumps all state under this account.
+        #[structopt(long, conflicts_with = "state-key-hex")]
+        address: Option<AccountAddress>,
+
+        /// Dumps a single state key, given as hex encoded BCS bytes.
+        #[structopt(long)]
+        state_key_hex: Option<String>,
+    },
+    /// Recomputes and checks every hash and signature in the DB from genesis, reporting the first
+    /// divergent version.
+    Verify {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+
+        /// Checks that the first ledger info in the DB matches this waypoint.
+        #[structopt(long)]
+        waypoint: Option<Waypoint>,
+
+        /// Resumes from and saves progress to this file.
+        #[structopt(long, parse(from_os_str))]
+        progress_file: Option<PathBuf>,
+
+        /// Number of versions verified between progress checkpoints.
+        #[structopt(long, default_value = "10000")]
+        batch_size: usize,
+    },
+    /// Deletes everything after the target version from both the ledger and the state merkle DB.
+    /// The node must be stopped.
+    Truncate {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+
+        #[structopt(long)]
+        target_version: Version,
+
+        /// Number of versions deleted in each atomic write.
+        #[structopt(long, default_value = "10000")]
+        batch_size: usize,
+
+        /// Creates a checkpoint of the DB in this directory before truncating.
+        #[structopt(long, parse(from_os_str))]
+        backup_checkpoint_dir: Option<PathBuf>,
+    },
+    /// Prunes the DB down to the given windows. The node must be stopped.
+    Prune {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+
+        #[structopt(long)]
+        state_store_prune_window: Option<Version>,
+
+        #[structopt(long)]
+        ledger_prune_window: Option<Version>,
+
+        /// Number of versions pruned in each atomic write.
+        #[structopt(long, default_value = "500")]
+        batch_size: usize,
+    },
+    /// Runs a full manual compaction of every column family. The node must be stopped.
+    Compact {
+        #[structopt(parse(from_os_str))]
+        db_dir: PathBuf,
+    },
+}
+
+fn main() -> Result<()> {
+    Logger::new().level(Level::Info).read_env().init();
+
+    match Command::from_args() {
+        Command::Summary { db_dir } => {
+            let (db, _tmpdir) = open_as_secondary(&db_dir)?;
+            print_summary(&db)
+        }
+        Command::DumpTxn { db_dir, version } => {
+            let (db, _tmpdir) = open_as_secondary(&db_dir)?;
+            dump_txn(&db, version)
+        }
+        Command::DumpState {
+            db_dir,
+            version,
+            address,
+            state_key_hex,
+        } => {
+            let (db, _tmpdir) = open_as_secondary(&db_dir)?;
+            match (address, state_key_hex) {
+                (Some(address), None) => {
+                    let values =
+                        db.get_state_values_by_key_prefix(&StateKeyPrefix::from(address), version)?;
+                    for (state_key, state_value) in values {
+                        println!("{:?} => {:?}", state_key, state_value);
+                    }
+                }
+                (None, Some(state_key_hex)) => {
+                    let state_key: StateKey = bcs::from_bytes(&hex::decode(state_key_hex)?)?;
+                    let state_value = db.get_state_value_by_version(&state_key, version)?;
+                    println!("{:?} => {:?}", state_key, state_value);
+                }
+                _ => bail!("Exactly one of --address and --state-key-hex is required."),
+            }
+            Ok(())
+        }
+        Command::Verify {
+            db_dir,
+            waypoint,
+            progress_file,
+            batch_size,
+        } => {
+            let (db, _tmpdir) = open_as_secondary(&db_dir)?;
+            let mut progress = match &progress_file {
+                Some(path) if path.exists() => bcs::from_bytes(&fs::read(path)?)?,
+                _ => IntegrityVerifierProgress::default(),
+            };
+            println!("Verifying from version {}.", progress.next_version);
+            let divergence = db.verify_integrity(&mut progress, waypoint, batch_size, |p| {
+                if let Some(path) = &progress_file {
+                    save_progress(path, p)?;
+                }
+                Ok(())
+            })?;
+            match divergence {
+                Some(divergence) => bail!("{}", divergence),
+                None => {
+                    println!("Verified all versions before {}.", progress.next_version);
+                    Ok(())
+                }
+            }
+        }
+        Command::Truncate {
+            db_dir,
+            target_version,
+            batch_size,
+            backup_checkpoint_dir,
+        } => {
+            let db = open(&db_dir)?;
+            if let Some(checkpoint_dir) = backup_checkpoint_dir {
+                db.create_checkpoint(&checkpoint_dir)
+                    .with_context(|| format_err!("Failed to create checkpoint."))?;
+                println!("Created checkpoint at {:?}.", checkpoint_dir);
+            }
+            db.truncate(target_version, batch_size)?;
+            println!("Truncated DB to version {}.", target_version);
+            Ok(())
+        }
+        Command::Prune {
+            db_dir,
+            state_store_prune_window,
+            ledger_prune_window,
+            batch_size,
+        } => {
+            let db = open(&db_dir)?;
+            db.prune(state_store_prune_window, ledger_prune_window, batch_size)?;
+            print_summary(&db)
+        }
+        Command::Compact { db_dir } => {
+            let db = open(&db_dir)?;
+            db.compact()?;
+            print_summary(&db)
+        }
+    }
+}
+
+/// Opens the DB for writing, without a pruner. This fails if a node is running on the DB.
+fn open(db_dir: &Path) -> Result<AptosDB> {
+    AptosDB::open(
+        db_dir,
+        false,                       /* readonly */
+        NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+        RocksdbConfig::default(),
+    )
+    .with_context(|| format_err!("Failed to open DB."))
+}
+
+/// Opens the DB as secondary so that read only commands can be used along side a running node.
+fn open_as_secondary(db_dir: &Path) -> Result<(AptosDB, TempPath)> {
+    let tmpdir = TempPath::new();
+    let db = AptosDB::open_as_secondary(
+        db_dir,
+        &tmpdir.as_ref().to_path_buf().join(LEDGER_DB_NAME),
+        &tmpdir.as_ref().to_path_buf().join(STATE_MERKLE_DB_NAME),
+        RocksdbConfig::default(),
+    )
+    .with_context(|| format_err!("Failed to open DB."))?;
+    Ok((db, tmpdir))
+}
+
+/// Replaces the progress file atomically, so an interruption never leaves a corrupted one.
+fn save_progress(path: &Path, progress: &IntegrityVerifierProgress) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bcs::to_bytes(progress)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn print_summary(db: &AptosDB) -> Result<()> {
+    let summary = db.get_db_summary()?;
+    println!("Latest version: {:?}", summary.latest_version);
+    println!(
+        "Latest ledger info: epoch {:?}, version {:?}",
+        summary.latest_ledger_info_epoch, summary.latest_ledger_info_version
+    );
+    println!("First transaction version: {:?}", summary.first_txn_version);
+    println!(
+        "Latest state checkpoint version: {:?}",
+        summary.latest_state_checkpoint_version
+    );
+    println!(
+        "Pruner progress: ledger {:?}, state merkle {:?}",
+        summary.ledger_pruner_progress, summary.state_merkle_pruner_progress
+    );
+    println!(
+        "{:<16} {:<28} {:>16} {:>16}",
+        "db", "column family", "keys (est.)", "sst bytes"
+    );
+    for cf in summary.column_families {
+        println!(
+            "{:<16} {:<28} {:>16} {:>16}",
+            cf.db_name, cf.cf_name, cf.estimated_num_keys, cf.total_sst_files_size
+        );
+    }
+    Ok(())
+}
+
+fn dump_txn(db: &AptosDB, version: Version) -> Result<()> {
+    let (ledger_version, _) = db
+        .get_latest_transaction_info_option()?
+        .ok_or_else(|| format_err!("DB is empty."))?;
+    let txn_with_proof = db.get_transaction_by_version(version, ledger_version, true)?;
+    let write_set = db
+        .get_write_sets(version, version + 1)?
+        .pop()
+        .ok_or_else(|| format_err!("Write set missing for version {}.", version))?;
+
+    println!("Transaction: {:#?}", txn_with_proof.transaction);
+    println!(
+        "Transaction info: {:#?}",
+        txn_with_proof.proof.transaction_info
+    );
+    println!("Events: {:#?}", txn_with_proof.events);
+    println!("Write set: {:#?}", write_set);
+    Ok(())
+}
@@ -223,9 +223,9 @@ impl InMemoryStateCalculator {
             self.checkpoint()
         } else {
             match txn {
-                Transaction::BlockMetadata(_) | Transaction::UserTransaction(_) => {
-                    Ok((HashMap::new(), HashMap::new(), None))
-                }
+                Transaction::BlockMetadata(_)
+                | Transaction::BlockMetadataV2(_)
+                | Transaction::UserTransaction(_) => Ok((HashMap::new(), HashMap::new(), None)),
                 Transaction::GenesisTransaction(_) | Transaction::StateCheckpoint => {
                     self.checkpoint()
                 }
@@ -404,7 +404,7 @@ fn ensure_txn_valid_for_vacant_entry(transaction: &Transaction) -> Result<()> {
     // maybe other writeset transactions).
     match transaction {
         Transaction::GenesisTransaction(_) => (),
-        Transaction::BlockMetadata(_) => {
+        Transaction::BlockMetadata(_) | Transaction::BlockMetadataV2(_) => {
             bail!("Write set should be a subset of read set.")
         }
         Transaction::UserTransaction(txn) => match txn.payload() {
@@ -6,12 +6,13 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{MempoolTransaction, MempoolTransactionInfo, TimelineState},
         transaction_store::TransactionStore,
         ttl_cache::TtlCache,
     },
     counters,
     logging::{LogEntry, LogSchema, TxnsLog},
+    mempooldb::{MempoolDB, MempoolJournal, PersistedTransaction},
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
@@ -42,8 +43,13 @@ pub struct Mempool {
 
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
+        let journal = if config.mempool.persist_transactions {
+            Some(MempoolJournal::new(MempoolDB::new(config.storage.dir())))
+        } else {
+            None
+        };
         Mempool {
-            transactions: TransactionStore::new(&config.mempool),
+            transactions: TransactionStore::new(&config.mempool, journal),
             sequence_number_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             metrics_cache: TtlCache::new(config.mempool.capacity, Duration::from_secs(100)),
             system_transaction_timeout: Duration::from_secs(
@@ -123,6 +129,32 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    pub(crate) fn get_info_by_hash(&self, hash: HashValue) -> Option<MempoolTransactionInfo> {
+        self.transactions.get_info_by_hash(hash)
+    }
+
+    /// Returns true iff mempool holds a transaction of `address` with the given sequence number.
+    pub(crate) fn contains_transaction(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> bool {
+        self.transactions.get(address, sequence_number).is_some()
+    }
+
+    /// Returns the number of transactions of `address` in mempool, both ready and parked.
+    pub(crate) fn num_transactions_for_sender(&self, address: &AccountAddress) -> usize {
+        self.transactions.num_transactions_for_sender(address)
+    }
+
+    /// Lists all transactions of `address` in mempool, both ready and parked.
+    pub(crate) fn get_account_transactions_info(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<MempoolTransactionInfo> {
+        self.transactions.get_account_transactions_info(address)
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
     pub(crate) fn add_txn(
@@ -179,15 +211,20 @@ impl Mempool {
 
     /// Fetches next block of transactions for consensus.
     /// `batch_size` - size of requested block.
+    /// `max_batch_gas` - max sum of the `max_gas_amount` of the requested block. The block stops
+    ///  before the first transaction that would exceed it, but always has at least one transaction
+    ///  so that a transaction exceeding it on its own can't stall consensus.
     /// `seen_txns` - transactions that were sent to Consensus but were not committed yet,
     ///  mempool should filter out such transactions.
     #[allow(clippy::explicit_counter_loop)]
     pub(crate) fn get_batch(
         &self,
         batch_size: u64,
+        max_batch_gas: u64,
         mut seen: HashSet<TxnPointer>,
     ) -> Vec<SignedTransaction> {
         let mut result = vec![];
+        let mut batch_gas = 0u64;
         // Helper DS. Helps to mitigate scenarios where account submits several transactions
         // with increasing gas price (e.g. user submits transactions with sequence number 1, 2
         // and gas_price 1, 10 respectively)
@@ -215,6 +252,9 @@ impl Mempool {
                 || matches!(account_seqtype, AccountSequenceInfo::CRSN { .. })
             {
                 let ptr = TxnPointer::from(txn);
+                if !self.fits_in_batch_gas(&ptr, &result, &mut batch_gas, max_batch_gas) {
+                    break;
+                }
                 seen.insert(ptr);
                 result.push(ptr);
                 if (result.len() as u64) == batch_size {
@@ -225,6 +265,10 @@ impl Mempool {
                 // that were skipped before for given account
                 let mut skipped_txn = (txn.address, tx_seq + 1);
                 while skipped.contains(&skipped_txn) {
+                    if !self.fits_in_batch_gas(&skipped_txn, &result, &mut batch_gas, max_batch_gas)
+                    {
+                        break 'main;
+                    }
                     seen.insert(skipped_txn);
                     result.push(skipped_txn);
                     if (result.len() as u64) == batch_size {
@@ -265,6 +309,27 @@ impl Mempool {
         block
     }
 
+    /// Adds the `max_gas_amount` of the transaction to `batch_gas`, unless that brings the
+    /// non-empty `batch` over `max_batch_gas`. Returns whether the transaction fits in the batch.
+    fn fits_in_batch_gas(
+        &self,
+        txn: &TxnPointer,
+        batch: &[TxnPointer],
+        batch_gas: &mut u64,
+        max_batch_gas: u64,
+    ) -> bool {
+        let txn_gas = self
+            .transactions
+            .get_max_gas_amount(&txn.0, txn.1)
+            .unwrap_or(0);
+        let new_batch_gas = batch_gas.saturating_add(txn_gas);
+        if !batch.is_empty() && new_batch_gas > max_batch_gas {
+            return false;
+        }
+        *batch_gas = new_batch_gas;
+        true
+    }
+
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
@@ -296,6 +361,22 @@ impl Mempool {
         self.transactions.timeline_range(start_id, end_id)
     }
 
+    /// Returns the transactions in the on-disk journal (if enabled). They must be resubmitted
+    /// through validation to be kept, and forgotten if they are rejected.
+    pub(crate) fn get_persisted_transactions(&self) -> Vec<PersistedTransaction> {
+        self.transactions.get_persisted_transactions()
+    }
+
+    /// Removes a transaction that could not be restored from the on-disk journal.
+    pub(crate) fn forget_persisted_transaction(
+        &self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+    ) {
+        self.transactions
+            .forget_persisted_transaction(*sender, sequence_number)
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot(&self.metrics_cache)
     }
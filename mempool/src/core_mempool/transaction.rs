@@ -48,7 +48,7 @@ impl MempoolTransaction {
         self.txn.gas_unit_price()
     }
     pub(crate) fn get_committed_hash(&self) -> HashValue {
-        self.txn.clone().committed_hash()
+        self.txn.committed_hash()
     }
 }
 
@@ -64,6 +64,30 @@ pub enum TimelineState {
     NonQualified,
 }
 
+/// Whether a transaction in mempool can be included in the next block.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolTransactionStatus {
+    // The transaction is sequential to the account's sequence number.
+    Ready,
+    // The transaction is waiting for a transaction with a lower sequence number.
+    Parked,
+}
+
+/// Read-only view of a transaction in mempool, used for introspection.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MempoolTransactionInfo {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub hash: HashValue,
+    pub gas_unit_price: u64,
+    pub max_gas_amount: u64,
+    pub expiration_timestamp_secs: u64,
+    pub status: MempoolTransactionStatus,
+    // Position in the log of transactions ready for broadcast, if any.
+    pub timeline_id: Option<u64>,
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct SequenceInfo {
     pub transaction_sequence_number: u64,
@@ -7,11 +7,14 @@ use crate::{
             AccountTransactions, ParkingLotIndex, PriorityIndex, PriorityQueueIter, TTLIndex,
             TimelineIndex,
         },
-        transaction::{MempoolTransaction, TimelineState},
+        transaction::{
+            MempoolTransaction, MempoolTransactionInfo, MempoolTransactionStatus, TimelineState,
+        },
         ttl_cache::TtlCache,
     },
     counters,
     logging::{LogEntry, LogEvent, LogSchema, TxnsLog},
+    mempooldb::{MempoolJournal, PersistedTransaction},
 };
 use aptos_config::config::MempoolConfig;
 use aptos_crypto::HashValue;
@@ -52,13 +55,16 @@ pub struct TransactionStore {
     // one valid hash.
     hash_index: HashMap<HashValue, (AccountAddress, u64)>,
 
+    // optional on-disk journal of all transactions in the store
+    journal: Option<MempoolJournal>,
+
     // configuration
     capacity: usize,
     capacity_per_user: usize,
 }
 
 impl TransactionStore {
-    pub(crate) fn new(config: &MempoolConfig) -> Self {
+    pub(crate) fn new(config: &MempoolConfig, journal: Option<MempoolJournal>) -> Self {
         Self {
             // main DS
             transactions: HashMap::new(),
@@ -72,6 +78,7 @@ impl TransactionStore {
             timeline_index: TimelineIndex::new(),
             parking_lot_index: ParkingLotIndex::new(),
             hash_index: HashMap::new(),
+            journal,
 
             // configuration
             capacity: config.capacity,
@@ -80,6 +87,18 @@ impl TransactionStore {
     }
 
     /// Fetch transaction by account address + sequence_number.
+    /// Returns the `max_gas_amount` of the transaction, if it's in the store.
+    pub(crate) fn get_max_gas_amount(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<u64> {
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.get(&sequence_number))
+            .map(|txn| txn.txn.max_gas_amount())
+    }
+
     pub(crate) fn get(
         &self,
         address: &AccountAddress,
@@ -102,6 +121,56 @@ impl TransactionStore {
         }
     }
 
+    /// Returns the number of transactions of `address` in the store, both ready and parked.
+    pub(crate) fn num_transactions_for_sender(&self, address: &AccountAddress) -> usize {
+        self.transactions.get(address).map_or(0, |txns| txns.len())
+    }
+
+    /// Returns introspection info for all transactions of `address`, ordered by sequence number.
+    pub(crate) fn get_account_transactions_info(
+        &self,
+        address: &AccountAddress,
+    ) -> Vec<MempoolTransactionInfo> {
+        self.transactions
+            .get(address)
+            .map(|txns| txns.values().map(|txn| self.txn_info(txn)).collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn get_info_by_hash(&self, hash: HashValue) -> Option<MempoolTransactionInfo> {
+        let (address, sequence_number) = self.hash_index.get(&hash)?;
+        self.transactions
+            .get(address)
+            .and_then(|txns| txns.get(sequence_number))
+            .map(|txn| self.txn_info(txn))
+    }
+
+    fn txn_info(&self, txn: &MempoolTransaction) -> MempoolTransactionInfo {
+        let sequence_number = txn.sequence_info.transaction_sequence_number;
+        let status = if self
+            .parking_lot_index
+            .contains(&txn.get_sender(), &sequence_number)
+        {
+            MempoolTransactionStatus::Parked
+        } else {
+            MempoolTransactionStatus::Ready
+        };
+        let timeline_id = match txn.timeline_state {
+            TimelineState::Ready(timeline_id) => Some(timeline_id),
+            _ => None,
+        };
+        MempoolTransactionInfo {
+            sender: txn.get_sender(),
+            sequence_number,
+            hash: txn.get_committed_hash(),
+            gas_unit_price: txn.get_gas_price(),
+            max_gas_amount: txn.txn.max_gas_amount(),
+            expiration_timestamp_secs: txn.txn.expiration_timestamp_secs(),
+            status,
+            timeline_id,
+        }
+    }
+
     /// Fetch mempool transaction by account address + sequence_number.
     pub(crate) fn get_mempool_txn(
         &self,
@@ -179,6 +248,12 @@ impl TransactionStore {
             }
 
             // insert into storage and other indexes
+            if let Some(journal) = &self.journal {
+                journal.save_transaction(PersistedTransaction {
+                    txn: txn.txn.clone(),
+                    timeline_state: txn.timeline_state,
+                });
+            }
             self.system_ttl_index.insert(&txn);
             self.expiration_time_index.insert(&txn);
             self.hash_index.insert(
@@ -410,9 +485,39 @@ impl TransactionStore {
         self.timeline_index.remove(txn);
         self.parking_lot_index.remove(txn);
         self.hash_index.remove(&txn.get_committed_hash());
+        if let Some(journal) = &self.journal {
+            journal.delete_transaction(
+                txn.get_sender(),
+                txn.sequence_info.transaction_sequence_number,
+            );
+        }
         self.track_indices();
     }
 
+    /// Returns all transactions in the on-disk journal, so they can be revalidated and
+    /// reinserted. They stay journaled until they are removed with `forget_persisted_transaction`.
+    pub(crate) fn get_persisted_transactions(&self) -> Vec<PersistedTransaction> {
+        match &self.journal {
+            Some(journal) => journal.get_transactions().unwrap_or_else(|e| {
+                error!(LogSchema::new(LogEntry::DBError).error(&e));
+                counters::DB_ERROR.inc();
+                vec![]
+            }),
+            None => vec![],
+        }
+    }
+
+    /// Removes a transaction that could not be restored from the on-disk journal.
+    pub(crate) fn forget_persisted_transaction(
+        &self,
+        sender: AccountAddress,
+        sequence_number: u64,
+    ) {
+        if let Some(journal) = &self.journal {
+            journal.delete_transaction(sender, sequence_number);
+        }
+    }
+
     /// Read `count` transactions from timeline since `timeline_id`.
     /// Returns block of transactions and new last_timeline_id.
     pub(crate) fn read_timeline(
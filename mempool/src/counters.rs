@@ -32,6 +32,15 @@ pub const GC_CLIENT_EXP_LABEL: &str = "client_expiration";
 pub const GC_ACTIVE_TXN_LABEL: &str = "active";
 pub const GC_PARKED_TXN_LABEL: &str = "parked";
 
+// Journaled txn restore result labels
+pub const RESTORE_ACCEPTED_LABEL: &str = "accepted";
+pub const RESTORE_REJECTED_LABEL: &str = "rejected";
+pub const RESTORE_EXPIRED_LABEL: &str = "expired";
+
+// Admission quota type labels
+pub const PEER_QUOTA_LABEL: &str = "peer";
+pub const SENDER_QUOTA_LABEL: &str = "sender";
+
 // Mempool service request type labels
 pub const GET_BLOCK_LABEL: &str = "get_block";
 pub const COMMIT_STATE_SYNC_LABEL: &str = "commit_accepted";
@@ -67,6 +76,7 @@ pub const ACK_TXNS: &str = "ack_txns";
 pub const EXPIRED_BROADCAST_LABEL: &str = "expired";
 pub const RETRY_BROADCAST_LABEL: &str = "retry";
 pub const BACKPRESSURE_BROADCAST_LABEL: &str = "backpressure";
+pub const COMPRESSED_BROADCAST_LABEL: &str = "compressed";
 
 // ACK direction labels
 pub const RECEIVED_LABEL: &str = "received";
@@ -345,6 +355,38 @@ pub fn shared_mempool_ack_inc(network_id: NetworkId, direction: &str, label: &'s
         .inc();
 }
 
+/// Counter for transactions left out of broadcasts because the peer recently sent them to us
+static SHARED_MEMPOOL_RECENTLY_SEEN_FILTERED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "shared_mempool_recently_seen_filtered_txns",
+        "Number of transactions not broadcast to a peer because it recently sent them to us",
+        &["network"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_recently_seen_filtered_inc(network_id: NetworkId, num: usize) {
+    SHARED_MEMPOOL_RECENTLY_SEEN_FILTERED_TXNS
+        .with_label_values(&[network_id.as_str()])
+        .inc_by(num as u64);
+}
+
+/// Counter for broadcast transactions dropped for exceeding an admission quota
+static SHARED_MEMPOOL_QUOTA_DROPPED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "shared_mempool_quota_dropped_txns",
+        "Number of broadcast transactions dropped for exceeding a per-peer or per-sender quota",
+        &["network", "quota"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_quota_dropped_inc(network_id: NetworkId, quota: &'static str, num: usize) {
+    SHARED_MEMPOOL_QUOTA_DROPPED_TXNS
+        .with_label_values(&[network_id.as_str(), quota])
+        .inc_by(num as u64);
+}
+
 static TASK_SPAWN_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "mempool_bounded_executor_spawn_latency",
@@ -440,6 +482,22 @@ pub static DB_ERROR: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter for the number of journaled transactions processed on startup
+static RESTORED_TXNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "mempool_restored_txns_count",
+        "Number of journaled transactions processed when restoring mempool on startup",
+        &["status"]
+    )
+    .unwrap()
+});
+
+pub fn mempool_restored_txns_inc(status: &'static str, num: usize) {
+    RESTORED_TXNS_COUNT
+        .with_label_values(&[status])
+        .inc_by(num as u64);
+}
+
 /// Counter for the current number of active upstream peers mempool can
 /// broadcast to, summed across each of its networks
 static ACTIVE_UPSTREAM_PEERS_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
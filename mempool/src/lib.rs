@@ -57,11 +57,12 @@
 
 #[cfg(any(test, feature = "fuzzing"))]
 mod tests;
+pub use core_mempool::{MempoolTransactionInfo, MempoolTransactionStatus};
 pub use shared_mempool::{
     bootstrap, network,
     types::{
-        MempoolClientRequest, MempoolClientSender, MempoolEventsReceiver, QuorumStoreRequest,
-        QuorumStoreResponse, SubmissionStatus,
+        MempoolClientRequest, MempoolClientSender, MempoolEventsReceiver, PeerBroadcastState,
+        QuorumStoreRequest, QuorumStoreResponse, SubmissionStatus,
     },
 };
 #[cfg(any(test, feature = "fuzzing"))]
@@ -70,4 +71,5 @@ pub use tests::{fuzzing, mocks};
 mod core_mempool;
 pub mod counters;
 mod logging;
+mod mempooldb;
 mod shared_mempool;
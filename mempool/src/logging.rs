@@ -130,6 +130,7 @@ pub enum LogEntry {
     ReconfigUpdate,
     JsonRpc,
     GetTransaction,
+    Introspection,
     GetBlock,
     QuorumStore,
     StateSyncCommit,
@@ -144,6 +145,7 @@ pub enum LogEntry {
     CleanCommittedTxn,
     CleanRejectedTxn,
     ProcessReadyTxns,
+    AdmissionQuota,
     DBError,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
@@ -0,0 +1,34 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{core_mempool::TimelineState, tests::common::TestTransaction};
+use aptos_temppath::TempPath;
+
+#[test]
+fn test_put_get_delete() {
+    let tmp_dir = TempPath::new();
+    let txns: Vec<_> = vec![TestTransaction::new(0, 0, 1), TestTransaction::new(1, 3, 1)]
+        .into_iter()
+        .map(|txn| PersistedTransaction {
+            txn: txn.make_signed_transaction(),
+            timeline_state: TimelineState::NotReady,
+        })
+        .collect();
+
+    // Queued updates are flushed when the journal is dropped.
+    let journal = MempoolJournal::new(MempoolDB::new(&tmp_dir));
+    assert_eq!(journal.get_transactions().unwrap().len(), 0);
+    for txn in &txns {
+        journal.save_transaction(txn.clone());
+    }
+    drop(journal);
+
+    let journal = MempoolJournal::new(MempoolDB::new(&tmp_dir));
+    assert_eq!(journal.get_transactions().unwrap().len(), 2);
+    journal.delete_transaction(txns[0].txn.sender(), txns[0].txn.sequence_number());
+    drop(journal);
+
+    let db = MempoolDB::new(&tmp_dir);
+    assert_eq!(db.get_transactions().unwrap(), vec![txns[1].clone()]);
+}
@@ -0,0 +1,149 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! MempoolDB is an on-disk journal of the transactions held by mempool, used to reload pending
+//! transactions after a node restart.
+
+#[cfg(test)]
+mod mempooldb_test;
+mod schema;
+
+pub use schema::transaction::PersistedTransaction;
+
+use crate::{
+    counters,
+    logging::{LogEntry, LogSchema},
+    mempooldb::schema::{transaction::TransactionSchema, TRANSACTION_CF_NAME},
+};
+use anyhow::Result;
+use aptos_logger::prelude::*;
+use aptos_types::account_address::AccountAddress;
+use schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
+use std::{
+    path::Path,
+    sync::{mpsc, Arc},
+    thread,
+    time::Instant,
+};
+
+pub struct MempoolDB {
+    db: DB,
+}
+
+impl MempoolDB {
+    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
+        let column_families = vec![
+            /* UNUSED CF = */ DEFAULT_COLUMN_FAMILY_NAME,
+            TRANSACTION_CF_NAME,
+        ];
+
+        let path = db_root_path.as_ref().join("mempooldb");
+        let instant = Instant::now();
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open(path.clone(), "mempool", column_families, &opts)
+            .expect("MempoolDB open failed; unable to continue");
+
+        info!(
+            "Opened MempoolDB at {:?} in {} ms",
+            path,
+            instant.elapsed().as_millis()
+        );
+
+        Self { db }
+    }
+
+    /// Get all journaled transactions.
+    pub fn get_transactions(&self) -> Result<Vec<PersistedTransaction>> {
+        let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.map(|res| res.map(|(_key, txn)| txn)).collect()
+    }
+
+    fn write_ops(&self, ops: impl IntoIterator<Item = JournalOp>) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        for op in ops {
+            match op {
+                JournalOp::Save(txn) => batch.put::<TransactionSchema>(
+                    &(txn.txn.sender(), txn.txn.sequence_number()),
+                    &txn,
+                )?,
+                JournalOp::Delete(sender, sequence_number) => {
+                    batch.delete::<TransactionSchema>(&(sender, sequence_number))?
+                }
+            }
+        }
+        self.db.write_schemas(batch)
+    }
+}
+
+enum JournalOp {
+    Save(PersistedTransaction),
+    Delete(AccountAddress, u64),
+}
+
+/// Journal of the transactions held by mempool. Updates are queued and applied in order by a
+/// background writer, which batches all pending updates into a single write, so mempool never
+/// performs disk I/O while holding its lock.
+pub struct MempoolJournal {
+    db: Arc<MempoolDB>,
+    sender: Option<mpsc::Sender<JournalOp>>,
+    writer: Option<thread::JoinHandle<()>>,
+}
+
+impl MempoolJournal {
+    pub fn new(db: MempoolDB) -> Self {
+        let db = Arc::new(db);
+        let (sender, receiver) = mpsc::channel();
+        let writer_db = db.clone();
+        let writer = thread::Builder::new()
+            .name("mempool-journal".into())
+            .spawn(move || {
+                while let Ok(op) = receiver.recv() {
+                    let ops = std::iter::once(op).chain(receiver.try_iter());
+                    if let Err(e) = writer_db.write_ops(ops) {
+                        error!(LogSchema::new(LogEntry::DBError).error(&e));
+                        counters::DB_ERROR.inc();
+                    }
+                }
+            })
+            .expect("Failed to spawn the mempool journal writer");
+
+        Self {
+            db,
+            sender: Some(sender),
+            writer: Some(writer),
+        }
+    }
+
+    pub fn save_transaction(&self, txn: PersistedTransaction) {
+        self.send(JournalOp::Save(txn));
+    }
+
+    pub fn delete_transaction(&self, sender: AccountAddress, sequence_number: u64) {
+        self.send(JournalOp::Delete(sender, sequence_number));
+    }
+
+    /// Get all journaled transactions. Updates that are still queued are not reflected.
+    pub fn get_transactions(&self) -> Result<Vec<PersistedTransaction>> {
+        self.db.get_transactions()
+    }
+
+    fn send(&self, op: JournalOp) {
+        if let Some(sender) = &self.sender {
+            // The writer only exits once the sender is dropped.
+            let _ = sender.send(op);
+        }
+    }
+}
+
+impl Drop for MempoolJournal {
+    /// Flushes all queued updates before closing the journal.
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
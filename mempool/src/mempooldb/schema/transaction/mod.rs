@@ -0,0 +1,67 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for transactions journaled by mempool.
+//!
+//! Serialized transaction bytes identified by sender address and sequence number.
+//! ```text
+//! |<-------key------->|<--------value-------->|
+//! | address | seq_num | persisted transaction |
+//! ```
+
+use super::{ensure_slice_len_eq, TRANSACTION_CF_NAME};
+use crate::core_mempool::TimelineState;
+use anyhow::Result;
+use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use schemadb::schema::{KeyCodec, Schema, ValueCodec};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::{TryFrom, TryInto},
+    mem::size_of,
+};
+
+pub struct TransactionSchema;
+
+impl Schema for TransactionSchema {
+    const COLUMN_FAMILY_NAME: schemadb::ColumnFamilyName = TRANSACTION_CF_NAME;
+    type Key = (AccountAddress, u64);
+    type Value = PersistedTransaction;
+}
+
+/// A transaction as it was inserted into mempool, together with the timeline state it was
+/// inserted with so that it can be reinserted with the same broadcast eligibility.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PersistedTransaction {
+    pub txn: SignedTransaction,
+    pub timeline_state: TimelineState,
+}
+
+impl KeyCodec<TransactionSchema> for (AccountAddress, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref address, seq_num) = *self;
+
+        let mut encoded = address.to_vec();
+        encoded.extend_from_slice(&seq_num.to_be_bytes());
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, AccountAddress::LENGTH + size_of::<u64>())?;
+
+        let address = AccountAddress::try_from(&data[..AccountAddress::LENGTH])?;
+        let seq_num = u64::from_be_bytes(data[AccountAddress::LENGTH..].try_into()?);
+
+        Ok((address, seq_num))
+    }
+}
+
+impl ValueCodec<TransactionSchema> for PersistedTransaction {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
@@ -0,0 +1,56 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Admission quotas for transactions broadcast by other nodes.
+//!
+//! Quotas are tracked over fixed windows of `shared_mempool_admission_interval_ms`: each
+//! upstream peer may push at most `max_txns_per_peer_per_interval` transactions per window.
+//! This keeps a single peer from crowding everyone else out of broadcast. A peer's quota is only
+//! ever charged for the transactions that peer sends, so it cannot be used to censor others.
+//!
+//! How much of mempool a single sender may occupy through public broadcasts is capped
+//! separately, by `max_broadcast_txns_per_user`, against the transactions actually held in
+//! mempool (see `tasks::process_incoming_transactions`).
+use aptos_config::{config::MempoolConfig, network_id::PeerNetworkId};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+pub(crate) struct AdmissionQuotas {
+    interval: Duration,
+    max_txns_per_peer: usize,
+    window_start: Instant,
+    peer_txns: HashMap<PeerNetworkId, usize>,
+}
+
+impl AdmissionQuotas {
+    pub(crate) fn new(config: &MempoolConfig) -> Self {
+        Self {
+            interval: Duration::from_millis(config.shared_mempool_admission_interval_ms),
+            max_txns_per_peer: config.max_txns_per_peer_per_interval,
+            window_start: Instant::now(),
+            peer_txns: HashMap::new(),
+        }
+    }
+
+    /// Charges `num_txns` against `peer`'s quota for the current window.
+    /// Returns false (and charges nothing) if the whole batch does not fit.
+    pub(crate) fn admit_peer_batch(&mut self, peer: PeerNetworkId, num_txns: usize) -> bool {
+        self.maybe_reset_window();
+        let used = self.peer_txns.entry(peer).or_insert(0);
+        if *used + num_txns > self.max_txns_per_peer {
+            return false;
+        }
+        *used += num_txns;
+        true
+    }
+
+    fn maybe_reset_window(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.window_start) >= self.interval {
+            self.window_start = now;
+            self.peer_txns.clear();
+        }
+    }
+}
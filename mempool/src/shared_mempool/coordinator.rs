@@ -148,6 +148,15 @@ async fn handle_client_request<V>(
                 ))
                 .await;
         }
+        MempoolClientRequest::GetTransactionInfoByHash(hash, callback) => {
+            tasks::process_client_get_transaction_info(smp, hash, callback);
+        }
+        MempoolClientRequest::GetAccountTransactionsInfo(address, callback) => {
+            tasks::process_client_get_account_transactions_info(smp, address, callback);
+        }
+        MempoolClientRequest::GetPeerBroadcastStates(callback) => {
+            tasks::process_client_get_peer_broadcast_states(smp, callback);
+        }
     }
 }
 
@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod admission;
 pub mod network;
 mod runtime;
 pub(crate) mod types;
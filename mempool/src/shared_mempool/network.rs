@@ -9,7 +9,8 @@ use crate::{
     shared_mempool::{
         tasks,
         types::{
-            notify_subscribers, BatchId, PeerSyncState, SharedMempool, SharedMempoolNotification,
+            notify_subscribers, BatchId, PeerBroadcastState, PeerSyncState, SharedMempool,
+            SharedMempoolNotification,
         },
     },
 };
@@ -93,7 +94,10 @@ pub struct MempoolNetworkSender {
 
 pub fn network_endpoint_config(max_broadcasts_per_peer: usize) -> AppConfig {
     AppConfig::p2p(
-        [ProtocolId::MempoolDirectSend],
+        [
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::MempoolDirectSendCompressed,
+        ],
         aptos_channel::Config::new(max_broadcasts_per_peer)
             .queue_style(QueueStyle::KLAST)
             .counters(&counters::PENDING_MEMPOOL_NETWORK_EVENTS),
@@ -111,6 +115,22 @@ impl NewNetworkSender for MempoolNetworkSender {
     }
 }
 
+impl MempoolNetworkSender {
+    /// Sends `message` over the compressed mempool protocol.
+    /// Callers must check that the recipient supports `ProtocolId::MempoolDirectSendCompressed`.
+    pub fn send_compressed_to(
+        &self,
+        recipient: PeerId,
+        message: MempoolSyncMsg,
+    ) -> Result<(), NetworkError> {
+        fail_point!("mempool::send_to", |_| {
+            Err(anyhow::anyhow!("Injected error in mempool::send_to").into())
+        });
+        let protocol = ProtocolId::MempoolDirectSendCompressed;
+        self.inner.send_to(recipient, protocol, message)
+    }
+}
+
 #[async_trait]
 impl ApplicationNetworkSender<MempoolSyncMsg> for MempoolNetworkSender {
     fn send_to(&self, recipient: PeerId, message: MempoolSyncMsg) -> Result<(), NetworkError> {
@@ -159,6 +179,8 @@ type MempoolMultiNetworkSender = MultiNetworkSender<MempoolSyncMsg, MempoolNetwo
 pub(crate) struct MempoolNetworkInterface {
     peer_metadata_storage: Arc<PeerMetadataStorage>,
     sender: MempoolMultiNetworkSender,
+    // Per-network senders, used directly for protocols the multi-network sender doesn't expose
+    network_senders: HashMap<NetworkId, MempoolNetworkSender>,
     sync_states: Arc<LockingHashMap<PeerNetworkId, PeerSyncState>>,
     prioritized_peers: Arc<Mutex<Vec<PeerNetworkId>>>,
     role: RoleType,
@@ -174,7 +196,8 @@ impl MempoolNetworkInterface {
     ) -> MempoolNetworkInterface {
         MempoolNetworkInterface {
             peer_metadata_storage,
-            sender: MultiNetworkSender::new(network_senders),
+            sender: MultiNetworkSender::new(network_senders.clone()),
+            network_senders,
             sync_states: Arc::new(LockingHashMap::new()),
             prioritized_peers: Arc::new(Mutex::new(Vec::new())),
             role,
@@ -190,7 +213,7 @@ impl MempoolNetworkInterface {
             // If we have a new peer, let's insert new data, otherwise, let's just update the current state
             if is_new_peer {
                 counters::active_upstream_peers(&peer.network_id()).inc();
-                sync_states.insert(peer, PeerSyncState::new(metadata));
+                sync_states.insert(peer, PeerSyncState::new(metadata, &self.mempool_config));
             } else if let Some(peer_state) = sync_states.get_mut(&peer) {
                 peer_state.metadata = metadata;
             }
@@ -337,6 +360,30 @@ impl MempoolNetworkInterface {
         }
     }
 
+    /// Remembers the transactions `peer` sent us, so they aren't broadcast back to it.
+    /// Only upstream peers are tracked, as they are the only ones we broadcast to.
+    pub fn record_received_transactions(
+        &self,
+        peer: &PeerNetworkId,
+        transactions: &[SignedTransaction],
+    ) {
+        if let Some(state) = self.sync_states.write_lock().get_mut(peer) {
+            for txn in transactions {
+                state.recently_seen.insert(txn.committed_hash());
+            }
+        }
+    }
+
+    /// Returns a snapshot of the broadcast state of every upstream peer.
+    pub fn peer_broadcast_states(&self) -> Vec<PeerBroadcastState> {
+        self.sync_states
+            .read_all()
+            .iter()
+            .map(|(peer, state)| PeerBroadcastState::new(*peer, state))
+            .sorted_by_key(|state| state.peer)
+            .collect()
+    }
+
     /// Peers are prioritized when the local is a validator, or it's within the default failovers.
     /// One is added for the primary peer
     fn check_peer_prioritized(&self, peer: PeerNetworkId) -> Result<(), BroadcastError> {
@@ -363,7 +410,7 @@ impl MempoolNetworkInterface {
         peer: PeerNetworkId,
         scheduled_backoff: bool,
         smp: &mut SharedMempool<V>,
-    ) -> Result<(BatchId, Vec<SignedTransaction>, Option<&str>), BroadcastError>
+    ) -> Result<(BatchId, Vec<SignedTransaction>, Option<&str>, bool), BroadcastError>
     where
         V: TransactionValidation,
     {
@@ -450,7 +497,28 @@ impl MempoolNetworkInterface {
             return Err(BroadcastError::NoTransactions(peer));
         }
 
-        Ok((batch_id, transactions, metric_label))
+        // Don't send the peer transactions it recently sent us
+        let num_txns = transactions.len();
+        let transactions = state.recently_seen.filter_unseen(transactions);
+        counters::shared_mempool_recently_seen_filtered_inc(
+            peer.network_id(),
+            num_txns - transactions.len(),
+        );
+        if transactions.is_empty() {
+            // Nothing is left to send for this batch, so move past it
+            state.timeline_id = std::cmp::max(state.timeline_id, batch_id.1);
+            state.broadcast_info.sent_batches.remove(&batch_id);
+            state.broadcast_info.retry_batches.remove(&batch_id);
+            return Err(BroadcastError::NoTransactions(peer));
+        }
+
+        let use_compression = self.mempool_config.shared_mempool_compress_broadcasts
+            && state
+                .metadata
+                .application_protocols
+                .contains(ProtocolId::MempoolDirectSendCompressed);
+
+        Ok((batch_id, transactions, metric_label, use_compression))
     }
 
     /// Sends a batch to the given `Peer`
@@ -459,13 +527,22 @@ impl MempoolNetworkInterface {
         peer: PeerNetworkId,
         batch_id: BatchId,
         transactions: Vec<SignedTransaction>,
+        use_compression: bool,
     ) -> Result<(), BroadcastError> {
         let request = MempoolSyncMsg::BroadcastTransactionsRequest {
             request_id: bcs::to_bytes(&batch_id).expect("failed BCS serialization of batch ID"),
             transactions,
         };
 
-        if let Err(e) = self.sender.send_to(peer, request) {
+        let result = if use_compression {
+            match self.network_senders.get(&peer.network_id()) {
+                Some(sender) => sender.send_compressed_to(peer.peer_id(), request),
+                None => return Err(BroadcastError::PeerNotFound(peer)),
+            }
+        } else {
+            self.sender.send_to(peer, request)
+        };
+        if let Err(e) = result {
             counters::network_send_fail_inc(counters::BROADCAST_TXNS);
             return Err(BroadcastError::NetworkError(peer, e.into()));
         }
@@ -508,12 +585,13 @@ impl MempoolNetworkInterface {
     {
         // Start timer for tracking broadcast latency.
         let start_time = Instant::now();
-        let (batch_id, transactions, metric_label) =
+        let (batch_id, transactions, metric_label, use_compression) =
             self.determine_broadcast_batch(peer, scheduled_backoff, smp)?;
 
         let num_txns = transactions.len();
         let send_time = SystemTime::now();
-        self.send_batch(peer, batch_id, transactions).await?;
+        self.send_batch(peer, batch_id, transactions, use_compression)
+            .await?;
         let num_pending_broadcasts = self.update_broadcast_state(peer, batch_id, send_time)?;
         notify_subscribers(SharedMempoolNotification::Broadcast, &smp.subscribers);
 
@@ -539,6 +617,12 @@ impl MempoolNetworkInterface {
                 counters::BACKPRESSURE_BROADCAST_LABEL,
             );
         }
+        if use_compression {
+            counters::shared_mempool_broadcast_type_inc(
+                network_id,
+                counters::COMPRESSED_BROADCAST_LABEL,
+            );
+        }
         Ok(())
     }
 }
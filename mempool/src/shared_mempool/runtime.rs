@@ -6,6 +6,7 @@ use crate::{
     network::{MempoolNetworkEvents, MempoolNetworkSender},
     shared_mempool::{
         coordinator::{coordinator, gc_coordinator, snapshot_job},
+        tasks::restore_persisted_transactions,
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
@@ -63,6 +64,10 @@ pub(crate) fn start_shared_mempool<V>(
         peer_metadata_storage,
     );
 
+    if config.mempool.persist_transactions {
+        restore_persisted_transactions(&smp);
+    }
+
     executor.spawn(coordinator(
         smp,
         executor.clone(),
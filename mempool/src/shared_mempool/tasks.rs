@@ -3,13 +3,13 @@
 
 //! Tasks that are executed by coordinators (short-lived compared to coordinators)
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TxnPointer},
+    core_mempool::{CoreMempool, MempoolTransactionInfo, TimelineState, TxnPointer},
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
     shared_mempool::types::{
-        notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification,
-        SubmissionStatusBundle,
+        notify_subscribers, PeerBroadcastState, ScheduledBroadcast, SharedMempool,
+        SharedMempoolNotification, SubmissionStatusBundle,
     },
     QuorumStoreRequest, QuorumStoreResponse, SubmissionStatus,
 };
@@ -20,6 +20,7 @@ use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::HistogramTimer;
 use aptos_types::{
+    account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::OnChainConfigPayload,
     transaction::SignedTransaction,
@@ -106,7 +107,8 @@ pub(crate) async fn process_client_transaction_submission<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer_client();
-    let statuses = process_incoming_transactions(&smp, vec![transaction], TimelineState::NotReady);
+    let statuses =
+        process_incoming_transactions(&smp, vec![transaction], TimelineState::NotReady, None);
     log_txn_process_results(&statuses, None);
 
     if let Some(status) = statuses.get(0) {
@@ -142,6 +144,51 @@ pub(crate) async fn process_client_get_transaction<V>(
     }
 }
 
+/// Processes introspection request for a transaction by hash.
+pub(crate) fn process_client_get_transaction_info<V>(
+    smp: &SharedMempool<V>,
+    hash: HashValue,
+    callback: oneshot::Sender<Option<MempoolTransactionInfo>>,
+) where
+    V: TransactionValidation,
+{
+    let info = smp.mempool.lock().get_info_by_hash(hash);
+    send_introspection_response(callback, info);
+}
+
+/// Processes introspection request for all transactions of an account.
+pub(crate) fn process_client_get_account_transactions_info<V>(
+    smp: &SharedMempool<V>,
+    address: AccountAddress,
+    callback: oneshot::Sender<Vec<MempoolTransactionInfo>>,
+) where
+    V: TransactionValidation,
+{
+    let infos = smp.mempool.lock().get_account_transactions_info(&address);
+    send_introspection_response(callback, infos);
+}
+
+/// Processes introspection request for the broadcast state of upstream peers.
+pub(crate) fn process_client_get_peer_broadcast_states<V>(
+    smp: &SharedMempool<V>,
+    callback: oneshot::Sender<Vec<PeerBroadcastState>>,
+) where
+    V: TransactionValidation,
+{
+    let states = smp.network_interface.peer_broadcast_states();
+    send_introspection_response(callback, states);
+}
+
+fn send_introspection_response<T>(callback: oneshot::Sender<T>, response: T) {
+    if callback.send(response).is_err() {
+        error!(LogSchema::event_log(
+            LogEntry::Introspection,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
 /// Processes transactions from other nodes.
 pub(crate) async fn process_transaction_broadcast<V>(
     smp: SharedMempool<V>,
@@ -155,7 +202,9 @@ pub(crate) async fn process_transaction_broadcast<V>(
 {
     timer.stop_and_record();
     let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
-    let results = process_incoming_transactions(&smp, transactions, timeline_state);
+    smp.network_interface
+        .record_received_transactions(&peer, &transactions);
+    let results = process_incoming_transactions(&smp, transactions, timeline_state, Some(peer));
     log_txn_process_results(&results, Some(peer));
 
     let ack_response = gen_ack_response(request_id, results, &peer);
@@ -223,16 +272,30 @@ pub(crate) fn update_ack_counter(
 
 /// Submits a list of SignedTransaction to the local mempool
 /// and returns a vector containing AdmissionControlStatus.
+/// Transactions broadcast by `peer` are subject to the per-peer admission quota. Once validated,
+/// new transactions from public peers are only accepted if their sender holds fewer than
+/// `max_broadcast_txns_per_user` transactions in mempool, so invalid transactions can never
+/// count against a sender. Validator and VFN broadcasts, and re-broadcasts of transactions
+/// already in mempool, are exempt.
 pub(crate) fn process_incoming_transactions<V>(
     smp: &SharedMempool<V>,
     transactions: Vec<SignedTransaction>,
     timeline_state: TimelineState,
+    peer: Option<PeerNetworkId>,
 ) -> Vec<SubmissionStatusBundle>
 where
     V: TransactionValidation,
 {
     let mut statuses = vec![];
 
+    let transactions = match peer {
+        Some(peer) => enforce_admission_quotas(smp, transactions, peer, &mut statuses),
+        None => transactions,
+    };
+    if transactions.is_empty() {
+        return statuses;
+    }
+
     let start_storage_read = Instant::now();
     // Track latency: fetching seq number
     let seq_numbers = transactions
@@ -290,11 +353,34 @@ where
         .map(|t| smp.validator.read().validate_transaction(t.0.clone()))
         .collect::<Vec<_>>();
     vm_validation_timer.stop_and_record();
+    let enforce_sender_quota = peer.map_or(false, |peer| {
+        let network_id = peer.network_id();
+        !network_id.is_validator_network() && !network_id.is_vfn_network()
+    });
     {
         let mut mempool = smp.mempool.lock();
+        let mut num_over_sender_quota = 0;
         for (idx, (transaction, crsn_or_seqno)) in transactions.into_iter().enumerate() {
             if let Ok(validation_result) = &validation_results[idx] {
                 match validation_result.status() {
+                    None if enforce_sender_quota
+                        && !mempool.contains_transaction(
+                            &transaction.sender(),
+                            transaction.sequence_number(),
+                        )
+                        && mempool.num_transactions_for_sender(&transaction.sender())
+                            >= smp.config.max_broadcast_txns_per_user =>
+                    {
+                        num_over_sender_quota += 1;
+                        statuses.push((
+                            transaction,
+                            (
+                                MempoolStatus::new(MempoolStatusCode::TooManyTransactions)
+                                    .with_message("sender exceeded broadcast quota".to_string()),
+                                None,
+                            ),
+                        ));
+                    }
                     None => {
                         let gas_amount = transaction.max_gas_amount();
                         let ranking_score = validation_result.score();
@@ -319,11 +405,118 @@ where
                 }
             }
         }
+        if let Some(peer) = peer {
+            if num_over_sender_quota > 0 {
+                counters::shared_mempool_quota_dropped_inc(
+                    peer.network_id(),
+                    counters::SENDER_QUOTA_LABEL,
+                    num_over_sender_quota,
+                );
+            }
+        }
     }
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
     statuses
 }
 
+/// Drops the transactions broadcast by `peer` if they exceed its admission quota, and records a
+/// rejection status for each of them.
+/// An over-quota peer is answered with `MempoolIsFull` so that it backs off.
+fn enforce_admission_quotas<V>(
+    smp: &SharedMempool<V>,
+    transactions: Vec<SignedTransaction>,
+    peer: PeerNetworkId,
+    statuses: &mut Vec<SubmissionStatusBundle>,
+) -> Vec<SignedTransaction>
+where
+    V: TransactionValidation,
+{
+    if !smp
+        .admission_quotas
+        .lock()
+        .admit_peer_batch(peer, transactions.len())
+    {
+        counters::shared_mempool_quota_dropped_inc(
+            peer.network_id(),
+            counters::PEER_QUOTA_LABEL,
+            transactions.len(),
+        );
+        sample!(
+            SampleRate::Duration(Duration::from_secs(60)),
+            warn!(LogSchema::new(LogEntry::AdmissionQuota).peer(&peer))
+        );
+        statuses.extend(transactions.into_iter().map(|t| {
+            (
+                t,
+                (
+                    MempoolStatus::new(MempoolStatusCode::MempoolIsFull)
+                        .with_message("peer exceeded admission quota".to_string()),
+                    None,
+                ),
+            )
+        }));
+        return vec![];
+    }
+    transactions
+}
+
+/// Reloads transactions journaled on disk before the last shutdown. Expired transactions are
+/// dropped, the rest are revalidated and reinserted as if they were freshly submitted.
+/// Transactions are only removed from the journal once they have been rejected, so a crash
+/// while restoring does not lose them.
+pub(crate) fn restore_persisted_transactions<V>(smp: &SharedMempool<V>)
+where
+    V: TransactionValidation,
+{
+    let persisted_txns = smp.mempool.lock().get_persisted_transactions();
+    let now_secs = aptos_infallible::duration_since_epoch().as_secs();
+    let (unexpired_txns, expired_txns): (Vec<_>, Vec<_>) = persisted_txns
+        .into_iter()
+        .partition(|persisted_txn| persisted_txn.txn.expiration_timestamp_secs() > now_secs);
+    counters::mempool_restored_txns_inc(counters::RESTORE_EXPIRED_LABEL, expired_txns.len());
+    {
+        let mempool = smp.mempool.lock();
+        for persisted_txn in &expired_txns {
+            mempool.forget_persisted_transaction(
+                &persisted_txn.txn.sender(),
+                persisted_txn.txn.sequence_number(),
+            );
+        }
+    }
+
+    let (non_qualified_txns, txns): (Vec<_>, Vec<_>) = unexpired_txns
+        .into_iter()
+        .partition(|persisted_txn| persisted_txn.timeline_state == TimelineState::NonQualified);
+    for (txns, timeline_state) in [
+        (txns, TimelineState::NotReady),
+        (non_qualified_txns, TimelineState::NonQualified),
+    ] {
+        if txns.is_empty() {
+            continue;
+        }
+        let txns = txns
+            .into_iter()
+            .map(|persisted_txn| persisted_txn.txn)
+            .collect();
+        let statuses = process_incoming_transactions(smp, txns, timeline_state, None);
+        let mempool = smp.mempool.lock();
+        let mut num_accepted = 0;
+        for (txn, (mempool_status, _)) in &statuses {
+            if mempool_status.code == MempoolStatusCode::Accepted {
+                num_accepted += 1;
+            } else {
+                mempool.forget_persisted_transaction(&txn.sender(), txn.sequence_number());
+            }
+        }
+        drop(mempool);
+        counters::mempool_restored_txns_inc(counters::RESTORE_ACCEPTED_LABEL, num_accepted);
+        counters::mempool_restored_txns_inc(
+            counters::RESTORE_REJECTED_LABEL,
+            statuses.len() - num_accepted,
+        );
+    }
+}
+
 fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<PeerNetworkId>) {
     let network = match sender {
         Some(peer) => peer.network_id().to_string(),
@@ -371,7 +564,12 @@ pub(crate) fn process_quorum_store_request<V: TransactionValidation>(
     debug!(LogSchema::event_log(LogEntry::QuorumStore, LogEvent::Received).quorum_store_msg(&req));
 
     let (resp, callback, counter_label) = match req {
-        QuorumStoreRequest::GetBatchRequest(max_batch_size, transactions, callback) => {
+        QuorumStoreRequest::GetBatchRequest(
+            max_batch_size,
+            max_batch_gas,
+            transactions,
+            callback,
+        ) => {
             let exclude_transactions: HashSet<TxnPointer> = transactions
                 .iter()
                 .map(|txn| (txn.sender, txn.sequence_number))
@@ -384,7 +582,7 @@ pub(crate) fn process_quorum_store_request<V: TransactionValidation>(
                 let curr_time = aptos_infallible::duration_since_epoch();
                 mempool.gc_by_expiration_time(curr_time);
                 let batch_size = cmp::max(max_batch_size, 1);
-                txns = mempool.get_batch(batch_size, exclude_transactions);
+                txns = mempool.get_batch(batch_size, max_batch_gas, exclude_transactions);
             }
             counters::mempool_service_transactions(counters::GET_BLOCK_LABEL, txns.len());
 
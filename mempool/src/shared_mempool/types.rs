@@ -3,8 +3,9 @@
 
 //! Objects used by/related to shared mempool
 use crate::{
-    core_mempool::CoreMempool, network::MempoolNetworkInterface,
-    shared_mempool::network::MempoolNetworkSender,
+    core_mempool::{CoreMempool, MempoolTransactionInfo},
+    network::MempoolNetworkInterface,
+    shared_mempool::{admission::AdmissionQuotas, network::MempoolNetworkSender},
 };
 use anyhow::Result;
 use aptos_config::{
@@ -14,7 +15,8 @@ use aptos_config::{
 use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_types::{
-    mempool_status::MempoolStatus, transaction::SignedTransaction, vm_status::DiscardedVMStatus,
+    account_address::AccountAddress, mempool_status::MempoolStatus, transaction::SignedTransaction,
+    vm_status::DiscardedVMStatus,
 };
 use consensus_types::common::TransactionSummary;
 use futures::{
@@ -25,12 +27,12 @@ use futures::{
 use network::{application::storage::PeerMetadataStorage, transport::ConnectionMetadata};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     fmt,
     pin::Pin,
     sync::Arc,
     task::Waker,
-    time::{Instant, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 use storage_interface::DbReader;
 use tokio::runtime::Handle;
@@ -48,6 +50,7 @@ where
     pub db: Arc<dyn DbReader>,
     pub validator: Arc<RwLock<V>>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
+    pub(crate) admission_quotas: Arc<Mutex<AdmissionQuotas>>,
 }
 
 impl<V: TransactionValidation + 'static> SharedMempool<V> {
@@ -67,6 +70,7 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             role,
             config.clone(),
         );
+        let admission_quotas = Arc::new(Mutex::new(AdmissionQuotas::new(&config)));
         SharedMempool {
             mempool,
             config,
@@ -74,6 +78,7 @@ impl<V: TransactionValidation + 'static> SharedMempool<V> {
             db,
             validator,
             subscribers,
+            admission_quotas,
         }
     }
 
@@ -154,6 +159,8 @@ pub enum QuorumStoreRequest {
     GetBatchRequest(
         // max batch size
         u64,
+        // max sum of the max_gas_amount of the transactions in the batch
+        u64,
         // transactions to exclude from the requested batch
         Vec<TransactionSummary>,
         // callback to respond to
@@ -171,14 +178,14 @@ pub enum QuorumStoreRequest {
 impl fmt::Display for QuorumStoreRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let payload = match self {
-            QuorumStoreRequest::GetBatchRequest(batch_size, excluded_txns, _) => {
+            QuorumStoreRequest::GetBatchRequest(batch_size, batch_gas, excluded_txns, _) => {
                 let mut txns_str = "".to_string();
                 for tx in excluded_txns.iter() {
                     txns_str += &format!("{} ", tx);
                 }
                 format!(
-                    "GetBatchRequest [batch_size: {}, excluded_txns: {}]",
-                    batch_size, txns_str
+                    "GetBatchRequest [batch_size: {}, batch_gas: {}, excluded_txns: {}]",
+                    batch_size, batch_gas, txns_str
                 )
             }
             QuorumStoreRequest::RejectNotification(rejected_txns, _) => {
@@ -208,6 +215,10 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Read-only introspection requests, e.g. for the debug interface.
+    GetTransactionInfoByHash(HashValue, oneshot::Sender<Option<MempoolTransactionInfo>>),
+    GetAccountTransactionsInfo(AccountAddress, oneshot::Sender<Vec<MempoolTransactionInfo>>),
+    GetPeerBroadcastStates(oneshot::Sender<Vec<PeerBroadcastState>>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
@@ -221,14 +232,101 @@ pub(crate) struct PeerSyncState {
     pub timeline_id: u64,
     pub broadcast_info: BroadcastInfo,
     pub metadata: ConnectionMetadata,
+    /// Transactions recently received from the peer, which are not broadcast back to it.
+    pub recently_seen: RecentlySeenTxns,
 }
 
 impl PeerSyncState {
-    pub fn new(metadata: ConnectionMetadata) -> Self {
+    pub fn new(metadata: ConnectionMetadata, config: &MempoolConfig) -> Self {
         PeerSyncState {
             timeline_id: 0,
             broadcast_info: BroadcastInfo::new(),
             metadata,
+            recently_seen: RecentlySeenTxns::new(
+                Duration::from_millis(config.shared_mempool_recently_seen_ttl_ms),
+                config.shared_mempool_recently_seen_capacity,
+            ),
+        }
+    }
+}
+
+/// Bounded, short-lived set of transaction hashes.
+/// Entries expire after `ttl`, and the oldest entries are evicted once `capacity` is reached.
+#[derive(Clone, Debug)]
+pub(crate) struct RecentlySeenTxns {
+    ttl: Duration,
+    capacity: usize,
+    hashes: HashSet<HashValue>,
+    insertion_order: VecDeque<(Instant, HashValue)>,
+}
+
+impl RecentlySeenTxns {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            hashes: HashSet::new(),
+            insertion_order: VecDeque::new(),
+        }
+    }
+
+    pub fn insert(&mut self, hash: HashValue) {
+        let now = Instant::now();
+        self.expire(now);
+        if self.capacity == 0 || !self.hashes.insert(hash) {
+            return;
+        }
+        self.insertion_order.push_back((now, hash));
+        while self.insertion_order.len() > self.capacity {
+            if let Some((_, evicted)) = self.insertion_order.pop_front() {
+                self.hashes.remove(&evicted);
+            }
+        }
+    }
+
+    /// Drops the transactions in `txns` that were recently seen.
+    pub fn filter_unseen(&mut self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+        self.expire(Instant::now());
+        if self.hashes.is_empty() {
+            return txns;
+        }
+        txns.into_iter()
+            .filter(|txn| !self.hashes.contains(&txn.committed_hash()))
+            .collect()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((inserted_at, hash)) = self.insertion_order.front() {
+            if now.duration_since(*inserted_at) < self.ttl {
+                break;
+            }
+            self.hashes.remove(hash);
+            self.insertion_order.pop_front();
+        }
+    }
+}
+
+/// Read-only view of the broadcast progress to an upstream peer, used for introspection.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerBroadcastState {
+    pub peer: PeerNetworkId,
+    // Position in the timeline up to which transactions have been broadcast to the peer.
+    pub timeline_id: u64,
+    pub backoff_mode: bool,
+    // Number of sent broadcasts that have not yet received an ack.
+    pub pending_acks: usize,
+    // Number of broadcasts that received a retry ack and are pending a resend.
+    pub pending_retries: usize,
+}
+
+impl PeerBroadcastState {
+    pub(crate) fn new(peer: PeerNetworkId, state: &PeerSyncState) -> Self {
+        Self {
+            peer,
+            timeline_id: state.timeline_id,
+            backoff_mode: state.broadcast_info.backoff_mode,
+            pending_acks: state.broadcast_info.sent_batches.len(),
+            pending_retries: state.broadcast_info.retry_batches.len(),
         }
     }
 }
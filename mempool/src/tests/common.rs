@@ -4,7 +4,7 @@
 use crate::core_mempool::{CoreMempool, TimelineState, TxnPointer};
 use anyhow::{format_err, Result};
 use aptos_config::config::NodeConfig;
-use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::AccountSequenceInfo,
@@ -77,12 +77,32 @@ impl TestTransaction {
         self.make_signed_transaction_impl(100, u64::max_value())
     }
 
+    /// Makes a transaction whose signature does not match its contents, e.g., spam forged by a
+    /// peer on behalf of the sender.
+    pub(crate) fn make_signed_transaction_with_invalid_signature(&self) -> SignedTransaction {
+        let privkey = Self::private_key();
+        let signature = privkey.sign(&self.make_raw_transaction(101, u64::max_value()));
+        SignedTransaction::new(
+            self.make_raw_transaction(100, u64::max_value()),
+            privkey.public_key(),
+            signature,
+        )
+    }
+
     fn make_signed_transaction_impl(
         &self,
         max_gas_amount: u64,
         exp_timestamp_secs: u64,
     ) -> SignedTransaction {
-        let raw_txn = RawTransaction::new_script(
+        let privkey = Self::private_key();
+        self.make_raw_transaction(max_gas_amount, exp_timestamp_secs)
+            .sign(&privkey, privkey.public_key())
+            .expect("Failed to sign raw transaction.")
+            .into_inner()
+    }
+
+    fn make_raw_transaction(&self, max_gas_amount: u64, exp_timestamp_secs: u64) -> RawTransaction {
+        RawTransaction::new_script(
             TestTransaction::get_address(self.address),
             self.sequence_number,
             Script::new(vec![], vec![], vec![]),
@@ -90,15 +110,14 @@ impl TestTransaction {
             self.gas_price,
             exp_timestamp_secs,
             ChainId::test(),
-        );
+        )
+    }
+
+    fn private_key() -> Ed25519PrivateKey {
         let mut seed: [u8; 32] = [0u8; 32];
         seed[..4].copy_from_slice(&[1, 2, 3, 4]);
         let mut rng: StdRng = StdRng::from_seed(seed);
-        let privkey = Ed25519PrivateKey::generate(&mut rng);
-        raw_txn
-            .sign(&privkey, privkey.public_key())
-            .expect("Failed to sign raw transaction.")
-            .into_inner()
+        Ed25519PrivateKey::generate(&mut rng)
     }
 
     pub(crate) fn get_address(address: usize) -> AccountAddress {
@@ -170,7 +189,7 @@ impl ConsensusMock {
         mempool: &mut CoreMempool,
         block_size: u64,
     ) -> Vec<SignedTransaction> {
-        let block = mempool.get_batch(block_size, self.0.clone());
+        let block = mempool.get_batch(block_size, u64::MAX, self.0.clone());
         self.0 = self
             .0
             .union(
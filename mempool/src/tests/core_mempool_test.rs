@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{CoreMempool, TimelineState, TtlCache},
+    core_mempool::{CoreMempool, MempoolTransactionStatus, TimelineState, TtlCache},
     tests::common::{
         add_signed_txn, add_txn, add_txns_to_mempool, exist_in_metrics_cache, setup_mempool,
         TestTransaction,
@@ -10,6 +10,7 @@ use crate::{
 };
 use aptos_config::config::NodeConfig;
 use aptos_crypto::HashValue;
+use aptos_temppath::TempPath;
 use aptos_types::{account_config::AccountSequenceInfo, transaction::SignedTransaction};
 use std::{
     collections::HashSet,
@@ -375,7 +376,7 @@ fn test_system_ttl() {
 
     // GC routine should clear transaction from first insert but keep last one.
     mempool.gc();
-    let batch = mempool.get_batch(1, HashSet::new());
+    let batch = mempool.get_batch(1, u64::MAX, HashSet::new());
     assert_eq!(vec![transaction.make_signed_transaction()], batch);
 }
 
@@ -387,11 +388,11 @@ fn test_commit_callback() {
     let txns = add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 6, 1)]);
 
     // Check that pool is empty.
-    assert!(pool.get_batch(1, HashSet::new()).is_empty());
+    assert!(pool.get_batch(1, u64::MAX, HashSet::new()).is_empty());
     // Transaction 5 got back from consensus.
     pool.remove_transaction(&TestTransaction::get_address(1), 5, false);
     // Verify that we can execute transaction 6.
-    assert_eq!(pool.get_batch(1, HashSet::new())[0], txns[0]);
+    assert_eq!(pool.get_batch(1, u64::MAX, HashSet::new())[0], txns[0]);
 }
 
 #[test]
@@ -405,7 +406,7 @@ fn test_sequence_number_cache() {
     // for AC is 0).
     add_txns_to_mempool(&mut pool, vec![TestTransaction::new(1, 6, 1)]);
     // Verify that we can execute transaction 6.
-    assert_eq!(pool.get_batch(1, HashSet::new()).len(), 1);
+    assert_eq!(pool.get_batch(1, u64::MAX, HashSet::new()).len(), 1);
 }
 
 #[test]
@@ -502,7 +503,7 @@ fn test_parking_lot_eviction() {
     }
     // Make sure that we have correct txns in Mempool.
     let mut txns: Vec<_> = pool
-        .get_batch(5, HashSet::new())
+        .get_batch(5, u64::MAX, HashSet::new())
         .iter()
         .map(SignedTransaction::sequence_number)
         .collect();
@@ -531,7 +532,7 @@ fn test_parking_lot_evict_only_for_ready_txn_insertion() {
 
     // Make sure that we have correct txns in Mempool.
     let mut txns: Vec<_> = pool
-        .get_batch(5, HashSet::new())
+        .get_batch(5, u64::MAX, HashSet::new())
         .iter()
         .map(SignedTransaction::sequence_number)
         .collect();
@@ -573,7 +574,7 @@ fn test_gc_ready_transaction() {
     pool.gc_by_expiration_time(Duration::from_secs(1));
 
     // Make sure txns 2 and 3 became not ready and we can't read them from any API.
-    let block = pool.get_batch(10, HashSet::new());
+    let block = pool.get_batch(10, u64::MAX, HashSet::new());
     assert_eq!(block.len(), 1);
     assert_eq!(block[0].sequence_number(), 0);
 
@@ -597,11 +598,31 @@ fn test_clean_stuck_transactions() {
         AccountSequenceInfo::Sequential(db_sequence_number),
         TimelineState::NotReady,
     );
-    let block = pool.get_batch(10, HashSet::new());
+    let block = pool.get_batch(10, u64::MAX, HashSet::new());
     assert_eq!(block.len(), 1);
     assert_eq!(block[0].sequence_number(), 10);
 }
 
+#[test]
+fn test_get_batch_max_gas() {
+    let mut pool = setup_mempool().0;
+    for seq in 0..5 {
+        let txn = TestTransaction::new(0, seq, 1).make_signed_transaction_with_max_gas_amount(100);
+        add_signed_txn(&mut pool, txn).unwrap();
+    }
+
+    // Stop before the batch gas would exceed the limit.
+    let block = pool.get_batch(10, 250, HashSet::new());
+    assert_eq!(block.len(), 2);
+    assert_eq!(block[0].sequence_number(), 0);
+    assert_eq!(block[1].sequence_number(), 1);
+
+    // The first transaction is always included so a single large one can't get stuck.
+    let block = pool.get_batch(10, 50, HashSet::new());
+    assert_eq!(block.len(), 1);
+    assert_eq!(block[0].sequence_number(), 0);
+}
+
 #[test]
 fn test_ttl_cache() {
     let mut cache = TtlCache::new(2, Duration::from_secs(1));
@@ -638,7 +659,7 @@ fn test_get_transaction_by_hash() {
         AccountSequenceInfo::Sequential(db_sequence_number),
         TimelineState::NotReady,
     );
-    let hash = txn.clone().committed_hash();
+    let hash = txn.committed_hash();
     let ret = pool.get_by_hash(hash);
     assert_eq!(ret, Some(txn));
 
@@ -669,7 +690,7 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
         AccountSequenceInfo::Sequential(db_sequence_number),
         TimelineState::NotReady,
     );
-    let new_txn_hash = new_txn.clone().committed_hash();
+    let new_txn_hash = new_txn.committed_hash();
 
     let txn_by_old_hash = pool.get_by_hash(hash);
     assert!(txn_by_old_hash.is_none());
@@ -677,3 +698,76 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     let txn_by_new_hash = pool.get_by_hash(new_txn_hash);
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
+
+#[test]
+fn test_persisted_transactions() {
+    let tmp_dir = TempPath::new();
+    let mut config = NodeConfig::random();
+    config.mempool.persist_transactions = true;
+    config.storage.dir = tmp_dir.path().to_path_buf();
+
+    let mut pool = CoreMempool::new(&config);
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![
+            TestTransaction::new(0, 0, 1),
+            TestTransaction::new(0, 1, 1),
+            TestTransaction::new(1, 0, 1),
+        ],
+    );
+    // Committed transactions are removed from the journal.
+    pool.remove_transaction(&TestTransaction::get_address(0), 0, false);
+    drop(pool);
+
+    // Restart mempool and check the remaining transactions are reloaded.
+    let pool = CoreMempool::new(&config);
+    let get_persisted_txns = |pool: &CoreMempool| -> HashSet<_> {
+        pool.get_persisted_transactions()
+            .into_iter()
+            .map(|persisted_txn| persisted_txn.txn)
+            .collect()
+    };
+    let expected_txns: HashSet<_> = vec![txns[1].clone(), txns[2].clone()].into_iter().collect();
+    assert_eq!(get_persisted_txns(&pool), expected_txns);
+
+    // Reading the journal does not drain it, only forgetting a transaction does.
+    assert_eq!(get_persisted_txns(&pool), expected_txns);
+    pool.forget_persisted_transaction(&TestTransaction::get_address(0), 1);
+    drop(pool);
+    let pool = CoreMempool::new(&config);
+    assert_eq!(
+        get_persisted_txns(&pool),
+        vec![txns[2].clone()].into_iter().collect()
+    );
+}
+
+#[test]
+fn test_account_transactions_info() {
+    let mut pool = setup_mempool().0;
+    let txns = add_txns_to_mempool(
+        &mut pool,
+        vec![TestTransaction::new(0, 0, 1), TestTransaction::new(0, 2, 1)],
+    );
+
+    // The transaction after the sequence number gap is parked.
+    let infos = pool.get_account_transactions_info(&TestTransaction::get_address(0));
+    assert_eq!(infos.len(), 2);
+    assert_eq!(infos[0].sequence_number, 0);
+    assert_eq!(infos[0].status, MempoolTransactionStatus::Ready);
+    assert_eq!(infos[1].sequence_number, 2);
+    assert_eq!(infos[1].status, MempoolTransactionStatus::Parked);
+    assert_eq!(infos[1].timeline_id, None);
+
+    let info = pool.get_info_by_hash(txns[1].committed_hash()).unwrap();
+    assert_eq!(info, infos[1]);
+
+    // Filling the gap makes the parked transaction ready.
+    add_txn(&mut pool, TestTransaction::new(0, 1, 1)).unwrap();
+    let info = pool.get_info_by_hash(txns[1].committed_hash()).unwrap();
+    assert_eq!(info.status, MempoolTransactionStatus::Ready);
+    assert!(info.timeline_id.is_some());
+
+    assert!(pool
+        .get_account_transactions_info(&TestTransaction::get_address(1))
+        .is_empty());
+}
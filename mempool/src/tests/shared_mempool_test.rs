@@ -2,15 +2,35 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    core_mempool::{CoreMempool, TimelineState},
     mocks::MockSharedMempool,
+    shared_mempool::{
+        admission::AdmissionQuotas,
+        tasks,
+        types::{RecentlySeenTxns, SharedMempool},
+    },
     tests::common::{batch_add_signed_txn, TestTransaction},
     QuorumStoreRequest,
 };
-use aptos_types::transaction::Transaction;
+use aptos_config::{
+    config::{MempoolConfig, NodeConfig},
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_temppath::TempPath;
+use aptos_types::{mempool_status::MempoolStatusCode, transaction::Transaction};
 use consensus_types::common::TransactionSummary;
 use futures::{channel::oneshot, executor::block_on, sink::SinkExt};
 use mempool_notifications::MempoolNotificationSender;
+use network::application::storage::PeerMetadataStorage;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+use storage_interface::mock::MockDbReaderWriter;
 use tokio::runtime::Builder;
+use vm_validator::mocks::mock_vm_validator::MockVMValidator;
 
 #[test]
 fn test_consensus_events_rejected_txns() {
@@ -93,3 +113,226 @@ fn test_mempool_notify_committed_txns() {
     assert_eq!(timeline.len(), 1);
     assert_eq!(timeline.get(0).unwrap(), &kept_txn);
 }
+
+#[test]
+fn test_admission_quotas() {
+    let config = MempoolConfig {
+        shared_mempool_admission_interval_ms: 60_000,
+        max_txns_per_peer_per_interval: 10,
+        ..MempoolConfig::default()
+    };
+    let mut quotas = AdmissionQuotas::new(&config);
+
+    // A peer can push up to its quota; a batch that does not fit is rejected as a whole
+    let peer = PeerNetworkId::random();
+    assert!(quotas.admit_peer_batch(peer, 6));
+    assert!(!quotas.admit_peer_batch(peer, 5));
+    assert!(quotas.admit_peer_batch(peer, 4));
+    assert!(!quotas.admit_peer_batch(peer, 1));
+
+    // Other peers are unaffected
+    assert!(quotas.admit_peer_batch(PeerNetworkId::random(), 10));
+
+    // Quotas are replenished once the interval elapses
+    let config = MempoolConfig {
+        shared_mempool_admission_interval_ms: 0,
+        max_txns_per_peer_per_interval: 1,
+        ..MempoolConfig::default()
+    };
+    let mut quotas = AdmissionQuotas::new(&config);
+    assert!(quotas.admit_peer_batch(peer, 1));
+    assert!(quotas.admit_peer_batch(peer, 1));
+}
+
+#[test]
+fn test_sender_quota_ignores_invalid_transactions() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_broadcast_txns_per_user = 2;
+    let smp = SharedMempool::new(
+        Arc::new(Mutex::new(CoreMempool::new(&config))),
+        config.mempool.clone(),
+        HashMap::new(),
+        Arc::new(MockDbReaderWriter),
+        Arc::new(RwLock::new(MockVMValidator)),
+        vec![],
+        config.base.role,
+        PeerMetadataStorage::new(&[NetworkId::Validator]),
+    );
+
+    // A peer floods mempool with forged transactions on behalf of the victim
+    let junk_txns: Vec<_> = (0..10)
+        .map(|i| TestTransaction::new(0, i, 1).make_signed_transaction_with_invalid_signature())
+        .collect();
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        junk_txns,
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random()),
+    );
+    assert!(statuses
+        .iter()
+        .all(|(_, (status, _))| status.code == MempoolStatusCode::VmError));
+
+    // The victim's own transactions are still accepted from another peer, up to the sender quota
+    let txns: Vec<_> = (0..3)
+        .map(|i| TestTransaction::new(0, i, 1).make_signed_transaction())
+        .collect();
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        txns,
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random()),
+    );
+    let codes: Vec<_> = statuses
+        .into_iter()
+        .map(|(_, (status, _))| status.code)
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            MempoolStatusCode::Accepted,
+            MempoolStatusCode::Accepted,
+            MempoolStatusCode::TooManyTransactions
+        ]
+    );
+}
+
+#[test]
+fn test_sender_quota_exemptions() {
+    let mut config = NodeConfig::random();
+    config.mempool.max_broadcast_txns_per_user = 2;
+    let smp = SharedMempool::new(
+        Arc::new(Mutex::new(CoreMempool::new(&config))),
+        config.mempool.clone(),
+        HashMap::new(),
+        Arc::new(MockDbReaderWriter),
+        Arc::new(RwLock::new(MockVMValidator)),
+        vec![],
+        config.base.role,
+        PeerMetadataStorage::new(&[NetworkId::Validator]),
+    );
+
+    // Fill the sender's broadcast quota from a public peer
+    let txns: Vec<_> = (0..2)
+        .map(|i| TestTransaction::new(0, i, 1).make_signed_transaction())
+        .collect();
+    tasks::process_incoming_transactions(
+        &smp,
+        txns.clone(),
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random()),
+    );
+
+    // Re-broadcasts of transactions already in mempool are still accepted
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        txns,
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random()),
+    );
+    assert!(statuses
+        .iter()
+        .all(|(_, (status, _))| status.code == MempoolStatusCode::Accepted));
+
+    // Broadcasts from validators are not capped
+    let txns = vec![TestTransaction::new(0, 2, 1).make_signed_transaction()];
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        txns,
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random_validator()),
+    );
+    assert_eq!(statuses[0].1 .0.code, MempoolStatusCode::Accepted);
+
+    // New transactions from public peers are still capped
+    let txns = vec![TestTransaction::new(0, 3, 1).make_signed_transaction()];
+    let statuses = tasks::process_incoming_transactions(
+        &smp,
+        txns,
+        TimelineState::NotReady,
+        Some(PeerNetworkId::random()),
+    );
+    assert_eq!(
+        statuses[0].1 .0.code,
+        MempoolStatusCode::TooManyTransactions
+    );
+}
+
+#[test]
+fn test_recently_seen_txns() {
+    let txns: Vec<_> = (0..3)
+        .map(|i| TestTransaction::new(1, i, 1).make_signed_transaction())
+        .collect();
+    let mut recently_seen = RecentlySeenTxns::new(Duration::from_secs(60), 2);
+
+    // Nothing has been seen yet
+    assert_eq!(recently_seen.filter_unseen(txns.clone()), txns);
+
+    // Seen transactions are filtered out
+    recently_seen.insert(txns[0].committed_hash());
+    assert_eq!(
+        recently_seen.filter_unseen(txns.clone()),
+        txns[1..].to_vec()
+    );
+
+    // The oldest entries are evicted once capacity is reached
+    recently_seen.insert(txns[1].committed_hash());
+    recently_seen.insert(txns[2].committed_hash());
+    assert_eq!(
+        recently_seen.filter_unseen(txns.clone()),
+        txns[..1].to_vec()
+    );
+
+    // Entries expire after the ttl
+    let mut recently_seen = RecentlySeenTxns::new(Duration::from_secs(0), 2);
+    recently_seen.insert(txns[0].committed_hash());
+    assert_eq!(recently_seen.filter_unseen(txns.clone()), txns);
+}
+
+#[test]
+fn test_restore_persisted_transactions() {
+    let tmp_dir = TempPath::new();
+    let mut config = NodeConfig::random();
+    config.mempool.persist_transactions = true;
+    config.storage.dir = tmp_dir.path().to_path_buf();
+
+    // Journal a valid and an expired transaction, then shut down mempool
+    let valid_txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    let expired_txn = TestTransaction::new(1, 0, 1).make_signed_transaction_with_expiration_time(0);
+    {
+        let mut pool = CoreMempool::new(&config);
+        assert!(batch_add_signed_txn(&mut pool, vec![valid_txn.clone(), expired_txn]).is_ok());
+    }
+
+    // Restart mempool and restore the journaled transactions
+    let new_shared_mempool = |config: &NodeConfig| {
+        SharedMempool::new(
+            Arc::new(Mutex::new(CoreMempool::new(config))),
+            config.mempool.clone(),
+            HashMap::new(),
+            Arc::new(MockDbReaderWriter),
+            Arc::new(RwLock::new(MockVMValidator)),
+            vec![],
+            config.base.role,
+            PeerMetadataStorage::new(&[NetworkId::Validator]),
+        )
+    };
+    let smp = new_shared_mempool(&config);
+    tasks::restore_persisted_transactions(&smp);
+
+    // Only the valid transaction is reinserted
+    let (timeline, _) = smp.mempool.lock().read_timeline(0, 10);
+    assert_eq!(timeline, vec![valid_txn.clone()]);
+    drop(smp);
+
+    // Only the expired transaction is removed from the journal
+    let smp = new_shared_mempool(&config);
+    let persisted_txns: HashSet<_> = smp
+        .mempool
+        .lock()
+        .get_persisted_transactions()
+        .into_iter()
+        .map(|persisted_txn| persisted_txn.txn)
+        .collect();
+    assert_eq!(persisted_txns, vec![valid_txn].into_iter().collect());
+}
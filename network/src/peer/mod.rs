@@ -477,12 +477,13 @@ where
     }
 
     /// Handle an inbound DirectSendMsg from the remote peer. There's not much to
-    /// do here other than bump some counters and forward the message up to the
-    /// PeerManager.
+    /// do here other than bump some counters, decompress the message if its
+    /// protocol is compressed, and forward it up to the PeerManager.
     fn handle_inbound_direct_send(&mut self, message: DirectSendMsg) {
         let peer_id = self.remote_peer_id();
         let protocol_id = message.protocol_id;
         let data = message.raw_msg;
+        let data_len = data.len() as u64;
 
         trace!(
             NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
@@ -492,11 +493,30 @@ where
             peer_id.short_str(),
             protocol_id
         );
-        let data_len = data.len() as u64;
         counters::direct_send_messages(&self.network_context, RECEIVED_LABEL).inc();
         counters::direct_send_bytes(&self.network_context, RECEIVED_LABEL).inc_by(data_len);
         network_application_inbound_traffic(self.network_context, message.protocol_id, data_len);
 
+        let data = if protocol_id.is_compressed() {
+            match aptos_compression::decompress(&data, self.max_frame_size) {
+                Ok(data) => data,
+                Err(err) => {
+                    warn!(
+                        NetworkSchema::new(&self.network_context).remote_peer(&peer_id),
+                        error = ?err,
+                        "{} Dropping inbound DirectSend message for protocol {:?} from peer {}: {}",
+                        self.network_context,
+                        protocol_id,
+                        peer_id.short_str(),
+                        err
+                    );
+                    return;
+                }
+            }
+        } else {
+            data
+        };
+
         let notif = PeerNotification::RecvMessage(Message {
             protocol_id,
             mdata: Bytes::from(data),
@@ -527,11 +547,31 @@ where
             request
         );
         match request {
-            // To send an outbound DirectSendMsg, we just bump some counters and
-            // push it onto our outbound writer queue.
+            // To send an outbound DirectSendMsg, we just bump some counters,
+            // compress the message if its protocol asks for it, and push it onto
+            // our outbound writer queue.
             PeerRequest::SendDirectSend(message) => {
-                let message_len = message.mdata.len();
                 let protocol_id = message.protocol_id;
+                let raw_msg = if protocol_id.is_compressed() {
+                    match aptos_compression::compress(&message.mdata, self.max_frame_size) {
+                        Ok(raw_msg) => raw_msg,
+                        Err(e) => {
+                            warn!(
+                                NetworkSchema::new(&self.network_context)
+                                    .connection_metadata(&self.connection_metadata),
+                                error = ?e,
+                                "Failed to compress direct send message for protocol {} to peer: {}. Error: {:?}",
+                                protocol_id,
+                                self.remote_peer_id().short_str(),
+                                e,
+                            );
+                            return;
+                        }
+                    }
+                } else {
+                    Vec::from(message.mdata.as_ref())
+                };
+                let message_len = raw_msg.len();
                 network_application_outbound_traffic(
                     self.network_context,
                     protocol_id,
@@ -540,7 +580,7 @@ where
                 let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
                     protocol_id,
                     priority: Priority::default(),
-                    raw_msg: Vec::from(message.mdata.as_ref()),
+                    raw_msg,
                 });
                 let (ack_tx, _ack_rx) = oneshot::channel();
 
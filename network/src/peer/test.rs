@@ -336,6 +336,60 @@ fn peers_send_message_concurrent() {
     rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
 }
 
+// Messages of compressed protocols should be compressed on the wire and handed to
+// the PeerManager decompressed.
+#[test]
+fn peer_send_recv_compressed_message() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let (peer, mut peer_handle, mut connection, _connection_notifs_rx, mut peer_notifs_rx) =
+        build_test_peer(
+            rt.handle().clone(),
+            TimeService::mock(),
+            ConnectionOrigin::Inbound,
+        );
+    let (mut client_sink, mut client_stream) = build_network_sink_stream(&mut connection);
+
+    let protocol_id = ProtocolId::MempoolDirectSendCompressed;
+    let message = Message {
+        protocol_id,
+        mdata: Bytes::from(vec![7u8; 4096]),
+    };
+
+    let client = async {
+        // The client should receive the compressed message
+        let raw_msg = match client_stream.next().await.unwrap().unwrap() {
+            NetworkMessage::DirectSendMsg(msg) => msg.raw_msg,
+            msg => panic!("Expected a DirectSendMsg, received: {:?}", msg),
+        };
+        assert!(raw_msg.len() < message.mdata.len());
+        assert_eq!(
+            aptos_compression::decompress(&raw_msg, MAX_FRAME_SIZE).unwrap(),
+            message.mdata
+        );
+
+        // Send a message that fails to decompress, followed by the valid one
+        for raw_msg in [vec![1, 2], raw_msg] {
+            let msg = NetworkMessage::DirectSendMsg(DirectSendMsg {
+                protocol_id,
+                priority: 0,
+                raw_msg,
+            });
+            client_sink.send(&msg).await.unwrap();
+        }
+        client_sink.close().await.unwrap();
+    };
+
+    let server = async {
+        peer_handle.send_direct_send(message.clone());
+
+        // Only the valid message is forwarded, decompressed
+        let received = peer_notifs_rx.next().await.unwrap();
+        assert_eq!(received, PeerNotification::RecvMessage(message.clone()));
+    };
+    rt.block_on(future::join3(peer.start(), server, client));
+}
+
 #[test]
 fn peer_recv_rpc() {
     ::aptos_logger::Logger::init_for_testing();
@@ -340,6 +340,20 @@ impl<TMessage: Message> NetworkSender<TMessage> {
         req_msg: TMessage,
         timeout: Duration,
     ) -> Result<TMessage, RpcError> {
+        self.send_rpc_and_get_response_size(recipient, protocol, req_msg, timeout)
+            .await
+            .map(|(res_msg, _)| res_msg)
+    }
+
+    /// Same as `send_rpc()`, but also returns the size of the serialized response,
+    /// e.g., to measure throughput without serializing the response again.
+    pub async fn send_rpc_and_get_response_size(
+        &self,
+        recipient: PeerId,
+        protocol: ProtocolId,
+        req_msg: TMessage,
+        timeout: Duration,
+    ) -> Result<(TMessage, usize), RpcError> {
         // serialize request
         let req_data = protocol.to_bytes(&req_msg)?.into();
         let res_data = self
@@ -347,7 +361,7 @@ impl<TMessage: Message> NetworkSender<TMessage> {
             .send_rpc(recipient, protocol, req_data, timeout)
             .await?;
         let res_msg: TMessage = protocol.from_bytes(&res_data)?;
-        Ok(res_msg)
+        Ok((res_msg, res_data.len()))
     }
 }
 
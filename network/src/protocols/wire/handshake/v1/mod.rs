@@ -52,6 +52,9 @@ pub enum ProtocolId {
     StorageServiceRpc = 8,
     MempoolRpc = 9,
     PeerMonitoringServiceRpc = 10,
+    // Same as MempoolDirectSend, but messages are LZ4 compressed on the wire by the peer actor.
+    // Used for bulk mempool transaction broadcasts.
+    MempoolDirectSendCompressed = 11,
 }
 
 /// The encoding types for Protocols
@@ -75,6 +78,7 @@ impl ProtocolId {
             StorageServiceRpc => "StorageServiceRpc",
             MempoolRpc => "MempoolRpc",
             PeerMonitoringServiceRpc => "PeerMonitoringServiceRpc",
+            MempoolDirectSendCompressed => "MempoolDirectSendCompressed",
         }
     }
 
@@ -91,6 +95,7 @@ impl ProtocolId {
             ProtocolId::StorageServiceRpc,
             ProtocolId::MempoolRpc,
             ProtocolId::PeerMonitoringServiceRpc,
+            ProtocolId::MempoolDirectSendCompressed,
         ]
     }
 
@@ -102,6 +107,11 @@ impl ProtocolId {
         }
     }
 
+    /// Whether messages of this protocol are compressed on the wire
+    pub fn is_compressed(self) -> bool {
+        matches!(self, ProtocolId::MempoolDirectSendCompressed)
+    }
+
     #[cfg(test)]
     pub fn mock() -> Self {
         ProtocolId::DiscoveryDirectSend
@@ -14,7 +14,19 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
-
+//!
+//! Optionally, the client and server can be configured with x25519 keys. Each new connection then
+//! starts with a Noise IK handshake, in which the client authenticates the server with the server's
+//! static public key and the server only accepts the client whose static public key it expects.
+//! The client sends a millisecond timestamp as the handshake payload, and the server rejects a
+//! handshake whose timestamp isn't newer than the last one it accepted, so that a recorded
+//! handshake can't be replayed. Every block exchanged afterwards is encrypted and authenticated
+//! with the session keys.
+
+use aptos_crypto::{
+    noise::{self, NoiseConfig, NoiseError},
+    x25519,
+};
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_secure_push_metrics::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
@@ -23,6 +35,7 @@ use std::{
     io::{Read, Write},
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     thread, time,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 
@@ -55,6 +68,7 @@ enum LogEvent {
     ConnectionAttempt,
     ConnectionSuccessful,
     ConnectionFailed,
+    HandshakeFailed,
     DisconnectedPeerOnRead,
     DisconnectedPeerOnWrite,
     Shutdown,
@@ -138,14 +152,79 @@ pub enum Error {
     AlreadyShutdown,
     #[error("Found data that is too large to decode: {0}")]
     DataTooLarge(usize),
+    #[error("Handshake payload is not a timestamp, found {0} bytes")]
+    InvalidHandshakeTimestamp(usize),
     #[error("Internal network error:")]
     NetworkError(#[from] std::io::Error),
     #[error("No active stream")]
     NoActiveStream,
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] NoiseError),
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Replayed handshake, timestamp {0} is not newer than the last one accepted")]
+    ReplayedHandshake(u64),
+    #[error("Peer authenticated with an unexpected static public key: {0}")]
+    UnauthenticatedPeer(x25519::PublicKey),
+}
+
+/// Prologue of the Noise IK handshake, binding the handshake to this protocol.
+const NOISE_PROLOGUE: &[u8] = b"aptos-secure-net";
+
+/// Size of the timestamp sent as the payload of the handshake initialization message.
+const HANDSHAKE_TIMESTAMP_SIZE: usize = 8;
+
+/// The largest plaintext that fits in a single Noise message.
+const MAX_NOISE_PLAINTEXT: usize = noise::MAX_SIZE_NOISE_MSG - noise::AES_GCM_TAGLEN;
+
+/// The local static key and the expected static public key of the remote end of a connection.
+struct NoiseAuth {
+    config: NoiseConfig,
+    remote_public_key: x25519::PublicKey,
+    /// The last handshake timestamp sent by a client, or accepted by a server.
+    last_timestamp: Option<u64>,
+}
+
+impl NoiseAuth {
+    fn new(private_key: x25519::PrivateKey, remote_public_key: x25519::PublicKey) -> Self {
+        Self {
+            config: NoiseConfig::new(private_key),
+            remote_public_key,
+            last_timestamp: None,
+        }
+    }
+
+    /// The timestamp of a new handshake in milliseconds, strictly greater than the previous one
+    /// even if the client reconnects within the same millisecond.
+    fn next_timestamp(&mut self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before the UNIX epoch")
+            .as_millis() as u64;
+        let timestamp = match self.last_timestamp {
+            Some(last) if now <= last => last + 1,
+            _ => now,
+        };
+        self.last_timestamp = Some(timestamp);
+        timestamp
+    }
+
+    /// Accepts the timestamp of a handshake only if it is newer than the last one accepted.
+    fn check_timestamp(&mut self, payload: &[u8]) -> Result<(), Error> {
+        if payload.len() != HANDSHAKE_TIMESTAMP_SIZE {
+            return Err(Error::InvalidHandshakeTimestamp(payload.len()));
+        }
+        let mut timestamp = [0u8; HANDSHAKE_TIMESTAMP_SIZE];
+        timestamp.copy_from_slice(payload);
+        let timestamp = u64::from_le_bytes(timestamp);
+        if self.last_timestamp.map_or(false, |last| timestamp <= last) {
+            return Err(Error::ReplayedHandshake(timestamp));
+        }
+        self.last_timestamp = Some(timestamp);
+        Ok(())
+    }
 }
 
 pub struct NetworkClient {
@@ -154,6 +233,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseAuth>,
 }
 
 impl NetworkClient {
@@ -163,6 +243,22 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a client whose connections are authenticated and encrypted: the server must prove
+    /// ownership of `server_public_key`, and the client identifies itself with `private_key`.
+    pub fn new_with_noise(
+        service: &'static str,
+        server: SocketAddr,
+        timeout_ms: u64,
+        private_key: x25519::PrivateKey,
+        server_public_key: x25519::PublicKey,
+    ) -> Self {
+        Self {
+            noise: Some(NoiseAuth::new(private_key, server_public_key)),
+            ..Self::new(service, server, timeout_ms)
         }
     }
 
@@ -257,7 +353,21 @@ impl NetworkClient {
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise) = &mut self.noise {
+                if let Err(err) = stream.initiate_handshake(noise) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Client,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -277,6 +387,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseAuth>,
 }
 
 impl NetworkServer {
@@ -287,6 +398,22 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a server whose connections are authenticated and encrypted: the server identifies
+    /// itself with `private_key` and only accepts the client owning `client_public_key`.
+    pub fn new_with_noise(
+        service: &'static str,
+        listen: SocketAddr,
+        timeout_ms: u64,
+        private_key: x25519::PrivateKey,
+        client_public_key: x25519::PublicKey,
+    ) -> Self {
+        Self {
+            noise: Some(NoiseAuth::new(private_key, client_public_key)),
+            ..Self::new(service, listen, timeout_ms)
         }
     }
 
@@ -390,6 +517,22 @@ impl NetworkServer {
                 }
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise) = &mut self.noise {
+                if let Err(err) = stream.respond_to_handshake(noise) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Server,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&stream_addr));
+                    return Err(err);
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -397,9 +540,7 @@ impl NetworkServer {
                 LogEvent::ConnectionSuccessful,
             )
             .remote_peer(&stream_addr));
-
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
@@ -411,6 +552,8 @@ struct NetworkStream {
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// Set once a Noise handshake completed, all blocks are encrypted from then on.
+    session: Option<noise::NoiseSession>,
 }
 
 impl NetworkStream {
@@ -425,11 +568,96 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            session: None,
         }
     }
 
-    /// Blocking read until able to successfully read an entire message
+    /// Runs the initiator side of the Noise IK handshake, with a fresh timestamp as payload.
+    fn initiate_handshake(&mut self, noise: &mut NoiseAuth) -> Result<(), Error> {
+        let timestamp = noise.next_timestamp().to_le_bytes();
+        let mut init_msg = vec![0; noise::handshake_init_msg_len(HANDSHAKE_TIMESTAMP_SIZE)];
+        let handshake_state = noise.config.initiate_connection(
+            &mut rand::rngs::OsRng,
+            NOISE_PROLOGUE,
+            noise.remote_public_key,
+            Some(&timestamp),
+            &mut init_msg,
+        )?;
+        self.write(&init_msg)?;
+        let response = self.read()?;
+        let (_, session) = noise
+            .config
+            .finalize_connection(handshake_state, &response)?;
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Runs the responder side of the Noise IK handshake, rejecting unexpected initiators and
+    /// replayed handshakes.
+    fn respond_to_handshake(&mut self, noise: &mut NoiseAuth) -> Result<(), Error> {
+        let init_msg = self.read()?;
+        let (remote_public_key, handshake_state, payload) = noise
+            .config
+            .parse_client_init_message(NOISE_PROLOGUE, &init_msg)?;
+        if remote_public_key != noise.remote_public_key {
+            return Err(Error::UnauthenticatedPeer(remote_public_key));
+        }
+        noise.check_timestamp(&payload)?;
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session = noise.config.respond_to_client(
+            &mut rand::rngs::OsRng,
+            handshake_state,
+            None,
+            &mut response,
+        )?;
+        self.write(&response)?;
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Blocking read until able to successfully read and, within a Noise session, decrypt an
+    /// entire message
     pub fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let mut data = self.read_block()?;
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return Ok(data),
+        };
+
+        // A message is encrypted in chunks of at most MAX_NOISE_PLAINTEXT bytes
+        let mut plaintext = Vec::with_capacity(data.len());
+        for chunk in data.chunks_mut(noise::encrypted_len(MAX_NOISE_PLAINTEXT)) {
+            plaintext.extend_from_slice(session.read_message_in_place(chunk)?);
+        }
+        Ok(plaintext)
+    }
+
+    /// Blocking write until able to successfully send an entire message, encrypted if within a
+    /// Noise session
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => return self.write_block(data),
+        };
+
+        // An empty message still gets an authentication tag
+        let chunks: Vec<&[u8]> = if data.is_empty() {
+            vec![data]
+        } else {
+            data.chunks(MAX_NOISE_PLAINTEXT).collect()
+        };
+        let mut ciphertext = Vec::with_capacity(noise::encrypted_len(data.len()));
+        for chunk in chunks {
+            let start = ciphertext.len();
+            ciphertext.extend_from_slice(chunk);
+            let tag = session.write_message_in_place(&mut ciphertext[start..])?;
+            ciphertext.extend_from_slice(&tag);
+        }
+        self.write_block(&ciphertext)
+    }
+
+    /// Blocking read until able to successfully read an entire block
+    fn read_block(&mut self) -> Result<Vec<u8>, Error> {
         let result = self.read_buffer();
         if !result.is_empty() {
             return Ok(result);
@@ -457,8 +685,8 @@ impl NetworkStream {
         Ok(self.stream.shutdown(Shutdown::Both)?)
     }
 
-    /// Blocking write until able to successfully send an entire message
-    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+    /// Blocking write until able to successfully send an entire block
+    fn write_block(&mut self, data: &[u8]) -> Result<(), Error> {
         let u32_max = u32::max_value() as usize;
         if u32_max <= data.len() {
             return Err(Error::DataTooLarge(data.len()));
@@ -518,6 +746,7 @@ impl NetworkStream {
 mod test {
     use super::*;
     use aptos_config::utils;
+    use aptos_crypto::Uniform;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     /// Read, Write, Connect timeout in milliseconds.
@@ -656,4 +885,128 @@ mod test {
         let result2 = server2.read().unwrap();
         assert_eq!(data2, result2);
     }
+
+    #[test]
+    fn test_noise_ping() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let server_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let client_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let server_public_key = server_key.public_key();
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            server_key,
+            client_key.public_key(),
+        );
+        let mut client = NetworkClient::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            client_key,
+            server_public_key,
+        );
+
+        // Larger than a single noise message, so it gets encrypted in several chunks
+        let data: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        let expected = data.clone();
+        // The client blocks on the handshake until the server accepts the connection
+        let client_thread = thread::spawn(move || {
+            client.write(&data).unwrap();
+            client.read().unwrap()
+        });
+
+        let result = server.read().unwrap();
+        assert_eq!(expected, result);
+        let data = vec![4, 5, 6, 7];
+        server.write(&data).unwrap();
+        assert_eq!(data, client_thread.join().unwrap());
+    }
+
+    #[test]
+    fn test_noise_unexpected_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let server_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let client_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let other_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let server_public_key = server_key.public_key();
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            server_key,
+            client_key.public_key(),
+        );
+        let mut client = NetworkClient::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            other_key,
+            server_public_key,
+        );
+
+        let client_thread = thread::spawn(move || client.write(&[0, 1, 2, 3]).unwrap_err());
+
+        assert!(matches!(
+            server.read().unwrap_err(),
+            Error::UnauthenticatedPeer(_)
+        ));
+        client_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_noise_replayed_handshake() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let server_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let client_key = x25519::PrivateKey::generate(&mut rand::rngs::OsRng);
+        let server_public_key = server_key.public_key();
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            server_key,
+            client_key.public_key(),
+        );
+
+        // An eavesdropper records the first handshake of the client and sends it again
+        let mut client_auth = NoiseAuth::new(client_key, server_public_key);
+        let mut init_msg = vec![];
+        let client_thread = thread::spawn(move || {
+            for _ in 0..2 {
+                let stream = TcpStream::connect(server_addr).unwrap();
+                let mut stream = NetworkStream::new(stream, server_addr, TIMEOUT);
+                if init_msg.is_empty() {
+                    let timestamp = client_auth.next_timestamp().to_le_bytes();
+                    init_msg = vec![0; noise::handshake_init_msg_len(HANDSHAKE_TIMESTAMP_SIZE)];
+                    client_auth
+                        .config
+                        .initiate_connection(
+                            &mut rand::rngs::OsRng,
+                            NOISE_PROLOGUE,
+                            server_public_key,
+                            Some(&timestamp),
+                            &mut init_msg,
+                        )
+                        .unwrap();
+                }
+                stream.write(&init_msg).unwrap();
+                // Wait for the server to answer or drop the connection before closing it
+                let _ = stream.read();
+            }
+        });
+
+        // The first handshake succeeds, and the server then sees the connection close
+        assert!(matches!(
+            server.read().unwrap_err(),
+            Error::RemoteStreamClosed | Error::NetworkError(_)
+        ));
+        assert!(matches!(
+            server.read().unwrap_err(),
+            Error::ReplayedHandshake(_)
+        ));
+        client_thread.join().unwrap();
+    }
 }
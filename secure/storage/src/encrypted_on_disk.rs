@@ -0,0 +1,240 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{from_base64, to_base64, CryptoKVStorage, Error, GetResponse, KVStorage};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    Aes256Gcm,
+};
+use aptos_temppath::TempPath;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use argon2::Argon2;
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// The version of the on-disk envelope, bumped whenever the layout or the key derivation changes.
+const FORMAT_VERSION: u32 = 1;
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const SALT_LENGTH: usize = 16;
+
+/// EncryptedOnDiskStorage is a variant of OnDiskStorage that keeps the file encrypted at rest.
+/// The file key is derived from a passphrase (or the contents of a key file) using Argon2id and a
+/// random per-file salt, and the serialized key value store is sealed with AES-256-GCM under a
+/// fresh nonce on every write. Writes go to a temporary file that is fsynced and then renamed over
+/// the original, so a crash never leaves a partially written store behind. Like OnDiskStorage it
+/// is intended for single threads (or must be wrapped by a Arc<RwLock<>>) and provides no
+/// permission checks, but it is suitable for hosts where the data directory may be exposed.
+pub struct EncryptedOnDiskStorage {
+    file_path: PathBuf,
+    file_dir: PathBuf,
+    temp_path: TempPath,
+    time_service: TimeService,
+    salt: Vec<u8>,
+    cipher: Aes256Gcm,
+}
+
+/// The envelope written to disk. Only the salt, nonce and ciphertext are stored, the derived key
+/// never leaves memory.
+#[derive(Deserialize, Serialize)]
+struct EncryptedFile {
+    version: u32,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    salt: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    nonce: Vec<u8>,
+    #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedOnDiskStorage {
+    /// Opens the storage at `file_path`, creating it if it does not exist. Returns an error if an
+    /// existing file cannot be decrypted with the given passphrase.
+    pub fn new(file_path: PathBuf, passphrase: &[u8]) -> Result<Self, Error> {
+        Self::new_with_time_service(file_path, passphrase, TimeService::real())
+    }
+
+    fn new_with_time_service(
+        file_path: PathBuf,
+        passphrase: &[u8],
+        time_service: TimeService,
+    ) -> Result<Self, Error> {
+        // The parent will be empty when only a filename is supplied. Therefore use the current
+        // working directory.
+        let file_dir = file_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .map_or(PathBuf::from("."), |p| p.to_path_buf());
+
+        let existing = Self::read_envelope(&file_path)?;
+        let salt = match &existing {
+            Some(envelope) => envelope.salt.clone(),
+            None => new_salt(),
+        };
+
+        let storage = Self {
+            cipher: derive_cipher(passphrase, &salt)?,
+            salt,
+            temp_path: TempPath::new_with_temp_dir(file_dir.clone()),
+            file_dir,
+            file_path,
+            time_service,
+        };
+
+        match existing {
+            // Verify the passphrase up front rather than on the first access
+            Some(envelope) => {
+                storage.decrypt(&envelope)?;
+            }
+            None => storage.write(&HashMap::new())?,
+        }
+        Ok(storage)
+    }
+
+    /// Re-encrypts the storage under a key derived from `new_passphrase` and a new salt. After
+    /// this returns, the previous passphrase can no longer open the file.
+    pub fn rotate_passphrase(&mut self, new_passphrase: &[u8]) -> Result<(), Error> {
+        let data = self.read()?;
+        let salt = new_salt();
+        let cipher = derive_cipher(new_passphrase, &salt)?;
+        // Only switch keys once the file is re-encrypted, so that a failed write leaves the
+        // storage usable with the previous passphrase
+        self.write_with(&cipher, &salt, &data)?;
+        self.cipher = cipher;
+        self.salt = salt;
+        Ok(())
+    }
+
+    fn read_envelope(file_path: &Path) -> Result<Option<EncryptedFile>, Error> {
+        if !file_path.exists() {
+            return Ok(None);
+        }
+        let mut file = File::open(file_path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        if contents.is_empty() {
+            return Ok(None);
+        }
+        let envelope: EncryptedFile = serde_json::from_str(&contents)?;
+        if envelope.version != FORMAT_VERSION {
+            return Err(Error::InternalError(format!(
+                "Unsupported encrypted storage version: {}",
+                envelope.version
+            )));
+        }
+        Ok(Some(envelope))
+    }
+
+    fn decrypt(&self, envelope: &EncryptedFile) -> Result<HashMap<String, Value>, Error> {
+        if envelope.nonce.len() != NONCE_LENGTH {
+            return Err(Error::SerializationError(format!(
+                "Invalid nonce length: {}",
+                envelope.nonce.len()
+            )));
+        }
+        let plaintext = self
+            .cipher
+            .decrypt(
+                GenericArray::from_slice(&envelope.nonce),
+                envelope.ciphertext.as_ref(),
+            )
+            .map_err(|_| {
+                Error::InternalError(
+                    "Unable to decrypt storage, the passphrase may be incorrect".into(),
+                )
+            })?;
+        let data = serde_json::from_slice(&plaintext)?;
+        Ok(data)
+    }
+
+    fn read(&self) -> Result<HashMap<String, Value>, Error> {
+        match Self::read_envelope(&self.file_path)? {
+            Some(envelope) => self.decrypt(&envelope),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    fn write(&self, data: &HashMap<String, Value>) -> Result<(), Error> {
+        self.write_with(&self.cipher, &self.salt, data)
+    }
+
+    fn write_with(
+        &self,
+        cipher: &Aes256Gcm,
+        salt: &[u8],
+        data: &HashMap<String, Value>,
+    ) -> Result<(), Error> {
+        let plaintext = serde_json::to_vec(data)?;
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(GenericArray::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| Error::InternalError("Unable to encrypt storage".into()))?;
+        let contents = serde_json::to_vec(&EncryptedFile {
+            version: FORMAT_VERSION,
+            salt: salt.to_vec(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })?;
+
+        let mut file = File::create(self.temp_path.path())?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        fs::rename(&self.temp_path, &self.file_path)?;
+        // Persist the rename itself, otherwise a crash could still surface the old file
+        #[cfg(unix)]
+        File::open(&self.file_dir)?.sync_all()?;
+        Ok(())
+    }
+}
+
+fn new_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+fn derive_cipher(passphrase: &[u8], salt: &[u8]) -> Result<Aes256Gcm, Error> {
+    let mut key = [0u8; KEY_LENGTH];
+    Argon2::default()
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(|e| Error::InternalError(format!("Unable to derive storage key: {}", e)))?;
+    Ok(Aes256Gcm::new(GenericArray::from_slice(&key)))
+}
+
+impl KVStorage for EncryptedOnDiskStorage {
+    fn available(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        let mut data = self.read()?;
+        data.remove(key)
+            .ok_or_else(|| Error::KeyNotSet(key.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.into()))
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        let now = self.time_service.now_secs();
+        let mut data = self.read()?;
+        data.insert(
+            key.to_string(),
+            serde_json::to_value(&GetResponse::new(value, now))?,
+        );
+        self.write(&data)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.write(&HashMap::new())
+    }
+}
+
+impl CryptoKVStorage for EncryptedOnDiskStorage {}
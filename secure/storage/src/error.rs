@@ -15,6 +15,8 @@ pub enum Error {
     KeyAlreadyExists(String),
     #[error("Key not set: {0}")]
     KeyNotSet(String),
+    #[error("Key cannot be exported: {0}")]
+    KeyNotExportable(String),
     #[error("Permission denied")]
     PermissionDenied,
     #[error("Serialization error: {0}")]
@@ -0,0 +1,30 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, PublicKeyResponse};
+use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
+
+/// HardwareModule is the interface to a device that generates and holds Ed25519 keys and signs on
+/// their behalf, in the style of a PKCS#11 token. Keys are addressed by label and private key
+/// material never leaves the module: there is deliberately no way to read a private key back.
+pub trait HardwareModule: Send + Sync {
+    /// Generates a new non-extractable key pair under 'label'.
+    fn generate_key(&mut self, label: &str) -> Result<Ed25519PublicKey, Error>;
+
+    /// Imports an existing private key under 'label'. Once imported the key is treated like any
+    /// generated key and cannot be extracted again.
+    fn import_key(&mut self, label: &str, key: Ed25519PrivateKey) -> Result<(), Error>;
+
+    /// Returns the public key stored under 'label' along with its creation time.
+    fn public_key(&self, label: &str) -> Result<PublicKeyResponse, Error>;
+
+    /// Destroys the key stored under 'label'.
+    fn delete_key(&mut self, label: &str) -> Result<(), Error>;
+
+    /// Signs the raw 'message' bytes using the key stored under 'label'.
+    fn sign(&self, label: &str, message: &[u8]) -> Result<Ed25519Signature, Error>;
+
+    /// Destroys all keys held by the module.
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error>;
+}
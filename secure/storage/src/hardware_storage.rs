@@ -0,0 +1,222 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    CryptoStorage, Error, GetResponse, HardwareModule, KVStorage, PublicKeyResponse, Storage,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    signing_message,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// HardwareStorage offers a CryptoStorage whose keys live inside a HardwareModule. Signing and
+/// key rotation happen within the module and requests to export private keys are refused, which
+/// makes it a non-exportable alternative to Vault. As modules only hold keys, all key value
+/// operations are forwarded to a separate backing storage.
+///
+/// Each version of a key is held in the module under its own label and the backing storage keeps
+/// a pointer to the current version. Rotation generates the new key first and then moves the
+/// pointer with a single write, so an interrupted rotation leaves either the old or the new key
+/// in place, never neither.
+pub struct HardwareStorage {
+    module: Box<dyn HardwareModule>,
+    storage: Box<Storage>,
+}
+
+impl HardwareStorage {
+    pub fn new(module: Box<dyn HardwareModule>, storage: Box<Storage>) -> Self {
+        Self { module, storage }
+    }
+
+    /// Returns the current version of 'name', as recorded in the backing storage.
+    fn current_version(&self, name: &str) -> Result<u64, Error> {
+        match self.storage.get::<u64>(&get_version_pointer_name(name)) {
+            Ok(response) => Ok(response.value),
+            Err(Error::KeyNotSet(_)) => Err(Error::KeyNotSet(name.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the label of the current key of 'name'.
+    fn current_label(&self, name: &str) -> Result<String, Error> {
+        Ok(get_version_label(name, self.current_version(name)?))
+    }
+
+    /// Returns the label of the previous key of 'name', if there has been a rotation.
+    fn previous_label(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self
+            .current_version(name)?
+            .checked_sub(1)
+            .map(|version| get_version_label(name, version)))
+    }
+
+    /// Returns the label of the key that matches 'version', either the current or the previous
+    /// version of 'name'.
+    fn label_for_version(&self, name: &str, version: &Ed25519PublicKey) -> Result<String, Error> {
+        let current_label = self.current_label(name)?;
+        if &self.module.public_key(&current_label)?.public_key == version {
+            return Ok(current_label);
+        }
+
+        if let Some(previous_label) = self.previous_label(name)? {
+            match self.module.public_key(&previous_label) {
+                Ok(previous) if &previous.public_key == version => return Ok(previous_label),
+                Ok(_) | Err(Error::KeyNotSet(_)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(Error::KeyVersionNotFound(name.into(), version.to_string()))
+    }
+
+    /// Returns the label for the first version of 'name', refusing names that already have a key.
+    fn first_version_label(&mut self, name: &str) -> Result<String, Error> {
+        match self.current_version(name) {
+            Ok(_) => return Err(Error::KeyAlreadyExists(name.to_string())),
+            Err(Error::KeyNotSet(_)) => (/* Expected this for new keys! */),
+            Err(e) => return Err(e),
+        }
+
+        // A key under this label was left behind by a create that never got published.
+        let label = get_version_label(name, 0);
+        self.delete_if_present(&label)?;
+        Ok(label)
+    }
+
+    /// Makes 'version' the current version of 'name'. This is a single write to the backing
+    /// storage and is the point at which a create or rotate takes effect.
+    fn publish_version(&mut self, name: &str, version: u64) -> Result<(), Error> {
+        self.storage.set(&get_version_pointer_name(name), version)
+    }
+
+    /// Deletes 'label' from the module, ignoring labels that are not set. This is used to clear
+    /// out keys left behind by an interrupted create or rotate.
+    fn delete_if_present(&mut self, label: &str) -> Result<(), Error> {
+        match self.module.delete_key(label) {
+            Ok(()) | Err(Error::KeyNotSet(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl KVStorage for HardwareStorage {
+    fn available(&self) -> Result<(), Error> {
+        self.storage.available()
+    }
+
+    fn get<V: DeserializeOwned>(&self, key: &str) -> Result<GetResponse<V>, Error> {
+        self.storage.get(key)
+    }
+
+    fn set<V: Serialize>(&mut self, key: &str, value: V) -> Result<(), Error> {
+        self.storage.set(key, value)
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.module.reset_and_clear()?;
+        self.storage.reset_and_clear()
+    }
+}
+
+impl CryptoStorage for HardwareStorage {
+    fn create_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let label = self.first_version_label(name)?;
+        let public_key = self.module.generate_key(&label)?;
+        self.publish_version(name, 0)?;
+        Ok(public_key)
+    }
+
+    fn export_private_key(&self, name: &str) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::KeyNotExportable(name.to_string()))
+    }
+
+    fn import_private_key(&mut self, name: &str, key: Ed25519PrivateKey) -> Result<(), Error> {
+        let label = self.first_version_label(name)?;
+        self.module.import_key(&label, key)?;
+        self.publish_version(name, 0)
+    }
+
+    fn export_private_key_for_version(
+        &self,
+        name: &str,
+        _version: Ed25519PublicKey,
+    ) -> Result<Ed25519PrivateKey, Error> {
+        Err(Error::KeyNotExportable(name.to_string()))
+    }
+
+    fn get_public_key(&self, name: &str) -> Result<PublicKeyResponse, Error> {
+        self.module.public_key(&self.current_label(name)?)
+    }
+
+    fn get_public_key_previous_version(&self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let not_found = || Error::KeyVersionNotFound(name.into(), "previous version".into());
+        let previous_label = self.previous_label(name)?.ok_or_else(not_found)?;
+        match self.module.public_key(&previous_label) {
+            Ok(previous) => Ok(previous.public_key),
+            Err(Error::KeyNotSet(_)) => Err(not_found()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rotate_key(&mut self, name: &str) -> Result<Ed25519PublicKey, Error> {
+        let version = self.current_version(name)?;
+        let new_version = version
+            .checked_add(1)
+            .ok_or_else(|| Error::InternalError(format!("Key version overflow for {}", name)))?;
+
+        // Clear out a key that an interrupted rotation failed to delete after it took effect.
+        if let Some(stale_version) = version.checked_sub(2) {
+            self.delete_if_present(&get_version_label(name, stale_version))?;
+        }
+
+        // Generate the new key first so that a failure leaves the current key untouched. A key
+        // already under the new label is the remnant of an interrupted rotation that never got
+        // published, so it is safe to discard.
+        let new_label = get_version_label(name, new_version);
+        self.delete_if_present(&new_label)?;
+        let new_public_key = self.module.generate_key(&new_label)?;
+        self.publish_version(name, new_version)?;
+
+        // Only the current and previous versions are kept. The rotation has already taken
+        // effect, so failing to delete the older key is retried on the next rotation.
+        if let Some(stale_version) = version.checked_sub(1) {
+            if let Err(e) = self.delete_if_present(&get_version_label(name, stale_version)) {
+                aptos_logger::warn!("Unable to delete stale key version of {}: {}", name, e);
+            }
+        }
+        Ok(new_public_key)
+    }
+
+    fn sign<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        self.module
+            .sign(&self.current_label(name)?, &signing_message(message))
+    }
+
+    fn sign_using_version<T: CryptoHash + Serialize>(
+        &self,
+        name: &str,
+        version: Ed25519PublicKey,
+        message: &T,
+    ) -> Result<Ed25519Signature, Error> {
+        let label = self.label_for_version(name, &version)?;
+        self.module.sign(&label, &signing_message(message))
+    }
+}
+
+/// Private helper method to get the key in the backing storage that holds the current version of
+/// the given key pair.
+fn get_version_pointer_name(name: &str) -> String {
+    format!("{}_key_version", name)
+}
+
+/// Private helper method to get the label under which the given version of a key pair is held in
+/// the hardware module.
+fn get_version_label(name: &str, version: u64) -> String {
+    format!("{}_v{}", name, version)
+}
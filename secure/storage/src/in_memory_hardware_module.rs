@@ -0,0 +1,95 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Error, HardwareModule, PublicKeyResponse};
+use aptos_crypto::{
+    ed25519::{ed25519_dalek, Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    PrivateKey, Uniform,
+};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use rand::{rngs::OsRng, Rng, SeedableRng};
+use std::{collections::HashMap, convert::TryFrom};
+
+/// InMemoryHardwareModule is an in-process HardwareModule intended for tests. It keeps keys in
+/// memory and, like a real module, only ever hands out public keys and signatures. It is only
+/// compiled for tests so that it can never back a production node.
+#[derive(Default)]
+pub struct InMemoryHardwareModule {
+    keys: HashMap<String, (Ed25519PrivateKey, u64)>,
+    time_service: TimeService,
+}
+
+impl InMemoryHardwareModule {
+    pub fn new() -> Self {
+        Self::new_with_time_service(TimeService::real())
+    }
+
+    pub fn new_with_time_service(time_service: TimeService) -> Self {
+        Self {
+            keys: HashMap::new(),
+            time_service,
+        }
+    }
+
+    fn private_key(&self, label: &str) -> Result<&Ed25519PrivateKey, Error> {
+        self.keys
+            .get(label)
+            .map(|(key, _)| key)
+            .ok_or_else(|| Error::KeyNotSet(label.to_string()))
+    }
+}
+
+impl HardwareModule for InMemoryHardwareModule {
+    fn generate_key(&mut self, label: &str) -> Result<Ed25519PublicKey, Error> {
+        let mut seed_rng = OsRng;
+        let mut rng = rand::rngs::StdRng::from_seed(seed_rng.gen());
+        let private_key = Ed25519PrivateKey::generate(&mut rng);
+        let public_key = private_key.public_key();
+        self.import_key(label, private_key)?;
+        Ok(public_key)
+    }
+
+    fn import_key(&mut self, label: &str, key: Ed25519PrivateKey) -> Result<(), Error> {
+        if self.keys.contains_key(label) {
+            return Err(Error::KeyAlreadyExists(label.to_string()));
+        }
+        let now = self.time_service.now_secs();
+        self.keys.insert(label.to_string(), (key, now));
+        Ok(())
+    }
+
+    fn public_key(&self, label: &str) -> Result<PublicKeyResponse, Error> {
+        let (key, last_update) = self
+            .keys
+            .get(label)
+            .ok_or_else(|| Error::KeyNotSet(label.to_string()))?;
+        Ok(PublicKeyResponse {
+            last_update: *last_update,
+            public_key: key.public_key(),
+        })
+    }
+
+    fn delete_key(&mut self, label: &str) -> Result<(), Error> {
+        self.keys
+            .remove(label)
+            .map(|_| ())
+            .ok_or_else(|| Error::KeyNotSet(label.to_string()))
+    }
+
+    fn sign(&self, label: &str, message: &[u8]) -> Result<Ed25519Signature, Error> {
+        let key_bytes = self.private_key(label)?.to_bytes();
+        let secret_key = ed25519_dalek::SecretKey::from_bytes(&key_bytes)
+            .map_err(|e| Error::InternalError(e.to_string()))?;
+        let public_key = ed25519_dalek::PublicKey::from(&secret_key);
+        let signature =
+            ed25519_dalek::ExpandedSecretKey::from(&secret_key).sign(message, &public_key);
+        Ed25519Signature::try_from(&signature.to_bytes()[..])
+            .map_err(|e| Error::InternalError(e.to_string()))
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    fn reset_and_clear(&mut self) -> Result<(), Error> {
+        self.keys.clear();
+        Ok(())
+    }
+}
@@ -5,9 +5,14 @@
 
 mod crypto_kv_storage;
 mod crypto_storage;
+mod encrypted_on_disk;
 mod error;
 mod github;
+mod hardware_module;
+mod hardware_storage;
 mod in_memory;
+#[cfg(any(test, feature = "testing"))]
+mod in_memory_hardware_module;
 mod kv_storage;
 mod namespaced;
 mod on_disk;
@@ -18,8 +23,11 @@ mod vault;
 pub use crate::{
     crypto_kv_storage::CryptoKVStorage,
     crypto_storage::{CryptoStorage, PublicKeyResponse},
+    encrypted_on_disk::EncryptedOnDiskStorage,
     error::Error,
     github::GitHubStorage,
+    hardware_module::HardwareModule,
+    hardware_storage::HardwareStorage,
     in_memory::InMemoryStorage,
     kv_storage::{GetResponse, KVStorage},
     namespaced::Namespaced,
@@ -29,6 +37,9 @@ pub use crate::{
     vault::VaultStorage,
 };
 
+#[cfg(any(test, feature = "testing"))]
+pub use crate::in_memory_hardware_module::InMemoryHardwareModule;
+
 // Some common serializations for interacting with bytes these must be manually added to types via:
 // #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
 // some_value: Vec<u8>
@@ -1,8 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    CryptoStorage, Error, GetResponse, GitHubStorage, InMemoryStorage, KVStorage, Namespaced,
-    OnDiskStorage, PublicKeyResponse, VaultStorage,
+    CryptoStorage, EncryptedOnDiskStorage, Error, GetResponse, GitHubStorage, HardwareStorage,
+    InMemoryStorage, KVStorage, Namespaced, OnDiskStorage, PublicKeyResponse, VaultStorage,
 };
 use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 use enum_dispatch::enum_dispatch;
@@ -21,6 +21,8 @@ pub enum Storage {
     InMemoryStorage(InMemoryStorage),
     NamespacedStorage(Namespaced<Box<Storage>>),
     OnDiskStorage(OnDiskStorage),
+    EncryptedOnDiskStorage(EncryptedOnDiskStorage),
+    HardwareStorage(HardwareStorage),
 }
 
 impl KVStorage for Box<Storage> {
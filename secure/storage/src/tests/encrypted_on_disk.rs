@@ -0,0 +1,50 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{tests::suite, EncryptedOnDiskStorage, Error, KVStorage, Storage};
+use aptos_temppath::TempPath;
+use std::fs;
+
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+
+#[test]
+fn encrypted_on_disk() {
+    let path_buf = TempPath::new().path().to_path_buf();
+    let mut storage = Storage::from(EncryptedOnDiskStorage::new(path_buf, PASSPHRASE).unwrap());
+    suite::execute_all_storage_tests(&mut storage);
+}
+
+#[test]
+fn encrypted_on_disk_at_rest() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    storage.set("key", "plaintext_value").unwrap();
+
+    let contents = fs::read_to_string(&path_buf).unwrap();
+    assert!(!contents.contains("plaintext_value"));
+
+    // Reopening with the same passphrase recovers the data, a different one is rejected
+    let storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    assert_eq!(
+        storage.get::<String>("key").unwrap().value,
+        "plaintext_value"
+    );
+    assert!(matches!(
+        EncryptedOnDiskStorage::new(path_buf, b"wrong passphrase"),
+        Err(Error::InternalError(_))
+    ));
+}
+
+#[test]
+fn encrypted_on_disk_rotate_passphrase() {
+    let temp_path = TempPath::new();
+    let path_buf = temp_path.path().to_path_buf();
+    let mut storage = EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).unwrap();
+    storage.set("key", 5u64).unwrap();
+    storage.rotate_passphrase(b"new passphrase").unwrap();
+
+    assert!(EncryptedOnDiskStorage::new(path_buf.clone(), PASSPHRASE).is_err());
+    let storage = EncryptedOnDiskStorage::new(path_buf, b"new passphrase").unwrap();
+    assert_eq!(storage.get::<u64>("key").unwrap().value, 5);
+}
@@ -0,0 +1,157 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    CryptoStorage, Error, HardwareModule, HardwareStorage, InMemoryHardwareModule, InMemoryStorage,
+    KVStorage, Storage,
+};
+use aptos_crypto::{
+    ed25519::Ed25519PrivateKey, test_utils::TestAptosCrypto, PrivateKey, Signature, SigningKey,
+    Uniform,
+};
+
+const CRYPTO_NAME: &str = "Test_Key_Name";
+
+fn hardware_storage() -> Storage {
+    Storage::from(HardwareStorage::new(
+        Box::new(InMemoryHardwareModule::new()),
+        Box::new(Storage::from(InMemoryStorage::new())),
+    ))
+}
+
+#[test]
+fn test_export_is_refused() {
+    let mut storage = hardware_storage();
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+
+    assert_eq!(
+        storage.export_private_key(CRYPTO_NAME).unwrap_err(),
+        Error::KeyNotExportable(CRYPTO_NAME.into())
+    );
+    assert_eq!(
+        storage
+            .export_private_key_for_version(CRYPTO_NAME, public_key)
+            .unwrap_err(),
+        Error::KeyNotExportable(CRYPTO_NAME.into())
+    );
+    assert!(matches!(
+        storage.create_key(CRYPTO_NAME),
+        Err(Error::KeyAlreadyExists(_))
+    ));
+}
+
+#[test]
+fn test_import_and_sign() {
+    let mut storage = hardware_storage();
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    storage
+        .import_private_key(CRYPTO_NAME, private_key.clone())
+        .unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        private_key.public_key()
+    );
+
+    // Signatures produced inside the module must match those of the key itself
+    let message = TestAptosCrypto("Hello, World".to_string());
+    let signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    assert_eq!(signature, private_key.sign(&message));
+}
+
+#[test]
+fn test_rotate_and_sign_using_version() {
+    let mut storage = hardware_storage();
+    let message = TestAptosCrypto("Hello, World".to_string());
+
+    let mut public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    let mut signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+    for _ in 0..3 {
+        let new_public_key = storage.rotate_key(CRYPTO_NAME).unwrap();
+        assert_ne!(new_public_key, public_key);
+        assert_eq!(
+            storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+            new_public_key
+        );
+        assert_eq!(
+            storage
+                .get_public_key_previous_version(CRYPTO_NAME)
+                .unwrap(),
+            public_key
+        );
+
+        // The previous version can still sign, anything older is gone
+        let previous_signature = storage
+            .sign_using_version(CRYPTO_NAME, public_key.clone(), &message)
+            .unwrap();
+        assert_eq!(previous_signature, signature);
+
+        signature = storage.sign(CRYPTO_NAME, &message).unwrap();
+        signature.verify(&message, &new_public_key).unwrap();
+        public_key = new_public_key;
+    }
+
+    let unknown_key = Ed25519PrivateKey::generate_for_testing().public_key();
+    assert!(matches!(
+        storage.sign_using_version(CRYPTO_NAME, unknown_key, &message),
+        Err(Error::KeyVersionNotFound(_, _))
+    ));
+}
+
+#[test]
+fn test_interrupted_rotation() {
+    // A rotation that crashed after generating its key, but before publishing it, leaves an
+    // unreferenced key in the module under the next version's label.
+    let mut module = InMemoryHardwareModule::new();
+    let leftover_key = module.generate_key(&format!("{}_v1", CRYPTO_NAME)).unwrap();
+    let mut storage = Storage::from(HardwareStorage::new(
+        Box::new(module),
+        Box::new(Storage::from(InMemoryStorage::new())),
+    ));
+
+    let public_key = storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        public_key
+    );
+    assert!(matches!(
+        storage.get_public_key_previous_version(CRYPTO_NAME),
+        Err(Error::KeyVersionNotFound(_, _))
+    ));
+
+    // The leftover key is discarded rather than adopted by the next rotation
+    let new_public_key = storage.rotate_key(CRYPTO_NAME).unwrap();
+    assert_ne!(new_public_key, leftover_key);
+    assert_eq!(
+        storage.get_public_key(CRYPTO_NAME).unwrap().public_key,
+        new_public_key
+    );
+    assert_eq!(
+        storage
+            .get_public_key_previous_version(CRYPTO_NAME)
+            .unwrap(),
+        public_key
+    );
+}
+
+#[test]
+fn test_key_value_and_reset() {
+    let mut storage = hardware_storage();
+    storage.available().unwrap();
+    storage.set("U64_Key", 10u64).unwrap();
+    storage.create_key(CRYPTO_NAME).unwrap();
+    assert_eq!(storage.get::<u64>("U64_Key").unwrap().value, 10);
+
+    storage.reset_and_clear().unwrap();
+    assert!(matches!(
+        storage.get::<u64>("U64_Key"),
+        Err(Error::KeyNotSet(_))
+    ));
+    assert!(matches!(
+        storage.get_public_key(CRYPTO_NAME),
+        Err(Error::KeyNotSet(_))
+    ));
+}
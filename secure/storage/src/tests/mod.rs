@@ -1,7 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+mod encrypted_on_disk;
 mod github;
+mod hardware_storage;
 mod in_memory;
 mod on_disk;
 mod suite;
@@ -8,6 +8,7 @@ use crate::{
             increment_request_counter, set_gauge, start_request_timer, DataType, PRIORITIZED_PEER,
             REGULAR_PEER,
         },
+        range::{split_range, MergeableRange},
         state::{ErrorType, PeerStates},
     },
     AptosDataClient, Error, GlobalDataSummary, Response, ResponseCallback, ResponseContext,
@@ -17,6 +18,7 @@ use aptos_config::{
     config::{AptosDataClientConfig, BaseConfig, StorageServiceConfig},
     network_id::PeerNetworkId,
 };
+use aptos_crypto::HashValue;
 use aptos_id_generator::{IdGenerator, U64IdGenerator};
 use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
@@ -28,7 +30,7 @@ use aptos_types::{
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{future, StreamExt};
 use network::{
     application::interface::NetworkInterface,
     protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId},
@@ -38,14 +40,15 @@ use std::{convert::TryFrom, fmt, sync::Arc, time::Duration};
 use storage_service_client::StorageServiceClient;
 use storage_service_types::{
     AccountStatesChunkWithProofRequest, Epoch, EpochEndingLedgerInfoRequest,
-    NewTransactionOutputsWithProofRequest, NewTransactionsWithProofRequest, StorageServerSummary,
-    StorageServiceRequest, StorageServiceResponse, TransactionOutputsWithProofRequest,
-    TransactionsWithProofRequest,
+    NewTransactionOutputsWithProofRequest, NewTransactionsWithProofRequest, ServerProtocolVersion,
+    StorageServerSummary, StorageServiceError, StorageServiceRequest, StorageServiceResponse,
+    TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 use tokio::{runtime::Handle, task::JoinHandle};
 
 mod logging;
 mod metrics;
+mod range;
 mod state;
 #[cfg(test)]
 mod tests;
@@ -71,8 +74,10 @@ const REGULAR_PEER_SAMPLE_FREQ: u64 = 3;
 /// 3. Routes requests to peers that advertise availability for that data.
 /// 4. Maintains peer scores based on each peer's observed quality of service
 ///    and upper client reports of invalid or malicious data.
-/// 5. Selects high quality peers to send each request to.
-/// 6. Exposes a condensed data summary of our peers' data advertisements.
+/// 5. Selects high quality peers to send each request to, preferring peers
+///    with low response latencies and high throughputs.
+/// 6. Optionally splits large range requests across several peers.
+/// 7. Exposes a condensed data summary of our peers' data advertisements.
 ///
 /// The client currently assumes 1-request => 1-response. Streaming responses
 /// are handled at an upper layer.
@@ -91,6 +96,8 @@ pub struct AptosNetDataClient {
     global_summary_cache: Arc<RwLock<GlobalDataSummary>>,
     /// Used for generating the next request/response id.
     response_id_generator: Arc<U64IdGenerator>,
+    /// The service used to measure response latencies.
+    time_service: TimeService,
 }
 
 impl AptosNetDataClient {
@@ -107,11 +114,13 @@ impl AptosNetDataClient {
             network_client: network_client.clone(),
             peer_states: Arc::new(RwLock::new(PeerStates::new(
                 base_config,
+                data_client_config,
                 storage_service_config,
                 network_client.get_peer_metadata_storage(),
             ))),
             global_summary_cache: Arc::new(RwLock::new(GlobalDataSummary::empty())),
             response_id_generator: Arc::new(U64IdGenerator::new()),
+            time_service: time_service.clone(),
         };
         let poller = DataSummaryPoller::new(
             client.clone(),
@@ -144,6 +153,18 @@ impl AptosNetDataClient {
         &self,
         request: &StorageServiceRequest,
     ) -> Result<PeerNetworkId, Error> {
+        self.choose_peers_for_request(request, 1)
+            .map(|peers| peers[0])
+    }
+
+    /// Choose up to `num_peers` distinct connected peers that can service the
+    /// given request, preferring the fastest ones. Returns an error if no such
+    /// peer can be found.
+    fn choose_peers_for_request(
+        &self,
+        request: &StorageServiceRequest,
+        num_peers: usize,
+    ) -> Result<Vec<PeerNetworkId>, Error> {
         // All requests should be sent to prioritized peers (if possible).
         // If none can handle the request, fall back to the regular peers.
         let (priority_peers, regular_peers) = self.get_priority_and_regular_peers()?;
@@ -154,27 +175,36 @@ impl AptosNetDataClient {
             self.identify_serviceable(regular_peers, request)
         };
 
-        // Randomly select a peer to handle the request
-        serviceable_peers
-            .choose(&mut rand::thread_rng())
-            .copied()
-            .ok_or_else(|| {
-                Error::DataIsUnavailable(
-                    format!("No connected peers are advertising that they can serve this data! Request: {:?}",request),
-                )
-            })
+        // Select the peers to handle the request
+        let peers = self.peer_states.read().choose_peers_for_request(
+            &serviceable_peers,
+            request,
+            num_peers,
+        );
+        if peers.is_empty() {
+            return Err(Error::DataIsUnavailable(format!(
+                "No connected peers are advertising that they can serve this data! Request: {:?}",
+                request
+            )));
+        }
+        Ok(peers)
     }
 
     /// Identifies the peers in the given set of prospective peers
-    /// that can service the specified request.
+    /// that can service the specified request (and are not throttled).
     fn identify_serviceable(
         &self,
         prospective_peers: Vec<PeerNetworkId>,
         request: &StorageServiceRequest,
     ) -> Vec<PeerNetworkId> {
+        let now = self.time_service.now();
         prospective_peers
             .into_iter()
-            .filter(|peer| self.peer_states.read().can_service_request(peer, request))
+            .filter(|peer| {
+                let peer_states = self.peer_states.read();
+                peer_states.can_service_request(peer, request)
+                    && !peer_states.is_throttled(peer, now)
+            })
             .collect::<Vec<_>>()
     }
 
@@ -211,12 +241,18 @@ impl AptosNetDataClient {
     }
 
     /// Randomly selects a peer to poll that does not have an in-flight request
+    /// (and is not throttled).
     fn select_peer_to_poll(
         &self,
         mut peers: Vec<PeerNetworkId>,
     ) -> Result<Option<PeerNetworkId>, Error> {
-        // Identify the peers who do not already have in-flight requests.
-        peers.retain(|peer| !self.peer_states.read().existing_in_flight_request(peer));
+        // Identify the peers who do not already have in-flight requests
+        // and who have not asked us to back off.
+        let now = self.time_service.now();
+        peers.retain(|peer| {
+            let peer_states = self.peer_states.read();
+            !peer_states.existing_in_flight_request(peer) && !peer_states.is_throttled(peer, now)
+        });
 
         // Select a peer at random for polling
         let peer_to_poll = peers.choose(&mut rand::thread_rng());
@@ -315,6 +351,220 @@ impl AptosNetDataClient {
         self.send_request_to_peer_and_decode(peer, request).await
     }
 
+    /// Sends a request for the items from `start` to `end` (inclusive) and
+    /// decodes the response. If the range is large enough (and parallel range
+    /// requests are enabled), it is split into chunks that are requested from
+    /// different peers in parallel, and the responses are merged. Peers that
+    /// return only a prefix of the requested items are asked for the remainder.
+    async fn send_range_request_and_decode<T, E>(
+        &self,
+        start: u64,
+        end: u64,
+        make_request: impl Fn(u64, u64) -> StorageServiceRequest,
+    ) -> Result<Response<T>>
+    where
+        T: TryFrom<StorageServiceResponse, Error = E> + MergeableRange,
+        E: Into<Error>,
+    {
+        let max_parallel_range_requests = self.data_client_config.max_parallel_range_requests;
+        let min_chunk_size = self.data_client_config.min_parallel_range_chunk_size;
+        let request = make_request(start, end);
+        let chunks = split_range(start, end, max_parallel_range_requests, min_chunk_size);
+        if chunks.len() <= 1 {
+            return self
+                .send_range_request_to_single_peer_and_decode(start, end, &make_request)
+                .await;
+        }
+
+        // Select a different peer for each chunk. If there are fewer peers
+        // than chunks, split the range between the available peers.
+        let peers = self
+            .choose_peers_for_request(&request, chunks.len())
+            .map_err(|error| {
+                debug!(
+                    (LogSchema::new(LogEntry::StorageServiceRequest)
+                        .event(LogEvent::PeerSelectionError)
+                        .message("Unable to select peers")
+                        .error(&error))
+                );
+                error
+            })?;
+        if peers.len() <= 1 {
+            return self
+                .send_range_request_to_single_peer_and_decode(start, end, &make_request)
+                .await;
+        }
+        let chunks = split_range(start, end, peers.len() as u64, min_chunk_size);
+
+        // Fetch the chunks in parallel
+        let chunk_responses = future::try_join_all(peers.into_iter().zip(chunks).map(
+            |(peer, (chunk_start, chunk_end))| {
+                self.fetch_range_chunk::<T, E>(peer, chunk_start, chunk_end, &make_request)
+            },
+        ))
+        .await?;
+        let responses = chunk_responses.into_iter().flatten().collect::<Vec<_>>();
+
+        // All chunks must be proven against the same ledger info. Otherwise, some
+        // of the peers sent bad proofs, but they can't be identified without the
+        // ledger info. In that case, fall back to requesting the whole range from
+        // a single peer.
+        if responses
+            .iter()
+            .any(|(_, root_hash)| *root_hash != responses[0].1)
+        {
+            warn!(
+                (LogSchema::new(LogEntry::StorageServiceResponse)
+                    .event(LogEvent::ResponseError)
+                    .request_type(request.get_label())
+                    .message("The chunks of a range request have conflicting proofs!"))
+            );
+            return self
+                .send_range_request_to_single_peer_and_decode(start, end, &make_request)
+                .await;
+        }
+
+        Ok(self.merge_range_responses(responses))
+    }
+
+    /// Sends a request for the items from `start` to `end` (inclusive) to a
+    /// single peer and decodes the response. If the peer returns only a prefix
+    /// of the items (e.g., because all of them don't fit in a single network
+    /// message), the remainder is requested from the same peer and the
+    /// responses are merged.
+    async fn send_range_request_to_single_peer_and_decode<T, E>(
+        &self,
+        start: u64,
+        end: u64,
+        make_request: &impl Fn(u64, u64) -> StorageServiceRequest,
+    ) -> Result<Response<T>>
+    where
+        T: TryFrom<StorageServiceResponse, Error = E> + MergeableRange,
+        E: Into<Error>,
+    {
+        let request = make_request(start, end);
+        let peer = self.choose_peer_for_request(&request).map_err(|error| {
+            debug!(
+                (LogSchema::new(LogEntry::StorageServiceRequest)
+                    .event(LogEvent::PeerSelectionError)
+                    .message("Unable to select peer")
+                    .error(&error))
+            );
+            error
+        })?;
+        let response = {
+            let _timer =
+                start_request_timer(&metrics::REQUEST_LATENCIES, request.get_label(), peer);
+            self.send_request_to_peer_and_decode::<T, E>(peer, request)
+                .await?
+        };
+
+        // Return the response unless it holds a strict prefix of the items.
+        // Complete (and invalid) responses are verified by the caller.
+        let num_items = match response.payload.num_items_covered(start, end) {
+            Some(num_items) if num_items < end - start + 1 => num_items,
+            _ => return Ok(response),
+        };
+        let root_hash = verify_range_response(&response)?;
+        let mut responses = vec![(response, root_hash)];
+        responses.extend(
+            self.fetch_range_chunk::<T, E>(peer, start + num_items, end, make_request)
+                .await?,
+        );
+
+        // All responses must be proven against the same ledger info
+        if responses
+            .iter()
+            .any(|(_, root_hash)| *root_hash != responses[0].1)
+        {
+            for (response, _) in &responses {
+                response
+                    .context
+                    .response_callback
+                    .notify_bad_response(ResponseError::ProofVerificationError);
+            }
+            return Err(Error::InvalidResponse(format!(
+                "The responses for the range have conflicting proofs: ({}, {})",
+                start, end
+            )));
+        }
+
+        Ok(self.merge_range_responses(responses))
+    }
+
+    /// Merges the responses for consecutive ranges (in order) into a single
+    /// response. If the merged response turns out to be bad, all the peers
+    /// that sent the responses are notified.
+    fn merge_range_responses<T: MergeableRange>(
+        &self,
+        responses: Vec<(Response<T>, HashValue)>,
+    ) -> Response<T> {
+        let mut payloads = vec![];
+        let mut response_callbacks = vec![];
+        for (response, _) in responses {
+            let (context, payload) = response.into_parts();
+            payloads.push(payload);
+            response_callbacks.push(context.response_callback);
+        }
+        let context = ResponseContext {
+            id: self.next_response_id(),
+            response_callback: Box::new(MergedResponseCallback { response_callbacks }),
+        };
+        Response::new(context, T::merge(payloads))
+    }
+
+    /// Fetches the items from `start` to `end` (inclusive) from the given peer.
+    /// If the peer returns fewer items than requested, the remainder is requested
+    /// until the chunk is complete. Each response is verified on its own, so that
+    /// a bad response is only reported to the peer that sent it. Returns the
+    /// responses in order, along with the root hash each one is proven against.
+    async fn fetch_range_chunk<T, E>(
+        &self,
+        peer: PeerNetworkId,
+        start: u64,
+        end: u64,
+        make_request: &impl Fn(u64, u64) -> StorageServiceRequest,
+    ) -> Result<Vec<(Response<T>, HashValue)>>
+    where
+        T: TryFrom<StorageServiceResponse, Error = E> + MergeableRange,
+        E: Into<Error>,
+    {
+        let mut responses = vec![];
+        let mut next_start = start;
+        loop {
+            let request = make_request(next_start, end);
+            let _timer =
+                start_request_timer(&metrics::REQUEST_LATENCIES, request.get_label(), peer);
+            let response = self
+                .send_request_to_peer_and_decode::<T, E>(peer, request)
+                .await?;
+
+            // Verify the response holds (a prefix of) the requested items
+            let num_items = match response.payload.num_items_covered(next_start, end) {
+                Some(num_items) => num_items,
+                None => {
+                    response
+                        .context
+                        .response_callback
+                        .notify_bad_response(ResponseError::InvalidData);
+                    return Err(Error::InvalidResponse(format!(
+                        "The response does not hold a prefix of the requested chunk: ({}, {})",
+                        next_start, end
+                    )));
+                }
+            };
+            let root_hash = verify_range_response(&response)?;
+            responses.push((response, root_hash));
+
+            // Request the remainder of the chunk (if any)
+            let last_version = next_start + num_items - 1;
+            if last_version >= end {
+                return Ok(responses);
+            }
+            next_start = last_version + 1;
+        }
+    }
+
     /// Sends a request to a specific peer and decodes the response
     async fn send_request_to_peer_and_decode<T, E>(
         &self,
@@ -361,17 +611,36 @@ impl AptosNetDataClient {
 
         increment_request_counter(&metrics::SENT_REQUESTS, request.get_label(), peer);
 
+        // Request a compressed response (if the peer supports it) and announce
+        // our protocol version (if the peer understands it)
+        let (use_compression, request_protocol_version) = {
+            let peer_states = self.peer_states.read();
+            (
+                peer_states.should_compress_request(&peer, &request),
+                peer_states.get_request_protocol_version(&peer),
+            )
+        };
+        let mut network_request = if use_compression {
+            request.clone().into_compressed()
+        } else {
+            request.clone()
+        };
+        if let Some(protocol_version) = request_protocol_version {
+            network_request = network_request.with_protocol_version(protocol_version);
+        }
+
+        let request_start_time = self.time_service.now();
         let result = self
             .network_client
-            .send_request(
+            .send_request_and_get_response_size(
                 peer,
-                request.clone(),
+                network_request,
                 Duration::from_millis(self.data_client_config.response_timeout_ms),
             )
             .await;
 
         match result {
-            Ok(response) => {
+            Ok((response, num_response_bytes)) => {
                 debug!(
                     (LogSchema::new(LogEntry::StorageServiceResponse)
                         .event(LogEvent::ResponseSuccess)
@@ -382,6 +651,39 @@ impl AptosNetDataClient {
 
                 increment_request_counter(&metrics::SUCCESS_RESPONSES, request.get_label(), peer);
 
+                // Update the latency and throughput of the peer. The throughput
+                // is measured using the size of the (compressed) response, as
+                // received over the wire.
+                self.peer_states.write().update_response_metrics(
+                    peer,
+                    &request,
+                    self.time_service.now() - request_start_time,
+                    num_response_bytes as u64,
+                );
+
+                // Decompress the response (if required)
+                let response = match response.decompress() {
+                    Ok(response) => response,
+                    Err(error) => {
+                        let client_error = Error::from(error);
+                        error!(
+                            (LogSchema::new(LogEntry::StorageServiceResponse)
+                                .event(LogEvent::ResponseError)
+                                .request_type(request.get_label())
+                                .request_id(id)
+                                .peer(&peer)
+                                .error(&client_error))
+                        );
+                        increment_request_counter(
+                            &metrics::ERROR_RESPONSES,
+                            client_error.get_label(),
+                            peer,
+                        );
+                        self.notify_bad_response(id, peer, &request, ErrorType::NotUseful);
+                        return Err(client_error);
+                    }
+                };
+
                 // For now, record all responses that at least pass the data
                 // client layer successfully. An alternative might also have the
                 // consumer notify both success and failure via the callback.
@@ -404,7 +706,54 @@ impl AptosNetDataClient {
                 };
                 Ok(Response::new(context, response))
             }
+            Err(storage_service_client::Error::StorageServiceError(
+                StorageServiceError::TooManyRequests(error),
+            )) => {
+                // The peer is rate limiting us. This isn't a bad response, so
+                // don't penalize the peer. Instead, back off for a while.
+                let client_error = Error::TooManyRequests(error);
+                warn!(
+                    (LogSchema::new(LogEntry::StorageServiceResponse)
+                        .event(LogEvent::ResponseError)
+                        .request_type(request.get_label())
+                        .request_id(id)
+                        .peer(&peer)
+                        .error(&client_error))
+                );
+                increment_request_counter(
+                    &metrics::ERROR_RESPONSES,
+                    client_error.get_label(),
+                    peer,
+                );
+
+                let throttled_until = self.time_service.now()
+                    + Duration::from_millis(self.data_client_config.throttled_peer_backoff_ms);
+                self.peer_states
+                    .write()
+                    .throttle_peer(peer, throttled_until);
+                Err(client_error)
+            }
             Err(error) => {
+                // Timeouts count as (very slow) responses
+                if matches!(
+                    error,
+                    storage_service_client::Error::RpcError(RpcError::TimedOut)
+                ) {
+                    self.peer_states.write().update_response_metrics(
+                        peer,
+                        &request,
+                        self.time_service.now() - request_start_time,
+                        0,
+                    );
+                }
+
+                // The peer may no longer support compression or the protocol
+                // version (e.g., if it was downgraded), so force the protocol
+                // version to be renegotiated.
+                if use_compression || request_protocol_version.is_some() {
+                    self.peer_states.write().update_protocol_version(peer, None);
+                }
+
                 // Convert network error and storage service error types into
                 // data client errors. Also categorize the error type for scoring
                 // purposes.
@@ -440,6 +789,28 @@ impl AptosNetDataClient {
         }
     }
 
+    /// Fetches and stores the protocol version of the given peer. This is only
+    /// done if the version isn't already known.
+    async fn negotiate_protocol_version(&self, peer: PeerNetworkId) -> Result<(), Error> {
+        if self
+            .peer_states
+            .read()
+            .get_protocol_version(&peer)
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let server_protocol_version: ServerProtocolVersion = self
+            .send_request_to_peer_and_decode(peer, StorageServiceRequest::GetServerProtocolVersion)
+            .await
+            .map(Response::into_payload)?;
+        self.peer_states
+            .write()
+            .update_protocol_version(peer, Some(server_protocol_version.protocol_version));
+        Ok(())
+    }
+
     /// Updates the score of the peer who sent the response with the specified id
     fn notify_bad_response(
         &self,
@@ -530,14 +901,20 @@ impl AptosDataClient for AptosNetDataClient {
         start_version: Version,
         end_version: Version,
     ) -> Result<Response<TransactionOutputListWithProof>> {
-        let request = StorageServiceRequest::GetTransactionOutputsWithProof(
-            TransactionOutputsWithProofRequest {
-                proof_version,
-                start_version,
-                end_version,
+        self.send_range_request_and_decode(
+            start_version,
+            end_version,
+            |start_version, end_version| {
+                StorageServiceRequest::GetTransactionOutputsWithProof(
+                    TransactionOutputsWithProofRequest {
+                        proof_version,
+                        start_version,
+                        end_version,
+                    },
+                )
             },
-        );
-        self.send_request_and_decode(request).await
+        )
+        .await
     }
 
     async fn get_transactions_with_proof(
@@ -547,14 +924,19 @@ impl AptosDataClient for AptosNetDataClient {
         end_version: Version,
         include_events: bool,
     ) -> Result<Response<TransactionListWithProof>> {
-        let request =
-            StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
-                proof_version,
-                start_version,
-                end_version,
-                include_events,
-            });
-        self.send_request_and_decode(request).await
+        self.send_range_request_and_decode(
+            start_version,
+            end_version,
+            |start_version, end_version| {
+                StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                    proof_version,
+                    start_version,
+                    end_version,
+                    include_events,
+                })
+            },
+        )
+        .await
     }
 }
 
@@ -574,6 +956,40 @@ impl ResponseCallback for AptosNetResponseCallback {
     }
 }
 
+/// Verifies the response for a range on its own (see
+/// [`MergeableRange::verify_and_compute_root_hash`]) and returns the root hash
+/// it is proven against. The peer is notified if the response is bad.
+fn verify_range_response<T: MergeableRange>(response: &Response<T>) -> Result<HashValue> {
+    response
+        .payload
+        .verify_and_compute_root_hash()
+        .map_err(|error| {
+            response
+                .context
+                .response_callback
+                .notify_bad_response(ResponseError::ProofVerificationError);
+            error
+        })
+}
+
+/// The callbacks of all the responses merged into a single response. Each of
+/// the responses has been verified on its own and all of them are proven against
+/// the same ledger info, so if the merged response turns out to be bad (e.g., it
+/// isn't proven against the expected ledger info), each of them is bad on its
+/// own and all of them are notified.
+#[derive(Debug)]
+struct MergedResponseCallback {
+    response_callbacks: Vec<Box<dyn ResponseCallback>>,
+}
+
+impl ResponseCallback for MergedResponseCallback {
+    fn notify_bad_response(&self, error: ResponseError) {
+        for response_callback in &self.response_callbacks {
+            response_callback.notify_bad_response(error.clone());
+        }
+    }
+}
+
 impl fmt::Debug for AptosNetResponseCallback {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("AptosNetResponseCallback")
@@ -734,6 +1150,19 @@ pub(crate) fn poll_peer(
             .map(Response::into_payload);
         drop(timer);
 
+        // Negotiate the protocol version with the peer (if required)
+        if result.is_ok() {
+            if let Err(error) = data_client.negotiate_protocol_version(peer).await {
+                error!(
+                    (LogSchema::new(LogEntry::StorageSummaryResponse)
+                        .event(LogEvent::PeerPollingError)
+                        .message("Error encountered when negotiating the protocol version!")
+                        .error(&error)
+                        .peer(&peer))
+                );
+            }
+        }
+
         // Mark the in-flight poll as now complete
         data_client.in_flight_request_complete(&peer);
 
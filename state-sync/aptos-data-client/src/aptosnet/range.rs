@@ -0,0 +1,211 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    block_info::BlockInfo,
+    ledger_info::LedgerInfo,
+    proof::{AccumulatorRangeProof, TransactionInfoListWithProof},
+    transaction::{
+        TransactionInfo, TransactionListWithProof, TransactionOutputListWithProof, Version,
+    },
+};
+use std::cmp::{max, min};
+
+/// A response for a range of versions that can be merged with the responses
+/// for the neighbouring ranges (e.g., when a single range request is split
+/// across several peers).
+pub(crate) trait MergeableRange: Sized {
+    /// Returns the number of items in the response iff it holds a non-empty
+    /// prefix of the items from `start` to `end` (inclusive). Peers may return
+    /// fewer items than requested, e.g., to stay within their size limits.
+    fn num_items_covered(&self, start: u64, end: u64) -> Option<u64>;
+
+    /// Verifies the response on its own, i.e., that the items match their
+    /// transaction infos and that the range proof is well formed, and returns
+    /// the root hash of the transaction accumulator the proof is relative to.
+    fn verify_and_compute_root_hash(&self) -> Result<HashValue, Error>;
+
+    /// Merges the responses for consecutive ranges (in order) into a single
+    /// response. All responses must have proofs relative to the same version.
+    fn merge(parts: Vec<Self>) -> Self;
+}
+
+impl MergeableRange for TransactionListWithProof {
+    fn num_items_covered(&self, start: u64, end: u64) -> Option<u64> {
+        let num_items = self.transactions.len() as u64;
+        let is_prefix = self.first_transaction_version == Some(start)
+            && num_items > 0
+            && num_items <= range_length(start, end)
+            && self.proof.transaction_infos.len() as u64 == num_items
+            && self
+                .events
+                .as_ref()
+                .map_or(true, |events| events.len() as u64 == num_items);
+        is_prefix.then(|| num_items)
+    }
+
+    fn verify_and_compute_root_hash(&self) -> Result<HashValue, Error> {
+        let root_hash = compute_root_hash(&self.proof, self.first_transaction_version)?;
+        self.verify(
+            &ledger_info_with_root_hash(root_hash),
+            self.first_transaction_version,
+        )
+        .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+        Ok(root_hash)
+    }
+
+    fn merge(parts: Vec<Self>) -> Self {
+        let first_transaction_version = parts
+            .first()
+            .and_then(|part| part.first_transaction_version);
+        let proof = merge_proofs(parts.iter().map(|part| &part.proof));
+        let has_events = parts.iter().all(|part| part.events.is_some());
+
+        let mut transactions = vec![];
+        let mut events = vec![];
+        for part in parts {
+            transactions.extend(part.transactions);
+            events.extend(part.events.into_iter().flatten());
+        }
+        let events = if has_events { Some(events) } else { None };
+        TransactionListWithProof::new(transactions, events, first_transaction_version, proof)
+    }
+}
+
+impl MergeableRange for TransactionOutputListWithProof {
+    fn num_items_covered(&self, start: u64, end: u64) -> Option<u64> {
+        let num_items = self.transactions_and_outputs.len() as u64;
+        let is_prefix = self.first_transaction_output_version == Some(start)
+            && num_items > 0
+            && num_items <= range_length(start, end)
+            && self.proof.transaction_infos.len() as u64 == num_items;
+        is_prefix.then(|| num_items)
+    }
+
+    fn verify_and_compute_root_hash(&self) -> Result<HashValue, Error> {
+        let root_hash = compute_root_hash(&self.proof, self.first_transaction_output_version)?;
+        self.verify(
+            &ledger_info_with_root_hash(root_hash),
+            self.first_transaction_output_version,
+        )
+        .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+        Ok(root_hash)
+    }
+
+    fn merge(parts: Vec<Self>) -> Self {
+        let first_transaction_output_version = parts
+            .first()
+            .and_then(|part| part.first_transaction_output_version);
+        let proof = merge_proofs(parts.iter().map(|part| &part.proof));
+
+        let transactions_and_outputs = parts
+            .into_iter()
+            .flat_map(|part| part.transactions_and_outputs)
+            .collect();
+        TransactionOutputListWithProof::new(
+            transactions_and_outputs,
+            first_transaction_output_version,
+            proof,
+        )
+    }
+}
+
+/// Returns the root hash of the transaction accumulator implied by the range
+/// proof and the transaction infos starting at `first_version`.
+fn compute_root_hash(
+    proof: &TransactionInfoListWithProof,
+    first_version: Option<Version>,
+) -> Result<HashValue, Error> {
+    let first_version = first_version
+        .ok_or_else(|| Error::InvalidResponse("The response holds no transaction infos!".into()))?;
+    let transaction_info_hashes: Vec<HashValue> = proof
+        .transaction_infos
+        .iter()
+        .map(CryptoHash::hash)
+        .collect();
+    proof
+        .ledger_info_to_transaction_infos_proof
+        .compute_root_hash(first_version, &transaction_info_hashes)
+        .map_err(|error| Error::InvalidResponse(error.to_string()))
+}
+
+/// Returns a ledger info that only carries the given root hash of the
+/// transaction accumulator. This is all that is read when verifying a
+/// transaction (output) list against a ledger info.
+fn ledger_info_with_root_hash(root_hash: HashValue) -> LedgerInfo {
+    LedgerInfo::new(
+        BlockInfo::new(0, 0, HashValue::zero(), root_hash, 0, 0, None),
+        HashValue::zero(),
+    )
+}
+
+/// Merges the proofs of consecutive transaction info lists. The range proof
+/// of the merged list consists of the left siblings of its first leaf (i.e.,
+/// those of the first list) and the right siblings of its last leaf (i.e.,
+/// those of the last list).
+fn merge_proofs<'a>(
+    proofs: impl Iterator<Item = &'a TransactionInfoListWithProof> + Clone,
+) -> TransactionInfoListWithProof {
+    let left_siblings = proofs
+        .clone()
+        .next()
+        .map(|proof| {
+            proof
+                .ledger_info_to_transaction_infos_proof
+                .left_siblings()
+                .clone()
+        })
+        .unwrap_or_default();
+    let right_siblings = proofs
+        .clone()
+        .last()
+        .map(|proof| {
+            proof
+                .ledger_info_to_transaction_infos_proof
+                .right_siblings()
+                .clone()
+        })
+        .unwrap_or_default();
+    let transaction_infos: Vec<TransactionInfo> = proofs
+        .flat_map(|proof| proof.transaction_infos.iter().cloned())
+        .collect();
+    TransactionInfoListWithProof::new(
+        AccumulatorRangeProof::new(left_siblings, right_siblings),
+        transaction_infos,
+    )
+}
+
+/// Splits the range from `start` to `end` (inclusive) into at most
+/// `max_num_chunks` consecutive chunks of (almost) equal size, each holding at
+/// least `min_chunk_size` items. Invalid ranges are returned unchanged.
+pub(crate) fn split_range(
+    start: u64,
+    end: u64,
+    max_num_chunks: u64,
+    min_chunk_size: u64,
+) -> Vec<(u64, u64)> {
+    if end < start {
+        return vec![(start, end)];
+    }
+
+    let num_items = (end - start) as u128 + 1;
+    let num_chunks = min(
+        max_num_chunks as u128,
+        num_items / max(min_chunk_size, 1) as u128,
+    );
+    let num_chunks = max(num_chunks, 1);
+    (0..num_chunks)
+        .map(|chunk| {
+            let chunk_start = start as u128 + num_items * chunk / num_chunks;
+            let chunk_end = start as u128 + num_items * (chunk + 1) / num_chunks - 1;
+            (chunk_start as u64, chunk_end as u64)
+        })
+        .collect()
+}
+
+/// Returns the number of items from `start` to `end` (inclusive)
+fn range_length(start: u64, end: u64) -> u64 {
+    end.saturating_sub(start).saturating_add(1)
+}
@@ -6,19 +6,24 @@ use crate::{
     AdvertisedData, GlobalDataSummary, OptimalChunkSizes, ResponseError,
 };
 use aptos_config::{
-    config::{BaseConfig, StorageServiceConfig},
+    config::{AptosDataClientConfig, BaseConfig, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_logger::prelude::*;
 use itertools::Itertools;
 use netcore::transport::ConnectionOrigin;
 use network::application::storage::PeerMetadataStorage;
+use rand::{seq::SliceRandom, Rng};
 use std::{
     cmp::min,
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
+};
+use storage_service_types::{
+    StorageServerSummary, StorageServiceRequest, COMPRESSION_PROTOCOL_VERSION,
+    RATE_LIMITING_PROTOCOL_VERSION,
 };
-use storage_service_types::{StorageServerSummary, StorageServiceRequest};
 
 /// Scores for peer rankings based on preferences and behavior.
 const MAX_SCORE: f64 = 100.0;
@@ -32,6 +37,11 @@ const NOT_USEFUL_MULTIPLIER: f64 = 0.95;
 const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
+/// The weight of each new sample in the response latency and throughput
+/// moving averages.
+const MOVING_AVERAGE_WEIGHT: f64 = 0.2;
+/// The latest storage service protocol version supported by the client.
+const CLIENT_PROTOCOL_VERSION: u64 = RATE_LIMITING_PROTOCOL_VERSION;
 
 pub(crate) enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
@@ -60,6 +70,19 @@ struct PeerState {
     storage_summary: Option<StorageServerSummary>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+    /// The moving average of the peer's response latency (in seconds), or
+    /// `None` if no response has been measured yet.
+    response_latency_secs: Option<f64>,
+    /// The moving average of the peer's throughput (in bytes per second) when
+    /// responding to data chunk requests, or `None` if no data chunk response
+    /// has been measured yet.
+    response_throughput_bps: Option<f64>,
+    /// The protocol version negotiated with the peer, or `None` if it hasn't
+    /// been negotiated yet (or must be renegotiated).
+    protocol_version: Option<u64>,
+    /// The time until which the peer has asked us to back off (i.e., it
+    /// rejected our requests as too many), or `None` if it hasn't.
+    throttled_until: Option<Instant>,
 }
 
 impl Default for PeerState {
@@ -67,6 +90,10 @@ impl Default for PeerState {
         Self {
             storage_summary: None,
             score: STARTING_SCORE,
+            response_latency_secs: None,
+            response_throughput_bps: None,
+            protocol_version: None,
+            throttled_until: None,
         }
     }
 }
@@ -99,6 +126,49 @@ impl PeerState {
         };
         self.score = f64::max(self.score * multiplier, MIN_SCORE);
     }
+
+    /// Updates the latency (and throughput, for data chunk responses) of the
+    /// peer according to a response that took `latency` to arrive.
+    fn update_response_metrics(&mut self, latency: Duration, num_response_bytes: Option<u64>) {
+        // Avoid dividing by zero (e.g., when time is mocked)
+        let latency_secs = f64::max(latency.as_secs_f64(), f64::EPSILON);
+        update_moving_average(&mut self.response_latency_secs, latency_secs);
+        if let Some(num_response_bytes) = num_response_bytes {
+            let throughput_bps = num_response_bytes as f64 / latency_secs;
+            update_moving_average(&mut self.response_throughput_bps, throughput_bps);
+        }
+    }
+
+    /// Returns the expected speed of the peer when servicing a request: the
+    /// throughput for data chunk requests and the inverse of the latency for
+    /// all other requests. Higher is better.
+    fn expected_speed(&self, is_data_chunk_request: bool) -> Option<f64> {
+        let throughput = self
+            .response_throughput_bps
+            .filter(|_| is_data_chunk_request);
+        throughput.or_else(|| self.response_latency_secs.map(|latency| 1.0 / latency))
+    }
+}
+
+/// Folds the given sample into the exponential moving average
+fn update_moving_average(moving_average: &mut Option<f64>, sample: f64) {
+    let new_average = match *moving_average {
+        Some(average) => average + MOVING_AVERAGE_WEIGHT * (sample - average),
+        None => sample,
+    };
+    *moving_average = Some(new_average);
+}
+
+/// Returns true iff the request fetches a chunk of data whose size is
+/// determined by the request (and not by the server).
+fn is_data_chunk_request(request: &StorageServiceRequest) -> bool {
+    matches!(
+        request,
+        StorageServiceRequest::GetAccountStatesChunkWithProof(_)
+            | StorageServiceRequest::GetEpochEndingLedgerInfos(_)
+            | StorageServiceRequest::GetTransactionOutputsWithProof(_)
+            | StorageServiceRequest::GetTransactionsWithProof(_)
+    )
 }
 
 /// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
@@ -107,6 +177,7 @@ impl PeerState {
 #[derive(Debug)]
 pub(crate) struct PeerStates {
     base_config: BaseConfig,
+    data_client_config: AptosDataClientConfig,
     storage_service_config: StorageServiceConfig,
     peer_to_state: HashMap<PeerNetworkId, PeerState>,
     in_flight_priority_polls: HashSet<PeerNetworkId>, // The priority peers with in-flight polls
@@ -117,11 +188,13 @@ pub(crate) struct PeerStates {
 impl PeerStates {
     pub fn new(
         base_config: BaseConfig,
+        data_client_config: AptosDataClientConfig,
         storage_service_config: StorageServiceConfig,
         peer_metadata_storage: Arc<PeerMetadataStorage>,
     ) -> Self {
         Self {
             base_config,
+            data_client_config,
             storage_service_config,
             peer_to_state: HashMap::new(),
             in_flight_priority_polls: HashSet::new(),
@@ -192,6 +265,78 @@ impl PeerStates {
         }
     }
 
+    /// Updates the latency and throughput of the peer according to a response
+    /// to the given request. Data subscriptions are ignored, as the peer only
+    /// responds to them once new data is available.
+    pub fn update_response_metrics(
+        &mut self,
+        peer: PeerNetworkId,
+        request: &StorageServiceRequest,
+        latency: Duration,
+        num_response_bytes: u64,
+    ) {
+        if request.is_data_subscription_request() {
+            return;
+        }
+        let num_response_bytes =
+            Some(num_response_bytes).filter(|_| is_data_chunk_request(request));
+        self.peer_to_state
+            .entry(peer)
+            .or_default()
+            .update_response_metrics(latency, num_response_bytes);
+    }
+
+    /// Chooses up to `num_peers` distinct peers (from the given serviceable
+    /// peers) to send the request to. Peers are chosen at random, weighted by
+    /// the square of their expected speed, so fast peers are strongly preferred
+    /// and slow ones are still used occasionally. Peers that have not been
+    /// measured yet are assumed to be as fast as the fastest peer, and a fraction
+    /// of the peers are chosen uniformly at random, to keep exploring.
+    pub fn choose_peers_for_request(
+        &self,
+        serviceable_peers: &[PeerNetworkId],
+        request: &StorageServiceRequest,
+        num_peers: usize,
+    ) -> Vec<PeerNetworkId> {
+        let mut rng = rand::thread_rng();
+        let is_data_chunk_request = is_data_chunk_request(request);
+        let speeds = serviceable_peers
+            .iter()
+            .map(|peer| {
+                self.peer_to_state
+                    .get(peer)
+                    .and_then(|peer_state| peer_state.expected_speed(is_data_chunk_request))
+            })
+            .collect::<Vec<_>>();
+        let max_speed = speeds.iter().flatten().copied().fold(0.0, f64::max);
+
+        let mut candidates = serviceable_peers
+            .iter()
+            .zip(speeds)
+            .map(|(peer, speed)| {
+                let speed = speed.unwrap_or(max_speed);
+                (*peer, speed * speed)
+            })
+            .collect::<Vec<_>>();
+        let mut chosen_peers = vec![];
+        while chosen_peers.len() < num_peers && !candidates.is_empty() {
+            let exploration_ratio = self.data_client_config.peer_exploration_ratio;
+            let explore = rng.gen_bool(exploration_ratio.clamp(0.0, 1.0));
+            let index = if explore {
+                rng.gen_range(0, candidates.len())
+            } else {
+                let indices = (0..candidates.len()).collect::<Vec<_>>();
+                indices
+                    .choose_weighted(&mut rng, |index| candidates[*index].1)
+                    // All weights are zero if no peer has been measured yet
+                    .map(|index| *index)
+                    .unwrap_or_else(|_| rng.gen_range(0, candidates.len()))
+            };
+            chosen_peers.push(candidates.swap_remove(index).0);
+        }
+        chosen_peers
+    }
+
     /// Returns the number of in-flight priority polls
     pub fn num_in_flight_priority_polls(&self) -> u64 {
         self.in_flight_priority_polls.len() as u64
@@ -207,6 +352,14 @@ impl PeerStates {
         self.in_flight_priority_polls.contains(peer) || self.in_flight_regular_polls.contains(peer)
     }
 
+    /// Returns the score of the given peer (if it has one)
+    #[cfg(test)]
+    pub fn get_score(&self, peer: &PeerNetworkId) -> Option<f64> {
+        self.peer_to_state
+            .get(peer)
+            .map(|peer_state| peer_state.score)
+    }
+
     /// Marks an in-flight request as started for the specified peer
     pub fn new_in_flight_request(&mut self, peer: &PeerNetworkId) {
         // Get the current in-flight polls
@@ -276,6 +429,58 @@ impl PeerStates {
         false
     }
 
+    /// Returns the protocol version negotiated with the given peer (if any)
+    pub fn get_protocol_version(&self, peer: &PeerNetworkId) -> Option<u64> {
+        self.peer_to_state
+            .get(peer)
+            .and_then(|peer_state| peer_state.protocol_version)
+    }
+
+    /// Updates the protocol version negotiated with the given peer. A version
+    /// of `None` forces the protocol version to be renegotiated.
+    pub fn update_protocol_version(&mut self, peer: PeerNetworkId, protocol_version: Option<u64>) {
+        self.peer_to_state.entry(peer).or_default().protocol_version = protocol_version;
+    }
+
+    /// Returns true iff the request should be compressed when sent to the
+    /// given peer, i.e., compression is enabled, the request fetches data
+    /// and the peer has negotiated a protocol version that supports it.
+    pub fn should_compress_request(
+        &self,
+        peer: &PeerNetworkId,
+        request: &StorageServiceRequest,
+    ) -> bool {
+        self.data_client_config.use_compression
+            && (is_data_chunk_request(request) || request.is_data_subscription_request())
+            && self
+                .get_protocol_version(peer)
+                .map_or(false, |version| version >= COMPRESSION_PROTOCOL_VERSION)
+    }
+
+    /// Returns the protocol version to announce in requests sent to the given
+    /// peer (if any). Only peers that support rate limiting understand the
+    /// announcement, and they only send back errors known to that version.
+    pub fn get_request_protocol_version(&self, peer: &PeerNetworkId) -> Option<u64> {
+        self.get_protocol_version(peer)
+            .map(|version| min(version, CLIENT_PROTOCOL_VERSION))
+            .filter(|version| *version >= RATE_LIMITING_PROTOCOL_VERSION)
+    }
+
+    /// Marks the given peer as throttled (i.e., we should not send it any
+    /// requests) until the specified time.
+    pub fn throttle_peer(&mut self, peer: PeerNetworkId, throttled_until: Instant) {
+        self.peer_to_state.entry(peer).or_default().throttled_until = Some(throttled_until);
+    }
+
+    /// Returns true iff the given peer has asked us to back off and the
+    /// back-off has not yet expired at the given time.
+    pub fn is_throttled(&self, peer: &PeerNetworkId, now: Instant) -> bool {
+        self.peer_to_state
+            .get(peer)
+            .and_then(|peer_state| peer_state.throttled_until)
+            .map_or(false, |throttled_until| now < throttled_until)
+    }
+
     /// Updates the storage summary for the given peer
     pub fn update_summary(&mut self, peer: PeerNetworkId, summary: StorageServerSummary) {
         self.peer_to_state
@@ -2,17 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{AptosDataClient, AptosNetDataClient, DataSummaryPoller, Error};
-use crate::aptosnet::{poll_peer, state::calculate_optimal_chunk_sizes};
+use crate::aptosnet::{poll_peer, range::split_range, state::calculate_optimal_chunk_sizes};
 use aptos_config::{
     config::{AptosDataClientConfig, BaseConfig, RoleType, StorageServiceConfig},
     network_id::{NetworkId, PeerNetworkId},
 };
-use aptos_crypto::HashValue;
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{
     block_info::BlockInfo,
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
-    transaction::{TransactionListWithProof, Version},
+    proof::{
+        AccumulatorRangeProof, TransactionAccumulatorInternalNode, TransactionInfoListWithProof,
+    },
+    transaction::{
+        ExecutionStatus, Transaction, TransactionInfo, TransactionListWithProof, Version,
+    },
     PeerId,
 };
 use channel::{aptos_channel, message_queues::QueueStyle};
@@ -35,9 +40,10 @@ use storage_service_client::{StorageServiceClient, StorageServiceNetworkSender};
 use storage_service_server::network::{NetworkRequest, ResponseSender};
 use storage_service_types::{
     CompleteDataRange, DataSummary, NewTransactionOutputsWithProofRequest,
-    NewTransactionsWithProofRequest, ProtocolMetadata, StorageServerSummary, StorageServiceError,
-    StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
+    NewTransactionsWithProofRequest, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
+    StorageServiceError, StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    RATE_LIMITING_PROTOCOL_VERSION,
 };
 
 fn mock_ledger_info(version: Version) -> LedgerInfoWithSignatures {
@@ -239,25 +245,37 @@ async fn request_works_only_when_data_available() {
     // Let the poller finish processing the response
     tokio::task::yield_now().await;
 
-    // Handle the client's transactions request
+    // Handle the poller's protocol version request and the client's transactions request
     tokio::spawn(async move {
-        let (peer, protocol, request, response_sender) = mock_network.next_request().await.unwrap();
-
-        assert_eq!(peer, expected_peer.peer_id());
-        assert_eq!(protocol, ProtocolId::StorageServiceRpc);
-        assert_matches!(
-            request,
-            StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
-                start_version: 50,
-                end_version: 100,
-                proof_version: 100,
-                include_events: false,
-            })
-        );
+        while let Some((peer, protocol, request, response_sender)) =
+            mock_network.next_request().await
+        {
+            assert_eq!(peer, expected_peer.peer_id());
+            assert_eq!(protocol, ProtocolId::StorageServiceRpc);
+
+            let (_, request) = request.split_protocol_version();
+            if request == StorageServiceRequest::GetServerProtocolVersion {
+                response_sender.send(Ok(StorageServiceResponse::ServerProtocolVersion(
+                    ServerProtocolVersion {
+                        protocol_version: RATE_LIMITING_PROTOCOL_VERSION,
+                    },
+                )));
+                continue;
+            }
 
-        response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
-            TransactionListWithProof::new_empty(),
-        )));
+            assert_matches!(
+                request,
+                StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                    start_version: 50,
+                    end_version: 100,
+                    proof_version: 100,
+                    include_events: false,
+                })
+            );
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                TransactionListWithProof::new_empty(),
+            )));
+        }
     });
 
     // The client's request should succeed since a peer finally has advertised
@@ -1019,6 +1037,7 @@ async fn bad_peer_is_eventually_added_back() {
     tokio::spawn(poller.start_poller());
     tokio::spawn(async move {
         while let Some((_, _, request, response_sender)) = mock_network.next_request().await {
+            let (_, request) = request.split_protocol_version();
             match request {
                 StorageServiceRequest::GetTransactionsWithProof(_) => {
                     response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
@@ -1028,6 +1047,11 @@ async fn bad_peer_is_eventually_added_back() {
                 StorageServiceRequest::GetStorageServerSummary => response_sender.send(Ok(
                     StorageServiceResponse::StorageServerSummary(mock_storage_summary(200)),
                 )),
+                StorageServiceRequest::GetServerProtocolVersion => response_sender.send(Ok(
+                    StorageServiceResponse::ServerProtocolVersion(ServerProtocolVersion {
+                        protocol_version: RATE_LIMITING_PROTOCOL_VERSION,
+                    }),
+                )),
                 _ => panic!("unexpected: {:?}", request),
             }
         }
@@ -1081,6 +1105,49 @@ async fn bad_peer_is_eventually_added_back() {
         .contains(&CompleteDataRange::new(0, 200).unwrap()));
 }
 
+#[tokio::test]
+async fn throttled_peer_is_backed_off_but_not_banned() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, mock_time, client, _) = MockNetwork::new(None, None, None);
+
+    // Add a priority peer that advertises txns 0 -> 200
+    let peer = mock_network.add_peer(true);
+    client.update_summary(peer, mock_storage_summary(200));
+    client.update_global_summary_cache();
+
+    // Spawn a handler for the peer that always rate limits our requests
+    tokio::spawn(async move {
+        while let Some((_, _, _, response_sender)) = mock_network.next_request().await {
+            response_sender.send(Err(StorageServiceError::TooManyRequests("".to_string())));
+        }
+    });
+
+    // Send many requests to the peer and back off after each one
+    let backoff = Duration::from_millis(AptosDataClientConfig::default().throttled_peer_backoff_ms);
+    for _ in 0..20 {
+        // The peer should reject the request as too many
+        let result = client
+            .get_transactions_with_proof(200, 200, 200, false)
+            .await;
+        assert_matches!(result, Err(Error::TooManyRequests(_)));
+
+        // The peer should not be selected until the back-off expires
+        let result = client
+            .get_transactions_with_proof(200, 200, 200, false)
+            .await;
+        assert_matches!(result, Err(Error::DataIsUnavailable(_)));
+        mock_time.advance_async(backoff).await;
+    }
+
+    // The peer should not have been penalized, so its advertisement remains
+    client.update_global_summary_cache();
+    let global_summary = client.get_global_data_summary();
+    assert!(global_summary
+        .advertised_data
+        .transactions
+        .contains(&CompleteDataRange::new(0, 200).unwrap()));
+}
+
 #[tokio::test]
 async fn optimal_chunk_size_calculations() {
     // Create a test storage service config
@@ -1089,11 +1156,19 @@ async fn optimal_chunk_size_calculations() {
     let max_transaction_chunk_size = 700;
     let max_transaction_output_chunk_size = 800;
     let storage_service_config = StorageServiceConfig {
+        enable_request_rate_limiting: false,
         max_account_states_chunk_sizes,
         max_concurrent_requests: 0,
         max_epoch_chunk_size,
         max_lru_cache_size: 0,
         max_network_channel_size: 0,
+        max_network_chunk_bytes: 0,
+        max_priority_peer_requests_per_sec: 0,
+        max_priority_peer_response_bytes_per_sec: 0,
+        max_public_network_requests_per_sec: 0,
+        max_public_network_response_bytes_per_sec: 0,
+        max_public_peer_requests_per_sec: 0,
+        max_public_peer_response_bytes_per_sec: 0,
         max_subscription_period_ms: 0,
         max_transaction_chunk_size,
         max_transaction_output_chunk_size,
@@ -1150,6 +1225,348 @@ async fn optimal_chunk_size_calculations() {
     assert_eq!(400, optimal_chunk_sizes.transaction_output_chunk_size);
 }
 
+#[tokio::test]
+async fn fast_peers_are_preferred() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, None, None);
+
+    // Add two priority peers that advertise the same data
+    let fast_peer = mock_network.add_peer(true);
+    let slow_peer = mock_network.add_peer(true);
+    for peer in [fast_peer, slow_peer] {
+        client.update_summary(peer, mock_storage_summary(200));
+    }
+
+    // Record the responses of the peers: the slow peer takes 100x longer
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        start_version: 0,
+        end_version: 100,
+        proof_version: 200,
+        include_events: false,
+    });
+    for (peer, latency) in [(fast_peer, 10), (slow_peer, 1_000)] {
+        client.peer_states.write().update_response_metrics(
+            peer,
+            &request,
+            Duration::from_millis(latency),
+            10_000,
+        );
+    }
+
+    // Verify the fast peer is (almost) always chosen, but the slow peer is
+    // still explored every now and then.
+    let num_requests = 1_000;
+    let mut num_slow_peer_requests = 0;
+    for _ in 0..num_requests {
+        if client.choose_peer_for_request(&request).unwrap() == slow_peer {
+            num_slow_peer_requests += 1;
+        }
+    }
+    assert!(num_slow_peer_requests > 0);
+    assert!(num_slow_peer_requests < num_requests / 5);
+}
+
+#[tokio::test]
+async fn parallel_range_requests() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, client, _) = create_parallel_range_request_client(3);
+
+    // Respond to each chunk request with exactly the requested chunk
+    tokio::spawn(async move {
+        let mut peers = vec![];
+        for _ in 0..3 {
+            let (peer, _, request, response_sender) = mock_network.next_request().await.unwrap();
+            assert!(!peers.contains(&peer));
+            peers.push(peer);
+
+            let (start_version, end_version) = get_transactions_request_range(request);
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                mock_transaction_list(start_version, end_version, 0),
+            )));
+        }
+    });
+
+    // Verify the chunks are merged into a single response for the whole range,
+    // which is proven against the ledger.
+    let response = client
+        .get_transactions_with_proof(200, 0, 89, false)
+        .await
+        .unwrap();
+    let transaction_list = response.payload;
+    assert_eq!(transaction_list.first_transaction_version, Some(0));
+    assert_eq!(transaction_list.transactions.len(), 90);
+    transaction_list
+        .verify(&mock_ledger_info_with_root_hash(0), Some(0))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn parallel_range_requests_with_truncated_chunks() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, client, _) = create_parallel_range_request_client(3);
+
+    // Respond to each request with at most 20 items, so that the chunks
+    // (of 30 items each) are truncated.
+    let max_response_size = 20;
+    let num_requests = tokio::spawn(async move {
+        let mut requested_ranges: Vec<(PeerNetworkId, Version, Version)> = vec![];
+        while let Some((peer_id, _, request, response_sender)) = mock_network.next_request().await {
+            // Verify the remainder of a chunk is requested from the same peer
+            let (start_version, end_version) = get_transactions_request_range(request);
+            let peer = PeerNetworkId::new(NetworkId::Validator, peer_id);
+            if let Some((previous_peer, _, _)) = requested_ranges
+                .iter()
+                .find(|(_, _, previous_end)| *previous_end == end_version)
+            {
+                assert_eq!(*previous_peer, peer);
+            }
+            requested_ranges.push((peer, start_version, end_version));
+
+            let end_version = std::cmp::min(end_version, start_version + max_response_size - 1);
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                mock_transaction_list(start_version, end_version, 0),
+            )));
+        }
+        requested_ranges.len()
+    });
+
+    // Verify the remainders are requested and the whole range is returned
+    let (context, transaction_list) = client
+        .get_transactions_with_proof(200, 0, 89, false)
+        .await
+        .unwrap()
+        .into_parts();
+    assert_eq!(transaction_list.transactions.len(), 90);
+    transaction_list
+        .verify(&mock_ledger_info_with_root_hash(0), Some(0))
+        .unwrap();
+
+    // Verify each chunk needs two requests (the network is closed once the
+    // client and the response callbacks are dropped).
+    drop((context, client));
+    assert_eq!(num_requests.await.unwrap(), 6);
+}
+
+#[tokio::test]
+async fn parallel_range_requests_blame_bad_peer_only() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, client, peers) = create_parallel_range_request_client(3);
+
+    // Respond to the first chunk request with transaction infos that don't
+    // match the transactions, and to the others with the requested chunks.
+    let bad_peer = tokio::spawn(async move {
+        let mut bad_peer = None;
+        while let Some((peer_id, _, request, response_sender)) = mock_network.next_request().await {
+            let (start_version, end_version) = get_transactions_request_range(request);
+            let mut transaction_list = mock_transaction_list(start_version, end_version, 0);
+            if bad_peer.is_none() {
+                bad_peer = Some(PeerNetworkId::new(NetworkId::Validator, peer_id));
+                transaction_list.proof.transaction_infos[0] =
+                    TransactionInfo::new_placeholder(0, ExecutionStatus::Success);
+            }
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                transaction_list,
+            )));
+        }
+        bad_peer.unwrap()
+    });
+
+    // Verify the request fails and only the bad peer is penalized
+    assert_err!(client.get_transactions_with_proof(200, 0, 89, false).await);
+    let peer_states = client.peer_states.clone();
+    drop(client);
+    let bad_peer = bad_peer.await.unwrap();
+    for peer in peers {
+        let score = peer_states.read().get_score(&peer);
+        if peer == bad_peer {
+            assert!(score.unwrap() < 50.0);
+        } else {
+            assert!(score.map_or(true, |score| score >= 50.0));
+        }
+    }
+}
+
+#[tokio::test]
+async fn parallel_range_requests_with_conflicting_proofs() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, client, _) = create_parallel_range_request_client(3);
+
+    // Respond to the first chunk request with a chunk of a different ledger,
+    // and to all other requests with chunks of the expected ledger.
+    let requests = tokio::spawn(async move {
+        let mut requested_ranges = vec![];
+        while let Some((_, _, request, response_sender)) = mock_network.next_request().await {
+            let (start_version, end_version) = get_transactions_request_range(request);
+            let ledger = if requested_ranges.is_empty() { 1 } else { 0 };
+            requested_ranges.push((start_version, end_version));
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                mock_transaction_list(start_version, end_version, ledger),
+            )));
+        }
+        requested_ranges
+    });
+
+    // Verify the whole range is requested from a single peer once the
+    // conflicting chunks are detected.
+    let (context, transaction_list) = client
+        .get_transactions_with_proof(200, 0, 89, false)
+        .await
+        .unwrap()
+        .into_parts();
+    transaction_list
+        .verify(&mock_ledger_info_with_root_hash(0), Some(0))
+        .unwrap();
+    drop((context, client));
+    let requested_ranges = requests.await.unwrap();
+    assert_eq!(requested_ranges.len(), 4);
+    assert_eq!(requested_ranges[3], (0, 89));
+}
+
+#[tokio::test]
+async fn range_requests_with_truncated_responses() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, None, None);
+    let peer = mock_network.add_peer(true);
+    client.update_summary(peer, mock_storage_summary(200));
+
+    // Respond to each request with at most 20 items
+    let max_response_size = 20;
+    let requests = tokio::spawn(async move {
+        let mut requested_ranges = vec![];
+        while let Some((_, _, request, response_sender)) = mock_network.next_request().await {
+            let (start_version, end_version) = get_transactions_request_range(request);
+            requested_ranges.push((start_version, end_version));
+
+            let end_version = std::cmp::min(end_version, start_version + max_response_size - 1);
+            response_sender.send(Ok(StorageServiceResponse::TransactionsWithProof(
+                mock_transaction_list(start_version, end_version, 0),
+            )));
+        }
+        requested_ranges
+    });
+
+    // Verify the remainders are requested and the whole range is returned
+    let (context, transaction_list) = client
+        .get_transactions_with_proof(200, 0, 49, false)
+        .await
+        .unwrap()
+        .into_parts();
+    assert_eq!(transaction_list.transactions.len(), 50);
+    transaction_list
+        .verify(&mock_ledger_info_with_root_hash(0), Some(0))
+        .unwrap();
+    drop((context, client));
+    assert_eq!(requests.await.unwrap(), vec![(0, 49), (20, 49), (40, 49)]);
+}
+
+#[tokio::test]
+async fn compression_is_negotiated() {
+    ::aptos_logger::Logger::init_for_testing();
+
+    // Only peers that support compression should receive compressed requests,
+    // and only peers that support rate limiting should receive our version.
+    for (protocol_version, expect_compression, expected_request_protocol_version) in [
+        (1, false, None),
+        (2, true, None),
+        (3, true, Some(RATE_LIMITING_PROTOCOL_VERSION)),
+    ] {
+        // Create a data client that requests compressed responses
+        let data_client_config = AptosDataClientConfig {
+            use_compression: true,
+            ..Default::default()
+        };
+        let (mut mock_network, mock_time, client, poller) =
+            MockNetwork::new(None, Some(data_client_config), None);
+        tokio::spawn(poller.start_poller());
+
+        // Add a connected peer
+        let expected_peer = mock_network.add_peer(true);
+
+        // Advance time so the poller sends a data summary request
+        tokio::task::yield_now().await;
+        mock_time.advance_async(Duration::from_millis(1_000)).await;
+
+        // Handle the data summary request
+        let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+        assert_matches!(request, StorageServiceRequest::GetStorageServerSummary);
+        response_sender.send(Ok(StorageServiceResponse::StorageServerSummary(
+            mock_storage_summary(200),
+        )));
+
+        // Handle the protocol version request
+        let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+        assert_matches!(request, StorageServiceRequest::GetServerProtocolVersion);
+        response_sender.send(Ok(StorageServiceResponse::ServerProtocolVersion(
+            ServerProtocolVersion { protocol_version },
+        )));
+
+        // Wait for the poller to store the protocol version
+        while client
+            .peer_states
+            .read()
+            .get_protocol_version(&expected_peer)
+            .is_none()
+        {
+            tokio::task::yield_now().await;
+        }
+
+        // Handle the client's transactions request
+        tokio::spawn(async move {
+            let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+            let (request_protocol_version, request) = request.split_protocol_version();
+            assert_eq!(request_protocol_version, expected_request_protocol_version);
+            assert_eq!(request.is_compressed(), expect_compression);
+            assert_matches!(
+                request.into_uncompressed(),
+                StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+                    start_version: 50,
+                    end_version: 100,
+                    proof_version: 100,
+                    include_events: false,
+                })
+            );
+
+            let response =
+                StorageServiceResponse::TransactionsWithProof(mock_transaction_list(50, 100, 0));
+            let response = if expect_compression {
+                response.compress().unwrap()
+            } else {
+                response
+            };
+            response_sender.send(Ok(response));
+        });
+
+        // Verify the client receives the decompressed response
+        let response = client
+            .get_transactions_with_proof(100, 50, 100, false)
+            .await
+            .unwrap();
+        assert_eq!(response.payload, mock_transaction_list(50, 100, 0));
+    }
+}
+
+#[test]
+fn split_range_chunks() {
+    // Ranges that are too small aren't split
+    assert_eq!(split_range(0, 9, 4, 10), vec![(0, 9)]);
+    assert_eq!(split_range(5, 24, 4, 10), vec![(5, 14), (15, 24)]);
+
+    // Chunks are of (almost) equal size and cover the whole range
+    assert_eq!(
+        split_range(0, 9, 4, 1),
+        vec![(0, 1), (2, 4), (5, 6), (7, 9)]
+    );
+    assert_eq!(split_range(100, 102, 1, 1), vec![(100, 102)]);
+
+    // Invalid ranges and extreme values are handled
+    assert_eq!(split_range(10, 5, 4, 1), vec![(10, 5)]);
+    assert_eq!(split_range(0, 9, 0, 0), vec![(0, 9)]);
+    assert_eq!(
+        split_range(0, u64::MAX, 2, 1),
+        vec![(0, u64::MAX / 2), (u64::MAX / 2 + 1, u64::MAX)]
+    );
+}
+
 /// A helper method that fetches peers to poll depending on the peer priority
 fn fetch_peer_to_poll(
     client: AptosNetDataClient,
@@ -1178,3 +1595,119 @@ fn get_num_in_flight_polls(client: AptosNetDataClient, is_priority_peer: bool) -
         client.peer_states.read().num_in_flight_regular_polls()
     }
 }
+
+/// Creates a data client that splits range requests across the given number
+/// of priority peers, which advertise the same data. Returns the peers too.
+fn create_parallel_range_request_client(
+    num_peers: u64,
+) -> (MockNetwork, AptosNetDataClient, Vec<PeerNetworkId>) {
+    let data_client_config = AptosDataClientConfig {
+        max_parallel_range_requests: num_peers,
+        min_parallel_range_chunk_size: 10,
+        ..Default::default()
+    };
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, Some(data_client_config), None);
+    let peers = (0..num_peers)
+        .map(|_| {
+            let peer = mock_network.add_peer(true);
+            client.update_summary(peer, mock_storage_summary(200));
+            peer
+        })
+        .collect();
+    (mock_network, client, peers)
+}
+
+/// Returns the range of the given transactions request
+fn get_transactions_request_range(request: StorageServiceRequest) -> (Version, Version) {
+    match request {
+        StorageServiceRequest::GetTransactionsWithProof(request) => {
+            assert_eq!(request.proof_version, 200);
+            (request.start_version, request.end_version)
+        }
+        request => panic!("Unexpected request: {:?}", request),
+    }
+}
+
+/// The number of transactions in the mock ledgers
+const MOCK_LEDGER_SIZE: u64 = 128;
+
+/// Returns the transaction info at the given version of the given mock ledger.
+/// The transaction infos of different versions and ledgers are distinct.
+fn mock_transaction_info(version: Version, ledger: u64) -> TransactionInfo {
+    TransactionInfo::new(
+        Transaction::StateCheckpoint.hash(),
+        HashValue::zero(),
+        HashValue::zero(),
+        Some(HashValue::zero()),
+        ledger * MOCK_LEDGER_SIZE + version,
+        ExecutionStatus::Success,
+    )
+}
+
+/// Returns the levels of the transaction accumulator of the given mock ledger,
+/// from the leaves up to the root.
+fn mock_accumulator_levels(ledger: u64) -> Vec<Vec<HashValue>> {
+    let mut levels = vec![(0..MOCK_LEDGER_SIZE)
+        .map(|version| mock_transaction_info(version, ledger).hash())
+        .collect::<Vec<_>>()];
+    while levels.last().unwrap().len() > 1 {
+        let parents = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|children| {
+                TransactionAccumulatorInternalNode::new(children[0], children[1]).hash()
+            })
+            .collect();
+        levels.push(parents);
+    }
+    levels
+}
+
+/// Returns a ledger info that carries the root hash of the given mock ledger
+fn mock_ledger_info_with_root_hash(ledger: u64) -> LedgerInfo {
+    let root_hash = mock_accumulator_levels(ledger).last().unwrap()[0];
+    LedgerInfo::new(
+        BlockInfo::new(0, 0, HashValue::zero(), root_hash, 0, 0, None),
+        HashValue::zero(),
+    )
+}
+
+/// Creates a transaction list for the given range of the given mock ledger,
+/// with a range proof for the ledger's transaction accumulator.
+fn mock_transaction_list(
+    start_version: Version,
+    end_version: Version,
+    ledger: u64,
+) -> TransactionListWithProof {
+    // Collect the siblings of the range at each level of the accumulator,
+    // from the leaves up to the root.
+    let levels = mock_accumulator_levels(ledger);
+    let mut left_siblings = vec![];
+    let mut right_siblings = vec![];
+    for (level, hashes) in levels.iter().enumerate().take(levels.len() - 1) {
+        let first_position = (start_version >> level) as usize;
+        let last_position = (end_version >> level) as usize;
+        if first_position % 2 == 1 {
+            left_siblings.push(hashes[first_position - 1]);
+        }
+        if last_position % 2 == 0 {
+            right_siblings.push(hashes[last_position + 1]);
+        }
+    }
+
+    let transaction_infos = (start_version..=end_version)
+        .map(|version| mock_transaction_info(version, ledger))
+        .collect::<Vec<_>>();
+    let num_transactions = transaction_infos.len();
+    let proof = TransactionInfoListWithProof::new(
+        AccumulatorRangeProof::new(left_siblings, right_siblings),
+        transaction_infos,
+    );
+    TransactionListWithProof::new(
+        vec![Transaction::StateCheckpoint; num_transactions],
+        None,
+        Some(start_version),
+        proof,
+    )
+}
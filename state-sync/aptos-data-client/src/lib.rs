@@ -36,6 +36,8 @@ pub enum Error {
     InvalidResponse(String),
     #[error("Timed out waiting for a response: {0}")]
     TimeoutWaitingForResponse(String),
+    #[error("The peer is throttling our requests: {0}")]
+    TooManyRequests(String),
     #[error("Unexpected error encountered: {0}")]
     UnexpectedErrorEncountered(String),
 }
@@ -49,6 +51,7 @@ impl Error {
             Self::InvalidRequest(_) => "invalid_request",
             Self::InvalidResponse(_) => "invalid_response",
             Self::TimeoutWaitingForResponse(_) => "timeout_waiting_for_response",
+            Self::TooManyRequests(_) => "too_many_requests",
             Self::UnexpectedErrorEncountered(_) => "unexpected_error_encountered",
         }
     }
@@ -119,6 +119,42 @@ impl VerifiedEpochStates {
         self.verify_waypoint(epoch_ending_ledger_info, waypoint)
     }
 
+    /// Verifies the given epoch ending ledger info against the waypoint (instead
+    /// of the latest epoch state) and, if it corresponds to the waypoint,
+    /// updates our latest trusted epoch state. This is used when bootstrapping
+    /// from the waypoint, so any ledger infos before the waypoint are skipped.
+    pub fn verify_waypoint_ledger_info(
+        &mut self,
+        epoch_ending_ledger_info: &LedgerInfoWithSignatures,
+        waypoint: &Waypoint,
+    ) -> Result<(), Error> {
+        // Skip any ledger infos before the waypoint
+        let ledger_info = epoch_ending_ledger_info.ledger_info();
+        if ledger_info.version() < waypoint.version() {
+            trace!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                "Skipping the epoch ending ledger info before the waypoint: {:?}",
+                ledger_info
+            )));
+            return Ok(());
+        }
+
+        // Verify the ledger info corresponds to the trusted waypoint (i.e., we
+        // haven't missed the waypoint and the ledger info hash matches).
+        self.verify_waypoint(epoch_ending_ledger_info, waypoint)?;
+
+        // Update the latest epoch state with the next epoch
+        if let Some(next_epoch_state) = ledger_info.next_epoch_state() {
+            self.highest_fetched_epoch_ending_version = ledger_info.version();
+            self.latest_epoch_state = next_epoch_state.clone();
+            self.insert_new_epoch_ending_ledger_info(epoch_ending_ledger_info.clone());
+            Ok(())
+        } else {
+            Err(Error::VerificationError(
+                "The waypoint ledger info was not epoch ending!".into(),
+            ))
+        }
+    }
+
     /// Attempts to verify the waypoint using the new epoch ending ledger info
     fn verify_waypoint(
         &mut self,
@@ -227,7 +263,6 @@ impl VerifiedEpochStates {
     }
 }
 
-// TODO(joshlind): persist the index (e.g., in case we crash mid-download)?
 /// A simple container to manage state related to account state snapshot syncing
 struct AccountStateSyncer {
     // Whether or not a state snapshot receiver has been initialized
@@ -247,6 +282,10 @@ struct AccountStateSyncer {
     // processed -- i.e., sent to the storage synchronizer).
     next_account_index_to_process: u64,
 
+    // Whether or not we're resuming a state snapshot sync that was interrupted
+    // (e.g., by a crash or node upgrade) using the progress persisted in storage.
+    resumed_from_storage: bool,
+
     // The transaction output (inc. info and proof) for the version we're syncing
     transaction_output_to_sync: Option<TransactionOutputListWithProof>,
 }
@@ -259,6 +298,7 @@ impl AccountStateSyncer {
             ledger_info_to_sync: None,
             next_account_index_to_commit: 0,
             next_account_index_to_process: 0,
+            resumed_from_storage: false,
             transaction_output_to_sync: None,
         }
     }
@@ -414,6 +454,28 @@ impl<
             || !self.verified_epoch_states.verified_waypoint()
     }
 
+    /// Returns true iff the bootstrapper should skip all epoch ending ledger
+    /// infos before the waypoint (and trust the waypoint ledger info).
+    fn should_bootstrap_from_waypoint(&self) -> bool {
+        matches!(
+            self.driver_configuration.config.bootstrapping_mode,
+            BootstrappingMode::ApplyTransactionOutputsFromWaypoint
+        ) && !self.verified_epoch_states.verified_waypoint()
+    }
+
+    /// Returns true iff the bootstrapper is currently downloading account states
+    /// (i.e., a state snapshot) from the network.
+    fn is_downloading_account_states(&self) -> bool {
+        match self.driver_configuration.config.bootstrapping_mode {
+            BootstrappingMode::DownloadLatestAccountStates => true,
+            BootstrappingMode::ApplyTransactionOutputsFromWaypoint => {
+                self.account_state_syncer.ledger_info_to_sync.is_some()
+                    && !self.account_state_syncer.is_sync_complete
+            }
+            _ => false,
+        }
+    }
+
     /// Initializes an active data stream so that we can begin to process notifications
     async fn initialize_active_data_stream(
         &mut self,
@@ -444,9 +506,39 @@ impl<
                 {
                     return self.bootstrapping_complete();
                 }
-                self.fetch_all_account_states(highest_known_ledger_info)
+                self.fetch_all_account_states(global_data_summary, highest_known_ledger_info)
                     .await
             }
+            BootstrappingMode::ApplyTransactionOutputsFromWaypoint => {
+                if highest_synced_version >= highest_known_ledger_version {
+                    return self.bootstrapping_complete();
+                }
+
+                // Download the account states at the waypoint (if we haven't
+                // already synced beyond it), before applying any outputs.
+                let waypoint_version = self.driver_configuration.waypoint.version();
+                if highest_synced_version < waypoint_version
+                    && !self.account_state_syncer.is_sync_complete
+                {
+                    let waypoint_ledger_info = self
+                        .verified_epoch_states
+                        .get_epoch_ending_ledger_info(waypoint_version)
+                        .ok_or_else(|| {
+                            Error::UnexpectedError(format!(
+                                "The waypoint ledger info was not found! Waypoint version: {:?}",
+                                waypoint_version
+                            ))
+                        })?;
+                    return self
+                        .fetch_all_account_states(global_data_summary, waypoint_ledger_info)
+                        .await;
+                }
+                self.fetch_missing_transaction_data(
+                    highest_synced_version,
+                    highest_known_ledger_info,
+                )
+                .await
+            }
             _ => {
                 if highest_synced_version >= highest_known_ledger_version {
                     return self.bootstrapping_complete();
@@ -533,38 +625,44 @@ impl<
     /// Fetches all account states (as required to bootstrap the node)
     async fn fetch_all_account_states(
         &mut self,
+        global_data_summary: &GlobalDataSummary,
         highest_known_ledger_info: LedgerInfoWithSignatures,
     ) -> Result<(), Error> {
         // Verify we're trying to sync to an unchanging ledger info
-        if let Some(ledger_info_to_sync) = &self.account_state_syncer.ledger_info_to_sync {
-            if ledger_info_to_sync != &highest_known_ledger_info {
-                panic!(
-                    "Mismatch in ledger info to sync! Highest: {:?}, target: {:?}",
-                    highest_known_ledger_info, ledger_info_to_sync
-                );
-            }
-        } else {
-            self.account_state_syncer.ledger_info_to_sync = Some(highest_known_ledger_info.clone());
-        }
+        let ledger_info_to_sync =
+            if let Some(ledger_info_to_sync) = &self.account_state_syncer.ledger_info_to_sync {
+                if !self.account_state_syncer.resumed_from_storage
+                    && ledger_info_to_sync != &highest_known_ledger_info
+                {
+                    panic!(
+                        "Mismatch in ledger info to sync! Highest: {:?}, target: {:?}",
+                        highest_known_ledger_info, ledger_info_to_sync
+                    );
+                }
+                ledger_info_to_sync.clone()
+            } else {
+                // Resume any interrupted state snapshot sync, otherwise sync to the highest ledger info
+                let ledger_info_to_sync = self
+                    .resume_account_states_sync(global_data_summary)?
+                    .unwrap_or(highest_known_ledger_info);
+                self.account_state_syncer.ledger_info_to_sync = Some(ledger_info_to_sync.clone());
+                ledger_info_to_sync
+            };
 
         // Fetch the transaction info first, before the account states
-        let highest_known_ledger_version = highest_known_ledger_info.ledger_info().version();
+        let version_to_sync = ledger_info_to_sync.ledger_info().version();
         let data_stream = if self
             .account_state_syncer
             .transaction_output_to_sync
             .is_none()
         {
             self.streaming_client
-                .get_all_transaction_outputs(
-                    highest_known_ledger_version,
-                    highest_known_ledger_version,
-                    highest_known_ledger_version,
-                )
+                .get_all_transaction_outputs(version_to_sync, version_to_sync, version_to_sync)
                 .await?
         } else {
             let start_account_index = Some(self.account_state_syncer.next_account_index_to_commit);
             self.streaming_client
-                .get_all_accounts(highest_known_ledger_version, start_account_index)
+                .get_all_accounts(version_to_sync, start_account_index)
                 .await?
         };
         self.active_data_stream = Some(data_stream);
@@ -572,6 +670,70 @@ impl<
         Ok(())
     }
 
+    /// Checks storage for the progress of a previously interrupted state
+    /// snapshot sync. If the progress is found, the snapshot version has been
+    /// verified and the account states at that version are still advertised by
+    /// peers, the account indices are updated to resume from the next account
+    /// and the ledger info to sync is returned.
+    fn resume_account_states_sync(
+        &mut self,
+        global_data_summary: &GlobalDataSummary,
+    ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
+        let progress = match self
+            .storage
+            .get_state_snapshot_progress()
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to get the state snapshot progress! Error: {:?}",
+                    error
+                ))
+            })? {
+            Some(progress) => progress,
+            None => return Ok(None),
+        };
+
+        // Verify the snapshot version is a verified epoch ending version
+        let ledger_info_to_sync = match self
+            .verified_epoch_states
+            .get_epoch_ending_ledger_info(progress.version)
+        {
+            Some(ledger_info_to_sync) => ledger_info_to_sync,
+            None => {
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "Unable to resume the state snapshot sync at version: {:?}. \
+                    The version is not a verified epoch ending version.",
+                    progress.version
+                )));
+                return Ok(None);
+            }
+        };
+
+        // Verify the account states at the snapshot version are still served
+        let account_states_advertised = global_data_summary
+            .advertised_data
+            .account_states
+            .iter()
+            .any(|account_states| account_states.contains(progress.version));
+        if !account_states_advertised {
+            info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                "Unable to resume the state snapshot sync at version: {:?}. \
+                The account states are no longer advertised by peers.",
+                progress.version
+            )));
+            return Ok(None);
+        }
+
+        info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+            "Resuming the state snapshot sync at version: {:?}, from account index: {:?}",
+            progress.version, progress.num_leaves
+        )));
+        self.account_state_syncer.next_account_index_to_commit = progress.num_leaves;
+        self.account_state_syncer.next_account_index_to_process = progress.num_leaves;
+        self.account_state_syncer.resumed_from_storage = true;
+
+        Ok(Some(ledger_info_to_sync))
+    }
+
     /// Fetches all missing transaction data in order to bootstrap the node
     async fn fetch_missing_transaction_data(
         &mut self,
@@ -587,7 +749,8 @@ impl<
             .next_epoch_ending_version(highest_synced_version)
             .expect("No higher epoch ending version known!");
         let data_stream = match self.driver_configuration.config.bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::ApplyTransactionOutputsFromWaypoint => {
                 self.streaming_client
                     .get_all_transaction_outputs(
                         next_version,
@@ -668,9 +831,17 @@ impl<
                 "Found higher epoch ending ledger infos in the network! Local: {:?}, advertised: {:?}",
                    highest_local_epoch_end, highest_advertised_epoch_end
             )));
-            let next_epoch_end = highest_local_epoch_end.checked_add(1).ok_or_else(|| {
+            let mut next_epoch_end = highest_local_epoch_end.checked_add(1).ok_or_else(|| {
                 Error::IntegerOverflow("The next epoch end has overflown!".into())
             })?;
+            if self.should_bootstrap_from_waypoint() {
+                // Start the stream at the waypoint's epoch. If the configured
+                // epoch is too low, the extra ledger infos before the waypoint
+                // are skipped. If it's too high, the waypoint is missed and
+                // verification fails.
+                let waypoint_epoch = self.driver_configuration.config.waypoint_epoch;
+                next_epoch_end = next_epoch_end.max(waypoint_epoch);
+            }
             let epoch_ending_stream = self
                 .streaming_client
                 .get_all_epoch_ending_ledger_infos(next_epoch_end)
@@ -789,13 +960,7 @@ impl<
         account_state_chunk_with_proof: StateValueChunkWithProof,
     ) -> Result<(), Error> {
         // Verify that we're expecting account payloads
-        let bootstrapping_mode = self.driver_configuration.config.bootstrapping_mode;
-        if self.should_fetch_epoch_ending_ledger_infos()
-            || !matches!(
-                bootstrapping_mode,
-                BootstrappingMode::DownloadLatestAccountStates
-            )
-        {
+        if self.should_fetch_epoch_ending_ledger_infos() || !self.is_downloading_account_states() {
             self.terminate_active_stream(notification_id, NotificationFeedback::InvalidPayloadData)
                 .await?;
             return Err(Error::InvalidPayload(
@@ -820,8 +985,16 @@ impl<
             .account_state_syncer
             .initialized_state_snapshot_receiver
         {
-            // Fetch all verified epoch change proofs
-            let epoch_change_proofs = self.verified_epoch_states.all_epoch_ending_ledger_infos();
+            // Fetch all verified epoch change proofs up to the target version. Any
+            // higher epoch ending ledger infos will be committed with the
+            // transaction outputs that follow the account states.
+            let target_version = ledger_info_to_sync.ledger_info().version();
+            let epoch_change_proofs = self
+                .verified_epoch_states
+                .all_epoch_ending_ledger_infos()
+                .into_iter()
+                .filter(|ledger_info| ledger_info.ledger_info().version() <= target_version)
+                .collect();
 
             // Initialize the account state synchronizer
             let _ = self.storage_synchronizer.initialize_account_synchronizer(
@@ -904,10 +1077,15 @@ impl<
         // Verify the epoch change proofs, update our latest epoch state and
         // verify our waypoint.
         for epoch_ending_ledger_info in epoch_ending_ledger_infos {
-            if let Err(error) = self.verified_epoch_states.verify_epoch_ending_ledger_info(
-                &epoch_ending_ledger_info,
-                &self.driver_configuration.waypoint,
-            ) {
+            let waypoint = &self.driver_configuration.waypoint;
+            let result = if self.should_bootstrap_from_waypoint() {
+                self.verified_epoch_states
+                    .verify_waypoint_ledger_info(&epoch_ending_ledger_info, waypoint)
+            } else {
+                self.verified_epoch_states
+                    .verify_epoch_ending_ledger_info(&epoch_ending_ledger_info, waypoint)
+            };
+            if let Err(error) = result {
                 self.terminate_active_stream(
                     notification_id,
                     NotificationFeedback::PayloadProofFailed,
@@ -934,13 +1112,11 @@ impl<
         // Verify that we're expecting transaction or output payloads
         let bootstrapping_mode = self.driver_configuration.config.bootstrapping_mode;
         if self.should_fetch_epoch_ending_ledger_infos()
-            || (matches!(
-                bootstrapping_mode,
-                BootstrappingMode::DownloadLatestAccountStates
-            ) && self
-                .account_state_syncer
-                .transaction_output_to_sync
-                .is_some())
+            || (self.is_downloading_account_states()
+                && self
+                    .account_state_syncer
+                    .transaction_output_to_sync
+                    .is_some())
         {
             self.terminate_active_stream(notification_id, NotificationFeedback::InvalidPayloadData)
                 .await?;
@@ -950,10 +1126,7 @@ impl<
         }
 
         // If we're account state syncing, we expect a single transaction info
-        if matches!(
-            bootstrapping_mode,
-            BootstrappingMode::DownloadLatestAccountStates
-        ) {
+        if self.is_downloading_account_states() {
             return self
                 .verify_transaction_info_to_sync(
                     notification_id,
@@ -990,7 +1163,8 @@ impl<
 
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs = match bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::ApplyTransactionOutputsFromWaypoint => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     let num_transaction_outputs = transaction_outputs_with_proof
                         .transactions_and_outputs
@@ -1161,7 +1335,8 @@ impl<
     ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         // Calculate the payload end version
         let num_versions = match self.driver_configuration.config.bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::ApplyTransactionOutputsFromWaypoint => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     transaction_outputs_with_proof
                         .transactions_and_outputs
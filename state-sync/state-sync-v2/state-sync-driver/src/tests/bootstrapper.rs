@@ -19,8 +19,10 @@ use crate::{
     },
 };
 use aptos_config::config::BootstrappingMode;
+use aptos_crypto::HashValue;
 use aptos_data_client::GlobalDataSummary;
 use aptos_types::{
+    state_store::state_value::StateSnapshotProgress,
     transaction::{TransactionOutputListWithProof, Version},
     waypoint::Waypoint,
 };
@@ -32,6 +34,99 @@ use data_streaming_service::{
 use futures::{channel::oneshot, FutureExt};
 use mockall::{predicate::eq, Sequence};
 use std::sync::Arc;
+use storage_service_types::CompleteDataRange;
+
+#[tokio::test]
+async fn test_bootstrap_from_waypoint() {
+    // Create a waypoint
+    let waypoint_version = 100;
+    let waypoint_epoch = 5;
+    let waypoint_ledger_info =
+        create_random_epoch_ending_ledger_info(waypoint_version, waypoint_epoch);
+
+    // Create a driver configuration that bootstraps from the waypoint
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode =
+        BootstrappingMode::ApplyTransactionOutputsFromWaypoint;
+    driver_configuration.config.waypoint_epoch = waypoint_epoch;
+    driver_configuration.waypoint = Waypoint::new_any(waypoint_ledger_info.ledger_info());
+
+    // Create the mock streaming client (the stream starts at the waypoint epoch)
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    let (_notification_sender_2, data_stream_listener_2) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_epoch_ending_ledger_infos()
+        .times(1)
+        .with(eq(waypoint_epoch))
+        .return_once(move |_| Ok(data_stream_listener_1));
+    let end_of_stream_id = 1;
+    mock_streaming_client
+        .expect_terminate_stream_with_feedback()
+        .with(eq(end_of_stream_id), eq(NotificationFeedback::EndOfStream))
+        .return_const(Ok(()));
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(waypoint_version),
+            eq(waypoint_version),
+            eq(waypoint_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener_2));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper(driver_configuration, mock_streaming_client, true);
+
+    // Create a global data summary up to the waypoint
+    let mut global_data_summary = create_global_summary(waypoint_epoch);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![waypoint_ledger_info.clone()];
+
+    // Drive progress to initialize the epoch ending data stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+
+    // Send an (unverifiable) ledger info before the waypoint and the waypoint
+    // ledger info along the stream, followed by the end of the stream.
+    let epoch_ending_ledger_infos = vec![
+        create_random_epoch_ending_ledger_info(waypoint_version - 1, waypoint_epoch - 1),
+        waypoint_ledger_info,
+    ];
+    for (notification_id, data_payload) in [
+        (
+            0,
+            DataPayload::EpochEndingLedgerInfos(epoch_ending_ledger_infos),
+        ),
+        (end_of_stream_id, DataPayload::EndOfStream),
+    ] {
+        let data_notification = DataNotification {
+            notification_id,
+            data_payload,
+        };
+        notification_sender_1.push((), data_notification).unwrap();
+    }
+
+    // Drive progress and verify the waypoint is verified (and earlier epochs skipped)
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+    let verified_epoch_states = bootstrapper.get_verified_epoch_states();
+    assert!(verified_epoch_states.verified_waypoint());
+    assert_none!(verified_epoch_states.get_epoch_ending_ledger_info(waypoint_version - 1));
+    assert!(verified_epoch_states
+        .get_epoch_ending_ledger_info(waypoint_version)
+        .is_some());
+
+    // Drive progress twice: once to finish fetching the epoch ending ledger
+    // infos and once to start downloading the account states at the waypoint.
+    for _ in 0..2 {
+        drive_progress(&mut bootstrapper, &global_data_summary, false)
+            .await
+            .unwrap();
+    }
+    assert!(!bootstrapper.is_bootstrapped());
+}
 
 #[tokio::test]
 async fn test_bootstrap_genesis_waypoint() {
@@ -257,6 +352,71 @@ async fn test_data_stream_accounts() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_data_stream_accounts_resume() {
+    for account_states_advertised in [true, false] {
+        // Create test data
+        let resume_version = 5000;
+        let highest_version = 10000;
+        let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 0);
+        let snapshot_progress = StateSnapshotProgress::new(
+            resume_version,
+            HashValue::random(),
+            HashValue::random(),
+            1234,
+        );
+
+        // Create a driver configuration with a genesis waypoint and account state syncing
+        let mut driver_configuration = create_full_node_driver_configuration();
+        driver_configuration.config.bootstrapping_mode =
+            BootstrappingMode::DownloadLatestAccountStates;
+
+        // Create the mock streaming client (the interrupted snapshot should
+        // only be resumed if the account states are still advertised).
+        let version_to_sync = if account_states_advertised {
+            resume_version
+        } else {
+            highest_version
+        };
+        let mut mock_streaming_client = create_mock_streaming_client();
+        let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+        mock_streaming_client
+            .expect_get_all_transaction_outputs()
+            .times(1)
+            .with(
+                eq(version_to_sync),
+                eq(version_to_sync),
+                eq(version_to_sync),
+            )
+            .return_once(move |_, _, _| Ok(data_stream_listener));
+
+        // Create the bootstrapper with the persisted snapshot progress
+        let mut bootstrapper = create_bootstrapper_with_snapshot_progress(
+            driver_configuration,
+            mock_streaming_client,
+            true,
+            Some(snapshot_progress),
+        );
+
+        // Insert the epoch ending ledger infos into the verified states of the bootstrapper
+        manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(resume_version));
+        manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+        // Create a global data summary
+        let mut global_data_summary = create_global_summary(1);
+        global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+        if account_states_advertised {
+            global_data_summary.advertised_data.account_states =
+                vec![CompleteDataRange::new(0, highest_version).unwrap()];
+        }
+
+        // Drive progress to initialize the account states stream
+        drive_progress(&mut bootstrapper, &global_data_summary, false)
+            .await
+            .unwrap();
+    }
+}
+
 #[tokio::test]
 async fn test_data_stream_transactions() {
     // Create test data
@@ -560,6 +720,22 @@ fn create_bootstrapper(
     driver_configuration: DriverConfiguration,
     mock_streaming_client: MockStreamingClient,
     expect_reset_executor: bool,
+) -> Bootstrapper<MockStorageSynchronizer, MockStreamingClient> {
+    create_bootstrapper_with_snapshot_progress(
+        driver_configuration,
+        mock_streaming_client,
+        expect_reset_executor,
+        None,
+    )
+}
+
+/// Creates a bootstrapper for testing with the given (persisted) state
+/// snapshot progress in storage
+fn create_bootstrapper_with_snapshot_progress(
+    driver_configuration: DriverConfiguration,
+    mock_streaming_client: MockStreamingClient,
+    expect_reset_executor: bool,
+    snapshot_progress: Option<StateSnapshotProgress>,
 ) -> Bootstrapper<MockStorageSynchronizer, MockStreamingClient> {
     // Initialize the logger for tests
     aptos_logger::Logger::init_for_testing();
@@ -575,6 +751,9 @@ fn create_bootstrapper(
     mock_database_reader
         .expect_get_latest_transaction_info_option()
         .returning(|| Ok(Some((0, create_transaction_info()))));
+    mock_database_reader
+        .expect_get_state_snapshot_progress()
+        .returning(move || Ok(snapshot_progress.clone()));
 
     Bootstrapper::new(
         driver_configuration,
@@ -20,7 +20,7 @@ use aptos_types::{
     state_proof::StateProof,
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateSnapshotProgress, StateValue, StateValueChunkWithProof},
     },
     transaction::{
         AccountTransactionsWithProof, TransactionInfo, TransactionListWithProof,
@@ -276,6 +276,8 @@ mock! {
             chunk_size: usize,
         ) -> Result<StateValueChunkWithProof>;
 
+        fn get_state_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>>;
+
         fn get_state_prune_window(&self) -> Result<Option<usize>>;
     }
 }
@@ -60,12 +60,30 @@ impl StorageServiceClient {
         request: StorageServiceRequest,
         timeout: Duration,
     ) -> Result<StorageServiceResponse, Error> {
-        let message = self
+        self.send_request_and_get_response_size(recipient, request, timeout)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// Same as `send_request()`, but also returns the size of the response
+    /// as received over the wire.
+    pub async fn send_request_and_get_response_size(
+        &self,
+        recipient: PeerNetworkId,
+        request: StorageServiceRequest,
+        timeout: Duration,
+    ) -> Result<(StorageServiceResponse, usize), Error> {
+        let (message, response_size) = self
             .network_sender
-            .send_rpc(recipient, StorageServiceMessage::Request(request), timeout)
+            .sender(&recipient.network_id())
+            .send_rpc_and_get_response_size(
+                recipient.peer_id(),
+                StorageServiceMessage::Request(request),
+                timeout,
+            )
             .await?;
         match message {
-            StorageServiceMessage::Response(Ok(response)) => Ok(response),
+            StorageServiceMessage::Response(Ok(response)) => Ok((response, response_size)),
             StorageServiceMessage::Response(Err(err)) => Err(Error::StorageServiceError(err)),
             StorageServiceMessage::Request(_) => Err(Error::RpcError(RpcError::InvalidRpcResponse)),
         }
@@ -115,6 +133,25 @@ pub struct StorageServiceNetworkSender {
     inner: NetworkSender<StorageServiceMessage>,
 }
 
+impl StorageServiceNetworkSender {
+    /// See `NetworkSender::send_rpc_and_get_response_size()`.
+    pub async fn send_rpc_and_get_response_size(
+        &self,
+        recipient: PeerId,
+        message: StorageServiceMessage,
+        timeout: Duration,
+    ) -> Result<(StorageServiceMessage, usize), RpcError> {
+        self.inner
+            .send_rpc_and_get_response_size(
+                recipient,
+                ProtocolId::StorageServiceRpc,
+                message,
+                timeout,
+            )
+            .await
+    }
+}
+
 impl NewNetworkSender for StorageServiceNetworkSender {
     fn new(
         peer_mgr_reqs_tx: PeerManagerRequestSender,
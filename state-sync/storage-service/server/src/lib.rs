@@ -6,17 +6,18 @@
 use crate::{
     logging::{LogEntry, LogSchema},
     metrics::{increment_counter, start_timer, LRU_CACHE_HIT, LRU_CACHE_PROBE},
+    moderator::RequestModerator,
     network::{ResponseSender, StorageServiceNetworkEvents},
 };
 use ::network::ProtocolId;
-use aptos_config::config::StorageServiceConfig;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     account_address::AccountAddress,
     epoch_change::EpochChangeProof,
-    ledger_info::LedgerInfoWithSignatures,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     state_store::state_value::StateValueChunkWithProof,
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
@@ -36,20 +37,24 @@ use storage_service_types::{
     EpochEndingLedgerInfoRequest, ProtocolMetadata, Result, ServerProtocolVersion,
     StorageServerSummary, StorageServiceError, StorageServiceRequest, StorageServiceResponse,
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    MAX_UNCOMPRESSED_RESPONSE_BYTES, RATE_LIMITING_PROTOCOL_VERSION,
 };
 use thiserror::Error;
 use tokio::runtime::Handle;
 
 mod logging;
 mod metrics;
+mod moderator;
 pub mod network;
 
 #[cfg(test)]
 mod tests;
 
 /// Storage server constants.
-const STORAGE_SERVER_VERSION: u64 = 1;
+const STORAGE_SERVER_VERSION: u64 = 3; // Version 3 supports rate limiting
+const RATE_LIMITER_GC_INTERVAL_SECS: u64 = 60;
 const SUMMARY_LOG_FREQUENCY_SECS: u64 = 5;
+const TOO_MANY_REQUESTS_LOG_FREQUENCY_SECS: u64 = 5;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
 pub enum Error {
@@ -57,6 +62,8 @@ pub enum Error {
     InvalidRequest(String),
     #[error("Storage error encountered: {0}")]
     StorageErrorEncountered(String),
+    #[error("Too many requests received: {0}")]
+    TooManyRequests(String),
     #[error("Unexpected error encountered: {0}")]
     UnexpectedErrorEncountered(String),
 }
@@ -67,15 +74,27 @@ impl Error {
         match self {
             Error::InvalidRequest(_) => "invalid_request",
             Error::StorageErrorEncountered(_) => "storage_error",
+            Error::TooManyRequests(_) => "too_many_requests",
             Error::UnexpectedErrorEncountered(_) => "unexpected_error",
         }
     }
 }
 
+impl From<Error> for StorageServiceError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::InvalidRequest(error) => StorageServiceError::InvalidRequest(error),
+            Error::TooManyRequests(error) => StorageServiceError::TooManyRequests(error),
+            error => StorageServiceError::InternalError(error.to_string()),
+        }
+    }
+}
+
 /// A subscription for data received by a client
 pub struct DataSubscriptionRequest {
     protocol: ProtocolId,
-    request: StorageServiceRequest,
+    request: StorageServiceRequest, // The (uncompressed) subscription request
+    use_compression: bool,          // Whether the response should be compressed
     response_sender: ResponseSender,
     subscription_start_time: Instant,
     time_service: TimeService,
@@ -90,7 +109,8 @@ impl DataSubscriptionRequest {
     ) -> Self {
         Self {
             protocol,
-            request,
+            use_compression: request.is_compressed(),
+            request: request.into_uncompressed(),
             response_sender,
             subscription_start_time: time_service.now(),
             time_service,
@@ -209,6 +229,9 @@ pub struct StorageServiceServer<T> {
     storage: T,
     time_service: TimeService,
 
+    // The moderator that rate limits the requests of each peer
+    request_moderator: Arc<RequestModerator>,
+
     // A cached storage server summary to avoid hitting the DB for every
     // request. This is refreshed periodically.
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
@@ -228,10 +251,16 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
         executor: Handle,
         storage: T,
         time_service: TimeService,
+        network_id: NetworkId,
         network_requests: StorageServiceNetworkEvents,
     ) -> Self {
         let bounded_executor =
             BoundedExecutor::new(config.max_concurrent_requests as usize, executor);
+        let request_moderator = Arc::new(RequestModerator::new(
+            config,
+            network_id,
+            time_service.clone(),
+        ));
         let cached_storage_server_summary = Arc::new(RwLock::new(StorageServerSummary::default()));
         let data_subscriptions = Arc::new(Mutex::new(HashMap::new()));
         let lru_storage_cache = Arc::new(Mutex::new(LruCache::new(
@@ -244,6 +273,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
             storage,
             network_requests,
             time_service,
+            request_moderator,
             cached_storage_server_summary,
             data_subscriptions,
             lru_storage_cache,
@@ -286,12 +316,36 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
             .await;
     }
 
+    /// Spawns a non-terminating task that garbage collects the rate limiter
+    /// state of idle peers
+    async fn spawn_rate_limiter_garbage_collector(&mut self) {
+        let request_moderator = self.request_moderator.clone();
+        let time_service = self.time_service.clone();
+
+        // Spawn the task
+        self.bounded_executor
+            .spawn(async move {
+                // Create a ticker for the garbage collection interval
+                let duration = Duration::from_secs(RATE_LIMITER_GC_INTERVAL_SECS);
+                let ticker = time_service.interval(duration);
+                futures::pin_mut!(ticker);
+
+                // Periodically garbage collect the idle peers
+                loop {
+                    ticker.next().await;
+                    request_moderator.garbage_collect_idle_peers();
+                }
+            })
+            .await;
+    }
+
     /// Spawns a non-terminating task that handles subscriptions
     async fn spawn_subscription_handler(&mut self) {
         let cached_storage_server_summary = self.cached_storage_server_summary.clone();
         let config = self.config;
         let data_subscriptions = self.data_subscriptions.clone();
         let lru_storage_cache = self.lru_storage_cache.clone();
+        let request_moderator = self.request_moderator.clone();
         let storage = self.storage.clone();
         let time_service = self.time_service.clone();
 
@@ -312,6 +366,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
 
                     // Identify the peers with ready subscriptions
                     let peers_with_ready_subscriptions = match get_peers_with_ready_subscriptions(
+                        config,
                         cached_storage_server_summary.clone(),
                         data_subscriptions.clone(),
                         lru_storage_cache.clone(),
@@ -331,7 +386,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
                         if let Some(data_subscription) =
                             data_subscriptions.clone().lock().remove(&peer)
                         {
-                            if let Err(error) = notify_peer_of_new_data(
+                            match notify_peer_of_new_data(
                                 cached_storage_server_summary.clone(),
                                 config,
                                 data_subscriptions.clone(),
@@ -341,8 +396,15 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
                                 data_subscription,
                                 target_ledger_info,
                             ) {
-                                error!(LogSchema::new(LogEntry::SubscriptionRefresh)
-                                    .error(&Error::UnexpectedErrorEncountered(error.to_string())));
+                                Ok(num_response_bytes) => {
+                                    request_moderator
+                                        .record_response_bytes(&peer, num_response_bytes);
+                                }
+                                Err(error) => {
+                                    error!(LogSchema::new(LogEntry::SubscriptionRefresh).error(
+                                        &Error::UnexpectedErrorEncountered(error.to_string())
+                                    ));
+                                }
                             }
                         }
                     }
@@ -359,10 +421,14 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
         // Spawn the subscription handler
         self.spawn_subscription_handler().await;
 
+        // Spawn the garbage collector for the rate limiters
+        self.spawn_rate_limiter_garbage_collector().await;
+
         // Handle the storage requests
         while let Some(request) = self.network_requests.next().await {
             // Log the request
             let (peer, protocol, request, response_sender) = request;
+            let (protocol_version, request) = request.split_protocol_version();
             debug!(LogSchema::new(LogEntry::ReceivedStorageRequest)
                 .request(&request)
                 .message(&format!(
@@ -370,17 +436,48 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
                     peer, protocol,
                 )));
 
+            // Reject the request if the peer has exceeded its rate limits
+            if let Err(error) = self.request_moderator.validate_request(&peer) {
+                increment_counter(
+                    &metrics::STORAGE_ERRORS_ENCOUNTERED,
+                    protocol,
+                    error.get_label().into(),
+                );
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(TOO_MANY_REQUESTS_LOG_FREQUENCY_SECS)),
+                    warn!(LogSchema::new(LogEntry::StorageServiceError)
+                        .error(&error)
+                        .request(&request))
+                );
+
+                // Only clients that negotiated rate limiting know to back off
+                // on TooManyRequests errors (older clients can't even decode
+                // them), so other clients are sent an internal error instead.
+                let error = if protocol_version
+                    .map_or(false, |version| version >= RATE_LIMITING_PROTOCOL_VERSION)
+                {
+                    error.into()
+                } else {
+                    StorageServiceError::InternalError(error.to_string())
+                };
+                response_sender.send(Err(error));
+                continue;
+            }
+
             // All handler methods are currently CPU-bound and synchronous
             // I/O-bound, so we want to spawn on the blocking thread pool to
             // avoid starving other async tasks on the same runtime.
+            let config = self.config;
             let storage = self.storage.clone();
             let cached_storage_server_summary = self.cached_storage_server_summary.clone();
             let data_subscriptions = self.data_subscriptions.clone();
             let lru_storage_cache = self.lru_storage_cache.clone();
             let time_service = self.time_service.clone();
+            let request_moderator = self.request_moderator.clone();
             self.bounded_executor
                 .spawn_blocking(move || {
-                    Handler::new(
+                    let num_response_bytes = Handler::new(
+                        config,
                         cached_storage_server_summary,
                         data_subscriptions,
                         lru_storage_cache,
@@ -393,6 +490,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
                         request,
                         response_sender,
                     );
+                    request_moderator.record_response_bytes(&peer, num_response_bytes);
                 })
                 .await;
         }
@@ -403,6 +501,7 @@ impl<T: StorageReaderInterface> StorageServiceServer<T> {
 /// Returns the list of peers that made those subscriptions
 /// alongside the ledger info at the target version for the peer.
 fn get_peers_with_ready_subscriptions<T: StorageReaderInterface>(
+    config: StorageServiceConfig,
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
     data_subscriptions: Arc<Mutex<HashMap<AccountAddress, DataSubscriptionRequest>>>,
     lru_storage_cache: Arc<Mutex<LruCache<StorageServiceRequest, StorageServiceResponse>>>,
@@ -426,6 +525,7 @@ fn get_peers_with_ready_subscriptions<T: StorageReaderInterface>(
             let target_ledger_info = if highest_known_epoch < highest_synced_epoch {
                 // The peer needs to sync to their epoch ending ledger info
                 get_epoch_ending_ledger_info(
+                    config,
                     cached_storage_server_summary.clone(),
                     data_subscriptions.clone(),
                     highest_known_epoch,
@@ -445,6 +545,7 @@ fn get_peers_with_ready_subscriptions<T: StorageReaderInterface>(
 
 /// Gets the epoch ending ledger info at the given epoch
 fn get_epoch_ending_ledger_info<T: StorageReaderInterface>(
+    config: StorageServiceConfig,
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
     data_subscriptions: Arc<Mutex<HashMap<AccountAddress, DataSubscriptionRequest>>>,
     epoch: u64,
@@ -462,6 +563,7 @@ fn get_epoch_ending_ledger_info<T: StorageReaderInterface>(
 
     // Process the request
     let handler = Handler::new(
+        config,
         cached_storage_server_summary,
         data_subscriptions,
         lru_storage_cache,
@@ -492,7 +594,8 @@ fn get_epoch_ending_ledger_info<T: StorageReaderInterface>(
     }
 }
 
-/// Notifies a subscriber of new data according to the target ledger info
+/// Notifies a subscriber of new data according to the target ledger info.
+/// Returns the number of response bytes sent.
 fn notify_peer_of_new_data<T: StorageReaderInterface>(
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
     config: StorageServiceConfig,
@@ -502,11 +605,12 @@ fn notify_peer_of_new_data<T: StorageReaderInterface>(
     time_service: TimeService,
     subscription: DataSubscriptionRequest,
     target_ledger_info: LedgerInfoWithSignatures,
-) -> Result<(), Error> {
+) -> Result<u64, Error> {
     match subscription.get_storage_request_for_missing_data(config, &target_ledger_info) {
         Ok(storage_request) => {
             // Handle the storage service request to fetch the missing data
             let handler = Handler::new(
+                config,
                 cached_storage_server_summary,
                 data_subscriptions,
                 lru_storage_cache,
@@ -537,9 +641,12 @@ fn notify_peer_of_new_data<T: StorageReaderInterface>(
                 }
             };
 
-            // Send the response to the peer
-            handler.send_response(Ok(transformed_response), subscription.response_sender);
-            Ok(())
+            // Compress the response (if requested) and send it to the peer
+            let response = handler
+                .prepare_response_for_network(transformed_response, subscription.use_compression)
+                .map_err(StorageServiceError::from);
+            let num_response_bytes = handler.send_response(response, subscription.response_sender);
+            Ok(num_response_bytes as u64)
         }
         Err(error) => Err(error),
     }
@@ -589,6 +696,7 @@ fn remove_expired_data_subscriptions(
 /// request. We usually clone/create a new handler for every request.
 #[derive(Clone)]
 pub struct Handler<T> {
+    config: StorageServiceConfig,
     cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
     data_subscriptions: Arc<Mutex<HashMap<AccountAddress, DataSubscriptionRequest>>>,
     lru_storage_cache: Arc<Mutex<LruCache<StorageServiceRequest, StorageServiceResponse>>>,
@@ -598,6 +706,7 @@ pub struct Handler<T> {
 
 impl<T: StorageReaderInterface> Handler<T> {
     pub fn new(
+        config: StorageServiceConfig,
         cached_storage_server_summary: Arc<RwLock<StorageServerSummary>>,
         data_subscriptions: Arc<Mutex<HashMap<AccountAddress, DataSubscriptionRequest>>>,
         lru_storage_cache: Arc<Mutex<LruCache<StorageServiceRequest, StorageServiceResponse>>>,
@@ -605,6 +714,7 @@ impl<T: StorageReaderInterface> Handler<T> {
         time_service: TimeService,
     ) -> Self {
         Self {
+            config,
             storage,
             cached_storage_server_summary,
             data_subscriptions,
@@ -614,14 +724,16 @@ impl<T: StorageReaderInterface> Handler<T> {
     }
 
     /// Handles the given storage service request and responds to the
-    /// request directly.
+    /// request directly. Returns the number of response bytes sent (data
+    /// subscriptions are only responded to later, so they return zero and
+    /// their response bytes are recorded once they're notified of new data).
     pub fn process_request_and_respond(
         &self,
         peer: AccountAddress,
         protocol: ProtocolId,
         request: StorageServiceRequest,
         response_sender: ResponseSender,
-    ) {
+    ) -> u64 {
         // Update the request count
         increment_counter(
             &metrics::STORAGE_REQUESTS_RECEIVED,
@@ -632,12 +744,12 @@ impl<T: StorageReaderInterface> Handler<T> {
         // Handle any data subscriptions
         if request.is_data_subscription_request() {
             self.handle_subscription_request(peer, protocol, request, response_sender);
-            return;
+            return 0;
         }
 
         // Process the request and return the response to the client
         let response = self.process_request(protocol, request);
-        self.send_response(response, response_sender);
+        self.send_response(response, response_sender) as u64
     }
 
     /// Processes the given request and returns the response
@@ -653,12 +765,13 @@ impl<T: StorageReaderInterface> Handler<T> {
             request.get_label().into(),
         );
 
-        // Process the request
-        let response = match &request {
-            StorageServiceRequest::GetServerProtocolVersion => self.get_server_protocol_version(),
-            StorageServiceRequest::GetStorageServerSummary => self.get_storage_server_summary(),
-            _ => self.process_cachable_request(protocol, &request),
-        };
+        // Process the request and compress the response (if requested).
+        // Responses are always cached uncompressed.
+        let response = self.process_request_for_network(
+            protocol,
+            request.clone().into_uncompressed(),
+            request.is_compressed(),
+        );
 
         // Process the response and handle any errors
         match response {
@@ -674,10 +787,7 @@ impl<T: StorageReaderInterface> Handler<T> {
                     .request(&request));
 
                 // Return an appropriate response to the client
-                match error {
-                    Error::InvalidRequest(error) => Err(StorageServiceError::InvalidRequest(error)),
-                    error => Err(StorageServiceError::InternalError(error.to_string())),
-                }
+                Err(error.into())
             }
             Ok(response) => {
                 // The request was successful
@@ -691,14 +801,108 @@ impl<T: StorageReaderInterface> Handler<T> {
         }
     }
 
-    /// Sends a response via the provided sender
+    /// Processes the given (uncompressed) request and prepares the response
+    /// for the network. If the response to a transaction (output) request is
+    /// too large to be sent, the requested range is shrunk (from the end)
+    /// until the response fits. Clients request the remainder afterwards.
+    fn process_request_for_network(
+        &self,
+        protocol: ProtocolId,
+        mut request: StorageServiceRequest,
+        use_compression: bool,
+    ) -> Result<StorageServiceResponse, Error> {
+        loop {
+            let response = match &request {
+                StorageServiceRequest::GetServerProtocolVersion => {
+                    self.get_server_protocol_version()
+                }
+                StorageServiceRequest::GetStorageServerSummary => self.get_storage_server_summary(),
+                request => self.process_cachable_request(protocol, request),
+            }?;
+
+            // Return the response if it fits
+            let (response, num_response_bytes) =
+                self.compress_response_for_network(response, use_compression)?;
+            if num_response_bytes <= self.config.max_network_chunk_bytes {
+                return Ok(response);
+            }
+
+            // Otherwise, shrink the range proportionally and try again
+            match shrink_range_request(
+                &request,
+                num_response_bytes,
+                self.config.max_network_chunk_bytes,
+            ) {
+                Some(shrunk_request) => {
+                    debug!(LogSchema::new(LogEntry::ReceivedStorageRequest)
+                        .message(&format!(
+                            "The response is too large to be sent over the network ({} bytes). \
+                            Shrinking the requested range: {:?}",
+                            num_response_bytes, shrunk_request
+                        ))
+                        .request(&request));
+                    request = shrunk_request;
+                }
+                None => {
+                    return Err(response_too_large_error(
+                        num_response_bytes,
+                        self.config.max_network_chunk_bytes,
+                        use_compression,
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Compresses the response (if `use_compression` is true) and returns it
+    /// along with its size (in bytes) on the network. If the response is too
+    /// large to be compressed, it's returned uncompressed.
+    fn compress_response_for_network(
+        &self,
+        response: StorageServiceResponse,
+        use_compression: bool,
+    ) -> Result<(StorageServiceResponse, u64), Error> {
+        let num_response_bytes = bcs::serialized_size(&response)
+            .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        if !use_compression || num_response_bytes > MAX_UNCOMPRESSED_RESPONSE_BYTES {
+            return Ok((response, num_response_bytes as u64));
+        }
+
+        let response = response
+            .compress()
+            .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        let num_response_bytes = bcs::serialized_size(&response)
+            .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        Ok((response, num_response_bytes as u64))
+    }
+
+    /// Compresses the response (if `use_compression` is true) and verifies
+    /// that it fits within the maximum network chunk size.
+    fn prepare_response_for_network(
+        &self,
+        response: StorageServiceResponse,
+        use_compression: bool,
+    ) -> Result<StorageServiceResponse, Error> {
+        let (response, num_response_bytes) =
+            self.compress_response_for_network(response, use_compression)?;
+        if num_response_bytes > self.config.max_network_chunk_bytes {
+            return Err(response_too_large_error(
+                num_response_bytes,
+                self.config.max_network_chunk_bytes,
+                use_compression,
+            ));
+        }
+        Ok(response)
+    }
+
+    /// Sends a response via the provided sender. Returns the number of bytes sent.
     fn send_response(
         &self,
         response: Result<StorageServiceResponse>,
         response_sender: ResponseSender,
-    ) {
+    ) -> usize {
         log_storage_response(&response);
-        response_sender.send(response);
+        response_sender.send(response)
     }
 
     /// Handles the given data subscription request
@@ -854,6 +1058,61 @@ impl<T: StorageReaderInterface> Handler<T> {
     }
 }
 
+/// Returns the given transaction (output) request with its range shrunk by
+/// the ratio between the maximum and actual response sizes. Returns None if
+/// the request can't be shrunk (i.e., it's for a single item or of another type).
+fn shrink_range_request(
+    request: &StorageServiceRequest,
+    num_response_bytes: u64,
+    max_response_bytes: u64,
+) -> Option<StorageServiceRequest> {
+    let shrink_end_version = |start_version: Version, end_version: Version| {
+        let num_versions = end_version.checked_sub(start_version)?.checked_add(1)?;
+        if num_versions <= 1 {
+            return None;
+        }
+        let num_shrunk_versions =
+            (num_versions as u128 * max_response_bytes as u128 / num_response_bytes as u128) as u64;
+        let num_shrunk_versions = num_shrunk_versions.clamp(1, num_versions - 1);
+        Some(start_version + num_shrunk_versions - 1)
+    };
+
+    match request {
+        StorageServiceRequest::GetTransactionsWithProof(request) => {
+            let end_version = shrink_end_version(request.start_version, request.end_version)?;
+            Some(StorageServiceRequest::GetTransactionsWithProof(
+                TransactionsWithProofRequest {
+                    end_version,
+                    ..request.clone()
+                },
+            ))
+        }
+        StorageServiceRequest::GetTransactionOutputsWithProof(request) => {
+            let end_version = shrink_end_version(request.start_version, request.end_version)?;
+            Some(StorageServiceRequest::GetTransactionOutputsWithProof(
+                TransactionOutputsWithProofRequest {
+                    end_version,
+                    ..request.clone()
+                },
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the error for a response that is too large to be sent over the network
+fn response_too_large_error(
+    num_response_bytes: u64,
+    max_network_chunk_bytes: u64,
+    use_compression: bool,
+) -> Error {
+    Error::InvalidRequest(format!(
+        "The response is too large to be sent over the network ({} bytes, max: {} bytes). \
+        Compressed: {}. Request a smaller chunk instead!",
+        num_response_bytes, max_network_chunk_bytes, use_compression
+    ))
+}
+
 /// The interface into local storage (e.g., the Aptos DB) used by the storage
 /// server to handle client requests.
 pub trait StorageReaderInterface: Clone + Send + 'static {
@@ -960,6 +1219,37 @@ impl StorageReader {
         Ok(*transactions_range)
     }
 
+    /// Returns the epoch ending ledger info range held in the database (lowest
+    /// to highest). Nodes that didn't sync from genesis (e.g., nodes that
+    /// bootstrapped from a waypoint) only hold the ledger infos from the
+    /// lowest stored epoch onwards.
+    fn fetch_epoch_ending_ledger_info_range(
+        &self,
+        latest_ledger_info: &LedgerInfo,
+    ) -> Result<Option<CompleteDataRange<u64>>, Error> {
+        let highest_ending_epoch = if latest_ledger_info.ends_epoch() {
+            latest_ledger_info.epoch()
+        } else if latest_ledger_info.epoch() > 0 {
+            latest_ledger_info.epoch().checked_sub(1).ok_or_else(|| {
+                Error::UnexpectedErrorEncountered("Highest ending epoch overflowed!".into())
+            })?
+        } else {
+            return Ok(None); // We haven't seen an epoch change yet
+        };
+
+        let lowest_ending_epoch = self
+            .storage
+            .get_first_ledger_info_epoch()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?
+            .unwrap_or(0);
+        if lowest_ending_epoch > highest_ending_epoch {
+            return Ok(None); // We don't hold any epoch ending ledger info yet
+        }
+        let epoch_ending_range = CompleteDataRange::new(lowest_ending_epoch, highest_ending_epoch)
+            .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+        Ok(Some(epoch_ending_range))
+    }
+
     /// Returns the transaction range held in the database (lowest to highest).
     fn fetch_transaction_range(
         &self,
@@ -1008,18 +1298,8 @@ impl StorageReaderInterface for StorageReader {
 
         // Fetch the epoch ending ledger info range
         let latest_ledger_info = latest_ledger_info_with_sigs.ledger_info();
-        let epoch_ending_ledger_infos = if latest_ledger_info.ends_epoch() {
-            let highest_ending_epoch = latest_ledger_info.epoch();
-            Some(CompleteDataRange::from_genesis(highest_ending_epoch))
-        } else if latest_ledger_info.epoch() > 0 {
-            let highest_ending_epoch =
-                latest_ledger_info.epoch().checked_sub(1).ok_or_else(|| {
-                    Error::UnexpectedErrorEncountered("Highest ending epoch overflowed!".into())
-                })?;
-            Some(CompleteDataRange::from_genesis(highest_ending_epoch))
-        } else {
-            None // We haven't seen an epoch change yet
-        };
+        let epoch_ending_ledger_infos =
+            self.fetch_epoch_ending_ledger_info_range(latest_ledger_info)?;
 
         // Fetch the transaction and transaction output ranges
         let latest_version = latest_ledger_info.version();
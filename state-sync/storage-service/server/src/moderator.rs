@@ -0,0 +1,247 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Error;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
+use aptos_infallible::Mutex;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+/// Rate limiter labels
+const REQUEST_RATE_LIMITER: &str = "storage_service_requests";
+const RESPONSE_BYTES_RATE_LIMITER: &str = "storage_service_response_bytes";
+const NETWORK_REQUEST_RATE_LIMITER: &str = "storage_service_network_requests";
+const NETWORK_RESPONSE_BYTES_RATE_LIMITER: &str = "storage_service_network_response_bytes";
+
+/// The time after which the rate limiter state of an idle peer is dropped. A
+/// bucket refills within a second, so dropping it doesn't change the budget.
+const IDLE_PEER_TIMEOUT_SECS: u64 = 60;
+
+/// The request moderator ensures that no single peer can monopolize the
+/// storage service. Each peer has a token bucket for the number of requests
+/// it sends and another for the number of response bytes it receives. Peers
+/// on the validator and VFN networks are given (much) higher limits than
+/// public peers, so that public peers cannot starve the nodes we serve first.
+/// Public peers also share an aggregate budget, so that many public peers
+/// together cannot exhaust the node either.
+pub struct RequestModerator {
+    network_id: NetworkId,
+    time_service: TimeService,
+    request_rate_limiter: TokenBucketRateLimiter<PeerId>,
+    response_bytes_rate_limiter: TokenBucketRateLimiter<PeerId>,
+    network_request_rate_limiter: TokenBucketRateLimiter<NetworkId>,
+    network_response_bytes_rate_limiter: TokenBucketRateLimiter<NetworkId>,
+    last_request_times: Mutex<HashMap<PeerId, Instant>>, // The last request time of each peer (for garbage collection)
+}
+
+impl RequestModerator {
+    pub fn new(
+        config: StorageServiceConfig,
+        network_id: NetworkId,
+        time_service: TimeService,
+    ) -> Self {
+        let (max_requests_per_sec, max_response_bytes_per_sec) = if is_priority_network(network_id)
+        {
+            (
+                config.max_priority_peer_requests_per_sec,
+                config.max_priority_peer_response_bytes_per_sec,
+            )
+        } else {
+            (
+                config.max_public_peer_requests_per_sec,
+                config.max_public_peer_response_bytes_per_sec,
+            )
+        };
+
+        let request_rate_limiter = create_rate_limiter(
+            config,
+            network_id,
+            REQUEST_RATE_LIMITER,
+            Some(max_requests_per_sec),
+        );
+        let response_bytes_rate_limiter = create_rate_limiter(
+            config,
+            network_id,
+            RESPONSE_BYTES_RATE_LIMITER,
+            Some(max_response_bytes_per_sec),
+        );
+
+        // Only the public network has an aggregate budget. Priority networks
+        // are served by their own moderators, so they keep their capacity.
+        let (max_network_requests_per_sec, max_network_response_bytes_per_sec) =
+            if is_priority_network(network_id) {
+                (None, None)
+            } else {
+                (
+                    Some(config.max_public_network_requests_per_sec),
+                    Some(config.max_public_network_response_bytes_per_sec),
+                )
+            };
+        let network_request_rate_limiter = create_rate_limiter(
+            config,
+            network_id,
+            NETWORK_REQUEST_RATE_LIMITER,
+            max_network_requests_per_sec,
+        );
+        let network_response_bytes_rate_limiter = create_rate_limiter(
+            config,
+            network_id,
+            NETWORK_RESPONSE_BYTES_RATE_LIMITER,
+            max_network_response_bytes_per_sec,
+        );
+
+        Self {
+            network_id,
+            time_service,
+            request_rate_limiter,
+            response_bytes_rate_limiter,
+            network_request_rate_limiter,
+            network_response_bytes_rate_limiter,
+            last_request_times: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Validates that the given peer is allowed to send another request, i.e.,
+    /// neither the peer nor its network has exhausted its request or response
+    /// bytes budget. Otherwise, a `TooManyRequests` error is returned and the
+    /// peer should back off.
+    pub fn validate_request(&self, peer: &PeerId) -> Result<(), Error> {
+        self.last_request_times
+            .lock()
+            .insert(*peer, self.time_service.now());
+
+        if self
+            .request_rate_limiter
+            .bucket(*peer)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_err()
+        {
+            return Err(Error::TooManyRequests(format!(
+                "Peer {} on the {} network has sent too many requests!",
+                peer, self.network_id
+            )));
+        }
+        if self
+            .network_request_rate_limiter
+            .bucket(self.network_id)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_err()
+        {
+            return Err(Error::TooManyRequests(format!(
+                "The peers on the {} network have sent too many requests!",
+                self.network_id
+            )));
+        }
+
+        // The response size is only known once the request has been processed,
+        // so only require that some response bytes remain.
+        if self
+            .response_bytes_rate_limiter
+            .bucket(*peer)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_err()
+        {
+            return Err(Error::TooManyRequests(format!(
+                "Peer {} on the {} network has received too many response bytes!",
+                peer, self.network_id
+            )));
+        }
+        if self
+            .network_response_bytes_rate_limiter
+            .bucket(self.network_id)
+            .lock()
+            .acquire_all_tokens(1)
+            .is_err()
+        {
+            return Err(Error::TooManyRequests(format!(
+                "The peers on the {} network have received too many response bytes!",
+                self.network_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Deducts the given number of response bytes from the budgets of the
+    /// peer and its network. The budgets cannot drop below zero, so a single
+    /// response may exceed them.
+    pub fn record_response_bytes(&self, peer: &PeerId, num_response_bytes: u64) {
+        let _ = self
+            .response_bytes_rate_limiter
+            .bucket(*peer)
+            .lock()
+            .acquire_tokens(num_response_bytes as usize);
+        let _ = self
+            .network_response_bytes_rate_limiter
+            .bucket(self.network_id)
+            .lock()
+            .acquire_tokens(num_response_bytes as usize);
+    }
+
+    /// Drops the rate limiter state of the peers that haven't sent a request
+    /// for a while (e.g., because they disconnected).
+    pub fn garbage_collect_idle_peers(&self) {
+        let now = self.time_service.now();
+        let idle_peer_timeout = Duration::from_secs(IDLE_PEER_TIMEOUT_SECS);
+        self.last_request_times
+            .lock()
+            .retain(|peer, last_request_time| {
+                if now.duration_since(*last_request_time) < idle_peer_timeout {
+                    return true;
+                }
+
+                // Buckets are only held while a request is being moderated,
+                // and such a request would have refreshed the peer's time.
+                self.request_rate_limiter.try_garbage_collect_key(peer);
+                self.response_bytes_rate_limiter
+                    .try_garbage_collect_key(peer);
+                false
+            });
+    }
+
+    /// Returns the number of peers with rate limiter state
+    #[cfg(test)]
+    pub fn num_tracked_peers(&self) -> usize {
+        self.last_request_times.lock().len()
+    }
+}
+
+/// Returns true iff peers on the given network should be prioritized, i.e.,
+/// they are validators or VFNs (and not public peers).
+fn is_priority_network(network_id: NetworkId) -> bool {
+    network_id.is_validator_network() || network_id.is_vfn_network()
+}
+
+/// Creates a token bucket rate limiter that allows `max_tokens_per_sec` tokens
+/// per second for each key (or an open rate limiter if rate limiting is
+/// disabled or no limit is given).
+fn create_rate_limiter<Key: Eq + Hash + Clone + Debug>(
+    config: StorageServiceConfig,
+    network_id: NetworkId,
+    label: &'static str,
+    max_tokens_per_sec: Option<u64>,
+) -> TokenBucketRateLimiter<Key> {
+    match max_tokens_per_sec {
+        Some(max_tokens_per_sec) if config.enable_request_rate_limiting => {
+            TokenBucketRateLimiter::new(
+                label,
+                network_id.to_string(),
+                100, // Start with a full bucket
+                max_tokens_per_sec as usize,
+                max_tokens_per_sec as usize,
+                None,
+            )
+        }
+        _ => TokenBucketRateLimiter::open(label),
+    }
+}
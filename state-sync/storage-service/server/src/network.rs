@@ -92,11 +92,15 @@ impl ResponseSender {
         Self { response_tx }
     }
 
-    pub fn send(self, response: Result<StorageServiceResponse>) {
+    /// Sends the response and returns its size (in bytes) on the wire (or
+    /// zero if it couldn't be serialized)
+    pub fn send(self, response: Result<StorageServiceResponse>) -> usize {
         let msg = StorageServiceMessage::Response(response);
         let result = bcs::to_bytes(&msg)
             .map(Bytes::from)
             .map_err(RpcError::BcsError);
+        let num_bytes = result.as_ref().map_or(0, |bytes| bytes.len());
         let _ = self.response_tx.send(result);
+        num_bytes
     }
 }
@@ -3,9 +3,12 @@
 
 #![forbid(unsafe_code)]
 
-use crate::{network::StorageServiceNetworkEvents, StorageReader, StorageServiceServer};
+use crate::{
+    network::StorageServiceNetworkEvents, StorageReader, StorageReaderInterface,
+    StorageServiceServer,
+};
 use anyhow::{format_err, Result};
-use aptos_config::config::StorageServiceConfig;
+use aptos_config::{config::StorageServiceConfig, network_id::NetworkId};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_logger::Level;
 use aptos_time_service::{MockTimeService, TimeService};
@@ -57,12 +60,13 @@ use storage_service_types::{
     NewTransactionsWithProofRequest, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
     StorageServiceError, StorageServiceMessage, StorageServiceRequest, StorageServiceResponse,
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
+    RATE_LIMITING_PROTOCOL_VERSION,
 };
 use tokio::time::timeout;
 
 /// Various test constants for storage
 const MAX_RESPONSE_TIMEOUT_SECS: u64 = 30;
-const PROTOCOL_VERSION: u64 = 1;
+const PROTOCOL_VERSION: u64 = 3;
 
 #[tokio::test]
 async fn test_cachable_requests_eviction() {
@@ -217,6 +221,115 @@ async fn test_get_server_protocol_version() {
     assert_eq!(response, expected_response);
 }
 
+#[tokio::test]
+async fn test_too_many_requests_public_peer() {
+    // Create the storage client and server for a public network
+    let storage_config = StorageServiceConfig {
+        max_public_peer_requests_per_sec: 1,
+        ..Default::default()
+    };
+    let (mut mock_client, service, _) =
+        MockClient::new_with_config(None, storage_config, NetworkId::Public);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the protocol version
+    let request = StorageServiceRequest::GetServerProtocolVersion
+        .with_protocol_version(RATE_LIMITING_PROTOCOL_VERSION);
+    mock_client.process_request(request.clone()).await.unwrap();
+
+    // Verify the next request is rejected because the peer is rate limited
+    let response = mock_client.process_request(request).await.unwrap_err();
+    assert_matches!(response, StorageServiceError::TooManyRequests(_));
+
+    // Verify that clients that didn't negotiate rate limiting get an internal error
+    let request = StorageServiceRequest::GetServerProtocolVersion;
+    let response = mock_client.process_request(request).await.unwrap_err();
+    assert_matches!(response, StorageServiceError::InternalError(_));
+}
+
+#[tokio::test]
+async fn test_too_many_requests_public_network() {
+    // Create the storage client and server for a public network
+    let storage_config = StorageServiceConfig {
+        max_public_network_requests_per_sec: 2,
+        ..Default::default()
+    };
+    let (mut mock_client, service, _) =
+        MockClient::new_with_config(None, storage_config, NetworkId::Public);
+    tokio::spawn(service.start());
+
+    // Process a request from two different peers
+    let request = StorageServiceRequest::GetServerProtocolVersion
+        .with_protocol_version(RATE_LIMITING_PROTOCOL_VERSION);
+    for _ in 0..2 {
+        mock_client
+            .process_request_from_peer(PeerId::random(), request.clone())
+            .await
+            .unwrap();
+    }
+
+    // Verify a request from a new peer is rejected because the network is rate limited
+    let response = mock_client
+        .process_request_from_peer(PeerId::random(), request)
+        .await
+        .unwrap_err();
+    assert_matches!(response, StorageServiceError::TooManyRequests(_));
+}
+
+#[tokio::test]
+async fn test_rate_limiter_garbage_collection() {
+    // Create a request moderator for a public network
+    let storage_config = StorageServiceConfig::default();
+    let time_service = TimeService::mock();
+    let request_moderator = crate::moderator::RequestModerator::new(
+        storage_config,
+        NetworkId::Public,
+        time_service.clone(),
+    );
+
+    // Send requests from several peers
+    let peers: Vec<_> = (0..5).map(|_| PeerId::random()).collect();
+    for peer in &peers {
+        request_moderator.validate_request(peer).unwrap();
+    }
+    assert_eq!(request_moderator.num_tracked_peers(), peers.len());
+
+    // Verify that recently active peers are not garbage collected
+    let mock_time_service = time_service.into_mock();
+    mock_time_service.advance_secs(30);
+    request_moderator.validate_request(&peers[0]).unwrap();
+    request_moderator.garbage_collect_idle_peers();
+    assert_eq!(request_moderator.num_tracked_peers(), peers.len());
+
+    // Verify that the idle peers are garbage collected
+    mock_time_service.advance_secs(45);
+    request_moderator.garbage_collect_idle_peers();
+    assert_eq!(request_moderator.num_tracked_peers(), 1);
+
+    // Verify that the last peer is garbage collected once it's idle
+    mock_time_service.advance_secs(60);
+    request_moderator.garbage_collect_idle_peers();
+    assert_eq!(request_moderator.num_tracked_peers(), 0);
+}
+
+#[tokio::test]
+async fn test_too_many_requests_priority_peer() {
+    // Create the storage client and server for the validator network
+    let storage_config = StorageServiceConfig {
+        max_public_peer_requests_per_sec: 1,
+        ..Default::default()
+    };
+    let (mut mock_client, service, _) =
+        MockClient::new_with_config(None, storage_config, NetworkId::Validator);
+    tokio::spawn(service.start());
+
+    // Verify that the public peer limits do not apply to priority peers
+    for _ in 0..10 {
+        let request = StorageServiceRequest::GetServerProtocolVersion;
+        mock_client.process_request(request).await.unwrap();
+    }
+}
+
 #[tokio::test]
 async fn test_get_account_states_with_proof() {
     // Test small and large chunk requests
@@ -720,6 +833,10 @@ async fn test_get_storage_server_summary() {
         .expect_get_latest_ledger_info()
         .times(1)
         .return_once(move || Ok(highest_ledger_info_clone));
+    db_reader
+        .expect_get_first_ledger_info_epoch()
+        .times(1)
+        .return_once(move || Ok(Some(0)));
     db_reader
         .expect_get_first_txn_version()
         .times(1)
@@ -783,6 +900,42 @@ async fn test_get_storage_server_summary() {
     );
 }
 
+#[test]
+fn test_get_data_summary_from_waypoint() {
+    // Create test data for a node that bootstrapped from a waypoint ending epoch 10
+    let highest_version = 506;
+    let highest_epoch = 30;
+    let waypoint_epoch = 10;
+    let waypoint_version = 200;
+    let highest_ledger_info = create_test_ledger_info_with_sigs(highest_epoch, highest_version);
+
+    // Create the mock db reader
+    let mut db_reader = create_mock_db_reader();
+    db_reader
+        .expect_get_latest_ledger_info()
+        .return_once(move || Ok(highest_ledger_info));
+    db_reader
+        .expect_get_first_ledger_info_epoch()
+        .return_once(move || Ok(Some(waypoint_epoch)));
+    db_reader
+        .expect_get_first_txn_version()
+        .return_once(move || Ok(Some(waypoint_version)));
+    db_reader
+        .expect_get_first_write_set_version()
+        .return_once(move || Ok(Some(waypoint_version)));
+    db_reader
+        .expect_get_state_prune_window()
+        .return_once(move || Ok(None));
+
+    // Verify only the epochs from the waypoint onwards are advertised
+    let storage = StorageReader::new(StorageServiceConfig::default(), Arc::new(db_reader));
+    let data_summary = storage.get_data_summary().unwrap();
+    assert_eq!(
+        data_summary.epoch_ending_ledger_infos,
+        Some(CompleteDataRange::new(waypoint_epoch, highest_epoch - 1).unwrap())
+    );
+}
+
 #[tokio::test]
 async fn test_get_transactions_with_proof() {
     // Test small and large chunk requests
@@ -845,6 +998,135 @@ async fn test_get_transactions_with_proof() {
     }
 }
 
+#[tokio::test]
+async fn test_get_transactions_with_proof_compressed() {
+    // Create test data
+    let start_version = 0;
+    let end_version = 99;
+    let proof_version = end_version;
+    let include_events = true;
+    let transaction_list_with_proof = create_transaction_list_with_proof(
+        start_version,
+        end_version,
+        proof_version,
+        include_events,
+    );
+
+    // Create the mock db reader (the uncompressed request should hit the cache)
+    let mut db_reader = create_mock_db_reader();
+    expect_get_transactions(
+        &mut db_reader,
+        start_version,
+        end_version - start_version + 1,
+        proof_version,
+        include_events,
+        transaction_list_with_proof.clone(),
+    );
+
+    // Create the storage client and server
+    let (mut mock_client, service, _) = MockClient::new(Some(db_reader));
+    tokio::spawn(service.start());
+
+    // Process a compressed request and verify the response is compressed
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+        include_events,
+    });
+    let response = mock_client
+        .process_request(request.clone().into_compressed())
+        .await
+        .unwrap();
+    assert_matches!(response, StorageServiceResponse::CompressedResponse(_));
+
+    // Verify the decompressed response is correct
+    let expected_response =
+        StorageServiceResponse::TransactionsWithProof(transaction_list_with_proof);
+    assert_eq!(response.decompress().unwrap(), expected_response);
+
+    // Process the uncompressed request and verify the response is correct
+    let response = mock_client.process_request(request).await.unwrap();
+    assert_eq!(response, expected_response);
+}
+
+#[tokio::test]
+async fn test_get_transactions_with_proof_network_limit() {
+    // Create test data
+    let start_version = 0;
+    let end_version = 99;
+    let proof_version = end_version;
+    let include_events = false;
+    let transaction_list_with_proof = create_transaction_list_with_proof(
+        start_version,
+        end_version,
+        proof_version,
+        include_events,
+    );
+
+    // Only allow responses that fit within the network limit once compressed
+    let uncompressed_response =
+        StorageServiceResponse::TransactionsWithProof(transaction_list_with_proof);
+    let compressed_response = uncompressed_response.compress().unwrap();
+    let max_network_chunk_bytes = bcs::serialized_size(&compressed_response).unwrap() as u64;
+    assert!(max_network_chunk_bytes < bcs::serialized_size(&uncompressed_response).unwrap() as u64);
+
+    // Create the mock db reader (which returns any requested transactions)
+    let mut db_reader = create_mock_db_reader();
+    db_reader.expect_get_transactions().returning(
+        |start_version, num_items, proof_version, include_events| {
+            Ok(create_transaction_list_with_proof(
+                start_version,
+                start_version + num_items - 1,
+                proof_version,
+                include_events,
+            ))
+        },
+    );
+
+    // Create the storage client and server
+    let storage_config = StorageServiceConfig {
+        max_network_chunk_bytes,
+        ..Default::default()
+    };
+    let (mut mock_client, service, _) =
+        MockClient::new_with_config(Some(db_reader), storage_config, NetworkId::Validator);
+    tokio::spawn(service.start());
+
+    // Verify the uncompressed response is shrunk to fit within the network limit
+    let request = StorageServiceRequest::GetTransactionsWithProof(TransactionsWithProofRequest {
+        proof_version,
+        start_version,
+        end_version,
+        include_events,
+    });
+    let response = mock_client.process_request(request.clone()).await.unwrap();
+    assert!(bcs::serialized_size(&response).unwrap() as u64 <= max_network_chunk_bytes);
+    match response {
+        StorageServiceResponse::TransactionsWithProof(transactions_with_proof) => {
+            let num_transactions = transactions_with_proof.transactions.len() as u64;
+            assert!(num_transactions > 0 && num_transactions < end_version - start_version + 1);
+            assert_eq!(
+                transactions_with_proof,
+                create_transaction_list_with_proof(
+                    start_version,
+                    start_version + num_transactions - 1,
+                    proof_version,
+                    include_events
+                )
+            );
+        }
+        response => panic!("Expected transactions with proof but got: {:?}", response),
+    };
+
+    // Verify the compressed response is returned in full
+    let response = mock_client
+        .process_request(request.into_compressed())
+        .await
+        .unwrap();
+    assert_eq!(response, compressed_response);
+}
+
 #[tokio::test]
 async fn test_get_transactions_with_proof_invalid() {
     // Create the storage client and server
@@ -1029,9 +1311,20 @@ struct MockClient {
 impl MockClient {
     fn new(
         db_reader: Option<MockDatabaseReader>,
+    ) -> (Self, StorageServiceServer<StorageReader>, MockTimeService) {
+        Self::new_with_config(
+            db_reader,
+            StorageServiceConfig::default(),
+            NetworkId::Validator,
+        )
+    }
+
+    fn new_with_config(
+        db_reader: Option<MockDatabaseReader>,
+        storage_config: StorageServiceConfig,
+        network_id: NetworkId,
     ) -> (Self, StorageServiceServer<StorageReader>, MockTimeService) {
         initialize_logger();
-        let storage_config = StorageServiceConfig::default();
         let storage = StorageReader::new(
             storage_config,
             Arc::new(db_reader.unwrap_or_else(create_mock_db_reader)),
@@ -1048,10 +1341,11 @@ impl MockClient {
         let executor = tokio::runtime::Handle::current();
         let mock_time_service = TimeService::mock();
         let storage_server = StorageServiceServer::new(
-            StorageServiceConfig::default(),
+            storage_config,
             executor,
             storage,
             mock_time_service.clone(),
+            network_id,
             network_requests,
         );
 
@@ -1064,7 +1358,16 @@ impl MockClient {
         &mut self,
         request: StorageServiceRequest,
     ) -> Result<StorageServiceResponse, StorageServiceError> {
-        let receiver = self.send_request(request).await;
+        self.process_request_from_peer(PeerId::ZERO, request).await
+    }
+
+    /// Send the given storage request from the specified peer and wait for a response
+    async fn process_request_from_peer(
+        &mut self,
+        peer_id: PeerId,
+        request: StorageServiceRequest,
+    ) -> Result<StorageServiceResponse, StorageServiceError> {
+        let receiver = self.send_request_from_peer(peer_id, request).await;
         self.wait_for_response(receiver).await
     }
 
@@ -1073,9 +1376,18 @@ impl MockClient {
     async fn send_request(
         &mut self,
         request: StorageServiceRequest,
+    ) -> Receiver<Result<bytes::Bytes, network::protocols::network::RpcError>> {
+        self.send_request_from_peer(PeerId::ZERO, request).await
+    }
+
+    /// Send the specified storage request from the given peer and return the
+    /// receiver on which to expect a result.
+    async fn send_request_from_peer(
+        &mut self,
+        peer_id: PeerId,
+        request: StorageServiceRequest,
     ) -> Receiver<Result<bytes::Bytes, network::protocols::network::RpcError>> {
         // Create the inbound rpc request
-        let peer_id = PeerId::ZERO;
         let protocol_id = ProtocolId::StorageServiceRpc;
         let data = protocol_id
             .to_bytes(&StorageServiceMessage::Request(request))
@@ -1188,6 +1500,9 @@ fn create_mock_db_for_subscription(
     db_reader
         .expect_get_latest_ledger_info()
         .return_once(move || Ok(highest_ledger_info_clone));
+    db_reader
+        .expect_get_first_ledger_info_epoch()
+        .return_once(move || Ok(Some(0)));
     db_reader
         .expect_get_first_txn_version()
         .return_once(move || Ok(Some(lowest_version)));
@@ -1454,6 +1769,8 @@ mock! {
 
         fn get_first_txn_version(&self) -> Result<Option<Version>>;
 
+        fn get_first_ledger_info_epoch(&self) -> Result<Option<u64>>;
+
         fn get_first_write_set_version(&self) -> Result<Option<Version>>;
 
         fn get_transaction_outputs(
@@ -3,6 +3,7 @@
 
 #![forbid(unsafe_code)]
 
+use aptos_compression::CompressedData;
 use aptos_config::config::StorageServiceConfig;
 use aptos_types::{
     epoch_change::EpochChangeProof,
@@ -26,6 +27,19 @@ use thiserror::Error;
 /// A type alias for different epochs.
 pub type Epoch = u64;
 
+/// The first server protocol version that supports compressed responses.
+pub const COMPRESSION_PROTOCOL_VERSION: u64 = 2;
+
+/// The first server protocol version that supports rate limiting, i.e., that
+/// accepts requests for a negotiated protocol version and sends
+/// `TooManyRequests` errors to the clients that negotiated (at least) it.
+pub const RATE_LIMITING_PROTOCOL_VERSION: u64 = 3;
+
+/// The maximum size (in bytes) of a response before it is compressed (or,
+/// equivalently, after it is decompressed). This bounds the memory a peer can
+/// make a client allocate, so it's only a small multiple of the max frame size.
+pub const MAX_UNCOMPRESSED_RESPONSE_BYTES: usize = 64 * 1024 * 1024; /* 64 MiB */
+
 pub type Result<T, E = StorageServiceError> = ::std::result::Result<T, E>;
 
 /// A storage service error that can be returned to the client on a failure
@@ -36,6 +50,8 @@ pub enum StorageServiceError {
     InternalError(String),
     #[error("Invalid storage request: {0}")]
     InvalidRequest(String),
+    #[error("Too many storage requests: {0}")]
+    TooManyRequests(String),
 }
 
 /// A single storage service message sent or received over AptosNet.
@@ -62,6 +78,9 @@ pub enum StorageServiceRequest {
     GetStorageServerSummary,               // Fetches a summary of the storage server state
     GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest), // Fetches a list of transaction outputs with a proof
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
+    // Note: new variants must be appended to preserve the wire format.
+    GetCompressedResponse(Box<StorageServiceRequest>), // Fetches the response to the inner request, compressed
+    GetResponseForProtocolVersion(u64, Box<StorageServiceRequest>), // Fetches the response to the inner request, for the negotiated protocol version
 }
 
 impl StorageServiceRequest {
@@ -77,6 +96,8 @@ impl StorageServiceRequest {
             Self::GetStorageServerSummary => "get_storage_server_summary",
             Self::GetTransactionOutputsWithProof(_) => "get_transaction_outputs_with_proof",
             Self::GetTransactionsWithProof(_) => "get_transactions_with_proof",
+            Self::GetCompressedResponse(request) => request.get_label(),
+            Self::GetResponseForProtocolVersion(_, request) => request.get_label(),
         }
     }
 
@@ -85,8 +106,57 @@ impl StorageServiceRequest {
     }
 
     pub fn is_data_subscription_request(&self) -> bool {
-        matches!(self, &Self::GetNewTransactionOutputsWithProof(_))
-            || matches!(self, &Self::GetNewTransactionsWithProof(_))
+        match self {
+            Self::GetNewTransactionOutputsWithProof(_) | Self::GetNewTransactionsWithProof(_) => {
+                true
+            }
+            Self::GetCompressedResponse(request) => request.is_data_subscription_request(),
+            Self::GetResponseForProtocolVersion(_, request) => {
+                request.is_data_subscription_request()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns true iff the response to the request should be compressed
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, &Self::GetCompressedResponse(_))
+    }
+
+    /// Returns a request for the compressed response to this request
+    pub fn into_compressed(self) -> Self {
+        match self {
+            Self::GetCompressedResponse(_) => self,
+            request => Self::GetCompressedResponse(Box::new(request)),
+        }
+    }
+
+    /// Returns the request for the uncompressed response, i.e., strips any
+    /// compression of the response (and any protocol version).
+    pub fn into_uncompressed(self) -> Self {
+        match self {
+            Self::GetCompressedResponse(request) => request.into_uncompressed(),
+            Self::GetResponseForProtocolVersion(_, request) => request.into_uncompressed(),
+            request => request,
+        }
+    }
+
+    /// Returns a request for the response to this request, for the given
+    /// protocol version (negotiated between the client and the server).
+    pub fn with_protocol_version(self, protocol_version: u64) -> Self {
+        let (_, request) = self.split_protocol_version();
+        Self::GetResponseForProtocolVersion(protocol_version, Box::new(request))
+    }
+
+    /// Splits the request into its negotiated protocol version (if any) and
+    /// the request for the response.
+    pub fn split_protocol_version(self) -> (Option<u64>, Self) {
+        match self {
+            Self::GetResponseForProtocolVersion(protocol_version, request) => {
+                (Some(protocol_version), *request)
+            }
+            request => (None, request),
+        }
     }
 }
 
@@ -103,6 +173,8 @@ pub enum StorageServiceResponse {
     StorageServerSummary(StorageServerSummary),
     TransactionOutputsWithProof(TransactionOutputListWithProof),
     TransactionsWithProof(TransactionListWithProof),
+    // Note: new variants must be appended to preserve the wire format.
+    CompressedResponse(CompressedData), // A compressed (BCS serialized) response
 }
 
 // TODO(philiphayes): is there a proc-macro for this?
@@ -119,6 +191,42 @@ impl StorageServiceResponse {
             Self::StorageServerSummary(_) => "storage_server_summary",
             Self::TransactionOutputsWithProof(_) => "transaction_outputs_with_proof",
             Self::TransactionsWithProof(_) => "transactions_with_proof",
+            Self::CompressedResponse(_) => "compressed_response",
+        }
+    }
+
+    /// Compresses the response
+    pub fn compress(&self) -> Result<Self> {
+        let raw_response = bcs::to_bytes(self).map_err(|error| {
+            StorageServiceError::InternalError(format!(
+                "Failed to serialize the response: {}",
+                error
+            ))
+        })?;
+        let compressed_data =
+            aptos_compression::compress(&raw_response, MAX_UNCOMPRESSED_RESPONSE_BYTES)
+                .map_err(|error| StorageServiceError::InternalError(error.to_string()))?;
+        Ok(Self::CompressedResponse(compressed_data))
+    }
+
+    /// Decompresses the response (if it is compressed)
+    pub fn decompress(self) -> Result<Self, UnexpectedResponseError> {
+        let compressed_data = match self {
+            Self::CompressedResponse(compressed_data) => compressed_data,
+            response => return Ok(response),
+        };
+        let raw_response =
+            aptos_compression::decompress(&compressed_data, MAX_UNCOMPRESSED_RESPONSE_BYTES)
+                .map_err(|error| UnexpectedResponseError(error.to_string()))?;
+        match bcs::from_bytes(&raw_response) {
+            Ok(Self::CompressedResponse(_)) => Err(UnexpectedResponseError(
+                "found a compressed response inside a compressed response".into(),
+            )),
+            Ok(response) => Ok(response),
+            Err(error) => Err(UnexpectedResponseError(format!(
+                "failed to deserialize the decompressed response: {}",
+                error
+            ))),
         }
     }
 }
@@ -396,6 +504,8 @@ impl ProtocolMetadata {
                     self.max_transaction_chunk_size >= chunk_size
                 })
             }),
+            GetCompressedResponse(request) => self.can_service(request),
+            GetResponseForProtocolVersion(_, request) => self.can_service(request),
         }
     }
 }
@@ -514,6 +624,8 @@ impl DataSummary {
 
                 can_serve_txns && can_create_proof
             }
+            GetCompressedResponse(request) => self.can_service(request),
+            GetResponseForProtocolVersion(_, request) => self.can_service(request),
         }
     }
 }
@@ -872,6 +984,74 @@ mod tests {
         assert!(!metadata.can_service(&get_account_state_chunks_request(200, 100, 200)));
     }
 
+    #[test]
+    fn test_compressed_request_can_service() {
+        let summary = StorageServerSummary {
+            protocol_metadata: ProtocolMetadata {
+                max_transaction_chunk_size: 100,
+                ..Default::default()
+            },
+            data_summary: DataSummary {
+                synced_ledger_info: Some(mock_ledger_info(250)),
+                transactions: Some(range(100, 200)),
+                ..Default::default()
+            },
+        };
+
+        let request = get_txns_request(225, 100, 199).into_compressed();
+        assert!(request.is_compressed());
+        assert!(summary.can_service(&request));
+        assert!(!summary.can_service(&get_txns_request(225, 100, 200).into_compressed()));
+        assert!(!summary.can_service(&get_txns_request(225, 150, 201).into_compressed()));
+
+        // compressing twice is a no-op, and the inner request can be recovered
+        assert_eq!(request.clone().into_compressed(), request);
+        assert_eq!(request.into_uncompressed(), get_txns_request(225, 100, 199));
+    }
+
+    #[test]
+    fn test_request_for_protocol_version() {
+        let request = get_txns_request(225, 100, 199).into_compressed();
+        let versioned_request = request.clone().with_protocol_version(3);
+        assert_eq!(versioned_request.get_label(), request.get_label());
+
+        // setting the version again replaces it, and the request can be recovered
+        let versioned_request = versioned_request.with_protocol_version(4);
+        assert_eq!(
+            versioned_request.clone().split_protocol_version(),
+            (Some(4), request.clone())
+        );
+        assert_eq!(request.clone().split_protocol_version(), (None, request));
+        assert_eq!(
+            versioned_request.into_uncompressed(),
+            get_txns_request(225, 100, 199)
+        );
+    }
+
+    #[test]
+    fn test_response_compression() {
+        let response = StorageServiceResponse::StorageServerSummary(StorageServerSummary {
+            protocol_metadata: ProtocolMetadata::default(),
+            data_summary: DataSummary {
+                synced_ledger_info: Some(mock_ledger_info(250)),
+                transactions: Some(range(100, 200)),
+                ..Default::default()
+            },
+        });
+
+        // uncompressed responses are left untouched
+        assert_eq!(response.clone().decompress().unwrap(), response);
+
+        let compressed_response = response.compress().unwrap();
+        assert_eq!(compressed_response.get_label(), "compressed_response");
+        assert_eq!(compressed_response.decompress().unwrap(), response);
+
+        // corrupt or nested compressed responses are rejected
+        assert_err!(StorageServiceResponse::CompressedResponse(vec![1, 2, 3]).decompress());
+        let nested_response = response.compress().unwrap().compress().unwrap();
+        assert_err!(nested_response.decompress());
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(1000))]
 
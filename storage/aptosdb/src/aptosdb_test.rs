@@ -84,6 +84,7 @@ fn test_error_if_version_is_pruned() {
     let mut pruner = Pruner::new(
         Arc::clone(&aptos_db.ledger_db),
         Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(aptos_db.state_store.node_cache()),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
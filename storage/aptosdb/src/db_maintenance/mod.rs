@@ -0,0 +1,351 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides offline maintenance operations on [`AptosDB`]: summarizing what is in the
+//! DB, truncating it back to an earlier version, pruning and compacting it on demand.
+//!
+//! None of these are safe to run against a DB which is being written to by a node, and the
+//! in-memory caches of the `AptosDB` instance are not updated, so the DB should be reopened before
+//! it is used again. They are exposed through `aptos-db-tool`.
+
+use crate::{
+    db_options::{ledger_db_column_families, state_merkle_db_column_families},
+    pruner::{db_pruner::DBPruner, utils, PrunerIndex},
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        epoch_by_version::EpochByVersionSchema,
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        ledger_info::LedgerInfoSchema,
+        stale_node_index::StaleNodeIndexSchema,
+        stale_state_value_index::StaleStateValueIndexSchema,
+        state_value::StateValueSchema,
+        transaction_accumulator::TransactionAccumulatorSchema,
+    },
+    AptosDB, LEDGER_DB_NAME, STATE_MERKLE_DB_NAME,
+};
+use anyhow::{ensure, format_err, Result};
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
+use aptos_logger::prelude::*;
+use aptos_types::{proof::position::Position, transaction::Version};
+use schemadb::{SchemaBatch, DB};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod test;
+
+/// The size on disk and the number of keys in a single column family.
+#[derive(Clone, Debug)]
+pub struct ColumnFamilySummary {
+    pub db_name: &'static str,
+    pub cf_name: &'static str,
+    pub estimated_num_keys: u64,
+    pub total_sst_files_size: u64,
+}
+
+/// A snapshot of what is currently stored in the DB.
+#[derive(Clone, Debug)]
+pub struct DbSummary {
+    pub latest_version: Option<Version>,
+    pub latest_ledger_info_epoch: Option<u64>,
+    pub latest_ledger_info_version: Option<Version>,
+    pub first_txn_version: Option<Version>,
+    pub latest_state_checkpoint_version: Option<Version>,
+    pub ledger_pruner_progress: Option<Version>,
+    pub state_merkle_pruner_progress: Option<Version>,
+    pub column_families: Vec<ColumnFamilySummary>,
+}
+
+impl AptosDB {
+    /// Summarizes the ledger and state merkle DBs, including the size of every column family.
+    pub fn get_db_summary(&self) -> Result<DbSummary> {
+        let latest_ledger_info = self.ledger_store.get_latest_ledger_info_option();
+
+        let mut column_families = Vec::new();
+        for (db_name, db, cf_names) in [
+            (LEDGER_DB_NAME, &self.ledger_db, ledger_db_column_families()),
+            (
+                STATE_MERKLE_DB_NAME,
+                &self.state_merkle_db,
+                state_merkle_db_column_families(),
+            ),
+        ] {
+            for cf_name in cf_names {
+                column_families.push(ColumnFamilySummary {
+                    db_name,
+                    cf_name,
+                    estimated_num_keys: db.get_property(cf_name, "rocksdb.estimate-num-keys")?,
+                    total_sst_files_size: db
+                        .get_property(cf_name, "rocksdb.total-sst-files-size")?,
+                });
+            }
+        }
+
+        Ok(DbSummary {
+            latest_version: self
+                .ledger_store
+                .get_latest_transaction_info_option()?
+                .map(|(version, _)| version),
+            latest_ledger_info_epoch: latest_ledger_info
+                .as_ref()
+                .map(|li| li.ledger_info().epoch()),
+            latest_ledger_info_version: latest_ledger_info
+                .as_ref()
+                .map(|li| li.ledger_info().version()),
+            first_txn_version: self.transaction_store.get_first_txn_version()?,
+            latest_state_checkpoint_version: self
+                .state_store
+                .latest_checkpoint()
+                .map(|(version, _)| version),
+            ledger_pruner_progress: get_pruner_progress(
+                &self.ledger_db,
+                DbMetadataKey::LedgerPrunerProgress,
+            )?,
+            state_merkle_pruner_progress: get_pruner_progress(
+                &self.state_merkle_db,
+                DbMetadataKey::StateMerklePrunerProgress,
+            )?,
+            column_families,
+        })
+    }
+
+    /// Deletes everything committed after `target_version` from both the ledger DB and the state
+    /// merkle DB, so that `target_version` becomes the latest version.
+    ///
+    /// The state merkle DB is truncated first, then the ledger DB is truncated from the top in
+    /// chunks of `batch_size` versions, each written atomically. The ledger DB therefore always
+    /// holds a prefix of the original history and an interrupted truncation can simply be rerun.
+    pub fn truncate(&self, target_version: Version, batch_size: usize) -> Result<()> {
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let (latest_version, _) = self.ledger_store.get_latest_transaction_info()?;
+        ensure!(
+            target_version <= latest_version,
+            "Target version {} is newer than the latest version {}.",
+            target_version,
+            latest_version,
+        );
+        if let Some(first_txn_version) = self.transaction_store.get_first_txn_version()? {
+            ensure!(
+                target_version >= first_txn_version,
+                "Target version {} is pruned, the first transaction in DB is at version {}.",
+                target_version,
+                first_txn_version,
+            );
+        }
+        let state_checkpoint_version = self
+            .state_store
+            .find_latest_persisted_version_less_than(target_version + 1)?
+            .ok_or_else(|| {
+                format_err!(
+                    "No state snapshot at or before target version {}.",
+                    target_version
+                )
+            })?;
+        info!(
+            target_version = target_version,
+            latest_version = latest_version,
+            state_checkpoint_version = state_checkpoint_version,
+            "Truncating AptosDB."
+        );
+
+        self.truncate_state_merkle_db(target_version)?;
+
+        let mut end = latest_version + 1;
+        while end > target_version + 1 {
+            let begin = std::cmp::max(target_version + 1, end.saturating_sub(batch_size as u64));
+            self.truncate_ledger_db(begin, end)?;
+            info!(version = begin - 1, "Truncated ledger DB.");
+            end = begin;
+        }
+        Ok(())
+    }
+
+    /// Removes all Jellyfish Merkle nodes created after `target_version`, together with the
+    /// stale node indices recorded after it.
+    fn truncate_state_merkle_db(&self, target_version: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+        batch.delete_range::<JellyfishMerkleNodeSchema>(
+            &NodeKey::new_empty_path(target_version + 1),
+            &NodeKey::new_empty_path(Version::MAX),
+        )?;
+        batch.delete_range::<StaleNodeIndexSchema>(
+            &StaleNodeIndex {
+                stale_since_version: target_version + 1,
+                node_key: NodeKey::new_empty_path(0),
+            },
+            &StaleNodeIndex {
+                stale_since_version: Version::MAX,
+                node_key: NodeKey::new_empty_path(0),
+            },
+        )?;
+        self.state_merkle_db.write_schemas(batch)?;
+        // Truncated nodes may be recreated with different content.
+        self.state_store.node_cache().clear();
+        Ok(())
+    }
+
+    /// Removes versions in [begin, end) from the ledger DB, `end` must be the current latest
+    /// version plus one.
+    fn truncate_ledger_db(&self, begin: Version, end: Version) -> Result<()> {
+        let mut batch = SchemaBatch::new();
+
+        let transactions = self
+            .transaction_store
+            .get_transaction_iter(begin, (end - begin) as usize)?
+            .collect::<Result<Vec<_>>>()?;
+        self.transaction_store
+            .prune_transaction_by_hash(&transactions, &mut batch)?;
+        self.transaction_store
+            .prune_transaction_by_account(&transactions, &mut batch)?;
+        self.transaction_store
+            .prune_transaction_schema(begin, end, &mut batch)?;
+        self.transaction_store
+            .prune_transaction_info_schema(begin, end, &mut batch)?;
+
+        // State values are keyed by state key first, they are found through the write sets.
+        let write_sets = self.transaction_store.get_write_sets(begin, end)?;
+        for (version, write_set) in (begin..end).zip(write_sets.iter()) {
+            for (state_key, _) in write_set.iter() {
+                batch.delete::<StateValueSchema>(&(state_key.clone(), version))?;
+            }
+        }
+        // The truncated writes no longer make older values stale. Versions written again after the
+        // truncation must be picked up by the stale value pruner, so pull its progress back too.
+        let mut iter = self
+            .ledger_db
+            .iter::<StaleStateValueIndexSchema>(Default::default())?;
+        iter.seek(&begin)?;
+        for res in iter {
+            let (index, _) = res?;
+            batch.delete::<StaleStateValueIndexSchema>(&index)?;
+        }
+        if let Some(progress) =
+            get_pruner_progress(&self.ledger_db, DbMetadataKey::StateValuePrunerProgress)?
+        {
+            if progress >= begin {
+                match begin.checked_sub(1) {
+                    Some(version) => batch.put::<DbMetadataSchema>(
+                        &DbMetadataKey::StateValuePrunerProgress,
+                        &DbMetadataValue::Version(version),
+                    )?,
+                    None => batch
+                        .delete::<DbMetadataSchema>(&DbMetadataKey::StateValuePrunerProgress)?,
+                }
+            }
+        }
+        self.transaction_store
+            .prune_write_set(begin, end, &mut batch)?;
+
+        self.event_store.prune_events(begin, end, &mut batch)?;
+        self.ledger_store
+            .prune_ledger_counters(begin, end, &mut batch)?;
+
+        // In post-order, every node of the accumulator that covers a truncated leaf comes at or
+        // after the first truncated leaf, and every node before it is still frozen.
+        let mut iter = self
+            .ledger_db
+            .iter::<TransactionAccumulatorSchema>(Default::default())?;
+        iter.seek(&Position::from_leaf_index(begin))?;
+        for res in iter {
+            let (position, _) = res?;
+            batch.delete::<TransactionAccumulatorSchema>(&position)?;
+        }
+
+        batch.delete_range::<EpochByVersionSchema>(&begin, &Version::MAX)?;
+        let mut iter = self
+            .ledger_db
+            .rev_iter::<LedgerInfoSchema>(Default::default())?;
+        iter.seek_to_last();
+        for res in iter {
+            let (epoch, ledger_info) = res?;
+            if ledger_info.ledger_info().version() < begin {
+                break;
+            }
+            batch.delete::<LedgerInfoSchema>(&epoch)?;
+        }
+
+        self.ledger_db.write_schemas(batch)
+    }
+
+    /// Prunes the DB so that only the latest `state_store_prune_window` versions of the state
+    /// and the latest `ledger_prune_window` versions of the ledger history are kept, blocking
+    /// until done. Must be called on a DB opened without a pruner.
+    pub fn prune(
+        &self,
+        state_store_prune_window: Option<Version>,
+        ledger_prune_window: Option<Version>,
+        batch_size: usize,
+    ) -> Result<()> {
+        ensure!(
+            self.pruner.is_none(),
+            "Open the DB without a pruner to prune it manually."
+        );
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let (latest_version, _) = self.ledger_store.get_latest_transaction_info()?;
+
+        let db_pruners = utils::create_db_pruners(
+            self.ledger_db.clone(),
+            self.state_merkle_db.clone(),
+            Arc::clone(self.state_store.node_cache()),
+        );
+        for (pruner_index, window) in [
+            (PrunerIndex::StateStorePrunerIndex, state_store_prune_window),
+            (PrunerIndex::LedgerPrunerIndex, ledger_prune_window),
+        ] {
+            let target_version = window.map_or(0, |window| latest_version.saturating_sub(window));
+            db_pruners[pruner_index as usize]
+                .lock()
+                .set_target_version(target_version);
+        }
+
+        loop {
+            let mut made_progress = false;
+            let mut ledger_db_batch = SchemaBatch::new();
+            for db_pruner in &db_pruners {
+                let db_pruner = db_pruner.lock();
+                if !db_pruner.is_pruning_pending() {
+                    continue;
+                }
+                let min_readable_version = db_pruner.min_readable_version();
+                if db_pruner.prune(&mut ledger_db_batch, batch_size as u64)? > min_readable_version
+                {
+                    made_progress = true;
+                }
+            }
+            self.ledger_db.write_schemas(ledger_db_batch)?;
+            if !made_progress {
+                break;
+            }
+            info!(
+                state_store_min_readable_version = db_pruners
+                    [PrunerIndex::StateStorePrunerIndex as usize]
+                    .lock()
+                    .min_readable_version(),
+                ledger_min_readable_version = db_pruners[PrunerIndex::LedgerPrunerIndex as usize]
+                    .lock()
+                    .min_readable_version(),
+                "Pruning in progress."
+            );
+        }
+        Ok(())
+    }
+
+    /// Runs a full manual compaction over every column family of both DBs.
+    pub fn compact(&self) -> Result<()> {
+        for (db, cf_names) in [
+            (&self.ledger_db, ledger_db_column_families()),
+            (&self.state_merkle_db, state_merkle_db_column_families()),
+        ] {
+            for cf_name in cf_names {
+                info!(cf_name = cf_name, "Compacting column family.");
+                db.compact_cf(cf_name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn get_pruner_progress(db: &DB, key: DbMetadataKey) -> Result<Option<Version>> {
+    Ok(db
+        .get::<DbMetadataSchema>(&key)?
+        .map(|value| value.expect_version()))
+}
@@ -0,0 +1,78 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    test_helper::{arb_blocks_to_commit, verify_committed_transactions},
+    AptosDB,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{ledger_info::LedgerInfoWithSignatures, transaction::TransactionToCommit};
+use proptest::prelude::*;
+use storage_interface::DbReader;
+
+fn test_truncate_and_recommit_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let mut cur_ver = 0;
+    for (txns_to_commit, ledger_info_with_sigs) in &input {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let latest_version = cur_ver - 1;
+
+    // Truncate back to the end of the first block.
+    let target_version = input[0].0.len() as u64 - 1;
+    db.truncate(target_version, 3 /* batch_size */).unwrap();
+    drop(db);
+
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let summary = db.get_db_summary().unwrap();
+    assert_eq!(summary.latest_version, Some(target_version));
+    assert!(summary.latest_ledger_info_version.unwrap() <= target_version);
+    assert!(summary.latest_state_checkpoint_version.unwrap() <= target_version);
+    if latest_version > target_version {
+        assert!(db
+            .get_transaction_by_version(target_version + 1, latest_version, false)
+            .is_err());
+    }
+
+    // The truncated history can be committed again and reads back the same as before.
+    let mut cur_ver = target_version + 1;
+    for (txns_to_commit, ledger_info_with_sigs) in &input[1..] {
+        db.save_transactions(txns_to_commit, cur_ver, Some(ledger_info_with_sigs))
+            .unwrap();
+        cur_ver += txns_to_commit.len() as u64;
+    }
+    let latest_ledger_info = &input.last().unwrap().1;
+    let mut cur_ver = 0;
+    for (batch_idx, (txns_to_commit, _)) in input.iter().enumerate() {
+        verify_committed_transactions(
+            &db,
+            txns_to_commit,
+            cur_ver,
+            latest_ledger_info,
+            batch_idx + 1 == input.len(), /* is_latest */
+        );
+        cur_ver += txns_to_commit.len() as u64;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_truncate_and_recommit(input in arb_blocks_to_commit()) {
+        test_truncate_and_recommit_impl(input);
+    }
+}
+
+#[test]
+fn test_truncate_rejects_future_version() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    // Nothing is committed yet.
+    assert!(db.truncate(0, 1 /* batch_size */).is_err());
+}
@@ -0,0 +1,318 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an integrity verifier which walks [`AptosDB`] from genesis and recomputes
+//! everything a `TransactionInfo` or a `LedgerInfoWithSignatures` commits to:
+//!
+//! - the transaction, write set and event accumulator root hashes of every transaction,
+//! - the Jellyfish Merkle root hash at every state checkpoint,
+//! - the transaction accumulator, both against the signed ledger infos and the stored one,
+//! - the signatures on every ledger info, against the validator set of its epoch.
+//!
+//! Verification runs in batches. After each batch the progress is handed to the caller so it can
+//! be persisted and the verification resumed from there later.
+
+use crate::{
+    db_maintenance::get_pruner_progress,
+    schema::{db_metadata::DbMetadataKey, ledger_info::LedgerInfoSchema},
+    AptosDB,
+};
+use anyhow::{ensure, Result};
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher, TransactionAccumulatorHasher},
+    HashValue,
+};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    epoch_change::Verifier, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+    proof::accumulator::InMemoryAccumulator, transaction::Version, waypoint::Waypoint,
+};
+use itertools::izip;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(test)]
+mod test;
+
+/// How far the verification got. Everything before `next_version` has been verified.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct IntegrityVerifierProgress {
+    pub next_version: Version,
+    /// The frozen subtree roots of the recomputed transaction accumulator at `next_version`.
+    frozen_subtree_roots: Vec<HashValue>,
+    /// The first epoch whose ledger info has not been verified yet.
+    next_epoch: u64,
+    /// The validator set signing ledger infos of `next_epoch`, `None` before the first ledger
+    /// info is seen.
+    epoch_state: Option<EpochState>,
+}
+
+/// The first place where the data in the DB does not match what it has been committed to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Divergence {
+    pub version: Version,
+    pub reason: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Divergence at version {}: {}", self.version, self.reason)
+    }
+}
+
+impl AptosDB {
+    /// Verifies the DB from `progress` up to the latest version, `batch_size` versions at a time,
+    /// calling `on_checkpoint` with the new progress after every batch. Returns the first
+    /// divergence found, if any. An `Err` means the verification could not be carried out, e.g.
+    /// because data is missing.
+    ///
+    /// The first ledger info in the DB is the root of trust for the validator sets. If
+    /// `trusted_waypoint` is given, that ledger info has to match it.
+    pub fn verify_integrity<F>(
+        &self,
+        progress: &mut IntegrityVerifierProgress,
+        trusted_waypoint: Option<Waypoint>,
+        batch_size: usize,
+        mut on_checkpoint: F,
+    ) -> Result<Option<Divergence>>
+    where
+        F: FnMut(&IntegrityVerifierProgress) -> Result<()>,
+    {
+        ensure!(batch_size > 0, "Batch size must be positive.");
+        let latest_version = match self.ledger_store.get_latest_transaction_info_option()? {
+            Some((version, _)) => version,
+            None => return Ok(None),
+        };
+        if progress.next_version == 0 {
+            if let Some(first_txn_version) = self.transaction_store.get_first_txn_version()? {
+                ensure!(
+                    first_txn_version == 0,
+                    "Transactions before version {} are pruned, cannot verify from genesis.",
+                    first_txn_version,
+                );
+            }
+        }
+        let state_pruner_progress = get_pruner_progress(
+            &self.state_merkle_db,
+            DbMetadataKey::StateMerklePrunerProgress,
+        )?
+        .unwrap_or(0);
+
+        while progress.next_version <= latest_version {
+            let begin = progress.next_version;
+            let end = std::cmp::min(begin + batch_size as u64, latest_version + 1);
+            let mut new_progress = progress.clone();
+            if let Some(divergence) = self.verify_batch(
+                &mut new_progress,
+                end,
+                state_pruner_progress,
+                trusted_waypoint,
+            )? {
+                return Ok(Some(divergence));
+            }
+            *progress = new_progress;
+            info!(next_version = end, "Verified AptosDB integrity.");
+            on_checkpoint(progress)?;
+        }
+        Ok(None)
+    }
+
+    /// Verifies versions [progress.next_version, end) and the ledger infos among them.
+    fn verify_batch(
+        &self,
+        progress: &mut IntegrityVerifierProgress,
+        end: Version,
+        state_pruner_progress: Version,
+        trusted_waypoint: Option<Waypoint>,
+    ) -> Result<Option<Divergence>> {
+        let begin = progress.next_version;
+        let num_versions = (end - begin) as usize;
+        let mut accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::new(
+            progress.frozen_subtree_roots.clone(),
+            begin,
+        )?;
+
+        let mut ledger_infos = self
+            .get_ledger_infos_before(progress.next_epoch, end)?
+            .into_iter()
+            .peekable();
+        // A ledger info of the epoch in progress is replaced as new blocks are committed, so one
+        // may point before the current batch when resuming. Check it against the stored
+        // accumulator, which was verified when the previous batches were.
+        while let Some(ledger_info) = ledger_infos.next_if(|li| li.ledger_info().version() < begin)
+        {
+            let version = ledger_info.ledger_info().version();
+            let root_hash = self.ledger_store.get_root_hash(version)?;
+            if let Some(divergence) =
+                verify_ledger_info(progress, &ledger_info, root_hash, trusted_waypoint)
+            {
+                return Ok(Some(divergence));
+            }
+        }
+
+        let write_sets = self.transaction_store.get_write_sets(begin, end)?;
+        for (version, txn, txn_info, write_set, events) in izip!(
+            begin..end,
+            self.transaction_store
+                .get_transaction_iter(begin, num_versions)?,
+            self.ledger_store
+                .get_transaction_info_iter(begin, num_versions)?,
+            write_sets.iter(),
+            self.event_store
+                .get_events_by_version_iter(begin, num_versions)?,
+        ) {
+            let (txn, txn_info, events) = (txn?, txn_info?, events?);
+            let divergence = |reason: String| Ok(Some(Divergence { version, reason }));
+
+            if txn.hash() != txn_info.transaction_hash() {
+                return divergence(format!(
+                    "Transaction hash {:x} does not match {:x} in TransactionInfo.",
+                    txn.hash(),
+                    txn_info.transaction_hash(),
+                ));
+            }
+            if write_set.hash() != txn_info.state_change_hash() {
+                return divergence(format!(
+                    "Write set hash {:x} does not match {:x} in TransactionInfo.",
+                    write_set.hash(),
+                    txn_info.state_change_hash(),
+                ));
+            }
+            let event_hashes: Vec<_> = events.iter().map(CryptoHash::hash).collect();
+            let event_root_hash =
+                InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes)
+                    .root_hash();
+            if event_root_hash != txn_info.event_root_hash() {
+                return divergence(format!(
+                    "Event root hash {:x} does not match {:x} in TransactionInfo.",
+                    event_root_hash,
+                    txn_info.event_root_hash(),
+                ));
+            }
+            if let Some(state_checkpoint_hash) = txn_info.state_checkpoint_hash() {
+                match self.state_store.get_root_hash_option(version)? {
+                    Some(root_hash) if root_hash != state_checkpoint_hash => {
+                        return divergence(format!(
+                            "State root hash {:x} does not match checkpoint {:x} in \
+                             TransactionInfo.",
+                            root_hash, state_checkpoint_hash,
+                        ));
+                    }
+                    None if version >= state_pruner_progress => {
+                        return divergence(format!(
+                            "State root for checkpoint {:x} is missing.",
+                            state_checkpoint_hash,
+                        ));
+                    }
+                    // Either matching, or pruned.
+                    _ => (),
+                }
+            }
+
+            accumulator = accumulator.append(&[txn_info.hash()]);
+            while let Some(ledger_info) =
+                ledger_infos.next_if(|li| li.ledger_info().version() == version)
+            {
+                if let Some(divergence) = verify_ledger_info(
+                    progress,
+                    &ledger_info,
+                    accumulator.root_hash(),
+                    trusted_waypoint,
+                ) {
+                    return Ok(Some(divergence));
+                }
+            }
+        }
+
+        let stored_root_hash = self.ledger_store.get_root_hash(end - 1)?;
+        if stored_root_hash != accumulator.root_hash() {
+            return Ok(Some(Divergence {
+                version: end - 1,
+                reason: format!(
+                    "Stored transaction accumulator root {:x} does not match recomputed {:x}.",
+                    stored_root_hash,
+                    accumulator.root_hash(),
+                ),
+            }));
+        }
+
+        progress.next_version = end;
+        progress.frozen_subtree_roots = accumulator.frozen_subtree_roots().clone();
+        Ok(None)
+    }
+
+    /// Returns the ledger infos starting from `start_epoch` whose version is before `end`.
+    fn get_ledger_infos_before(
+        &self,
+        start_epoch: u64,
+        end: Version,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        let mut iter = self
+            .ledger_db
+            .iter::<LedgerInfoSchema>(Default::default())?;
+        iter.seek(&start_epoch)?;
+        let mut ledger_infos = Vec::new();
+        for res in iter {
+            let (_, ledger_info) = res?;
+            if ledger_info.ledger_info().version() >= end {
+                break;
+            }
+            ledger_infos.push(ledger_info);
+        }
+        Ok(ledger_infos)
+    }
+}
+
+/// Verifies a ledger info against the recomputed accumulator root at its version and the
+/// validator set of its epoch, then moves `progress` to the next epoch if it ends this one.
+fn verify_ledger_info(
+    progress: &mut IntegrityVerifierProgress,
+    ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    root_hash: HashValue,
+    trusted_waypoint: Option<Waypoint>,
+) -> Option<Divergence> {
+    let ledger_info = ledger_info_with_sigs.ledger_info();
+    let divergence = |reason: String| {
+        Some(Divergence {
+            version: ledger_info.version(),
+            reason,
+        })
+    };
+
+    if ledger_info.transaction_accumulator_hash() != root_hash {
+        return divergence(format!(
+            "LedgerInfo of epoch {} has accumulator root {:x}, recomputed {:x}.",
+            ledger_info.epoch(),
+            ledger_info.transaction_accumulator_hash(),
+            root_hash,
+        ));
+    }
+    match &progress.epoch_state {
+        Some(epoch_state) => {
+            if let Err(e) = epoch_state.verify(ledger_info_with_sigs) {
+                return divergence(format!(
+                    "LedgerInfo of epoch {} failed verification: {}",
+                    ledger_info.epoch(),
+                    e,
+                ));
+            }
+        }
+        None => {
+            if let Some(waypoint) = trusted_waypoint {
+                if let Err(e) = waypoint.verify(ledger_info) {
+                    return divergence(format!(
+                        "LedgerInfo of epoch {} does not match the trusted waypoint: {}",
+                        ledger_info.epoch(),
+                        e,
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(next_epoch_state) = ledger_info.next_epoch_state() {
+        progress.epoch_state = Some(next_epoch_state.clone());
+        progress.next_epoch = ledger_info.epoch() + 1;
+    }
+    None
+}
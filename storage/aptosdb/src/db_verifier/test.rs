@@ -0,0 +1,94 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_verifier::IntegrityVerifierProgress, schema::transaction::TransactionSchema,
+    test_helper::arb_blocks_to_commit, AptosDB,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    transaction::{Transaction, TransactionToCommit, Version},
+};
+use proptest::prelude::*;
+
+fn save_blocks(
+    db: &AptosDB,
+    blocks: &[(Vec<TransactionToCommit>, LedgerInfoWithSignatures)],
+    mut first_version: Version,
+) -> Version {
+    for (txns_to_commit, ledger_info_with_sigs) in blocks {
+        db.save_transactions(txns_to_commit, first_version, Some(ledger_info_with_sigs))
+            .unwrap();
+        first_version += txns_to_commit.len() as u64;
+    }
+    first_version
+}
+
+fn test_verify_incrementally_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+
+    // Verify what is committed so far, then resume once the rest is committed.
+    let num_first_blocks = (input.len() + 1) / 2;
+    let next_version = save_blocks(&db, &input[..num_first_blocks], 0);
+    let mut progress = IntegrityVerifierProgress::default();
+    let mut checkpoints = vec![];
+    let divergence = db
+        .verify_integrity(&mut progress, None, 3 /* batch_size */, |p| {
+            checkpoints.push(p.next_version);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(divergence, None);
+    assert_eq!(progress.next_version, next_version);
+    assert_eq!(checkpoints.last(), Some(&next_version));
+
+    let num_versions = save_blocks(&db, &input[num_first_blocks..], next_version);
+    let divergence = db
+        .verify_integrity(&mut progress, None, 3 /* batch_size */, |_| Ok(()))
+        .unwrap();
+    assert_eq!(divergence, None);
+    assert_eq!(progress.next_version, num_versions);
+}
+
+fn test_verify_detects_divergence_impl(
+    input: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    save_blocks(&db, &input, 0);
+
+    // Every block starts with a user transaction and ends with a state checkpoint.
+    let corrupted_version = input[0].0.len() as u64 - 2;
+    db.ledger_db
+        .put::<TransactionSchema>(&corrupted_version, &Transaction::StateCheckpoint)
+        .unwrap();
+
+    let divergence = db
+        .verify_integrity(
+            &mut IntegrityVerifierProgress::default(),
+            None,
+            100, /* batch_size */
+            |_| Ok(()),
+        )
+        .unwrap()
+        .unwrap();
+    assert_eq!(divergence.version, corrupted_version);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_verify_incrementally(input in arb_blocks_to_commit()) {
+        test_verify_incrementally_impl(input);
+    }
+
+    #[test]
+    fn test_verify_detects_divergence(input in arb_blocks_to_commit()) {
+        test_verify_detects_divergence_impl(input);
+    }
+}
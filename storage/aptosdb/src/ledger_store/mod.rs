@@ -133,6 +133,16 @@ impl LedgerStore {
             .store(Arc::new(Some(ledger_info_with_sigs)));
     }
 
+    /// Returns the lowest epoch with a stored ledger info, which is only above 0 if the node
+    /// didn't sync from genesis (e.g., it was bootstrapped from a state snapshot at a waypoint).
+    pub fn get_first_ledger_info_epoch(&self) -> Result<Option<u64>> {
+        let mut iter = self.db.iter::<LedgerInfoSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        iter.next()
+            .map(|res| res.map(|(epoch, _)| epoch))
+            .transpose()
+    }
+
     pub fn get_latest_ledger_info_in_epoch(&self, epoch: u64) -> Result<LedgerInfoWithSignatures> {
         self.db.get::<LedgerInfoSchema>(&epoch)?.ok_or_else(|| {
             AptosDbError::NotFound(format!("Last LedgerInfo of epoch {}", epoch)).into()
@@ -14,6 +14,8 @@
 pub mod test_helper;
 
 pub mod backup;
+pub mod db_maintenance;
+pub mod db_verifier;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -52,8 +54,11 @@ use crate::{
     system_store::SystemStore,
     transaction_store::TransactionStore,
 };
-use anyhow::{ensure, Result};
-use aptos_config::config::{RocksdbConfig, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use anyhow::{ensure, format_err, Result};
+use aptos_config::config::{
+    RocksdbConfig, StorageConfig, StoragePrunerConfig, DEFAULT_STATE_MERKLE_NODE_CACHE_SIZE,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
 use aptos_crypto::hash::{HashValue, SPARSE_MERKLE_PLACEHOLDER_HASH};
 use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
@@ -72,7 +77,7 @@ use aptos_types::{
     state_store::{
         state_key::StateKey,
         state_key_prefix::StateKeyPrefix,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateSnapshotProgress, StateValue, StateValueChunkWithProof},
     },
     transaction::{
         AccountTransactionsWithProof, Transaction, TransactionInfo, TransactionListWithProof,
@@ -106,6 +111,9 @@ const MAX_LIMIT: u64 = 5000;
 // TODO: Either implement an iteration API to allow a very old client to loop through a long history
 // or guarantee that there is always a recent enough waypoint and client knows to boot from there.
 const MAX_NUM_EPOCH_ENDING_LEDGER_INFO: usize = 100;
+// The smallest prune window that can be set while the node is running, so that a mistyped window
+// cannot wipe out the recent history that state sync and the API rely on.
+const MIN_RUNTIME_PRUNE_WINDOW: usize = 100_000;
 static ROCKSDB_PROPERTY_MAP: Lazy<HashMap<&str, String>> = Lazy::new(|| {
     [
         "rocksdb.num-immutable-mem-table",
@@ -258,9 +266,15 @@ impl AptosDB {
         ledger_rocksdb: DB,
         state_merkle_rocksdb: DB,
         storage_pruner_config: StoragePrunerConfig,
+        state_merkle_node_cache_size: usize,
     ) -> Self {
         let arc_ledger_rocksdb = Arc::new(ledger_rocksdb);
         let arc_state_merkle_rocksdb = Arc::new(state_merkle_rocksdb);
+        let state_store = Arc::new(StateStore::new(
+            Arc::clone(&arc_ledger_rocksdb),
+            Arc::clone(&arc_state_merkle_rocksdb),
+            state_merkle_node_cache_size,
+        ));
         let pruner = if storage_pruner_config.ledger_prune_window.is_none()
             && storage_pruner_config.state_store_prune_window.is_none()
         {
@@ -269,6 +283,7 @@ impl AptosDB {
             Some(Pruner::new(
                 Arc::clone(&arc_ledger_rocksdb),
                 Arc::clone(&arc_state_merkle_rocksdb),
+                Arc::clone(state_store.node_cache()),
                 storage_pruner_config,
             ))
         };
@@ -277,10 +292,7 @@ impl AptosDB {
             state_merkle_db: Arc::clone(&arc_state_merkle_rocksdb),
             event_store: Arc::new(EventStore::new(Arc::clone(&arc_ledger_rocksdb))),
             ledger_store: Arc::new(LedgerStore::new(Arc::clone(&arc_ledger_rocksdb))),
-            state_store: Arc::new(StateStore::new(
-                Arc::clone(&arc_ledger_rocksdb),
-                Arc::clone(&arc_state_merkle_rocksdb),
-            )),
+            state_store,
             system_store: Arc::new(SystemStore::new(Arc::clone(&arc_ledger_rocksdb))),
             transaction_store: Arc::new(TransactionStore::new(Arc::clone(&arc_ledger_rocksdb))),
             pruner,
@@ -296,6 +308,34 @@ impl AptosDB {
         readonly: bool,
         storage_pruner_config: StoragePrunerConfig,
         rocksdb_config: RocksdbConfig,
+    ) -> Result<Self> {
+        Self::open_internal(
+            db_root_path,
+            readonly,
+            storage_pruner_config,
+            rocksdb_config,
+            DEFAULT_STATE_MERKLE_NODE_CACHE_SIZE,
+        )
+    }
+
+    /// Opens the DB under `config.dir()` with the pruner, RocksDB and node cache settings of
+    /// `config`, the way a node does.
+    pub fn open_with_storage_config(config: &StorageConfig, readonly: bool) -> Result<Self> {
+        Self::open_internal(
+            config.dir(),
+            readonly,
+            config.storage_pruner_config,
+            config.rocksdb_config,
+            config.state_merkle_node_cache_size,
+        )
+    }
+
+    fn open_internal<P: AsRef<Path> + Clone>(
+        db_root_path: P,
+        readonly: bool,
+        storage_pruner_config: StoragePrunerConfig,
+        rocksdb_config: RocksdbConfig,
+        state_merkle_node_cache_size: usize,
     ) -> Result<Self> {
         ensure!(
             storage_pruner_config.eq(&NO_OP_STORAGE_PRUNER_CONFIG) || !readonly,
@@ -342,7 +382,12 @@ impl AptosDB {
             )
         };
 
-        let ret = Self::new_with_dbs(ledger_db, state_merkle_db, storage_pruner_config);
+        let ret = Self::new_with_dbs(
+            ledger_db,
+            state_merkle_db,
+            storage_pruner_config,
+            state_merkle_node_cache_size,
+        );
         info!(
             ledger_db_path = ledger_db_path,
             state_merkle_db_path = state_merkle_db_path,
@@ -383,6 +428,7 @@ impl AptosDB {
                 state_merkle_db_column_families(),
             )?,
             NO_OP_STORAGE_PRUNER_CONFIG,
+            DEFAULT_STATE_MERKLE_NODE_CACHE_SIZE,
         ))
     }
 
@@ -732,10 +778,12 @@ impl DbReader for AptosDB {
         gauged_api("get_latest_state_value", || {
             let ledger_info_with_sigs = self.ledger_store.get_latest_ledger_info()?;
             let version = ledger_info_with_sigs.ledger_info().version();
-            let (blob, _proof) = self
+            // The flat index keeps deletions as empty values, which callers expect to see as
+            // a missing key
+            Ok(self
                 .state_store
-                .get_state_value_with_proof_by_version(&state_key, version)?;
-            Ok(blob)
+                .get_state_value_by_version(&state_key, version)?
+                .filter(|state_value| state_value.maybe_bytes.is_some()))
         })
     }
 
@@ -905,6 +953,13 @@ impl DbReader for AptosDB {
         })
     }
 
+    /// Get the lowest epoch with a stored ledger info.
+    fn get_first_ledger_info_epoch(&self) -> Result<Option<u64>> {
+        gauged_api("get_first_ledger_info_epoch", || {
+            self.ledger_store.get_first_ledger_info_epoch()
+        })
+    }
+
     /// Get the first version that write set starts existent.
     fn get_first_write_set_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_write_set_version", || {
@@ -1206,6 +1261,12 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>> {
+        gauged_api("get_state_snapshot_progress", || {
+            self.state_store.get_state_snapshot_progress()
+        })
+    }
+
     fn get_state_prune_window(&self) -> Result<Option<usize>> {
         gauged_api("get_state_prune_window", || {
             Ok(self
@@ -1223,6 +1284,24 @@ impl DbReader for AptosDB {
                 .map(|x| x.get_ledger_pruner_window() as usize))
         })
     }
+
+    fn get_state_min_readable_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_state_min_readable_version", || {
+            Ok(self
+                .pruner
+                .as_ref()
+                .map(|x| x.get_min_readable_state_store_version()))
+        })
+    }
+
+    fn get_ledger_min_readable_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_ledger_min_readable_version", || {
+            Ok(self
+                .pruner
+                .as_ref()
+                .map(|x| x.get_min_readable_ledger_version()))
+        })
+    }
 }
 
 impl DbWriter for AptosDB {
@@ -1465,6 +1544,42 @@ impl DbWriter for AptosDB {
             self.ledger_db.write_schemas(db_batch)
         })
     }
+
+    fn set_state_prune_window(&self, window: usize) -> Result<()> {
+        gauged_api("set_state_prune_window", || {
+            ensure!(
+                window >= MIN_RUNTIME_PRUNE_WINDOW,
+                "Prune window {} is smaller than the minimum of {}.",
+                window,
+                MIN_RUNTIME_PRUNE_WINDOW
+            );
+            let pruner = self
+                .pruner
+                .as_ref()
+                .ok_or_else(|| format_err!("Pruning is disabled."))?;
+            info!(window = window, "Updating state prune window.");
+            pruner.set_state_store_pruner_window(window as Version);
+            Ok(())
+        })
+    }
+
+    fn set_ledger_prune_window(&self, window: usize) -> Result<()> {
+        gauged_api("set_ledger_prune_window", || {
+            ensure!(
+                window >= MIN_RUNTIME_PRUNE_WINDOW,
+                "Prune window {} is smaller than the minimum of {}.",
+                window,
+                MIN_RUNTIME_PRUNE_WINDOW
+            );
+            let pruner = self
+                .pruner
+                .as_ref()
+                .ok_or_else(|| format_err!("Pruning is disabled."))?;
+            info!(window = window, "Updating ledger prune window.");
+            pruner.set_ledger_pruner_window(window as Version);
+            Ok(())
+        })
+    }
 }
 
 // Convert requested range and order to a range in ascending order.
@@ -41,6 +41,7 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
     let pruner = Pruner::new(
         Arc::clone(&aptos_db.ledger_db),
         Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(aptos_db.state_store.node_cache()),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
@@ -11,6 +11,7 @@ use crate::{
             transaction_store_pruner::TransactionStorePruner, write_set_pruner::WriteSetPruner,
         },
     },
+    schema::db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     transaction::TransactionSchema,
     EventStore, LedgerStore, TransactionStore,
 };
@@ -57,12 +58,25 @@ impl DBPruner for LedgerPruner {
 
         self.event_store_pruner
             .prune(db_batch, min_readable_version, current_target_version)?;
+        // Persist the progress in the same batch as the deletions, so it survives restarts.
+        db_batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::LedgerPrunerProgress,
+            &DbMetadataValue::Version(current_target_version),
+        )?;
 
         self.record_progress(current_target_version);
         Ok(current_target_version)
     }
 
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
+        if let Some(progress) = self
+            .db
+            .get::<DbMetadataSchema>(&DbMetadataKey::LedgerPrunerProgress)?
+        {
+            return Ok(progress.expect_version());
+        }
+
+        // No progress recorded yet, fall back to the first transaction still in the DB.
         let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
         iter.seek_to_first();
         let version = iter.next().transpose()?.map_or(0, |(version, _)| version);
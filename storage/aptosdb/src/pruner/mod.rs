@@ -4,7 +4,7 @@
 //! This module provides `Pruner` which manages a thread pruning old data in the background and is
 //! meant to be triggered by other threads as they commit new data to the DB.
 
-mod db_pruner;
+pub(crate) mod db_pruner;
 pub(crate) mod db_sub_pruner;
 pub(crate) mod event_store;
 mod ledger_store;
@@ -13,16 +13,20 @@ pub(crate) mod transaction_store;
 pub mod utils;
 pub(crate) mod worker;
 
-use crate::metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW};
+use crate::{
+    metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW},
+    state_store::node_cache::NodeCache,
+};
 
 use aptos_config::config::StoragePrunerConfig;
 use aptos_infallible::Mutex;
 
-use crate::pruner::PrunerIndex::LedgerPrunerIndex;
-use aptos_types::transaction::Version;
+use crate::pruner::PrunerIndex::{LedgerPrunerIndex, StateStorePrunerIndex};
+use aptos_types::transaction::{AtomicVersion, Version};
 use schemadb::DB;
 use std::{
     sync::{
+        atomic::Ordering,
         mpsc::{channel, Sender},
         Arc,
     },
@@ -38,11 +42,11 @@ use worker::{Command, Worker};
 #[derive(Debug)]
 pub(crate) struct Pruner {
     /// DB version window, which dictates how many versions of state store
-    /// to keep.
-    state_store_prune_window: Version,
+    /// to keep. It can be changed at runtime.
+    state_store_prune_window: AtomicVersion,
     /// DB version window, which dictates how many version of other stores like transaction, ledger
-    /// info, events etc to keep.
-    ledger_prune_window: Version,
+    /// info, events etc to keep. It can be changed at runtime.
+    ledger_prune_window: AtomicVersion,
     /// The worker thread handle, created upon Pruner instance construction and joined upon its
     /// destruction. It only becomes `None` after joined in `drop()`.
     worker_thread: Option<JoinHandle<()>>,
@@ -51,7 +55,6 @@ pub(crate) struct Pruner {
     /// A way for the worker thread to inform the `Pruner` the pruning progress. If it
     /// sets value to `V`, all versions before `V` can no longer be accessed. This is protected by Mutex
     /// as this is accessed both by the Pruner thread and the worker thread.
-    #[allow(dead_code)]
     min_readable_version: Arc<Mutex<Vec<Version>>>,
     /// We send a batch of version to the underlying pruners for performance reason. This tracks the
     /// last version we sent to the pruner.
@@ -63,7 +66,6 @@ pub(crate) struct Pruner {
 }
 
 pub enum PrunerIndex {
-    #[allow(dead_code)]
     StateStorePrunerIndex,
     LedgerPrunerIndex,
 }
@@ -73,6 +75,7 @@ impl Pruner {
     pub fn new(
         ledger_rocksdb: Arc<DB>,
         state_merkle_rocksdb: Arc<DB>,
+        state_merkle_node_cache: Arc<NodeCache>,
         storage_pruner_config: StoragePrunerConfig,
     ) -> Self {
         let (command_sender, command_receiver) = channel();
@@ -93,6 +96,7 @@ impl Pruner {
         let worker = Worker::new(
             ledger_rocksdb,
             state_merkle_rocksdb,
+            state_merkle_node_cache,
             command_receiver,
             min_readable_version,
             storage_pruner_config.pruning_batch_size as u64,
@@ -103,12 +107,16 @@ impl Pruner {
             .expect("Creating pruner thread should succeed.");
 
         Self {
-            state_store_prune_window: storage_pruner_config
-                .state_store_prune_window
-                .expect("State store prune window must be specified"),
-            ledger_prune_window: storage_pruner_config
-                .ledger_prune_window
-                .expect("Default prune window must be specified"),
+            state_store_prune_window: AtomicVersion::new(
+                storage_pruner_config
+                    .state_store_prune_window
+                    .expect("State store prune window must be specified"),
+            ),
+            ledger_prune_window: AtomicVersion::new(
+                storage_pruner_config
+                    .ledger_prune_window
+                    .expect("Default prune window must be specified"),
+            ),
             worker_thread: Some(worker_thread),
             command_sender: Mutex::new(command_sender),
             min_readable_version: worker_progress_clone,
@@ -119,11 +127,32 @@ impl Pruner {
     }
 
     pub fn get_state_store_pruner_window(&self) -> Version {
-        self.state_store_prune_window
+        self.state_store_prune_window.load(Ordering::Relaxed)
     }
 
     pub fn get_ledger_pruner_window(&self) -> Version {
-        self.ledger_prune_window
+        self.ledger_prune_window.load(Ordering::Relaxed)
+    }
+
+    /// Changes the state store prune window and immediately re-targets the pruners. Shrinking the
+    /// window prunes more data right away, growing it cannot bring back data already pruned.
+    pub fn set_state_store_pruner_window(&self, window: Version) {
+        self.state_store_prune_window
+            .store(window, Ordering::Relaxed);
+        PRUNER_WINDOW
+            .with_label_values(&["state_pruner"])
+            .set(window as i64);
+        self.wake_pruner(*self.latest_version.lock());
+    }
+
+    /// Changes the ledger prune window and immediately re-targets the pruners. Shrinking the
+    /// window prunes more data right away, growing it cannot bring back data already pruned.
+    pub fn set_ledger_pruner_window(&self, window: Version) {
+        self.ledger_prune_window.store(window, Ordering::Relaxed);
+        PRUNER_WINDOW
+            .with_label_values(&["ledger_pruner"])
+            .set(window as i64);
+        self.wake_pruner(*self.latest_version.lock());
     }
 
     pub fn get_min_readable_version_by_pruner_index(&self, pruner_index: PrunerIndex) -> Version {
@@ -133,6 +162,11 @@ impl Pruner {
     pub fn get_min_readable_ledger_version(&self) -> Version {
         self.get_min_readable_version_by_pruner_index(LedgerPrunerIndex)
     }
+
+    pub fn get_min_readable_state_store_version(&self) -> Version {
+        self.get_min_readable_version_by_pruner_index(StateStorePrunerIndex)
+    }
+
     /// Sends pruning command to the worker thread when necessary.
     pub fn maybe_wake_pruner(&self, latest_version: Version) {
         *self.latest_version.lock() = latest_version;
@@ -146,8 +180,9 @@ impl Pruner {
 
     fn wake_pruner(&self, latest_version: Version) {
         let min_readable_state_store_version =
-            latest_version.saturating_sub(self.state_store_prune_window);
-        let min_readable_ledger_version = latest_version.saturating_sub(self.ledger_prune_window);
+            latest_version.saturating_sub(self.get_state_store_pruner_window());
+        let min_readable_ledger_version =
+            latest_version.saturating_sub(self.get_ledger_pruner_window());
 
         self.command_sender
             .lock()
@@ -175,10 +210,11 @@ impl Pruner {
 
         self.maybe_wake_pruner(latest_version);
 
-        if latest_version > self.state_store_prune_window
-            || latest_version > self.ledger_prune_window
+        if latest_version > self.get_state_store_pruner_window()
+            || latest_version > self.get_ledger_pruner_window()
         {
-            let min_readable_state_store_version = latest_version - self.state_store_prune_window;
+            let min_readable_state_store_version =
+                latest_version - self.get_state_store_pruner_window();
             // Assuming no big pruning chunks will be issued by a test.
             const TIMEOUT: Duration = Duration::from_secs(10);
             let end = Instant::now() + TIMEOUT;
@@ -2,8 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    jellyfish_merkle_node::JellyfishMerkleNodeSchema, metrics::PRUNER_LEAST_READABLE_VERSION,
-    pruner::db_pruner::DBPruner, stale_node_index::StaleNodeIndexSchema, OTHER_TIMERS_SECONDS,
+    jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    metrics::PRUNER_LEAST_READABLE_VERSION,
+    pruner::db_pruner::DBPruner,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        stale_state_value_index::StaleStateValueIndexSchema,
+        state_value::StateValueSchema,
+    },
+    stale_node_index::StaleNodeIndexSchema,
+    state_store::node_cache::NodeCache,
+    OTHER_TIMERS_SECONDS,
 };
 use aptos_infallible::Mutex;
 use aptos_jellyfish_merkle::StaleNodeIndex;
@@ -23,6 +32,8 @@ pub const STATE_STORE_PRUNER_NAME: &str = "state store pruner";
 
 pub struct StateStorePruner {
     db: Arc<DB>,
+    ledger_db: Arc<DB>,
+    node_cache: Arc<NodeCache>,
     index_min_nonpurged_version: AtomicVersion,
     index_purged_at: Mutex<Instant>,
     /// Keeps track of the target version that the pruner needs to achieve.
@@ -37,7 +48,7 @@ impl DBPruner for StateStorePruner {
 
     fn prune(
         &self,
-        _ledger_db_batch: &mut SchemaBatch,
+        ledger_db_batch: &mut SchemaBatch,
         max_versions: u64,
     ) -> anyhow::Result<Version> {
         if !self.is_pruning_pending() {
@@ -47,11 +58,17 @@ impl DBPruner for StateStorePruner {
         let target_version = self.target_version();
         return match prune_state_store(
             &self.db,
+            &self.node_cache,
             min_readable_version,
             target_version,
             max_versions as usize,
         ) {
             Ok(new_min_readable_version) => {
+                prune_stale_state_values(
+                    &self.ledger_db,
+                    new_min_readable_version,
+                    ledger_db_batch,
+                )?;
                 self.record_progress(new_min_readable_version);
                 // Try to purge the log.
                 if let Err(e) = self.maybe_purge_index() {
@@ -74,6 +91,15 @@ impl DBPruner for StateStorePruner {
     }
 
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
+        if let Some(progress) = self
+            .db
+            .get::<DbMetadataSchema>(&DbMetadataKey::StateMerklePrunerProgress)?
+        {
+            return Ok(progress.expect_version());
+        }
+
+        // No progress recorded yet, fall back to the oldest stale node still in the index. This
+        // can lag behind the actual progress since the index is only purged periodically.
         let mut iter = self
             .db
             .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
@@ -110,11 +136,15 @@ impl DBPruner for StateStorePruner {
 impl StateStorePruner {
     pub fn new(
         db: Arc<DB>,
+        ledger_db: Arc<DB>,
+        node_cache: Arc<NodeCache>,
         index_min_nonpurged_version: Version,
         index_purged_at: Instant,
     ) -> Self {
         let pruner = StateStorePruner {
             db,
+            ledger_db,
+            node_cache,
             index_min_nonpurged_version: AtomicVersion::new(index_min_nonpurged_version),
             index_purged_at: Mutex::new(index_purged_at),
             target_version: AtomicVersion::new(0),
@@ -172,6 +202,7 @@ impl StateStorePruner {
 
 pub fn prune_state_store(
     db: &DB,
+    node_cache: &NodeCache,
     min_readable_version: Version,
     target_version: Version,
     max_versions: usize,
@@ -192,13 +223,65 @@ pub fn prune_state_store(
         let new_min_readable_version = indices.last().expect("Should exist.").stale_since_version;
         let mut batch = SchemaBatch::new();
         indices
-            .into_iter()
+            .iter()
             .try_for_each(|index| batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key))?;
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::StateMerklePrunerProgress,
+            &DbMetadataValue::Version(new_min_readable_version),
+        )?;
         db.write_schemas(batch)?;
+        node_cache.invalidate(indices.iter().map(|index| &index.node_key));
         Ok(new_min_readable_version)
     }
 }
 
+/// Deletes the state values which became stale up to `target_version` from the flat state value
+/// index, together with their stale index entries. The values live in the ledger DB, so their
+/// progress is tracked there too and goes into the same batch as the deletions. It may lag behind
+/// the state merkle progress after a crash, in which case it catches up on the next round.
+pub fn prune_stale_state_values(
+    ledger_db: &DB,
+    target_version: Version,
+    ledger_db_batch: &mut SchemaBatch,
+) -> anyhow::Result<()> {
+    let begin = ledger_db
+        .get::<DbMetadataSchema>(&DbMetadataKey::StateValuePrunerProgress)?
+        .map_or(0, |progress| progress.expect_version() + 1);
+    if target_version < begin {
+        return Ok(());
+    }
+
+    let mut iter = ledger_db.iter::<StaleStateValueIndexSchema>(ReadOptions::default())?;
+    iter.seek(&begin)?;
+    for res in iter {
+        let (index, _) = res?;
+        if index.stale_since_version > target_version {
+            break;
+        }
+        // All values of the key written before the one at `stale_since_version` are stale. Older
+        // ones were normally removed with earlier index entries already.
+        if let Some(latest_stale_version) = index.stale_since_version.checked_sub(1) {
+            let mut read_opts = ReadOptions::default();
+            read_opts.set_prefix_same_as_start(true);
+            let mut value_iter = ledger_db.iter::<StateValueSchema>(read_opts)?;
+            value_iter.seek(&(index.state_key.clone(), latest_stale_version))?;
+            for value_res in value_iter {
+                let ((state_key, version), _) = value_res?;
+                if state_key != index.state_key {
+                    break;
+                }
+                ledger_db_batch.delete::<StateValueSchema>(&(state_key, version))?;
+            }
+        }
+        ledger_db_batch.delete::<StaleStateValueIndexSchema>(&index)?;
+    }
+    ledger_db_batch.put::<DbMetadataSchema>(
+        &DbMetadataKey::StateValuePrunerProgress,
+        &DbMetadataValue::Version(target_version),
+    )?;
+    Ok(())
+}
+
 struct StaleNodeIndicesByVersionIterator<'a> {
     inner: Peekable<SchemaIterator<'a, StaleNodeIndexSchema>>,
     target_min_readable_version: Version,
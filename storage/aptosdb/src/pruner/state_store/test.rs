@@ -8,7 +8,16 @@ use aptos_temppath::TempPath;
 use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
 use storage_interface::{jmt_update_refs, jmt_updates, DbReader};
 
-use crate::{change_set::ChangeSet, pruner::*, state_store::StateStore, AptosDB};
+use crate::{
+    change_set::ChangeSet,
+    pruner::*,
+    schema::{
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        state_value::StateValueSchema,
+    },
+    state_store::StateStore,
+    AptosDB,
+};
 
 fn put_value_set(
     db: &DB,
@@ -56,13 +65,11 @@ fn test_state_store_pruner() {
     let num_versions = 25;
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
-    let state_store = &StateStore::new(
-        Arc::clone(&aptos_db.ledger_db),
-        Arc::clone(&aptos_db.state_merkle_db),
-    );
+    let state_store = &aptos_db.state_store;
     let pruner = Pruner::new(
         Arc::clone(&aptos_db.ledger_db),
         Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(state_store.node_cache()),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
@@ -131,6 +138,32 @@ fn test_state_store_pruner() {
                 i as u64,
             );
         }
+        // The values replaced before the min readable version are gone from the flat index too.
+        for i in 0..prune_batch_size - 1 {
+            assert!(aptos_db
+                .ledger_db
+                .get::<StateValueSchema>(&(key.clone(), i as u64))
+                .unwrap()
+                .is_none());
+        }
+        for i in prune_batch_size..num_versions as usize {
+            assert_eq!(
+                state_store
+                    .get_state_value_by_version(&key, i as u64)
+                    .unwrap(),
+                Some(StateValue::from(vec![i as u8])),
+            );
+        }
+        // The flat index records its own progress in the ledger DB.
+        assert_eq!(
+            aptos_db
+                .ledger_db
+                .get::<DbMetadataSchema>(&DbMetadataKey::StateValuePrunerProgress)
+                .unwrap(),
+            Some(DbMetadataValue::Version(
+                pruner.get_min_readable_version_by_pruner_index(PrunerIndex::StateStorePrunerIndex)
+            )),
+        );
     }
 }
 
@@ -145,7 +178,7 @@ fn test_worker_quit_eagerly() {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
     let db = aptos_db.ledger_db;
-    let state_store = &StateStore::new(Arc::clone(&db), Arc::clone(&aptos_db.state_merkle_db));
+    let state_store = &aptos_db.state_store;
 
     let _root0 = put_value_set(
         &db,
@@ -171,6 +204,7 @@ fn test_worker_quit_eagerly() {
         let worker = Worker::new(
             Arc::clone(&db),
             Arc::clone(&aptos_db.state_merkle_db),
+            Arc::clone(state_store.node_cache()),
             command_receiver,
             Arc::new(Mutex::new(vec![0, 0])), /* progress */
             100,
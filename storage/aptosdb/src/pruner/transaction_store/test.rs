@@ -1,9 +1,12 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{pruner::*, AptosDB, ChangeSet, LedgerStore, TransactionStore};
+use crate::{
+    pruner::*, AptosDB, ChangeSet, LedgerStore, TransactionStore, MIN_RUNTIME_PRUNE_WINDOW,
+};
 use aptos_temppath::TempPath;
 use proptest::proptest;
+use storage_interface::DbWriter;
 
 use aptos_types::{
     account_address::AccountAddress,
@@ -50,6 +53,7 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
     let pruner = Pruner::new(
         Arc::clone(&aptos_db.ledger_db),
         Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(aptos_db.state_store.node_cache()),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
@@ -85,6 +89,77 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
     }
 }
 
+#[test]
+fn test_ledger_pruner_resumes_after_restart_and_window_change() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let transaction_store = &aptos_db.transaction_store;
+    let storage_pruner_config = StoragePrunerConfig {
+        state_store_prune_window: Some(100),
+        ledger_prune_window: Some(100),
+        pruning_batch_size: 1,
+    };
+
+    let mut cs = ChangeSet::new();
+    for ver in 0..20 {
+        transaction_store
+            .put_write_set(ver, &WriteSet::default(), &mut cs)
+            .unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(cs.batch).unwrap();
+
+    {
+        let pruner = Pruner::new(
+            Arc::clone(&aptos_db.ledger_db),
+            Arc::clone(&aptos_db.state_merkle_db),
+            Arc::clone(aptos_db.state_store.node_cache()),
+            storage_pruner_config,
+        );
+        // Nothing is pruned with the configured window
+        pruner
+            .wake_and_wait(20, PrunerIndex::LedgerPrunerIndex as usize)
+            .unwrap();
+        assert_eq!(pruner.get_min_readable_ledger_version(), 0);
+
+        // Shrinking the window at runtime prunes right away
+        pruner.set_ledger_pruner_window(10);
+        let end = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while pruner.get_min_readable_ledger_version() < 10 {
+            assert!(
+                std::time::Instant::now() < end,
+                "Timeout waiting for pruner."
+            );
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    // A new pruner picks up the persisted progress without pruning again
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(aptos_db.state_store.node_cache()),
+        storage_pruner_config,
+    );
+    assert_eq!(pruner.get_min_readable_ledger_version(), 10);
+    assert_eq!(pruner.get_ledger_pruner_window(), 100);
+    assert!(transaction_store.get_write_set(9).is_err());
+    assert!(transaction_store.get_write_set(10).is_ok());
+}
+
+#[test]
+fn test_runtime_prune_window_minimum() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let ledger_err = aptos_db
+        .set_ledger_prune_window(MIN_RUNTIME_PRUNE_WINDOW - 1)
+        .unwrap_err();
+    assert!(ledger_err.to_string().contains("minimum"));
+    let state_err = aptos_db
+        .set_state_prune_window(MIN_RUNTIME_PRUNE_WINDOW - 1)
+        .unwrap_err();
+    assert!(state_err.to_string().contains("minimum"));
+}
+
 fn verify_txn_store_pruner(
     txns: Vec<Transaction>,
     txn_infos: Vec<TransactionInfo>,
@@ -99,6 +174,7 @@ fn verify_txn_store_pruner(
     let pruner = Pruner::new(
         Arc::clone(&aptos_db.ledger_db),
         Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(aptos_db.state_store.node_cache()),
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
@@ -8,6 +8,7 @@ use crate::{
         db_pruner::DBPruner, ledger_store::ledger_store_pruner::LedgerPruner,
         state_store::StateStorePruner,
     },
+    state_store::node_cache::NodeCache,
     EventStore, LedgerStore, TransactionStore,
 };
 use aptos_infallible::Mutex;
@@ -18,10 +19,13 @@ use std::{sync::Arc, time::Instant};
 pub fn create_db_pruners(
     ledger_db: Arc<DB>,
     state_merkle_db: Arc<DB>,
+    state_merkle_node_cache: Arc<NodeCache>,
 ) -> Vec<Mutex<Arc<dyn DBPruner + Send + Sync>>> {
     vec![
         Mutex::new(Arc::new(StateStorePruner::new(
             Arc::clone(&state_merkle_db),
+            Arc::clone(&ledger_db),
+            state_merkle_node_cache,
             0,
             Instant::now(),
         ))),
@@ -3,7 +3,10 @@
 use aptos_types::transaction::Version;
 use schemadb::{SchemaBatch, DB};
 
-use crate::pruner::{db_pruner::DBPruner, utils};
+use crate::{
+    pruner::{db_pruner::DBPruner, utils},
+    state_store::node_cache::NodeCache,
+};
 use aptos_infallible::Mutex;
 use itertools::zip_eq;
 use std::sync::{mpsc::Receiver, Arc};
@@ -29,19 +32,25 @@ impl Worker {
     pub(crate) fn new(
         ledger_db: Arc<DB>,
         state_merkle_db: Arc<DB>,
+        state_merkle_node_cache: Arc<NodeCache>,
         command_receiver: Receiver<Command>,
         min_readable_versions: Arc<Mutex<Vec<Version>>>,
         max_version_to_prune_per_batch: u64,
     ) -> Self {
-        let db_pruners = utils::create_db_pruners(ledger_db.clone(), state_merkle_db);
-        Self {
+        let db_pruners =
+            utils::create_db_pruners(ledger_db.clone(), state_merkle_db, state_merkle_node_cache);
+        let mut worker = Self {
             ledger_db: Arc::clone(&ledger_db),
             db_pruners,
             command_receiver,
             min_readable_versions,
             blocking_recv: true,
             max_version_to_prune_per_batch,
-        }
+        };
+        // The pruners resume from the progress persisted in the DB, make it visible to readers
+        // right away instead of after the first pruning round.
+        worker.record_progress();
+        worker
     }
 
     pub(crate) fn work(mut self) {
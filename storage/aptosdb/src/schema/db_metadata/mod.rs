@@ -0,0 +1,88 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for metadata the DB keeps about itself, such as
+//! the progress of the pruners, so that it survives restarts.
+//!
+//! ```text
+//! |<-------key------->|<------value------>|
+//! | metadata key      | metadata value    |
+//! ```
+
+use super::DB_METADATA_CF_NAME;
+use anyhow::Result;
+use aptos_types::{state_store::state_value::StateSnapshotProgress, transaction::Version};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) enum DbMetadataValue {
+    Version(Version),
+    StateSnapshotProgress(StateSnapshotProgress),
+}
+
+impl DbMetadataValue {
+    pub fn expect_version(self) -> Version {
+        match self {
+            Self::Version(version) => version,
+            _ => panic!("Expected a version, found: {:?}", self),
+        }
+    }
+
+    pub fn expect_state_snapshot_progress(self) -> StateSnapshotProgress {
+        match self {
+            Self::StateSnapshotProgress(progress) => progress,
+            _ => panic!("Expected a state snapshot progress, found: {:?}", self),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) enum DbMetadataKey {
+    /// The min readable version of the ledger store, written by the ledger pruner.
+    LedgerPrunerProgress,
+    /// The min readable version of the state merkle store, written by the state store pruner.
+    StateMerklePrunerProgress,
+    /// The progress of an in-flight state snapshot restore, written by the snapshot restore.
+    StateSnapshotRestoreProgress,
+    /// The version up to which stale values have been removed from the flat state value index,
+    /// written by the state store pruner in the same ledger DB batch as the deletions.
+    StateValuePrunerProgress,
+}
+
+define_schema!(
+    DbMetadataSchema,
+    DbMetadataKey,
+    DbMetadataValue,
+    DB_METADATA_CF_NAME
+);
+
+impl KeyCodec<DbMetadataSchema> for DbMetadataKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+impl ValueCodec<DbMetadataSchema> for DbMetadataValue {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
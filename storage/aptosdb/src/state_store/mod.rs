@@ -3,6 +3,7 @@
 
 //! This file defines state store APIs that are related account state Merkle tree.
 
+pub(crate) mod node_cache;
 #[cfg(test)]
 mod state_store_test;
 
@@ -10,9 +11,13 @@ use crate::{
     change_set::ChangeSet,
     ledger_counters::LedgerCounter,
     schema::{
-        jellyfish_merkle_node::JellyfishMerkleNodeSchema, stale_node_index::StaleNodeIndexSchema,
+        db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
+        jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+        stale_node_index::StaleNodeIndexSchema,
+        stale_state_value_index::{StaleStateValueIndex, StaleStateValueIndexSchema},
         state_value::StateValueSchema,
     },
+    state_store::node_cache::NodeCache,
     AptosDbError,
 };
 use anyhow::{anyhow, ensure, format_err, Result};
@@ -22,13 +27,14 @@ use aptos_jellyfish_merkle::{
     iterator::JellyfishMerkleIterator, node_type::NodeKey, restore::StateSnapshotRestore,
     JellyfishMerkleTree, StateValueWriter, TreeReader, TreeWriter,
 };
+use aptos_logger::prelude::*;
 use aptos_types::{
     nibble::{nibble_path::NibblePath, ROOT_NIBBLE_HEIGHT},
     proof::{SparseMerkleProof, SparseMerkleRangeProof},
     state_store::{
         state_key::StateKey,
         state_key_prefix::StateKeyPrefix,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateSnapshotProgress, StateValue, StateValueChunkWithProof},
     },
     transaction::{Version, PRE_GENESIS_VERSION},
 };
@@ -47,6 +53,7 @@ pub const MAX_VALUES_TO_FETCH_FOR_KEY_PREFIX: usize = 10_000;
 pub(crate) struct StateStore {
     ledger_db: Arc<DB>,
     state_merkle_db: Arc<DB>,
+    node_cache: Arc<NodeCache>,
     latest_checkpoint: Mutex<Option<(Version, HashValue)>>,
 }
 
@@ -72,9 +79,9 @@ impl DbReader for StateStore {
         ))
     }
 
-    /// Get the lastest state value of the given key up to the given version. Only used for testing for now
-    /// but should replace the `get_value_with_proof_by_version` call for VM execution if just fetch the
-    /// value without proof.
+    /// Get the lastest state value of the given key up to the given version with a single seek in
+    /// the flat state value index, without walking the Jellyfish Merkle tree. Used to serve reads
+    /// that don't need a proof.
     fn get_state_value_by_version(
         &self,
         state_key: &StateKey,
@@ -122,10 +129,11 @@ impl DbReader for StateStore {
 }
 
 impl StateStore {
-    pub fn new(ledger_db: Arc<DB>, state_merkle_db: Arc<DB>) -> Self {
+    pub fn new(ledger_db: Arc<DB>, state_merkle_db: Arc<DB>, node_cache_size: usize) -> Self {
         let myself = Self {
             ledger_db,
             state_merkle_db,
+            node_cache: Arc::new(NodeCache::new(node_cache_size)),
             latest_checkpoint: Mutex::new(None),
         };
 
@@ -255,6 +263,10 @@ impl StateStore {
             })
     }
 
+    pub fn node_cache(&self) -> &Arc<NodeCache> {
+        &self.node_cache
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_value_range_proof(
         &self,
@@ -264,13 +276,26 @@ impl StateStore {
         JellyfishMerkleTree::new(self).get_range_proof(rightmost_key, version)
     }
 
-    /// Put the `value_state_sets` into its own CF.
+    /// Put the `value_state_sets` into its own CF, indexing every write as making the older values
+    /// of its key stale so that the state store pruner can remove them later.
     pub fn put_value_sets(
         &self,
         value_state_sets: Vec<&HashMap<StateKey, StateValue>>,
         first_version: Version,
         cs: &mut ChangeSet,
     ) -> Result<()> {
+        for (i, kvs) in value_state_sets.iter().enumerate() {
+            for key in kvs.keys() {
+                cs.batch.put::<StaleStateValueIndexSchema>(
+                    &StaleStateValueIndex {
+                        stale_since_version: first_version + i as Version,
+                        state_key: key.clone(),
+                    },
+                    &(),
+                )?;
+            }
+        }
+
         let kv_batch = value_state_sets
             .iter()
             .enumerate()
@@ -325,6 +350,12 @@ impl StateStore {
 
         // commit jellyfish merkle nodes
         self.state_merkle_db.write_schemas(batch)?;
+        // The new nodes are the ones the next update and most proofs start from.
+        let generation = self.node_cache.generation();
+        for (node_key, node) in tree_update_batch.node_batch.iter() {
+            self.node_cache
+                .insert(node_key.clone(), node.clone(), generation);
+        }
 
         Ok(new_root_hash_vec)
     }
@@ -428,18 +459,58 @@ impl StateStore {
         version: Version,
         expected_root_hash: HashValue,
     ) -> Result<Box<dyn StateSnapshotReceiver<StateKey, StateValue>>> {
+        // If a previous restore of the same snapshot was interrupted, resume it from where it
+        // left off. Otherwise, start over (overwriting any previously restored nodes).
+        if let Some(progress) = self.get_state_snapshot_progress()? {
+            if progress.version == version && progress.expected_root_hash == expected_root_hash {
+                match StateSnapshotRestore::new_from_progress(Arc::clone(self), &progress) {
+                    Ok(restore) => {
+                        info!(
+                            version = version,
+                            num_leaves = progress.num_leaves,
+                            "Resuming state snapshot restore."
+                        );
+                        return Ok(Box::new(restore));
+                    }
+                    Err(error) => warn!(
+                        version = version,
+                        error = ?error,
+                        "Failed to resume state snapshot restore, starting over."
+                    ),
+                }
+            }
+        }
+
         Ok(Box::new(StateSnapshotRestore::new_overwrite(
             Arc::clone(self),
             version,
             expected_root_hash,
         )?))
     }
+
+    /// Returns the progress of an interrupted state snapshot restore, if any.
+    pub fn get_state_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>> {
+        Ok(self
+            .state_merkle_db
+            .get::<DbMetadataSchema>(&DbMetadataKey::StateSnapshotRestoreProgress)?
+            .map(|value| value.expect_state_snapshot_progress()))
+    }
 }
 
 impl TreeReader<StateKey> for StateStore {
     fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>> {
-        self.state_merkle_db
-            .get::<JellyfishMerkleNodeSchema>(node_key)
+        if let Some(node) = self.node_cache.get(node_key) {
+            return Ok(Some(node));
+        }
+        let generation = self.node_cache.generation();
+        let node_opt = self
+            .state_merkle_db
+            .get::<JellyfishMerkleNodeSchema>(node_key)?;
+        if let Some(node) = &node_opt {
+            self.node_cache
+                .insert(node_key.clone(), node.clone(), generation);
+        }
+        Ok(node_opt)
     }
 
     fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
@@ -511,6 +582,27 @@ impl TreeWriter<StateKey> for StateStore {
         self.state_merkle_db.write_schemas(batch)
     }
 
+    fn write_node_batch_with_progress(
+        &self,
+        node_batch: &NodeBatch,
+        progress: Option<&StateSnapshotProgress>,
+    ) -> Result<()> {
+        // Write the progress atomically with the nodes, so that it always matches the nodes in
+        // storage.
+        let mut batch = SchemaBatch::new();
+        add_node_batch(&mut batch, node_batch)?;
+        match progress {
+            Some(progress) => batch.put::<DbMetadataSchema>(
+                &DbMetadataKey::StateSnapshotRestoreProgress,
+                &DbMetadataValue::StateSnapshotProgress(progress.clone()),
+            )?,
+            None => {
+                batch.delete::<DbMetadataSchema>(&DbMetadataKey::StateSnapshotRestoreProgress)?
+            }
+        }
+        self.state_merkle_db.write_schemas(batch)
+    }
+
     fn finish_version(&self, version: Version, root_hash: HashValue) {
         self.set_latest_checkpoint(version, root_hash)
     }
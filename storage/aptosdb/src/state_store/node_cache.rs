@@ -0,0 +1,177 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This file defines an in-memory cache of Jellyfish Merkle nodes sitting in front of the state
+//! merkle DB.
+//!
+//! Every proof and every tree update walks down from the root, so the top levels of the tree are
+//! read over and over again. The latest node at each position in those levels is pinned in the
+//! cache, other nodes are kept in a bounded LRU cache. The LRU cache is split into shards by node
+//! key so that concurrent readers rarely contend on the same lock.
+//!
+//! A reader may fetch a node from the DB right before the pruner deletes it and only get to cache
+//! it afterwards. To keep such nodes out, every invalidation bumps a generation counter and a node
+//! read from the DB is only cached if no invalidation happened since the read started.
+
+use crate::metrics::STATE_MERKLE_NODE_CACHE_LOOKUPS;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_jellyfish_merkle::node_type::NodeKey;
+use aptos_types::{
+    nibble::nibble_path::NibblePath, state_store::state_key::StateKey, transaction::Version,
+};
+use lru::LruCache;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+type Node = aptos_jellyfish_merkle::node_type::Node<StateKey>;
+
+/// Nodes whose nibble path is at most this long are pinned, that is the root and the two levels
+/// of internal nodes below it, 273 positions in total.
+const MAX_PINNED_NIBBLES: usize = 2;
+
+/// The LRU cache is split into at most this many shards.
+const MAX_NUM_SHARDS: usize = 16;
+
+pub(crate) struct NodeCache {
+    /// The latest node known at each pinned position.
+    pinned: RwLock<HashMap<NibblePath, (Version, Node)>>,
+    /// Empty if the LRU cache is disabled by configuring a capacity of 0.
+    shards: Vec<Mutex<LruCache<NodeKey, Node>>>,
+    /// Bumped by every invalidation, see the module comment.
+    generation: AtomicU64,
+}
+
+impl fmt::Debug for NodeCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NodeCache")
+            .field("num_pinned", &self.pinned.read().len())
+            .field(
+                "num_cached",
+                &self
+                    .shards
+                    .iter()
+                    .map(|shard| shard.lock().len())
+                    .sum::<usize>(),
+            )
+            .finish()
+    }
+}
+
+impl NodeCache {
+    pub fn new(capacity: usize) -> Self {
+        let num_shards = std::cmp::min(capacity, MAX_NUM_SHARDS);
+        let shards = (0..num_shards)
+            .map(|_| Mutex::new(LruCache::new(capacity / num_shards)))
+            .collect();
+        Self {
+            pinned: RwLock::new(HashMap::new()),
+            shards,
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, node_key: &NodeKey) -> Option<Node> {
+        let node = self.get_pinned(node_key).or_else(|| {
+            self.shard(node_key)
+                .and_then(|shard| shard.lock().get(node_key).cloned())
+        });
+        STATE_MERKLE_NODE_CACHE_LOOKUPS
+            .with_label_values(&[if node.is_some() { "hit" } else { "miss" }])
+            .inc();
+        node
+    }
+
+    /// The current generation, to be taken before reading a node from the DB and handed to
+    /// `insert()` once the node is read.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Caches a node which has been read from or written to the DB. The node is dropped if any
+    /// node has been invalidated since `generation` was taken, as it may be deleted by now. A
+    /// pinned node replaced by a newer one at the same position moves to the LRU cache.
+    pub fn insert(&self, node_key: NodeKey, node: Node, generation: u64) {
+        let (node_key, node) = if is_pinned(&node_key) {
+            let mut pinned = self.pinned.write();
+            if self.generation() != generation {
+                return;
+            }
+            match pinned.get(node_key.nibble_path()) {
+                Some((version, _)) if *version == node_key.version() => return,
+                Some((version, _)) if *version > node_key.version() => (node_key, node),
+                _ => {
+                    let nibble_path = node_key.nibble_path().clone();
+                    match pinned.insert(nibble_path.clone(), (node_key.version(), node)) {
+                        Some((version, node)) => (NodeKey::new(version, nibble_path), node),
+                        None => return,
+                    }
+                }
+            }
+        } else {
+            (node_key, node)
+        };
+        if let Some(shard) = self.shard(&node_key) {
+            let mut shard = shard.lock();
+            if self.generation() == generation {
+                shard.put(node_key, node);
+            }
+        }
+    }
+
+    /// Drops nodes which have been deleted from the DB by the pruner. This must be called after
+    /// the deletion is committed, so that no reader can fetch the nodes from the DB afterwards.
+    pub fn invalidate<'a>(&self, node_keys: impl IntoIterator<Item = &'a NodeKey>) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        for node_key in node_keys {
+            if is_pinned(node_key) {
+                let mut pinned = self.pinned.write();
+                if matches!(
+                    pinned.get(node_key.nibble_path()),
+                    Some((version, _)) if *version == node_key.version()
+                ) {
+                    pinned.remove(node_key.nibble_path());
+                }
+            }
+            if let Some(shard) = self.shard(node_key) {
+                shard.lock().pop(node_key);
+            }
+        }
+    }
+
+    /// Drops everything, for when nodes in the DB are deleted other than by the pruner. Like
+    /// `invalidate()`, this must be called after the deletion is committed.
+    pub fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.pinned.write().clear();
+        for shard in &self.shards {
+            shard.lock().clear();
+        }
+    }
+
+    fn get_pinned(&self, node_key: &NodeKey) -> Option<Node> {
+        if !is_pinned(node_key) {
+            return None;
+        }
+        match self.pinned.read().get(node_key.nibble_path()) {
+            Some((version, node)) if *version == node_key.version() => Some(node.clone()),
+            _ => None,
+        }
+    }
+
+    fn shard(&self, node_key: &NodeKey) -> Option<&Mutex<LruCache<NodeKey, Node>>> {
+        if self.shards.is_empty() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        node_key.hash(&mut hasher);
+        Some(&self.shards[hasher.finish() as usize % self.shards.len()])
+    }
+}
+
+fn is_pinned(node_key: &NodeKey) -> bool {
+    node_key.nibble_path().num_nibbles() <= MAX_PINNED_NIBBLES
+}
@@ -50,6 +50,7 @@ fn prune_stale_indices(
 ) {
     pruner::state_store::prune_state_store(
         &store.state_merkle_db,
+        &store.node_cache,
         min_readable_version,
         target_min_readable_version,
         limit,
@@ -321,6 +322,51 @@ fn test_retired_records() {
     }
 }
 
+#[test]
+fn test_node_cache() {
+    let cache = NodeCache::new(1 /* capacity */);
+    let root_key = NodeKey::new_empty_path;
+    let deep_key = |version| NodeKey::new(version, NibblePath::new(vec![0x12, 0x34]));
+
+    // The latest root is pinned, the one it replaces moves to the LRU cache.
+    cache.insert(root_key(1), Node::new_null(), cache.generation());
+    cache.insert(root_key(2), Node::new_null(), cache.generation());
+    assert!(cache.get(&root_key(1)).is_some());
+    assert!(cache.get(&root_key(2)).is_some());
+
+    // Nodes deeper in the tree evict each other, but never a pinned one.
+    cache.insert(deep_key(1), Node::new_null(), cache.generation());
+    assert!(cache.get(&root_key(1)).is_none());
+    assert!(cache.get(&root_key(2)).is_some());
+    assert!(cache.get(&deep_key(1)).is_some());
+
+    // Pruned nodes are gone.
+    cache.invalidate(&[root_key(2), deep_key(1)]);
+    assert!(cache.get(&root_key(2)).is_none());
+    assert!(cache.get(&deep_key(1)).is_none());
+}
+
+#[test]
+fn test_node_cache_drops_nodes_read_before_invalidation() {
+    let cache = NodeCache::new(16 /* capacity */);
+    let root_key = NodeKey::new_empty_path;
+    let deep_key = |version| NodeKey::new(version, NibblePath::new(vec![0x12, 0x34]));
+
+    // Nodes read from the DB before the pruner deleted them are not cached afterwards.
+    let generation = cache.generation();
+    cache.invalidate(&[root_key(1), deep_key(1)]);
+    cache.insert(root_key(1), Node::new_null(), generation);
+    cache.insert(deep_key(1), Node::new_null(), generation);
+    assert!(cache.get(&root_key(1)).is_none());
+    assert!(cache.get(&deep_key(1)).is_none());
+
+    // Nodes read afterwards are.
+    cache.insert(root_key(2), Node::new_null(), cache.generation());
+    cache.insert(deep_key(2), Node::new_null(), cache.generation());
+    assert!(cache.get(&root_key(2)).is_some());
+    assert!(cache.get(&deep_key(2)).is_some());
+}
+
 #[test]
 pub fn test_find_latest_persisted_version_less_than() {
     let tmp_dir = TempPath::new();
@@ -6,8 +6,12 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient,
+        file_encoding::{FileCodec, FileEncodingOpt},
+        read_record_bytes::ReadRecordBytes,
+        should_cut_chunk,
+        storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -34,6 +38,7 @@ pub struct EpochEndingBackupController {
     start_epoch: u64,
     end_epoch: u64,
     max_chunk_size: usize,
+    file_encoding_opt: FileEncodingOpt,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -49,6 +54,7 @@ impl EpochEndingBackupController {
             start_epoch: opt.start_epoch,
             end_epoch: opt.end_epoch,
             max_chunk_size: global_opt.max_chunk_size,
+            file_encoding_opt: global_opt.file_encoding,
             client,
             storage,
         }
@@ -75,6 +81,7 @@ impl EpochEndingBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let codec = self.file_encoding_opt.new_codec()?;
 
         let mut chunks = Vec::new();
         let mut waypoints = Vec::new();
@@ -92,9 +99,10 @@ impl EpochEndingBackupController {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
-                        &chunk_bytes,
+                        chunk_bytes,
                         chunk_first_epoch,
                         current_epoch - 1,
+                        &codec,
                     )
                     .await?;
                 chunks.push(chunk);
@@ -113,14 +121,16 @@ impl EpochEndingBackupController {
         let chunk = self
             .write_chunk(
                 &backup_handle,
-                &chunk_bytes,
+                chunk_bytes,
                 chunk_first_epoch,
                 current_epoch - 1,
+                &codec,
             )
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, waypoints, chunks).await
+        self.write_manifest(&backup_handle, waypoints, chunks, &codec)
+            .await
     }
 
     fn backup_name(&self) -> String {
@@ -151,16 +161,20 @@ impl EpochEndingBackupController {
     async fn write_chunk(
         &self,
         backup_handle: &BackupHandleRef,
-        chunk_bytes: &[u8],
+        chunk_bytes: Vec<u8>,
         first_epoch: u64,
         last_epoch: u64,
+        codec: &FileCodec,
     ) -> Result<EpochEndingChunk> {
-        let (chunk_handle, mut chunk_file) = self
+        let chunk_handle = self
             .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_epoch))
+            .write_encoded(
+                backup_handle,
+                &Self::chunk_name(first_epoch),
+                chunk_bytes,
+                codec,
+            )
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
         Ok(EpochEndingChunk {
             first_epoch,
             last_epoch,
@@ -173,6 +187,7 @@ impl EpochEndingBackupController {
         backup_handle: &BackupHandleRef,
         waypoints: Vec<Waypoint>,
         chunks: Vec<EpochEndingChunk>,
+        codec: &FileCodec,
     ) -> Result<FileHandle> {
         let first_epoch = self.start_epoch;
         let last_epoch = self.end_epoch - 1;
@@ -182,6 +197,7 @@ impl EpochEndingBackupController {
             last_epoch,
             waypoints,
             chunks,
+            encoding: codec.encoding().clone(),
         };
         let (manifest_handle, mut manifest_file) = self
             .storage
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::FileHandle;
+use crate::{storage::FileHandle, utils::file_encoding::FileEncoding};
 use anyhow::{ensure, Result};
 use aptos_types::waypoint::Waypoint;
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,9 @@ pub struct EpochEndingBackup {
     pub last_epoch: u64,
     pub waypoints: Vec<Waypoint>,
     pub chunks: Vec<EpochEndingChunk>,
+    /// How the data files are encoded, they are in plain if absent.
+    #[serde(default, skip_serializing_if = "FileEncoding::is_plain")]
+    pub encoding: FileEncoding,
 }
 
 impl EpochEndingBackup {
@@ -9,8 +9,8 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle, FileHandleRef},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
-        GlobalRestoreOptions, RestoreRunMode,
+        file_encoding::FileCodec, read_record_bytes::ReadRecordBytes,
+        storage_ext::BackupStorageExt, stream::StreamX, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -37,6 +37,7 @@ pub struct EpochEndingRestoreController {
     manifest_handle: FileHandle,
     target_version: Version,
     trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
+    encryption_key: Option<Arc<Vec<u8>>>,
 }
 
 impl EpochEndingRestoreController {
@@ -51,6 +52,7 @@ impl EpochEndingRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             trusted_waypoints: global_opt.trusted_waypoints,
+            encryption_key: global_opt.encryption_key,
         }
     }
 
@@ -81,6 +83,10 @@ impl EpochEndingRestoreController {
         let manifest: EpochEndingBackup =
             self.storage.load_json_file(&self.manifest_handle).await?;
         manifest.verify()?;
+        let codec = FileCodec::new(
+            manifest.encoding.clone(),
+            self.encryption_key.as_deref().map(Vec::as_slice),
+        )?;
 
         let mut next_epoch = manifest.first_epoch;
         let mut waypoint_iter = manifest.waypoints.iter();
@@ -94,7 +100,7 @@ impl EpochEndingRestoreController {
                 break;
             }
 
-            let lis = self.read_chunk(&chunk.ledger_infos).await?;
+            let lis = self.read_chunk(&chunk.ledger_infos, &codec).await?;
             ensure!(
                 chunk.first_epoch + lis.len() as u64 == chunk.last_epoch + 1,
                 "Number of items in chunks doesn't match that in manifest. \
@@ -160,8 +166,10 @@ impl EpochEndingRestoreController {
     async fn read_chunk(
         &self,
         file_handle: &FileHandleRef,
+        codec: &FileCodec,
     ) -> Result<Vec<LedgerInfoWithSignatures>> {
-        let mut file = self.storage.open_for_read(file_handle).await?;
+        let bytes = self.storage.read_all_decoded(file_handle, codec).await?;
+        let mut file = &bytes[..];
         let mut chunk = vec![];
 
         while let Some(record_bytes) = file.read_record_bytes().await? {
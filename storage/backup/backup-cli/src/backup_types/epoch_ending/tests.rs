@@ -10,7 +10,9 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::tmp_db_with_random_content,
+        backup_service_client::BackupServiceClient,
+        file_encoding::{EncryptionKeyOpt, FileEncodingOpt},
+        test_utils::tmp_db_with_random_content,
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
 };
@@ -65,6 +67,7 @@ fn end_to_end() {
                 },
                 GlobalBackupOpt {
                     max_chunk_size: 1024,
+                    file_encoding: FileEncodingOpt::default(),
                 },
                 client,
                 Arc::clone(&store),
@@ -83,6 +86,7 @@ fn end_to_end() {
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
                 concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                encryption_key: EncryptionKeyOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -194,6 +198,7 @@ async fn test_trusted_waypoints_impl(
             },
             GlobalBackupOpt {
                 max_chunk_size: 1024,
+                file_encoding: FileEncodingOpt::default(),
             },
             client.clone(),
             Arc::clone(&store),
@@ -214,6 +219,7 @@ async fn test_trusted_waypoints_impl(
             trusted_waypoints: TrustedWaypointOpt::default(),
             rocksdb_opt: RocksdbOpt::default(),
             concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            encryption_key: EncryptionKeyOpt::default(),
         }
         .try_into()
         .unwrap(),
@@ -234,6 +240,7 @@ async fn test_trusted_waypoints_impl(
             },
             rocksdb_opt: RocksdbOpt::default(),
             concurernt_downloads: ConcurrentDownloadsOpt::default(),
+            encryption_key: EncryptionKeyOpt::default(),
         }
         .try_into()
         .unwrap(),
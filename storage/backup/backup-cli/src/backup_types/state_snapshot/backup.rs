@@ -6,8 +6,12 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient,
+        file_encoding::{FileCodec, FileEncodingOpt},
+        read_record_bytes::ReadRecordBytes,
+        should_cut_chunk,
+        storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, Result};
@@ -23,7 +27,7 @@ use bytes::Bytes;
 use once_cell::sync::Lazy;
 use std::{convert::TryInto, str::FromStr, sync::Arc};
 use structopt::StructOpt;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[derive(StructOpt)]
 pub struct StateSnapshotBackupOpt {
@@ -37,6 +41,7 @@ pub struct StateSnapshotBackupOpt {
 pub struct StateSnapshotBackupController {
     version: Version,
     max_chunk_size: usize,
+    file_encoding_opt: FileEncodingOpt,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -51,6 +56,7 @@ impl StateSnapshotBackupController {
         Self {
             version: opt.version,
             max_chunk_size: global_opt.max_chunk_size,
+            file_encoding_opt: global_opt.file_encoding,
             client,
             storage,
         }
@@ -74,6 +80,7 @@ impl StateSnapshotBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let codec = self.file_encoding_opt.new_codec()?;
 
         let mut chunks = vec![];
 
@@ -93,11 +100,12 @@ impl StateSnapshotBackupController {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
-                        &chunk_bytes,
+                        chunk_bytes,
                         chunk_first_idx,
                         current_idx,
                         chunk_first_key,
                         Self::parse_key(&prev_record_bytes)?,
+                        &codec,
                     )
                     .await?;
                 chunks.push(chunk);
@@ -116,16 +124,17 @@ impl StateSnapshotBackupController {
         let chunk = self
             .write_chunk(
                 &backup_handle,
-                &chunk_bytes,
+                chunk_bytes,
                 chunk_first_idx,
                 current_idx,
                 chunk_first_key,
                 Self::parse_key(&prev_record_bytes)?,
+                &codec,
             )
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, chunks).await
+        self.write_manifest(&backup_handle, chunks, &codec).await
     }
 }
 
@@ -164,31 +173,37 @@ impl StateSnapshotBackupController {
     async fn write_chunk(
         &self,
         backup_handle: &BackupHandleRef,
-        chunk_bytes: &[u8],
+        chunk_bytes: Vec<u8>,
         first_idx: usize,
         last_idx: usize,
         first_key: HashValue,
         last_key: HashValue,
+        codec: &FileCodec,
     ) -> Result<StateSnapshotChunk> {
-        let (chunk_handle, mut chunk_file) = self
+        let chunk_handle = self
             .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .write_encoded(
+                backup_handle,
+                &Self::chunk_name(first_idx),
+                chunk_bytes,
+                codec,
+            )
+            .await?;
+        let mut proof_bytes = Vec::new();
+        self.client
+            .get_account_range_proof(last_key, self.version)
+            .await?
+            .read_to_end(&mut proof_bytes)
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
-        let (proof_handle, mut proof_file) = self
+        let proof_handle = self
             .storage
-            .create_for_write(backup_handle, &Self::chunk_proof_name(first_idx, last_idx))
+            .write_encoded(
+                backup_handle,
+                &Self::chunk_proof_name(first_idx, last_idx),
+                proof_bytes,
+                codec,
+            )
             .await?;
-        tokio::io::copy(
-            &mut self
-                .client
-                .get_account_range_proof(last_key, self.version)
-                .await?,
-            &mut proof_file,
-        )
-        .await?;
-        proof_file.shutdown().await?;
 
         Ok(StateSnapshotChunk {
             first_idx,
@@ -204,23 +219,23 @@ impl StateSnapshotBackupController {
         &self,
         backup_handle: &BackupHandleRef,
         chunks: Vec<StateSnapshotChunk>,
+        codec: &FileCodec,
     ) -> Result<FileHandle> {
         let proof_bytes = self.client.get_state_root_proof(self.version).await?;
         let (txn_info, _): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
             bcs::from_bytes(&proof_bytes)?;
 
-        let (proof_handle, mut proof_file) = self
+        let proof_handle = self
             .storage
-            .create_for_write(backup_handle, Self::proof_name())
+            .write_encoded(backup_handle, Self::proof_name(), proof_bytes, codec)
             .await?;
-        proof_file.write_all(&proof_bytes).await?;
-        proof_file.shutdown().await?;
 
         let manifest = StateSnapshotBackup {
             version: self.version,
             root_hash: txn_info.transaction_info().ensure_state_checkpoint_hash()?,
             chunks,
             proof: proof_handle,
+            encoding: codec.encoding().clone(),
         };
 
         let (manifest_handle, mut manifest_file) = self
@@ -16,8 +16,8 @@ use crate::{
     },
     storage::{BackupStorage, FileHandle},
     utils::{
-        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, GlobalRestoreOptions,
-        RestoreRunMode,
+        file_encoding::FileCodec, read_record_bytes::ReadRecordBytes,
+        storage_ext::BackupStorageExt, GlobalRestoreOptions, RestoreRunMode,
     },
 };
 use anyhow::{anyhow, ensure, Result};
@@ -50,6 +50,7 @@ pub struct StateSnapshotRestoreController {
     /// nothing will be done, otherwise, this has no effect.
     target_version: Version,
     epoch_history: Option<Arc<EpochHistory>>,
+    encryption_key: Option<Arc<Vec<u8>>>,
 }
 
 impl StateSnapshotRestoreController {
@@ -66,6 +67,7 @@ impl StateSnapshotRestoreController {
             manifest_handle: opt.manifest_handle,
             target_version: global_opt.target_version,
             epoch_history,
+            encryption_key: global_opt.encryption_key,
         }
     }
 
@@ -97,8 +99,12 @@ impl StateSnapshotRestoreController {
 
         let manifest: StateSnapshotBackup =
             self.storage.load_json_file(&self.manifest_handle).await?;
+        let codec = FileCodec::new(
+            manifest.encoding.clone(),
+            self.encryption_key.as_deref().map(Vec::as_slice),
+        )?;
         let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
-            self.storage.load_bcs_file(&manifest.proof).await?;
+            self.storage.load_bcs_file(&manifest.proof, &codec).await?;
         txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
         let state_root_hash = txn_info_with_proof
             .transaction_info()
@@ -135,8 +141,8 @@ impl StateSnapshotRestoreController {
         ver_gauge.set(self.version as i64);
         tgt_leaf_idx.set(manifest.chunks.last().map_or(0, |c| c.last_idx as i64));
         for chunk in manifest.chunks {
-            let blobs = self.read_state_value(chunk.blobs).await?;
-            let proof = self.storage.load_bcs_file(&chunk.proof).await?;
+            let blobs = self.read_state_value(chunk.blobs, &codec).await?;
+            let proof = self.storage.load_bcs_file(&chunk.proof, &codec).await?;
             receiver.add_chunk(blobs, proof)?;
 
             leaf_idx.set(chunk.last_idx as i64);
@@ -148,8 +154,10 @@ impl StateSnapshotRestoreController {
     async fn read_state_value(
         &self,
         file_handle: FileHandle,
+        codec: &FileCodec,
     ) -> Result<Vec<(StateKey, StateValue)>> {
-        let mut file = self.storage.open_for_read(&file_handle).await?;
+        let bytes = self.storage.read_all_decoded(&file_handle, codec).await?;
+        let mut file = &bytes[..];
 
         let mut chunk = vec![];
 
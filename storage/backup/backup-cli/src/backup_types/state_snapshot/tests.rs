@@ -9,6 +9,7 @@ use crate::{
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
         backup_service_client::BackupServiceClient,
+        file_encoding::{EncryptionKeyOpt, FileEncodingOpt},
         test_utils::{start_local_backup_service, tmp_db_with_random_content},
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, RocksdbOpt, TrustedWaypointOpt,
     },
@@ -45,6 +46,7 @@ fn end_to_end() {
                 StateSnapshotBackupOpt { version },
                 GlobalBackupOpt {
                     max_chunk_size: 500,
+                    file_encoding: FileEncodingOpt::default(),
                 },
                 client,
                 Arc::clone(&store),
@@ -66,6 +68,7 @@ fn end_to_end() {
                 trusted_waypoints: TrustedWaypointOpt::default(),
                 rocksdb_opt: RocksdbOpt::default(),
                 concurernt_downloads: ConcurrentDownloadsOpt::default(),
+                encryption_key: EncryptionKeyOpt::default(),
             }
             .try_into()
             .unwrap(),
@@ -14,7 +14,9 @@ use crate::{
     },
     storage::{local_fs::LocalFs, BackupStorage},
     utils::{
-        backup_service_client::BackupServiceClient, test_utils::start_local_backup_service,
+        backup_service_client::BackupServiceClient,
+        file_encoding::{EncryptionKeyOpt, FileEncodingOpt},
+        test_utils::start_local_backup_service,
         ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, GlobalRestoreOptions,
         RocksdbOpt, TrustedWaypointOpt,
     },
@@ -28,6 +30,8 @@ use std::{convert::TryInto, sync::Arc};
 use storage_interface::DbReader;
 use tokio::time::Duration;
 
+const ENCRYPTION_KEY_ENV: &str = "TEST_BACKUP_E2E_ENCRYPTION_KEY";
+
 #[derive(Debug)]
 struct TestData {
     db: Arc<AptosDB>,
@@ -86,9 +90,18 @@ fn test_end_to_end_impl(d: TestData) {
     )));
     let num_txns_to_backup = d.target_ver - d.txn_start_ver + 1;
 
-    // Backup
+    // Backup, compressed and encrypted
+    std::env::set_var(ENCRYPTION_KEY_ENV, "passphrase");
+    let encryption_key = EncryptionKeyOpt {
+        encryption_key_file: None,
+        encryption_key_env: Some(ENCRYPTION_KEY_ENV.to_string()),
+    };
     let global_backup_opt = GlobalBackupOpt {
         max_chunk_size: 2048,
+        file_encoding: FileEncodingOpt {
+            compress: true,
+            encryption_key: encryption_key.clone(),
+        },
     };
     let state_snapshot_manifest = d.state_snapshot_ver.map(|version| {
         rt.block_on(
@@ -125,6 +138,7 @@ fn test_end_to_end_impl(d: TestData) {
         trusted_waypoints: TrustedWaypointOpt::default(),
         rocksdb_opt: RocksdbOpt::default(),
         concurernt_downloads: ConcurrentDownloadsOpt::default(),
+        encryption_key,
     }
     .try_into()
     .unwrap();
@@ -6,8 +6,12 @@ use crate::{
     metadata::Metadata,
     storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
     utils::{
-        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
-        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+        backup_service_client::BackupServiceClient,
+        file_encoding::{FileCodec, FileEncodingOpt},
+        read_record_bytes::ReadRecordBytes,
+        should_cut_chunk,
+        storage_ext::BackupStorageExt,
+        GlobalBackupOpt,
     },
 };
 use anyhow::{anyhow, Result};
@@ -16,7 +20,7 @@ use aptos_types::transaction::Version;
 use once_cell::sync::Lazy;
 use std::{convert::TryInto, str::FromStr, sync::Arc};
 use structopt::StructOpt;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[derive(StructOpt)]
 pub struct TransactionBackupOpt {
@@ -31,6 +35,7 @@ pub struct TransactionBackupController {
     start_version: u64,
     num_transactions: usize,
     max_chunk_size: usize,
+    file_encoding_opt: FileEncodingOpt,
     client: Arc<BackupServiceClient>,
     storage: Arc<dyn BackupStorage>,
 }
@@ -46,6 +51,7 @@ impl TransactionBackupController {
             start_version: opt.start_version,
             num_transactions: opt.num_transactions,
             max_chunk_size: global_opt.max_chunk_size,
+            file_encoding_opt: global_opt.file_encoding,
             client,
             storage,
         }
@@ -71,6 +77,7 @@ impl TransactionBackupController {
             .storage
             .create_backup_with_random_suffix(&self.backup_name())
             .await?;
+        let codec = self.file_encoding_opt.new_codec()?;
 
         let mut chunks = Vec::new();
         let mut chunk_bytes = Vec::new();
@@ -87,9 +94,10 @@ impl TransactionBackupController {
                 let chunk = self
                     .write_chunk(
                         &backup_handle,
-                        &chunk_bytes,
+                        chunk_bytes,
                         chunk_first_ver,
                         current_ver - 1,
+                        &codec,
                     )
                     .await?;
                 chunks.push(chunk);
@@ -110,15 +118,22 @@ impl TransactionBackupController {
         let chunk = self
             .write_chunk(
                 &backup_handle,
-                &chunk_bytes,
+                chunk_bytes,
                 chunk_first_ver,
                 current_ver - 1,
+                &codec,
             )
             .await?;
         chunks.push(chunk);
 
-        self.write_manifest(&backup_handle, self.start_version, current_ver - 1, chunks)
-            .await
+        self.write_manifest(
+            &backup_handle,
+            self.start_version,
+            current_ver - 1,
+            chunks,
+            &codec,
+        )
+        .await
     }
 
     fn backup_name(&self) -> String {
@@ -144,33 +159,36 @@ impl TransactionBackupController {
     async fn write_chunk(
         &self,
         backup_handle: &BackupHandleRef,
-        chunk_bytes: &[u8],
+        chunk_bytes: Vec<u8>,
         first_version: u64,
         last_version: u64,
+        codec: &FileCodec,
     ) -> Result<TransactionChunk> {
-        let (proof_handle, mut proof_file) = self
+        let mut proof_bytes = Vec::new();
+        self.client
+            .get_transaction_range_proof(first_version, last_version)
+            .await?
+            .read_to_end(&mut proof_bytes)
+            .await?;
+        let proof_handle = self
             .storage
-            .create_for_write(
+            .write_encoded(
                 backup_handle,
                 &Self::chunk_proof_name(first_version, last_version),
+                proof_bytes,
+                codec,
             )
             .await?;
-        tokio::io::copy(
-            &mut self
-                .client
-                .get_transaction_range_proof(first_version, last_version)
-                .await?,
-            &mut proof_file,
-        )
-        .await?;
-        proof_file.shutdown().await?;
 
-        let (chunk_handle, mut chunk_file) = self
+        let chunk_handle = self
             .storage
-            .create_for_write(backup_handle, &Self::chunk_name(first_version))
+            .write_encoded(
+                backup_handle,
+                &Self::chunk_name(first_version),
+                chunk_bytes,
+                codec,
+            )
             .await?;
-        chunk_file.write_all(chunk_bytes).await?;
-        chunk_file.shutdown().await?;
 
         Ok(TransactionChunk {
             first_version,
@@ -186,11 +204,13 @@ impl TransactionBackupController {
         first_version: Version,
         last_version: Version,
         chunks: Vec<TransactionChunk>,
+        codec: &FileCodec,
     ) -> Result<FileHandle> {
         let manifest = TransactionBackup {
             first_version,
             last_version,
             chunks,
+            encoding: codec.encoding().clone(),
         };
         let (manifest_handle, mut manifest_file) = self
             .storage
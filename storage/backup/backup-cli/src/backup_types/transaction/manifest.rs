@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::FileHandle;
+use crate::{storage::FileHandle, utils::file_encoding::FileEncoding};
 use anyhow::{ensure, Result};
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
@@ -30,6 +30,9 @@ pub struct TransactionBackup {
     pub first_version: Version,
     pub last_version: Version,
     pub chunks: Vec<TransactionChunk>,
+    /// How the data files are encoded, they are in plain if absent.
+    #[serde(default, skip_serializing_if = "FileEncoding::is_plain")]
+    pub encoding: FileEncoding,
 }
 
 impl TransactionBackup {
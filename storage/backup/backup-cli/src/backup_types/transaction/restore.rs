@@ -13,6 +13,7 @@ use crate::{
     storage::{BackupStorage, FileHandle},
     utils::{
         error_notes::ErrorNotes,
+        file_encoding::FileCodec,
         read_record_bytes::ReadRecordBytes,
         storage_ext::BackupStorageExt,
         stream::{StreamX, TryStreamX},
@@ -44,7 +45,6 @@ use itertools::zip_eq;
 use std::{cmp::min, pin::Pin, sync::Arc, time::Instant};
 use storage_interface::DbReaderWriter;
 use structopt::StructOpt;
-use tokio::io::BufReader;
 
 const BATCH_SIZE: usize = if cfg!(test) { 2 } else { 10000 };
 
@@ -84,10 +84,14 @@ struct LoadedChunk {
 impl LoadedChunk {
     async fn load(
         manifest: TransactionChunk,
+        codec: &FileCodec,
         storage: &Arc<dyn BackupStorage>,
         epoch_history: Option<&Arc<EpochHistory>>,
     ) -> Result<Self> {
-        let mut file = BufReader::new(storage.open_for_read(&manifest.transactions).await?);
+        let bytes = storage
+            .read_all_decoded(&manifest.transactions, codec)
+            .await?;
+        let mut file = &bytes[..];
         let mut txns = Vec::new();
         let mut txn_infos = Vec::new();
         let mut event_vecs = Vec::new();
@@ -110,6 +114,7 @@ impl LoadedChunk {
         let (range_proof, ledger_info) = storage
             .load_bcs_file::<(TransactionAccumulatorRangeProof, LedgerInfoWithSignatures)>(
                 &manifest.proof,
+                codec,
             )
             .await?;
         if let Some(epoch_history) = epoch_history {
@@ -248,14 +253,30 @@ impl TransactionRestoreBatchController {
             .buffered_x(con * 3, con)
             .and_then(|m: TransactionBackup| future::ready(m.verify().map(|_| m)));
 
+        let encryption_key = self.global_opt.encryption_key.clone();
         let target_version = self.global_opt.target_version;
         let chunk_manifest_stream = manifest_stream
-            .map_ok(|m| stream::iter(m.chunks.into_iter().map(Result::<_>::Ok)))
+            .and_then(move |m| {
+                future::ready(
+                    FileCodec::new(
+                        m.encoding.clone(),
+                        encryption_key.as_deref().map(Vec::as_slice),
+                    )
+                    .map(|codec| (m, Arc::new(codec))),
+                )
+            })
+            .map_ok(|(m, codec)| {
+                stream::iter(
+                    m.chunks
+                        .into_iter()
+                        .map(move |c| Result::<_>::Ok((c, codec.clone()))),
+                )
+            })
             .try_flatten()
-            .try_take_while(move |c| future::ready(Ok(c.first_version <= target_version)))
+            .try_take_while(move |(c, _)| future::ready(Ok(c.first_version <= target_version)))
             .scan(0, |last_chunk_last_version, chunk_res| {
                 let res = match &chunk_res {
-                    Ok(chunk) => {
+                    Ok((chunk, _)) => {
                         if *last_chunk_last_version != 0
                             && chunk.first_version != *last_chunk_last_version + 1
                         {
@@ -277,12 +298,12 @@ impl TransactionRestoreBatchController {
         let storage = self.storage.clone();
         let epoch_history = self.epoch_history.clone();
         chunk_manifest_stream
-            .and_then(move |chunk| {
+            .and_then(move |(chunk, codec)| {
                 let storage = storage.clone();
                 let epoch_history = epoch_history.clone();
                 future::ok(async move {
                     tokio::task::spawn(async move {
-                        LoadedChunk::load(chunk, &storage, epoch_history.as_ref()).await
+                        LoadedChunk::load(chunk, &codec, &storage, epoch_history.as_ref()).await
                     })
                     .err_into::<anyhow::Error>()
                     .await
@@ -8,7 +8,7 @@ use backup_cli::{
     coordinators::verify::VerifyCoordinator,
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
-    utils::{ConcurrentDownloadsOpt, TrustedWaypointOpt},
+    utils::{file_encoding::EncryptionKeyOpt, ConcurrentDownloadsOpt, TrustedWaypointOpt},
 };
 use structopt::StructOpt;
 
@@ -22,6 +22,8 @@ struct Opt {
     storage: StorageOpt,
     #[structopt(flatten)]
     concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(flatten)]
+    encryption_key_opt: EncryptionKeyOpt,
 }
 
 #[tokio::main]
@@ -42,6 +44,7 @@ async fn main_impl() -> Result<()> {
         opt.metadata_cache_opt,
         opt.trusted_waypoints_opt,
         opt.concurrent_downloads.get(),
+        opt.encryption_key_opt,
     )?
     .run()
     .await
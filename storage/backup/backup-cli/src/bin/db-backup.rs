@@ -14,7 +14,10 @@ use backup_cli::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
-    coordinators::backup::{BackupCoordinator, BackupCoordinatorOpt},
+    coordinators::{
+        backup::{BackupCoordinator, BackupCoordinatorOpt},
+        retention::{RetentionCoordinator, RetentionOpt},
+    },
     metadata::{cache, cache::MetadataCacheOpt},
     storage::StorageOpt,
     utils::{
@@ -38,6 +41,11 @@ enum OneShotCommand {
     Query(OneShotQueryType),
     #[structopt(about = "Do a one shot backup.")]
     Backup(OneShotBackupOpt),
+    #[structopt(
+        about = "Delete the backups no longer needed according to the retention policy, and \
+        compact the metadata files."
+    )]
+    GarbageCollect(OneShotGarbageCollectOpt),
 }
 
 #[derive(StructOpt)]
@@ -69,6 +77,18 @@ struct OneShotQueryBackupStorageStateOpt {
     storage: StorageOpt,
 }
 
+#[derive(StructOpt)]
+struct OneShotGarbageCollectOpt {
+    #[structopt(flatten)]
+    metadata_cache: MetadataCacheOpt,
+    #[structopt(flatten)]
+    retention: RetentionOpt,
+    #[structopt(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(subcommand)]
+    storage: StorageOpt,
+}
+
 #[derive(StructOpt)]
 struct OneShotBackupOpt {
     #[structopt(flatten)]
@@ -195,6 +215,17 @@ async fn main_impl() -> Result<()> {
                     }
                 }
             }
+            OneShotCommand::GarbageCollect(opt) => {
+                let plan = RetentionCoordinator::new(
+                    opt.retention,
+                    opt.metadata_cache,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                )?
+                .run()
+                .await?;
+                print!("{}", plan)
+            }
         },
         Command::Coordinator(coordinator_cmd) => match coordinator_cmd {
             CoordinatorCommand::Run(opt) => {
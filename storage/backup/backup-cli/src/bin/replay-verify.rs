@@ -10,7 +10,9 @@ use backup_cli::{
     coordinators::replay_verify::ReplayVerifyCoordinator,
     metadata::cache::MetadataCacheOpt,
     storage::StorageOpt,
-    utils::{ConcurrentDownloadsOpt, RocksdbOpt, TrustedWaypointOpt},
+    utils::{
+        file_encoding::EncryptionKeyOpt, ConcurrentDownloadsOpt, RocksdbOpt, TrustedWaypointOpt,
+    },
 };
 use std::{path::PathBuf, sync::Arc};
 use structopt::StructOpt;
@@ -25,6 +27,8 @@ struct Opt {
     storage: StorageOpt,
     #[structopt(flatten)]
     concurrent_downloads: ConcurrentDownloadsOpt,
+    #[structopt(flatten)]
+    encryption_key_opt: EncryptionKeyOpt,
     #[structopt(long = "target-db-dir", parse(from_os_str))]
     pub db_dir: PathBuf,
     #[structopt(flatten)]
@@ -66,6 +70,7 @@ async fn main_impl() -> Result<()> {
         opt.metadata_cache_opt,
         opt.trusted_waypoints_opt,
         opt.concurrent_downloads.get(),
+        opt.encryption_key_opt,
         restore_handler,
         opt.start_version.unwrap_or(0),
         opt.end_version.unwrap_or(Version::MAX),
@@ -7,10 +7,11 @@ use crate::{
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
+    coordinators::retention::{RetentionCoordinator, RetentionOpt},
     metadata,
     metadata::cache::MetadataCacheOpt,
     metrics::backup::{
-        EPOCH_ENDING_EPOCH, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
+        EPOCH_ENDING_EPOCH, GC_SUCC_TS, HEARTBEAT_TS, STATE_SNAPSHOT_VERSION, TRANSACTION_VERSION,
     },
     storage::BackupStorage,
     utils::{
@@ -48,6 +49,14 @@ pub struct BackupCoordinatorOpt {
     pub transaction_batch_size: usize,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+    #[structopt(
+        long,
+        help = "[Defaults to never] Interval in seconds between garbage collections of the backups \
+        no longer needed according to the retention policy."
+    )]
+    pub gc_interval_secs: Option<u64>,
+    #[structopt(flatten)]
+    pub retention_opt: RetentionOpt,
 }
 
 impl BackupCoordinatorOpt {
@@ -63,6 +72,10 @@ impl BackupCoordinatorOpt {
              that's not yet in a transaction backup, resulting in replaying all transactions \
              at restore time."
         );
+        ensure!(
+            self.gc_interval_secs != Some(0),
+            "Garbage collection interval must be greater than 0."
+        );
         Ok(())
     }
 }
@@ -75,6 +88,8 @@ pub struct BackupCoordinator {
     state_snapshot_interval: usize,
     transaction_batch_size: usize,
     concurrent_downloads: usize,
+    gc_interval_secs: Option<u64>,
+    retention_coordinator: RetentionCoordinator,
 }
 
 impl BackupCoordinator {
@@ -85,6 +100,14 @@ impl BackupCoordinator {
         storage: Arc<dyn BackupStorage>,
     ) -> Self {
         opt.validate().unwrap();
+        let concurrent_downloads = opt.concurernt_downloads.get();
+        let retention_coordinator = RetentionCoordinator::new(
+            opt.retention_opt,
+            opt.metadata_cache_opt.clone(),
+            Arc::clone(&storage),
+            concurrent_downloads,
+        )
+        .unwrap();
         Self {
             client,
             storage,
@@ -92,7 +115,9 @@ impl BackupCoordinator {
             metadata_cache_opt: opt.metadata_cache_opt,
             state_snapshot_interval: opt.state_snapshot_interval,
             transaction_batch_size: opt.transaction_batch_size,
-            concurrent_downloads: opt.concurernt_downloads.get(),
+            concurrent_downloads,
+            gc_interval_secs: opt.gc_interval_secs,
+            retention_coordinator,
         }
     }
     pub async fn run(&self) -> Result<()> {
@@ -141,6 +166,12 @@ impl BackupCoordinator {
                 Self::backup_transactions,
             )
             .boxed_local();
+        let garbage_collect = match self.gc_interval_secs {
+            Some(secs) => IntervalStream::new(interval(Duration::from_secs(secs)))
+                .then(|_| self.try_garbage_collect())
+                .boxed_local(),
+            None => stream::pending().boxed_local(),
+        };
 
         info!("Backup coordinator started.");
         let mut all_work = stream::select_all(vec![
@@ -148,6 +179,7 @@ impl BackupCoordinator {
             backup_epoch_endings,
             backup_state_snapshots,
             backup_transactions,
+            garbage_collect,
         ]);
 
         loop {
@@ -180,6 +212,16 @@ impl BackupCoordinator {
         };
     }
 
+    async fn try_garbage_collect(&self) {
+        match self.retention_coordinator.run().await {
+            Ok(plan) => {
+                GC_SUCC_TS.set(unix_timestamp_sec());
+                info!("Garbage collection finished. {}", plan);
+            }
+            Err(e) => warn!("Failed garbage collecting backups: {}. Will try again.", e),
+        }
+    }
+
     async fn backup_epoch_endings(
         &self,
         mut last_epoch_ending_epoch_in_backup: Option<u64>,
@@ -9,7 +9,9 @@ use crate::{
     metadata,
     metadata::cache::MetadataCacheOpt,
     storage::BackupStorage,
-    utils::{GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        file_encoding::EncryptionKeyOpt, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt,
+    },
 };
 use anyhow::{ensure, Result};
 use aptos_logger::prelude::*;
@@ -22,6 +24,7 @@ pub struct ReplayVerifyCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     trusted_waypoints_opt: TrustedWaypointOpt,
     concurrent_downloads: usize,
+    encryption_key: Option<Arc<Vec<u8>>>,
     restore_handler: RestoreHandler,
     start_version: Version,
     end_version: Version,
@@ -33,6 +36,7 @@ impl ReplayVerifyCoordinator {
         metadata_cache_opt: MetadataCacheOpt,
         trusted_waypoints_opt: TrustedWaypointOpt,
         concurrent_downloads: usize,
+        encryption_key_opt: EncryptionKeyOpt,
         restore_handler: RestoreHandler,
         start_version: Version,
         end_version: Version,
@@ -42,6 +46,7 @@ impl ReplayVerifyCoordinator {
             metadata_cache_opt,
             trusted_waypoints_opt,
             concurrent_downloads,
+            encryption_key: encryption_key_opt.load()?.map(Arc::new),
             restore_handler,
             start_version,
             end_version,
@@ -96,6 +101,7 @@ impl ReplayVerifyCoordinator {
                 restore_handler: self.restore_handler,
             }),
             concurrent_downloads: self.concurrent_downloads,
+            encryption_key: self.encryption_key.clone(),
         };
 
         if let Some(backup) = state_snapshot {
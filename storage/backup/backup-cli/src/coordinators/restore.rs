@@ -15,7 +15,7 @@ use crate::{
     storage::BackupStorage,
     utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode},
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, ensure, Result};
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use std::sync::Arc;
@@ -107,6 +107,15 @@ impl RestoreCoordinator {
             Some(b) => b.version + 1,
             None => 0,
         };
+        if let Some(first_backup) = transactions.first() {
+            ensure!(
+                first_backup.first_version <= replay_transactions_from_version,
+                "Transaction backups start from version {}, can't replay from version {}. Older \
+                 backups might have been garbage collected, try a later target version.",
+                first_backup.first_version,
+                replay_transactions_from_version,
+            );
+        }
         COORDINATOR_TARGET_VERSION.set(actual_target_version as i64);
         info!("Planned to restore to version {}.", actual_target_version);
 
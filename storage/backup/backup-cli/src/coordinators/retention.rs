@@ -0,0 +1,423 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup, state_snapshot::manifest::StateSnapshotBackup,
+        transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{cache::MetadataCacheOpt, view::MetadataView, Metadata, StateSnapshotBackupMeta},
+    metrics::backup::GC_DELETED_BACKUPS,
+    storage::{BackupStorage, FileHandle, ShellSafeName},
+    utils::{storage_ext::BackupStorageExt, unix_timestamp_sec},
+};
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use rand::random;
+use std::{cmp::Reverse, convert::TryInto, fmt, sync::Arc};
+use structopt::StructOpt;
+
+#[derive(Clone, StructOpt)]
+pub struct RetentionOpt {
+    #[structopt(
+        long,
+        default_value = "2",
+        help = "Number of the latest state snapshots that are always kept."
+    )]
+    pub keep_latest_state_snapshots: usize,
+    #[structopt(
+        long,
+        help = "[Defaults to keeping none] Among the older state snapshots, keep the oldest one in \
+        every this many versions, e.g. set it to N x state_snapshot_interval to keep every Nth \
+        state snapshot. Transactions are kept back to the oldest state snapshot kept."
+    )]
+    pub keep_state_snapshot_every_versions: Option<Version>,
+    #[structopt(
+        long,
+        help = "Only report the backups that would be deleted, without deleting anything."
+    )]
+    pub dry_run: bool,
+}
+
+impl RetentionOpt {
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            self.keep_latest_state_snapshots > 0,
+            "Must keep at least one state snapshot."
+        );
+        ensure!(
+            self.keep_state_snapshot_every_versions != Some(0),
+            "State snapshot retention interval must be greater than 0."
+        );
+        Ok(())
+    }
+}
+
+/// Which backups to keep and which to delete.
+pub struct RetentionPlan {
+    pub(crate) retained: Vec<Metadata>,
+    pub(crate) removed: Vec<Metadata>,
+}
+
+impl RetentionPlan {
+    /// Plans according to the retention policy:
+    ///   1. The latest `keep_latest_state_snapshots` state snapshots are kept, and among the older
+    /// ones the oldest in each `keep_state_snapshot_every_versions` versions.
+    ///   2. Transaction backups are kept back to the oldest state snapshot kept.
+    ///   3. Epoch ending backups are all kept, they are small and needed to verify everything else.
+    ///   4. A backup is deleted if it is superseded by others covering the same range, e.g. when
+    /// the same range has been backed up twice.
+    pub fn new(view: &MetadataView, opt: &RetentionOpt) -> Self {
+        let mut plan = Self {
+            retained: Vec::new(),
+            removed: Vec::new(),
+        };
+
+        // Epoch ending backups. For ones starting at the same epoch, the longest comes first.
+        let mut next_epoch = 0;
+        for backup in sorted_by_key(view.epoch_ending_backups(), |b| {
+            (b.first_epoch, Reverse(b.last_epoch))
+        }) {
+            let superseded = backup.last_epoch < next_epoch;
+            next_epoch = std::cmp::max(next_epoch, backup.last_epoch + 1);
+            plan.add(Metadata::EpochEndingBackup(backup.clone()), !superseded);
+        }
+
+        // State snapshots, from the latest.
+        let mut snapshots: Vec<&StateSnapshotBackupMeta> = Vec::new();
+        for backup in sorted_by_key(view.state_snapshot_backups(), |b| Reverse(b.version)) {
+            let superseded = snapshots
+                .last()
+                .map_or(false, |s| s.version == backup.version);
+            if superseded {
+                plan.add(Metadata::StateSnapshotBackup(backup.clone()), false);
+            } else {
+                snapshots.push(backup);
+            }
+        }
+        let mut oldest_snapshot_version = None;
+        for (idx, backup) in snapshots.iter().enumerate() {
+            let keep = idx < opt.keep_latest_state_snapshots
+                || opt.keep_state_snapshot_every_versions.map_or(false, |n| {
+                    // Whether it's the oldest in its range.
+                    snapshots
+                        .get(idx + 1)
+                        .map_or(true, |older| older.version / n != backup.version / n)
+                });
+            if keep {
+                oldest_snapshot_version = Some(backup.version);
+            }
+            plan.add(Metadata::StateSnapshotBackup((*backup).clone()), keep);
+        }
+
+        // Transaction backups. For ones starting at the same version, the longest comes first.
+        let mut next_version = 0;
+        for backup in sorted_by_key(view.transaction_backups(), |b| {
+            (b.first_version, Reverse(b.last_version))
+        }) {
+            let superseded = backup.last_version < next_version;
+            next_version = std::cmp::max(next_version, backup.last_version + 1);
+            let expired = oldest_snapshot_version.map_or(false, |v| backup.last_version < v);
+            plan.add(
+                Metadata::TransactionBackup(backup.clone()),
+                !superseded && !expired,
+            );
+        }
+
+        plan
+    }
+
+    fn add(&mut self, metadata: Metadata, keep: bool) {
+        if keep {
+            self.retained.push(metadata)
+        } else {
+            self.removed.push(metadata)
+        }
+    }
+}
+
+fn sorted_by_key<T, K: Ord, F: FnMut(&T) -> K>(items: &[T], mut f: F) -> Vec<&T> {
+    let mut res: Vec<_> = items.iter().collect();
+    res.sort_by_key(|item| f(*item));
+    res
+}
+
+impl fmt::Display for RetentionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Retaining {} backups, deleting {} backups.",
+            self.retained.len(),
+            self.removed.len(),
+        )?;
+        for metadata in &self.removed {
+            writeln!(
+                f,
+                "  delete {}, manifest: {}",
+                metadata.name().as_str(),
+                metadata.manifest(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Deletes backups that are no longer needed according to the retention policy, and compacts the
+/// metadata of the rest into a single metadata file.
+///
+/// The metadata is compacted before any backup is deleted, so an interruption never leaves
+/// metadata referring to missing files, at worst some backup files are leaked. Restoring from a
+/// backup while it is being deleted fails.
+pub struct RetentionCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    retention_opt: RetentionOpt,
+    concurrent_downloads: usize,
+}
+
+impl RetentionCoordinator {
+    pub fn new(
+        retention_opt: RetentionOpt,
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrent_downloads: usize,
+    ) -> Result<Self> {
+        retention_opt.validate()?;
+        Ok(Self {
+            storage,
+            metadata_cache_opt,
+            retention_opt,
+            concurrent_downloads,
+        })
+    }
+
+    pub async fn run(&self) -> Result<RetentionPlan> {
+        // Metadata files saved after the listing are not deleted, even if their content gets
+        // loaded below, in which case the same lines end up in the compacted file as well, which
+        // is fine since they are deduplicated on load. New backups are the latest ones, which are
+        // never deleted.
+        let metadata_files = self.storage.list_metadata_files().await?;
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let plan = RetentionPlan::new(&metadata_view, &self.retention_opt);
+        if self.retention_opt.dry_run {
+            info!("Dry run, not deleting anything.");
+            return Ok(plan);
+        }
+
+        if metadata_files.len() > 1 || !plan.removed.is_empty() {
+            self.compact_metadata(&plan.retained, &metadata_files)
+                .await?;
+        }
+        for metadata in &plan.removed {
+            info!(
+                name = %metadata.name().as_str(),
+                manifest = %metadata.manifest(),
+                "Deleting backup."
+            );
+            // The metadata is already gone, so files failed to be deleted are left behind.
+            match self.delete_backup(metadata).await {
+                Ok(()) => GC_DELETED_BACKUPS.inc(),
+                Err(e) => warn!(
+                    manifest = %metadata.manifest(),
+                    error = ?e,
+                    "Failed deleting backup, some files are left behind."
+                ),
+            }
+        }
+        info!(
+            num_retained = plan.retained.len(),
+            num_removed = plan.removed.len(),
+            "Backups garbage collected."
+        );
+        Ok(plan)
+    }
+
+    async fn compact_metadata(
+        &self,
+        retained: &[Metadata],
+        metadata_files: &[FileHandle],
+    ) -> Result<()> {
+        let name: ShellSafeName = format!(
+            "compacted_{}.{:04x}.meta",
+            unix_timestamp_sec(),
+            random::<u16>()
+        )
+        .try_into()?;
+        let lines = retained
+            .iter()
+            .map(Metadata::to_text_line)
+            .collect::<Result<Vec<_>>>()?;
+        self.storage.save_metadata_lines(&name, &lines).await?;
+        for file_handle in metadata_files {
+            self.storage.delete_file(file_handle).await?;
+        }
+        info!(
+            name = %name.as_str(),
+            num_lines = lines.len(),
+            num_files_replaced = metadata_files.len(),
+            "Metadata compacted."
+        );
+        Ok(())
+    }
+
+    async fn delete_backup(&self, metadata: &Metadata) -> Result<()> {
+        let manifest = metadata.manifest();
+        let file_handles: Vec<FileHandle> = match metadata {
+            Metadata::EpochEndingBackup(_) => self
+                .storage
+                .load_json_file::<EpochEndingBackup>(manifest)
+                .await?
+                .chunks
+                .into_iter()
+                .map(|chunk| chunk.ledger_infos)
+                .collect(),
+            Metadata::StateSnapshotBackup(_) => {
+                let backup: StateSnapshotBackup = self.storage.load_json_file(manifest).await?;
+                backup
+                    .chunks
+                    .into_iter()
+                    .flat_map(|chunk| vec![chunk.blobs, chunk.proof])
+                    .chain(std::iter::once(backup.proof))
+                    .collect()
+            }
+            Metadata::TransactionBackup(_) => self
+                .storage
+                .load_json_file::<TransactionBackup>(manifest)
+                .await?
+                .chunks
+                .into_iter()
+                .flat_map(|chunk| vec![chunk.transactions, chunk.proof])
+                .collect(),
+        };
+        for file_handle in file_handles {
+            self.storage.delete_file(&file_handle).await?;
+        }
+        // The manifest goes last, so an interrupted deletion can be spotted by the manifest left.
+        self.storage.delete_file(manifest).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        coordinators::retention::{RetentionOpt, RetentionPlan},
+        metadata::{view::MetadataView, Metadata},
+    };
+
+    fn opt(keep_latest: usize, every_versions: Option<u64>) -> RetentionOpt {
+        RetentionOpt {
+            keep_latest_state_snapshots: keep_latest,
+            keep_state_snapshot_every_versions: every_versions,
+            dry_run: false,
+        }
+    }
+
+    fn snapshot(version: u64) -> Metadata {
+        Metadata::new_state_snapshot_backup(version, format!("snapshot_{}", version))
+    }
+
+    fn transaction(first_version: u64, last_version: u64, manifest: &str) -> Metadata {
+        Metadata::new_transaction_backup(first_version, last_version, manifest.to_string())
+    }
+
+    fn epoch_ending(first_epoch: u64, last_epoch: u64, manifest: &str) -> Metadata {
+        Metadata::new_epoch_ending_backup(first_epoch, last_epoch, 0, 0, manifest.to_string())
+    }
+
+    fn plan(metadata: Vec<Metadata>, opt: &RetentionOpt) -> (Vec<String>, Vec<String>) {
+        let plan = RetentionPlan::new(&MetadataView::from(metadata), opt);
+        let mut retained: Vec<_> = plan
+            .retained
+            .iter()
+            .map(|m| m.manifest().to_string())
+            .collect();
+        let mut removed: Vec<_> = plan
+            .removed
+            .iter()
+            .map(|m| m.manifest().to_string())
+            .collect();
+        retained.sort();
+        removed.sort();
+        (retained, removed)
+    }
+
+    #[test]
+    fn test_keep_latest_state_snapshots() {
+        let metadata = vec![
+            snapshot(0),
+            snapshot(100),
+            snapshot(200),
+            snapshot(300),
+            transaction(0, 0, "txn_0"),
+            transaction(1, 100, "txn_1"),
+            transaction(101, 200, "txn_101"),
+            transaction(201, 300, "txn_201"),
+            transaction(301, 400, "txn_301"),
+        ];
+        let (retained, removed) = plan(metadata, &opt(2, None));
+        assert_eq!(
+            retained,
+            vec![
+                "snapshot_200",
+                "snapshot_300",
+                // Contains the oldest snapshot version, which is not replayed but kept anyway.
+                "txn_101",
+                "txn_201",
+                "txn_301",
+            ]
+        );
+        assert_eq!(
+            removed,
+            vec!["snapshot_0", "snapshot_100", "txn_0", "txn_1"]
+        );
+    }
+
+    #[test]
+    fn test_keep_every_nth_state_snapshot() {
+        let metadata = (0..10).map(|i| snapshot(i * 100)).collect();
+        let (retained, removed) = plan(metadata, &opt(1, Some(300)));
+        assert_eq!(
+            retained,
+            vec!["snapshot_0", "snapshot_300", "snapshot_600", "snapshot_900"]
+        );
+        assert_eq!(
+            removed,
+            vec![
+                "snapshot_100",
+                "snapshot_200",
+                "snapshot_400",
+                "snapshot_500",
+                "snapshot_700",
+                "snapshot_800",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_superseded() {
+        let metadata = vec![
+            epoch_ending(0, 0, "epoch_0"),
+            epoch_ending(1, 5, "epoch_1"),
+            epoch_ending(1, 3, "epoch_1_dup"),
+            epoch_ending(4, 5, "epoch_4_dup"),
+            epoch_ending(6, 6, "epoch_6"),
+            transaction(0, 0, "txn_0"),
+            transaction(1, 100, "txn_1"),
+            transaction(1, 100, "txn_1_dup"),
+            transaction(101, 200, "txn_101"),
+        ];
+        let (retained, removed) = plan(metadata, &opt(1, None));
+        assert_eq!(
+            retained,
+            vec!["epoch_0", "epoch_1", "epoch_6", "txn_0", "txn_1", "txn_101"]
+        );
+        assert_eq!(removed, vec!["epoch_1_dup", "epoch_4_dup", "txn_1_dup"]);
+    }
+}
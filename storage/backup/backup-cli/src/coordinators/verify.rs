@@ -13,7 +13,10 @@ use crate::{
         VERIFY_COORDINATOR_FAIL_TS, VERIFY_COORDINATOR_START_TS, VERIFY_COORDINATOR_SUCC_TS,
     },
     storage::BackupStorage,
-    utils::{unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode, TrustedWaypointOpt},
+    utils::{
+        file_encoding::EncryptionKeyOpt, unix_timestamp_sec, GlobalRestoreOptions, RestoreRunMode,
+        TrustedWaypointOpt,
+    },
 };
 use anyhow::Result;
 use aptos_logger::prelude::*;
@@ -25,6 +28,7 @@ pub struct VerifyCoordinator {
     metadata_cache_opt: MetadataCacheOpt,
     trusted_waypoints_opt: TrustedWaypointOpt,
     concurrent_downloads: usize,
+    encryption_key: Option<Arc<Vec<u8>>>,
 }
 
 impl VerifyCoordinator {
@@ -33,12 +37,14 @@ impl VerifyCoordinator {
         metadata_cache_opt: MetadataCacheOpt,
         trusted_waypoints_opt: TrustedWaypointOpt,
         concurrent_downloads: usize,
+        encryption_key_opt: EncryptionKeyOpt,
     ) -> Result<Self> {
         Ok(Self {
             storage,
             metadata_cache_opt,
             trusted_waypoints_opt,
             concurrent_downloads,
+            encryption_key: encryption_key_opt.load()?.map(Arc::new),
         })
     }
 
@@ -79,6 +85,7 @@ impl VerifyCoordinator {
             trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
             run_mode: Arc::new(RestoreRunMode::Verify),
             concurrent_downloads: self.concurrent_downloads,
+            encryption_key: self.encryption_key.clone(),
         };
 
         let epoch_history = Arc::new(
@@ -33,7 +33,7 @@ static TEMP_METADATA_CACHE_DIR: Lazy<TempPath> = Lazy::new(|| {
     dir
 });
 
-#[derive(StructOpt)]
+#[derive(Clone, StructOpt)]
 pub struct MetadataCacheOpt {
     #[structopt(
         long = "metadata-cache-dir",
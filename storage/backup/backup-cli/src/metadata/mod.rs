@@ -4,13 +4,13 @@
 pub mod cache;
 pub mod view;
 
-use crate::storage::{FileHandle, ShellSafeName, TextLine};
+use crate::storage::{FileHandle, FileHandleRef, ShellSafeName, TextLine};
 use anyhow::Result;
 use aptos_types::transaction::Version;
 use serde::{Deserialize, Serialize};
 use std::convert::TryInto;
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 #[allow(clippy::enum_variant_names)] // to introduce: BackupperId, etc
 pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
@@ -65,6 +65,14 @@ impl Metadata {
         .unwrap()
     }
 
+    pub fn manifest(&self) -> &FileHandleRef {
+        match self {
+            Self::EpochEndingBackup(e) => &e.manifest,
+            Self::StateSnapshotBackup(s) => &s.manifest,
+            Self::TransactionBackup(t) => &t.manifest,
+        }
+    }
+
     pub fn to_text_line(&self) -> Result<TextLine> {
         TextLine::new(&serde_json::to_string(self)?)
     }
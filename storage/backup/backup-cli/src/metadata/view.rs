@@ -53,15 +53,17 @@ impl MetadataView {
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
         // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // range (which is always true when we backup from a single backup coordinator). They
+        // don't necessarily start from version 0, since old ones can be garbage collected.
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
+            let next_ver = next_ver.get_or_insert(backup.first_version);
             ensure!(
-                backup.first_version == next_ver,
+                backup.first_version == *next_ver,
                 "Transactioon backup ranges not continuous, expecting version {}, got {}.",
                 next_ver,
                 backup.first_version,
@@ -71,7 +73,7 @@ impl MetadataView {
                 res.push(backup.clone());
             }
 
-            next_ver = backup.last_version + 1;
+            *next_ver = backup.last_version + 1;
         }
 
         Ok(res)
@@ -103,6 +105,18 @@ impl MetadataView {
 
         Ok(res)
     }
+
+    pub fn epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    pub fn state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    pub fn transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
 }
 
 impl From<Vec<Metadata>> for MetadataView {
@@ -119,6 +133,15 @@ impl From<Vec<Metadata>> for MetadataView {
             }
         }
 
+        // The same entry can show up in multiple metadata files, e.g. when a metadata compaction
+        // is interrupted before the compacted files are deleted.
+        epoch_ending_backups.sort();
+        epoch_ending_backups.dedup();
+        state_snapshot_backups.sort();
+        state_snapshot_backups.dedup();
+        transaction_backups.sort();
+        transaction_backups.dedup();
+
         Self {
             epoch_ending_backups,
             state_snapshot_backups,
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_secure_push_metrics::{register_int_gauge, IntGauge};
+use aptos_secure_push_metrics::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
 use once_cell::sync::Lazy;
 
 pub static HEARTBEAT_TS: Lazy<IntGauge> = Lazy::new(|| {
@@ -35,3 +35,19 @@ pub static TRANSACTION_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static GC_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_coordinator_gc_succeed_timestamp_s",
+        "Timestamp when the backup coordinator last garbage collected old backups successfully."
+    )
+    .unwrap()
+});
+
+pub static GC_DELETED_BACKUPS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_db_backup_coordinator_gc_deleted_backups",
+        "Number of backups deleted according to the retention policy."
+    )
+    .unwrap()
+});
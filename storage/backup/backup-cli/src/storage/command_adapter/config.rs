@@ -62,11 +62,16 @@ pub struct Commands {
     /// Command line to save a line of metadata
     /// input env vars:
     ///     $FILE_NAME
-    /// stdin will be fed with a line of text with a trailing newline.
+    /// stdin will be fed with lines of text, each with a trailing newline.
     pub save_metadata_line: String,
     /// Command line to list all existing metadata file handles.
     /// expected stdout to stream out lines of file handles.
     pub list_metadata_files: String,
+    /// Command line to delete a file, only needed for garbage collecting old backups.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    #[serde(default)]
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
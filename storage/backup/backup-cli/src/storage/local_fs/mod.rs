@@ -15,7 +15,7 @@ use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 use tokio::{
-    fs::{create_dir, create_dir_all, read_dir, OpenOptions},
+    fs::{create_dir, create_dir_all, read_dir, remove_dir, remove_file, OpenOptions},
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
 };
 
@@ -92,6 +92,11 @@ impl BackupStorage for LocalFs {
     }
 
     async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
         let dir = self.metadata_dir();
         create_dir_all(&dir).await.err_notes(name)?; // in case not yet created
 
@@ -102,9 +107,11 @@ impl BackupStorage for LocalFs {
             .open(&path)
             .await
             .err_notes(&path)?;
-        file.write_all(content.as_ref().as_bytes())
-            .await
-            .err_notes(&path)?;
+        for line in lines {
+            file.write_all(line.as_ref().as_bytes())
+                .await
+                .err_notes(&path)?;
+        }
 
         Ok(())
     }
@@ -122,4 +129,20 @@ impl BackupStorage for LocalFs {
         }
         Ok(res)
     }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let path = self.dir.join(file_handle);
+        remove_file(&path).await.err_notes(&path)?;
+
+        // Remove the backup folder once its last file is deleted.
+        if let Some(parent) = path.parent() {
+            if parent != self.dir && parent != self.metadata_dir() {
+                let mut entries = read_dir(parent).await.err_notes(parent)?;
+                if entries.next_entry().await.err_notes(parent)?.is_none() {
+                    remove_dir(parent).await.err_notes(parent)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@
 
 use super::*;
 use crate::storage::test_util::{
-    arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+    arb_backups, arb_metadata_files, test_delete_impl, test_save_and_list_metadata_files_impl,
     test_write_and_read_impl,
 };
 use aptos_temppath::TempPath;
@@ -36,4 +36,17 @@ proptest! {
         let rt = Runtime::new().unwrap();
         rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
     }
+
+    #[test]
+    fn test_delete(
+        backups in arb_backups(),
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let store = LocalFs::new(tmpdir.path().to_path_buf());
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_delete_impl(Box::new(store), backups, input));
+    }
 }
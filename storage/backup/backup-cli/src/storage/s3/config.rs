@@ -0,0 +1,107 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::{ensure, Result};
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// The smallest part S3 accepts in a multipart upload, except for the last part.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// Name of the bucket.
+    pub bucket: String,
+    /// Key prefix under which all files are put, without leading or trailing slashes. Empty means
+    /// the root of the bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// Region name, like "us-west-2". Used to sign requests, even for S3-compatible stores.
+    pub region: String,
+    /// Endpoint of an S3-compatible store, like "http://localhost:9000". The AWS endpoint of the
+    /// region is used if absent. Buckets are always addressed in the path style.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Names of the environment variables holding the access key id and the secret access key.
+    /// If absent, credentials are looked up the way the AWS CLI does: environment variables, the
+    /// profile file, then the container or instance metadata.
+    #[serde(default)]
+    pub access_key_id_env: Option<String>,
+    #[serde(default)]
+    pub secret_access_key_env: Option<String>,
+    /// Files larger than this are uploaded in parts of this size, and downloaded in ranges of this
+    /// size. S3 requires at least 5MiB.
+    #[serde(default = "S3Config::default_part_size")]
+    pub part_size: usize,
+    /// Maximum number of parts of a file uploaded or downloaded at the same time.
+    #[serde(default = "S3Config::default_concurrency")]
+    pub concurrency: usize,
+    /// Times a request is retried upon network errors and server side errors.
+    #[serde(default = "S3Config::default_max_retries")]
+    pub max_retries: usize,
+    /// Timeout of each request, including the transfer of its body.
+    #[serde(default = "S3Config::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Whether to check content read back against the ETag, which is the MD5 of the content (or of
+    /// its parts) unless the bucket is encrypted with KMS keys.
+    #[serde(default = "S3Config::default_verify_checksums")]
+    pub verify_checksums: bool,
+}
+
+impl S3Config {
+    fn default_part_size() -> usize {
+        16 * 1024 * 1024
+    }
+
+    fn default_concurrency() -> usize {
+        4
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    fn default_request_timeout_secs() -> u64 {
+        300
+    }
+
+    fn default_verify_checksums() -> bool {
+        true
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        toml::from_slice::<Self>(&content)?.validated()
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        toml::from_str::<Self>(content)?.validated()
+    }
+
+    fn validated(self) -> Result<Self> {
+        ensure!(
+            !self.prefix.starts_with('/') && !self.prefix.ends_with('/'),
+            "prefix should not start or end with '/': {}",
+            self.prefix,
+        );
+        ensure!(
+            self.access_key_id_env.is_some() == self.secret_access_key_env.is_some(),
+            "access_key_id_env and secret_access_key_env should be set together.",
+        );
+        ensure!(
+            self.part_size >= MIN_PART_SIZE,
+            "part_size should be at least {} bytes, S3 rejects smaller parts: {}",
+            MIN_PART_SIZE,
+            self.part_size,
+        );
+        ensure!(self.concurrency > 0, "concurrency should be positive.");
+        Ok(self)
+    }
+}
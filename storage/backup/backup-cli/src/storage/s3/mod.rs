@@ -0,0 +1,684 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+mod config;
+
+#[cfg(test)]
+mod tests;
+
+use crate::storage::{
+    s3::config::S3Config, BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef,
+    ShellSafeName, TextLine,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_logger::prelude::*;
+use aptos_retrier::exp_retry_strategy;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    future, ready, stream,
+    task::{Context, Poll},
+    Future, StreamExt, TryStreamExt,
+};
+use rusoto_core::{
+    credential::{DefaultCredentialsProvider, StaticProvider},
+    request::BufferedHttpResponse,
+    signature::SignedRequest,
+    Client, HttpClient, Region,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{cmp::min, io, path::PathBuf, pin::Pin, str::FromStr, time::Duration};
+use structopt::StructOpt;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    sync::oneshot,
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// User metadata recording the part size of a multipart upload, so the ETag can be verified when
+/// the parts are downloaded.
+const PART_SIZE_HEADER: &str = "x-amz-meta-part-size";
+/// Buffer between a writer returned by `create_for_write()` and the task uploading its content.
+const PIPE_BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(StructOpt)]
+pub struct S3Opt {
+    #[structopt(long = "config", help = "Config file for the S3 backup store.")]
+    config: PathBuf,
+}
+
+/// A BackupStorage that talks to S3 or an S3-compatible object store directly.
+/// see `S3Config`.
+#[derive(Clone)]
+pub struct S3 {
+    config: S3Config,
+    region: Region,
+    client: Client,
+}
+
+impl S3 {
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(config: S3Config) -> Result<Self> {
+        let region = match &config.endpoint {
+            Some(endpoint) => Region::Custom {
+                name: config.region.clone(),
+                endpoint: endpoint.clone(),
+            },
+            None => Region::from_str(&config.region)?,
+        };
+        let dispatcher =
+            HttpClient::new().map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+        let client = match (&config.access_key_id_env, &config.secret_access_key_env) {
+            (Some(access_key_id_env), Some(secret_access_key_env)) => Client::new_with(
+                StaticProvider::new_minimal(
+                    Self::env_var(access_key_id_env)?,
+                    Self::env_var(secret_access_key_env)?,
+                ),
+                dispatcher,
+            ),
+            _ => Client::new_with(DefaultCredentialsProvider::new()?, dispatcher),
+        };
+
+        Ok(Self {
+            config,
+            region,
+            client,
+        })
+    }
+
+    pub async fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        let config = S3Config::load_from_file(&opt.config).await?;
+
+        Self::new(config)
+    }
+
+    fn env_var(name: &str) -> Result<String> {
+        std::env::var(name).map_err(|e| anyhow!("Failed reading env var {}: {}", name, e))
+    }
+
+    fn key(&self, file_handle: &FileHandleRef) -> String {
+        if self.config.prefix.is_empty() {
+            file_handle.to_string()
+        } else {
+            format!("{}/{}", self.config.prefix, file_handle)
+        }
+    }
+
+    fn file_handle(&self, key: &str) -> Result<FileHandle> {
+        let file_handle = if self.config.prefix.is_empty() {
+            Some(key)
+        } else {
+            key.strip_prefix(&self.config.prefix)
+                .and_then(|k| k.strip_prefix('/'))
+        };
+        file_handle
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Key {} is not under prefix {}.", key, self.config.prefix))
+    }
+
+    fn request(&self, method: &str, key: &str) -> SignedRequest {
+        SignedRequest::new(
+            method,
+            "s3",
+            &self.region,
+            &format!("/{}/{}", self.config.bucket, key),
+        )
+    }
+
+    fn bucket_request(&self, method: &str) -> SignedRequest {
+        SignedRequest::new(
+            method,
+            "s3",
+            &self.region,
+            &format!("/{}", self.config.bucket),
+        )
+    }
+
+    /// Sends the request made by `make_request`, retrying upon network errors, timeouts and
+    /// server side errors, with exponential backoff.
+    async fn send(
+        &self,
+        what: &str,
+        make_request: impl Fn() -> SignedRequest,
+    ) -> Result<BufferedHttpResponse> {
+        let timeout = Duration::from_secs(self.config.request_timeout_secs);
+        let mut delays = exp_retry_strategy(100, 10_000, self.config.max_retries);
+        loop {
+            let err = match tokio::time::timeout(timeout, self.dispatch(make_request())).await {
+                Ok(Ok(response)) if response.status.is_success() => return Ok(response),
+                Ok(Ok(response)) => {
+                    let err = anyhow!("{} failed: {}", what, describe_error(&response));
+                    if !(response.status.is_server_error() || response.status.as_u16() == 429) {
+                        return Err(err);
+                    }
+                    err
+                }
+                Ok(Err(e)) => anyhow!("{} failed: {}", what, e),
+                Err(_) => anyhow!("{} timed out after {:?}.", what, timeout),
+            };
+            match delays.next() {
+                Some(delay) => {
+                    warn!(error = %err, "Retrying in {:?}.", delay);
+                    tokio::time::sleep(delay).await;
+                }
+                None => return Err(err),
+            }
+        }
+    }
+
+    async fn dispatch(&self, request: SignedRequest) -> Result<BufferedHttpResponse> {
+        let response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| anyhow!("{:?}", e))?;
+        response.buffer().await.map_err(|e| anyhow!("{}", e))
+    }
+
+    async fn put_object(&self, key: &str, content: Bytes) -> Result<()> {
+        let content_md5 = base64::encode(md5::compute(&content).0);
+        self.send(&format!("PutObject {}", key), || {
+            let mut request = self.request("PUT", key);
+            request.add_header("Content-MD5", &content_md5);
+            request.set_payload(Some(content.clone()));
+            request
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Uploads what's read from `reader` to `key`, in a single request if it fits in one part,
+    /// otherwise in a multipart upload. Nothing is stored under `key` unless `finished` is
+    /// signaled before the end of `reader` is reached, and a multipart upload already started is
+    /// aborted in that case.
+    async fn upload(
+        self,
+        key: String,
+        mut reader: DuplexStream,
+        mut finished: oneshot::Receiver<()>,
+    ) -> Result<()> {
+        let part_size = self.config.part_size;
+        let first_part = read_part(&mut reader, part_size).await?;
+        if first_part.len() < part_size {
+            ensure_finished(&mut finished, &key)?;
+            return self.put_object(&key, first_part).await;
+        }
+
+        let upload_id = self.create_multipart_upload(&key).await?;
+        let key_ref = &key;
+        let parts =
+            stream::once(future::ok::<_, anyhow::Error>(first_part)).chain(stream::try_unfold(
+                (reader, finished),
+                move |(mut reader, mut finished)| async move {
+                    let part = read_part(&mut reader, part_size).await?;
+                    Result::<_>::Ok(if part.is_empty() {
+                        ensure_finished(&mut finished, key_ref)?;
+                        None
+                    } else {
+                        Some((part, (reader, finished)))
+                    })
+                },
+            ));
+        let res = async {
+            let store = &self;
+            let etags = parts
+                .enumerate()
+                .map(|(idx, part_res)| {
+                    let (key, upload_id) = (&key, &upload_id);
+                    async move { store.upload_part(key, upload_id, idx + 1, part_res?).await }
+                })
+                .buffered(self.config.concurrency)
+                .try_collect::<Vec<_>>()
+                .await?;
+            self.complete_multipart_upload(&key, &upload_id, &etags)
+                .await
+        }
+        .await;
+
+        if res.is_err() {
+            if let Err(e) = self.abort_multipart_upload(&key, &upload_id).await {
+                warn!(error = %e, "Failed to abort multipart upload.");
+            }
+        }
+        res
+    }
+
+    async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let part_size = self.config.part_size.to_string();
+        let response = self
+            .send(&format!("CreateMultipartUpload {}", key), || {
+                let mut request = self.request("POST", key);
+                request.add_param("uploads", "");
+                request.add_header(PART_SIZE_HEADER, &part_size);
+                request
+            })
+            .await?;
+        Ok(parse_xml::<InitiateMultipartUploadResult>(&response.body)
+            .map_err(|e| anyhow!("Failed parsing CreateMultipartUpload {} result: {}", key, e))?
+            .upload_id)
+    }
+
+    async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        content: Bytes,
+    ) -> Result<String> {
+        let part_number = part_number.to_string();
+        let content_md5 = base64::encode(md5::compute(&content).0);
+        let response = self
+            .send(&format!("UploadPart {} {}", key, part_number), || {
+                let mut request = self.request("PUT", key);
+                request.add_param("partNumber", part_number.as_str());
+                request.add_param("uploadId", upload_id);
+                request.add_header("Content-MD5", &content_md5);
+                request.set_payload(Some(content.clone()));
+                request
+            })
+            .await?;
+        response
+            .headers
+            .get("etag")
+            .cloned()
+            .ok_or_else(|| anyhow!("ETag not found uploading part {} of {}.", part_number, key))
+    }
+
+    async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        etags: &[String],
+    ) -> Result<()> {
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            etags
+                .iter()
+                .enumerate()
+                .map(|(idx, etag)| format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    idx + 1,
+                    etag
+                ))
+                .collect::<String>(),
+        );
+        let response = self
+            .send(&format!("CompleteMultipartUpload {}", key), || {
+                let mut request = self.request("POST", key);
+                request.add_param("uploadId", upload_id);
+                request.set_payload(Some(body.clone()));
+                request
+            })
+            .await?;
+        // S3 can report an error after sending out a 200 status, in which case the body is an
+        // error document rather than a CompleteMultipartUploadResult.
+        ensure!(
+            parse_xml::<ErrorResponse>(&response.body)
+                .unwrap_or_default()
+                .code
+                .is_none(),
+            "CompleteMultipartUpload {} failed: {}",
+            key,
+            describe_error(&response),
+        );
+        Ok(())
+    }
+
+    async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(&format!("AbortMultipartUpload {}", key), || {
+            let mut request = self.request("DELETE", key);
+            request.add_param("uploadId", upload_id);
+            request
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn get_range(&self, key: &str, start: usize, end: usize) -> Result<Bytes> {
+        let range = format!("bytes={}-{}", start, end - 1);
+        let response = self
+            .send(&format!("GetObject {} {}", key, range), || {
+                let mut request = self.request("GET", key);
+                request.add_header("Range", &range);
+                request
+            })
+            .await?;
+        ensure!(
+            response.body.len() == end - start,
+            "GetObject {} {} returned {} bytes.",
+            key,
+            range,
+            response.body.len(),
+        );
+        Ok(response.body)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3 {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // No need to create "folders" in an object store.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = format!("{}/{}", backup_handle, name.as_str());
+        let (pipe, reader) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+        let (finished_tx, finished_rx) = oneshot::channel();
+        let upload = tokio::spawn(
+            self.clone()
+                .upload(self.key(&file_handle), reader, finished_rx),
+        );
+        Ok((
+            file_handle,
+            Box::new(S3FileWriter {
+                pipe,
+                finished: Some(finished_tx),
+                pipe_closed: false,
+                upload,
+            }),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let key = self.key(file_handle);
+        let head = self
+            .send(&format!("HeadObject {}", key), || {
+                self.request("HEAD", &key)
+            })
+            .await?;
+        let size: usize = head
+            .headers
+            .get("content-length")
+            .ok_or_else(|| anyhow!("Content-Length not found for {}.", key))?
+            .parse()?;
+        let part_size = head
+            .headers
+            .get(PART_SIZE_HEADER)
+            .map(|s| s.parse::<usize>())
+            .transpose()?;
+        ensure!(part_size != Some(0), "Invalid part size of {}.", key);
+        let checksum = match head.headers.get("etag") {
+            Some(etag) if self.config.verify_checksums => Checksum::new(etag, part_size),
+            _ => Checksum::None,
+        };
+
+        // Ranges are aligned with the parts if the file was uploaded in parts by us.
+        let range_size = part_size.unwrap_or(self.config.part_size);
+        let ranges = (0..size)
+            .step_by(range_size)
+            .map(move |start| (start, min(start + range_size, size)));
+        let store = self.clone();
+        let ranges_stream = stream::iter(ranges)
+            .map(move |(start, end)| {
+                let store = store.clone();
+                let key = key.clone();
+                async move { store.get_range(&key, start, end).await }
+            })
+            .buffered(self.config.concurrency)
+            .boxed();
+
+        let verified_stream = stream::try_unfold(
+            (ranges_stream, checksum),
+            |(mut ranges_stream, mut checksum)| async move {
+                match ranges_stream.try_next().await? {
+                    Some(bytes) => {
+                        checksum.update(&bytes);
+                        Ok(Some((bytes, (ranges_stream, checksum))))
+                    }
+                    None => {
+                        checksum.verify()?;
+                        Ok(None)
+                    }
+                }
+            },
+        );
+
+        Ok(Box::new(
+            verified_stream
+                .map_err(|e: anyhow::Error| io::Error::new(io::ErrorKind::Other, e))
+                .boxed()
+                .into_async_read()
+                .compat(),
+        ))
+    }
+
+    async fn save_metadata_line(&self, name: &ShellSafeName, content: &TextLine) -> Result<()> {
+        self.save_metadata_lines(name, std::slice::from_ref(content))
+            .await
+    }
+
+    async fn save_metadata_lines(&self, name: &ShellSafeName, lines: &[TextLine]) -> Result<()> {
+        let key = self.key(&format!("{}/{}", Self::METADATA_DIR, name.as_str()));
+        let content = lines.iter().map(AsRef::<str>::as_ref).collect::<String>();
+        self.put_object(&key, content.into()).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.key(&format!("{}/", Self::METADATA_DIR));
+        let mut file_handles = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let response = self
+                .send("ListObjectsV2", || {
+                    let mut request = self.bucket_request("GET");
+                    request.add_param("list-type", "2");
+                    request.add_param("prefix", prefix.as_str());
+                    if let Some(token) = &continuation_token {
+                        request.add_param("continuation-token", token.as_str());
+                    }
+                    request
+                })
+                .await?;
+            let result = parse_xml::<ListBucketResult>(&response.body)
+                .map_err(|e| anyhow!("Failed parsing ListObjectsV2 result: {}", e))?;
+            for object in result.contents {
+                file_handles.push(self.file_handle(&object.key)?);
+            }
+            continuation_token = result.next_continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+        Ok(file_handles)
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let key = self.key(file_handle);
+        self.send(&format!("DeleteObject {}", key), || {
+            self.request("DELETE", &key)
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+/// Returned by `create_for_write()`, bytes written are piped to a task uploading them, and
+/// `shutdown()` waits for the upload to finish.
+///
+/// If the writer is dropped without `shutdown()`, the upload task sees the end of the pipe
+/// without having been told the file is finished. It then stores nothing and aborts the multipart
+/// upload it may have started, so no incomplete upload is left behind.
+struct S3FileWriter {
+    pipe: DuplexStream,
+    /// Signaled right before the pipe is closed by `shutdown()`.
+    finished: Option<oneshot::Sender<()>>,
+    pipe_closed: bool,
+    upload: JoinHandle<Result<()>>,
+}
+
+impl AsyncWrite for S3FileWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        Pin::new(&mut self.pipe).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.pipe).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        if !self.pipe_closed {
+            if let Some(finished) = self.finished.take() {
+                // The upload task can't be gone before the pipe is closed.
+                let _ = finished.send(());
+            }
+            ready!(Pin::new(&mut self.pipe).poll_shutdown(cx))?;
+            self.pipe_closed = true;
+        }
+
+        Pin::new(&mut self.upload).poll(cx).map(|res| match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+        })
+    }
+}
+
+/// Verifies the content read back against the ETag.
+enum Checksum {
+    None,
+    /// The ETag of a file uploaded in a single request is the MD5 of the content.
+    Md5 {
+        expected: String,
+        context: md5::Context,
+    },
+    /// The ETag of a file uploaded in parts is the MD5 of the concatenated MD5s of the parts,
+    /// followed by "-" and the number of parts. Requires the ranges read to be the parts.
+    MultipartMd5 {
+        expected: String,
+        part_md5s: Vec<u8>,
+        num_parts: usize,
+    },
+}
+
+impl Checksum {
+    fn new(etag: &str, part_size: Option<usize>) -> Self {
+        let expected = etag.trim_matches('"').to_string();
+        match (expected.contains('-'), part_size) {
+            (false, _) => Self::Md5 {
+                expected,
+                context: md5::Context::new(),
+            },
+            (true, Some(_)) => Self::MultipartMd5 {
+                expected,
+                part_md5s: Vec::new(),
+                num_parts: 0,
+            },
+            // Uploaded in parts by others, the part size is unknown.
+            (true, None) => Self::None,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::None => (),
+            Self::Md5 { context, .. } => context.consume(bytes),
+            Self::MultipartMd5 {
+                part_md5s,
+                num_parts,
+                ..
+            } => {
+                part_md5s.extend_from_slice(&md5::compute(bytes).0);
+                *num_parts += 1;
+            }
+        }
+    }
+
+    fn verify(self) -> Result<()> {
+        let (expected, actual) = match self {
+            Self::None => return Ok(()),
+            Self::Md5 { expected, context } => (expected, format!("{:x}", context.compute())),
+            Self::MultipartMd5 {
+                expected,
+                part_md5s,
+                num_parts,
+            } => (
+                expected,
+                format!("{:x}-{}", md5::compute(&part_md5s), num_parts),
+            ),
+        };
+        if expected != actual {
+            bail!(
+                "Checksum mismatch, ETag: {}, calculated: {}.",
+                expected,
+                actual
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Reads until `part_size` bytes are read or the end is reached.
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut buf = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut buf).await?;
+    Ok(buf.into())
+}
+
+/// Fails if the writer was dropped before `shutdown()`, see `S3FileWriter`.
+fn ensure_finished(finished: &mut oneshot::Receiver<()>, key: &str) -> Result<()> {
+    ensure!(
+        finished.try_recv().is_ok(),
+        "Writer of {} dropped before shutdown, discarding the upload.",
+        key,
+    );
+    Ok(())
+}
+
+/// Response bodies of the S3 API, only the elements used are declared, others are ignored.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    contents: Vec<ListedObject>,
+    next_continuation_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedObject {
+    key: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorResponse {
+    code: Option<String>,
+    message: Option<String>,
+}
+
+fn parse_xml<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    Ok(quick_xml::de::from_reader(body)?)
+}
+
+fn describe_error(response: &BufferedHttpResponse) -> String {
+    let error = parse_xml::<ErrorResponse>(&response.body).unwrap_or_default();
+    format!(
+        "status {}, {} {}",
+        response.status,
+        error.code.unwrap_or_default(),
+        error.message.unwrap_or_default(),
+    )
+}
@@ -0,0 +1,532 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::test_util::{
+    arb_backups, arb_metadata_files, test_delete_impl, test_save_and_list_metadata_files_impl,
+    test_write_and_read_impl,
+};
+use aptos_infallible::Mutex;
+use proptest::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+use warp::{
+    filters::path::FullPath,
+    http::{HeaderMap, Method, Response, StatusCode},
+    Filter,
+};
+
+const BUCKET: &str = "bucket";
+const PAGE_SIZE: usize = 3;
+
+struct MockObject {
+    content: Bytes,
+    etag: String,
+    part_size: Option<String>,
+}
+
+struct MockUpload {
+    key: String,
+    part_size: Option<String>,
+    parts: BTreeMap<usize, Bytes>,
+}
+
+/// Mimics the subset of the S3 API the `S3` storage uses, without checking the signatures.
+#[derive(Default)]
+struct MockS3 {
+    objects: BTreeMap<String, MockObject>,
+    uploads: HashMap<String, MockUpload>,
+    num_uploads: usize,
+    num_requests: usize,
+    /// Fails every n-th request with a server side error, to exercise retries.
+    fail_every: Option<usize>,
+}
+
+type MockResponse = Response<Vec<u8>>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompleteMultipartUpload {
+    #[serde(rename = "Part")]
+    parts: Vec<CompletedPart>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompletedPart {
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+fn respond(status: StatusCode, headers: Vec<(&str, String)>, body: String) -> MockResponse {
+    let mut builder = Response::builder().status(status);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    builder.body(body.into_bytes()).unwrap()
+}
+
+fn error(status: StatusCode, code: &str) -> MockResponse {
+    respond(
+        status,
+        vec![],
+        format!(
+            "<Error><Code>{}</Code><Message>mock</Message></Error>",
+            code
+        ),
+    )
+}
+
+fn quoted_md5(content: &[u8]) -> String {
+    format!("\"{:x}\"", md5::compute(content))
+}
+
+impl MockS3 {
+    fn handle(
+        &mut self,
+        method: Method,
+        path: &str,
+        query: HashMap<String, String>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> MockResponse {
+        self.num_requests += 1;
+        if let Some(n) = self.fail_every {
+            if self.num_requests % n == 0 {
+                return error(StatusCode::SERVICE_UNAVAILABLE, "SlowDown");
+            }
+        }
+
+        let path = path.trim_start_matches('/');
+        if path == BUCKET {
+            return match method {
+                Method::GET if query.get("list-type").map(String::as_str) == Some("2") => {
+                    self.list(&query)
+                }
+                _ => error(StatusCode::BAD_REQUEST, "NotImplemented"),
+            };
+        }
+        let key = match path.strip_prefix(BUCKET).and_then(|k| k.strip_prefix('/')) {
+            Some(key) => key.to_string(),
+            None => return error(StatusCode::NOT_FOUND, "NoSuchBucket"),
+        };
+        if let Some(content_md5) = headers.get("content-md5") {
+            if content_md5.to_str().unwrap() != base64::encode(md5::compute(&body).0) {
+                return error(StatusCode::BAD_REQUEST, "BadDigest");
+            }
+        }
+
+        match (method, query.get("uploadId")) {
+            (Method::HEAD, None) => match self.objects.get(&key) {
+                Some(object) => {
+                    let mut headers = vec![
+                        ("content-length", object.content.len().to_string()),
+                        ("etag", object.etag.clone()),
+                    ];
+                    if let Some(part_size) = &object.part_size {
+                        headers.push((PART_SIZE_HEADER, part_size.clone()));
+                    }
+                    respond(StatusCode::OK, headers, String::new())
+                }
+                None => error(StatusCode::NOT_FOUND, "NoSuchKey"),
+            },
+            (Method::GET, None) => match self.objects.get(&key) {
+                Some(object) => {
+                    let range = headers
+                        .get("range")
+                        .and_then(|r| r.to_str().unwrap().strip_prefix("bytes="))
+                        .and_then(|r| r.split_once('-'))
+                        .map(|(start, end)| {
+                            (
+                                start.parse::<usize>().unwrap(),
+                                end.parse::<usize>().unwrap(),
+                            )
+                        });
+                    let mut response = Response::builder().header("etag", object.etag.clone());
+                    let body = match range {
+                        Some((start, end)) => {
+                            response = response.status(StatusCode::PARTIAL_CONTENT);
+                            object
+                                .content
+                                .slice(start..=min(end, object.content.len() - 1))
+                        }
+                        None => object.content.clone(),
+                    };
+                    response.body(body.to_vec()).unwrap()
+                }
+                None => error(StatusCode::NOT_FOUND, "NoSuchKey"),
+            },
+            (Method::PUT, None) => {
+                let etag = quoted_md5(&body);
+                self.objects.insert(
+                    key,
+                    MockObject {
+                        content: body,
+                        etag: etag.clone(),
+                        part_size: None,
+                    },
+                );
+                respond(StatusCode::OK, vec![("etag", etag)], String::new())
+            }
+            (Method::POST, None) if query.contains_key("uploads") => {
+                self.num_uploads += 1;
+                let upload_id = format!("upload{}", self.num_uploads);
+                self.uploads.insert(
+                    upload_id.clone(),
+                    MockUpload {
+                        key,
+                        part_size: headers
+                            .get(PART_SIZE_HEADER)
+                            .map(|v| v.to_str().unwrap().to_string()),
+                        parts: BTreeMap::new(),
+                    },
+                );
+                respond(
+                    StatusCode::OK,
+                    vec![],
+                    format!(
+                        "<InitiateMultipartUploadResult><UploadId>{}</UploadId>\
+                        </InitiateMultipartUploadResult>",
+                        upload_id
+                    ),
+                )
+            }
+            (Method::PUT, Some(upload_id)) => match self.uploads.get_mut(upload_id) {
+                Some(upload) if upload.key == key => {
+                    let part_number = query["partNumber"].parse().unwrap();
+                    let etag = quoted_md5(&body);
+                    upload.parts.insert(part_number, body);
+                    respond(StatusCode::OK, vec![("etag", etag)], String::new())
+                }
+                _ => error(StatusCode::NOT_FOUND, "NoSuchUpload"),
+            },
+            (Method::POST, Some(upload_id)) => match self.uploads.remove(upload_id) {
+                Some(upload) => {
+                    let etags = parse_xml::<CompleteMultipartUpload>(&body)
+                        .unwrap()
+                        .parts
+                        .into_iter()
+                        .map(|part| part.etag)
+                        .collect::<Vec<_>>();
+                    let part_etags = upload
+                        .parts
+                        .values()
+                        .map(|part| quoted_md5(part))
+                        .collect::<Vec<_>>();
+                    if etags != part_etags {
+                        return error(StatusCode::BAD_REQUEST, "InvalidPart");
+                    }
+                    let part_md5s = upload
+                        .parts
+                        .values()
+                        .flat_map(|part| md5::compute(part).0)
+                        .collect::<Vec<_>>();
+                    let etag = format!("\"{:x}-{}\"", md5::compute(&part_md5s), upload.parts.len());
+                    self.objects.insert(
+                        upload.key,
+                        MockObject {
+                            content: upload.parts.values().flatten().copied().collect(),
+                            etag,
+                            part_size: upload.part_size,
+                        },
+                    );
+                    respond(
+                        StatusCode::OK,
+                        vec![],
+                        "<CompleteMultipartUploadResult></CompleteMultipartUploadResult>"
+                            .to_string(),
+                    )
+                }
+                None => error(StatusCode::NOT_FOUND, "NoSuchUpload"),
+            },
+            (Method::DELETE, Some(upload_id)) => {
+                self.uploads.remove(upload_id);
+                respond(StatusCode::NO_CONTENT, vec![], String::new())
+            }
+            (Method::DELETE, None) => {
+                self.objects.remove(&key);
+                respond(StatusCode::NO_CONTENT, vec![], String::new())
+            }
+            _ => error(StatusCode::BAD_REQUEST, "NotImplemented"),
+        }
+    }
+
+    fn list(&self, query: &HashMap<String, String>) -> MockResponse {
+        let prefix = query.get("prefix").cloned().unwrap_or_default();
+        let mut keys = self
+            .objects
+            .keys()
+            .filter(|k| k.starts_with(&prefix))
+            .filter(|k| match query.get("continuation-token") {
+                Some(token) => k.as_str() > token.as_str(),
+                None => true,
+            })
+            .take(PAGE_SIZE + 1)
+            .collect::<Vec<_>>();
+        let next_token = if keys.len() > PAGE_SIZE {
+            keys.truncate(PAGE_SIZE);
+            format!(
+                "<NextContinuationToken>{}</NextContinuationToken>",
+                keys.last().unwrap()
+            )
+        } else {
+            String::new()
+        };
+        respond(
+            StatusCode::OK,
+            vec![],
+            format!(
+                "<ListBucketResult>{}{}</ListBucketResult>",
+                keys.iter()
+                    .map(|k| format!("<Contents><Key>{}</Key></Contents>", k))
+                    .collect::<String>(),
+                next_token,
+            ),
+        )
+    }
+}
+
+/// Starts a mock S3 server in the current runtime, and returns a store talking to it.
+fn get_store(
+    part_size: usize,
+    fail_every: Option<usize>,
+) -> (Box<dyn BackupStorage>, Arc<Mutex<MockS3>>) {
+    let mock = Arc::new(Mutex::new(MockS3 {
+        fail_every,
+        ..Default::default()
+    }));
+    let mock_clone = Arc::clone(&mock);
+    let route = warp::any()
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .map(move |method, path: FullPath, query, headers, body| {
+            mock_clone
+                .lock()
+                .handle(method, path.as_str(), query, headers, body)
+        });
+    let (address, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    std::env::set_var("TEST_S3_ACCESS_KEY_ID", "access_key_id");
+    std::env::set_var("TEST_S3_SECRET_ACCESS_KEY", "secret_access_key");
+    let mut config = S3Config::load_from_str(&format!(
+        r#"
+            bucket = "{}"
+            prefix = "backup1/e1"
+            region = "mock"
+            endpoint = "http://{}"
+            access_key_id_env = "TEST_S3_ACCESS_KEY_ID"
+            secret_access_key_env = "TEST_S3_SECRET_ACCESS_KEY"
+            concurrency = 3
+        "#,
+        BUCKET, address,
+    ))
+    .unwrap();
+    // The mock has no minimum part size, small parts keep multipart transfers cheap to exercise
+    config.part_size = part_size;
+
+    (Box::new(S3::new(config).unwrap()), mock)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups(),
+        part_size in 100usize..2000,
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (store, _mock) = get_store(part_size, None);
+            test_write_and_read_impl(store, backups).await
+        });
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (store, _mock) = get_store(100, None);
+            test_save_and_list_metadata_files_impl(store, input).await
+        });
+    }
+
+    #[test]
+    fn test_delete(
+        backups in arb_backups(),
+        input in arb_metadata_files(),
+    ) {
+        Runtime::new().unwrap().block_on(async {
+            let (store, _mock) = get_store(100, None);
+            test_delete_impl(store, backups, input).await
+        });
+    }
+}
+
+async fn write_file(store: &dyn BackupStorage, name: &str, content: &[u8]) -> Result<FileHandle> {
+    let backup_handle = store
+        .create_backup(&ShellSafeName::from_str("backup").unwrap())
+        .await?;
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &ShellSafeName::from_str(name).unwrap())
+        .await?;
+    file.write_all(content).await?;
+    file.shutdown().await?;
+    Ok(file_handle)
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+#[test]
+fn test_part_size_too_small() {
+    let config = |part_size| {
+        S3Config::load_from_str(&format!(
+            r#"
+                bucket = "{}"
+                region = "mock"
+                part_size = {}
+            "#,
+            BUCKET, part_size,
+        ))
+    };
+    assert!(config(5 * 1024 * 1024 - 1).is_err());
+    assert!(config(5 * 1024 * 1024).is_ok());
+}
+
+#[test]
+fn test_retries() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, mock) = get_store(100, Some(3));
+        let content = (0..1000u32).map(|i| i as u8).collect::<Vec<_>>();
+        for name in ["small", "large"] {
+            let content = if name == "small" {
+                &content[..10]
+            } else {
+                &content[..]
+            };
+            let file_handle = write_file(store.as_ref(), name, content).await.unwrap();
+            assert_eq!(
+                read_file(store.as_ref(), &file_handle).await.unwrap(),
+                content
+            );
+        }
+        assert!(mock.lock().uploads.is_empty());
+    });
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, mock) = get_store(100, None);
+        for (name, len) in [("small", 10), ("large", 1000)] {
+            let file_handle = write_file(store.as_ref(), name, &vec![1u8; len])
+                .await
+                .unwrap();
+            read_file(store.as_ref(), &file_handle).await.unwrap();
+
+            let key = format!("backup1/e1/{}", file_handle);
+            let mut content = mock.lock().objects[&key].content.to_vec();
+            content[len / 2] = 2;
+            mock.lock().objects.get_mut(&key).unwrap().content = content.into();
+            assert!(read_file(store.as_ref(), &file_handle).await.is_err());
+        }
+    });
+}
+
+#[test]
+fn test_dropped_writer_not_uploaded() {
+    Runtime::new().unwrap().block_on(async {
+        let (store, mock) = get_store(100, None);
+        let backup_handle = store
+            .create_backup(&ShellSafeName::from_str("backup").unwrap())
+            .await
+            .unwrap();
+        for (name, len, num_uploads) in [("small", 10, 0), ("large", 1000, 1)] {
+            let (file_handle, mut file) = store
+                .create_for_write(&backup_handle, &ShellSafeName::from_str(name).unwrap())
+                .await
+                .unwrap();
+            file.write_all(&vec![0u8; len]).await.unwrap();
+            drop(file);
+
+            // Wait for the multipart upload to be started and then aborted.
+            tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    {
+                        let mock = mock.lock();
+                        if mock.num_uploads == num_uploads && mock.uploads.is_empty() {
+                            break;
+                        }
+                    }
+                    tokio::task::yield_now().await;
+                }
+            })
+            .await
+            .unwrap();
+            assert!(read_file(store.as_ref(), &file_handle).await.is_err());
+            assert!(mock.lock().objects.is_empty());
+        }
+    });
+}
+
+#[test]
+fn test_parse_xml() {
+    let list: ListBucketResult = parse_xml(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+        <ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <Name>bucket</Name><Prefix>metadata/</Prefix><KeyCount>2</KeyCount>
+            <IsTruncated>true</IsTruncated>
+            <Contents><Key>metadata/a</Key><ETag>&quot;abc&quot;</ETag><Size>1</Size></Contents>
+            <Contents><Key>metadata/b&amp;c</Key><Size>2</Size></Contents>
+            <NextContinuationToken>token/+=</NextContinuationToken>
+        </ListBucketResult>"#,
+    )
+    .unwrap();
+    assert_eq!(
+        list.contents
+            .into_iter()
+            .map(|object| object.key)
+            .collect::<Vec<_>>(),
+        vec!["metadata/a", "metadata/b&c"],
+    );
+    assert_eq!(list.next_continuation_token.as_deref(), Some("token/+="));
+
+    let empty: ListBucketResult =
+        parse_xml(b"<ListBucketResult><KeyCount>0</KeyCount></ListBucketResult>").unwrap();
+    assert!(empty.contents.is_empty() && empty.next_continuation_token.is_none());
+
+    let error: ErrorResponse = parse_xml(
+        b"<Error><Code>InternalError</Code><Message>Try again.</Message>\
+        <RequestId>1</RequestId></Error>",
+    )
+    .unwrap();
+    assert_eq!(error.code.as_deref(), Some("InternalError"));
+    assert_eq!(error.message.as_deref(), Some("Try again."));
+    let completed: ErrorResponse = parse_xml(
+        b"<CompleteMultipartUploadResult><Key>k</Key><ETag>\"abc-2\"</ETag>\
+        </CompleteMultipartUploadResult>",
+    )
+    .unwrap();
+    assert!(completed.code.is_none());
+}
+
+#[test]
+fn test_sample_configs() {
+    S3Config::load_from_str(include_str!("aws.sample.toml")).unwrap();
+    S3Config::load_from_str(include_str!("minio.sample.toml")).unwrap();
+}
@@ -11,7 +11,7 @@ use proptest::{
     collection::{hash_map, vec},
     prelude::*,
 };
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, str::FromStr};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 fn to_file_name(backup_name: &str, file_name: &str) -> String {
@@ -102,3 +102,73 @@ pub fn arb_metadata_files() -> impl Strategy<Value = Vec<(ShellSafeName, TextLin
         .prop_map(HashMap::into_iter)
         .prop_map(Iterator::collect)
 }
+
+pub async fn test_delete_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+    input: Vec<(ShellSafeName, TextLine)>,
+) {
+    // Compact all metadata files into one.
+    for (name, content) in &input {
+        store.save_metadata_line(name, content).await.unwrap();
+    }
+    let old_metadata_files = store.list_metadata_files().await.unwrap();
+    let compacted_name = ShellSafeName::from_str("compacted.meta").unwrap();
+    let lines = input
+        .into_iter()
+        .map(|(_name, content)| content)
+        .sorted()
+        .collect::<Vec<_>>();
+    store
+        .save_metadata_lines(&compacted_name, &lines)
+        .await
+        .unwrap();
+    for file_handle in &old_metadata_files {
+        store.delete_file(file_handle).await.unwrap();
+    }
+
+    let metadata_files = store.list_metadata_files().await.unwrap();
+    assert_eq!(metadata_files.len(), 1);
+    let mut buf = String::new();
+    store
+        .open_for_read(&metadata_files[0])
+        .await
+        .unwrap()
+        .read_to_string(&mut buf)
+        .await
+        .unwrap();
+    let read_back = buf
+        .lines()
+        .map(TextLine::new)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(read_back, lines);
+
+    // Delete all backup files.
+    let mut file_handles = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (name, content) in files {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            file_handles.push(handle);
+        }
+    }
+    for handle in &file_handles {
+        store.delete_file(handle).await.unwrap();
+    }
+    for handle in &file_handles {
+        assert!(async {
+            let mut buf = Vec::new();
+            store
+                .open_for_read(handle)
+                .await?
+                .read_to_end(&mut buf)
+                .await?;
+            Result::<()>::Ok(())
+        }
+        .await
+        .is_err());
+    }
+}
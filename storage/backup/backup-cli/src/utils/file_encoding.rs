@@ -0,0 +1,268 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Optional compression and encryption of the data files in a backup.
+//!
+//! Files are compressed with zstd and then sealed with AES-256-GCM, each under a random nonce
+//! prepended to the ciphertext. The key is derived from a passphrase with Argon2id and a random
+//! salt per backup. How the files of a backup are encoded is recorded in its manifest, which
+//! itself stays in plain text, so restoring and verifying detect it automatically.
+
+use aes_gcm::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    Aes256Gcm,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use argon2::Argon2;
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, io::Read, path::PathBuf};
+use structopt::StructOpt;
+
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+const SALT_LENGTH: usize = 16;
+/// zstd's default level, a good trade-off between speed and ratio for backup data.
+const ZSTD_LEVEL: i32 = 3;
+/// Decoded files are read into memory as a whole, this guards against decompression bombs.
+const MAX_DECODED_FILE_SIZE: usize = 1 << 31;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FileEncoding {
+    pub compression: Option<Compression>,
+    pub encryption: Option<Encryption>,
+}
+
+impl FileEncoding {
+    pub fn is_plain(&self) -> bool {
+        self.compression.is_none() && self.encryption.is_none()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Compression {
+    Zstd,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Encryption {
+    /// AES-256-GCM under a key derived from the passphrase with Argon2id and this salt.
+    Aes256Gcm {
+        #[serde(serialize_with = "to_base64", deserialize_with = "from_base64")]
+        salt: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Default, StructOpt)]
+pub struct EncryptionKeyOpt {
+    #[structopt(
+        long,
+        parse(from_os_str),
+        help = "File holding the passphrase that backup files are encrypted with. Trailing \
+        newlines are ignored."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[structopt(
+        long,
+        conflicts_with = "encryption-key-file",
+        help = "Name of the environment variable holding the passphrase that backup files are \
+        encrypted with."
+    )]
+    pub encryption_key_env: Option<String>,
+}
+
+impl EncryptionKeyOpt {
+    pub fn load(&self) -> Result<Option<Vec<u8>>> {
+        let passphrase = match (&self.encryption_key_file, &self.encryption_key_env) {
+            (Some(path), None) => std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed reading encryption key file {:?}: {}", path, e))?,
+            (None, Some(var)) => std::env::var(var)
+                .map_err(|e| anyhow!("Failed reading encryption key env var {}: {}", var, e))?,
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => bail!("Only one source of the encryption key is allowed."),
+        };
+        let passphrase = passphrase.trim_end_matches(&['\r', '\n'][..]);
+        ensure!(!passphrase.is_empty(), "Encryption key is empty.");
+        Ok(Some(passphrase.as_bytes().to_vec()))
+    }
+}
+
+#[derive(Clone, Default, StructOpt)]
+pub struct FileEncodingOpt {
+    #[structopt(long, help = "Compress backup files with zstd.")]
+    pub compress: bool,
+    #[structopt(flatten)]
+    pub encryption_key: EncryptionKeyOpt,
+}
+
+impl FileEncodingOpt {
+    /// Returns a codec for writing a new backup. A new salt is generated each time.
+    pub fn new_codec(&self) -> Result<FileCodec> {
+        let encoding = FileEncoding {
+            compression: self.compress.then(|| Compression::Zstd),
+            encryption: None,
+        };
+        match self.encryption_key.load()? {
+            Some(passphrase) => {
+                let mut salt = vec![0u8; SALT_LENGTH];
+                OsRng.fill_bytes(&mut salt);
+                FileCodec::new(
+                    FileEncoding {
+                        encryption: Some(Encryption::Aes256Gcm { salt }),
+                        ..encoding
+                    },
+                    Some(&passphrase),
+                )
+            }
+            None => FileCodec::new(encoding, None),
+        }
+    }
+}
+
+/// Encodes data files of a backup before they are written, and decodes them after being read.
+pub struct FileCodec {
+    encoding: FileEncoding,
+    cipher: Option<Aes256Gcm>,
+}
+
+impl fmt::Debug for FileCodec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileCodec")
+            .field("encoding", &self.encoding)
+            .finish()
+    }
+}
+
+impl FileCodec {
+    /// Fails if the encoding requires encryption but no passphrase is given.
+    pub fn new(encoding: FileEncoding, passphrase: Option<&[u8]>) -> Result<Self> {
+        let cipher = match &encoding.encryption {
+            Some(Encryption::Aes256Gcm { salt }) => {
+                let passphrase = passphrase
+                    .ok_or_else(|| anyhow!("Backup is encrypted, but no encryption key given."))?;
+                let mut key = [0u8; KEY_LENGTH];
+                Argon2::default()
+                    .hash_password_into(passphrase, salt, &mut key)
+                    .map_err(|e| anyhow!("Failed deriving encryption key: {}", e))?;
+                Some(Aes256Gcm::new(GenericArray::from_slice(&key)))
+            }
+            None => None,
+        };
+        Ok(Self { encoding, cipher })
+    }
+
+    pub fn encoding(&self) -> &FileEncoding {
+        &self.encoding
+    }
+
+    pub fn encode(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let bytes = match self.encoding.compression {
+            Some(Compression::Zstd) => zstd::bulk::compress(&bytes, ZSTD_LEVEL)?,
+            None => bytes,
+        };
+        match &self.cipher {
+            Some(cipher) => {
+                let mut nonce = [0u8; NONCE_LENGTH];
+                OsRng.fill_bytes(&mut nonce);
+                let ciphertext = cipher
+                    .encrypt(GenericArray::from_slice(&nonce), bytes.as_ref())
+                    .map_err(|_| anyhow!("Failed encrypting backup file."))?;
+                Ok(nonce.iter().copied().chain(ciphertext).collect())
+            }
+            None => Ok(bytes),
+        }
+    }
+
+    pub fn decode(&self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        let bytes = match &self.cipher {
+            Some(cipher) => {
+                ensure!(bytes.len() >= NONCE_LENGTH, "Encrypted file too short.");
+                let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+                cipher
+                    .decrypt(GenericArray::from_slice(nonce), ciphertext)
+                    .map_err(|_| {
+                        anyhow!("Failed decrypting backup file, the encryption key may be wrong.")
+                    })?
+            }
+            None => bytes,
+        };
+        match self.encoding.compression {
+            Some(Compression::Zstd) => {
+                // Stream the decompression rather than allocating the upper bound up front.
+                let mut decompressed = Vec::new();
+                zstd::stream::read::Decoder::new(bytes.as_slice())?
+                    .take(MAX_DECODED_FILE_SIZE as u64 + 1)
+                    .read_to_end(&mut decompressed)?;
+                ensure!(
+                    decompressed.len() <= MAX_DECODED_FILE_SIZE,
+                    "Decompressed backup file exceeds {} bytes.",
+                    MAX_DECODED_FILE_SIZE,
+                );
+                Ok(decompressed)
+            }
+            None => Ok(bytes),
+        }
+    }
+}
+
+fn to_base64<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::encode(bytes))
+}
+
+fn from_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let s: String = Deserialize::deserialize(deserializer)?;
+    base64::decode(s).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoding_opt(compress: bool, key_env: Option<&str>) -> FileEncodingOpt {
+        FileEncodingOpt {
+            compress,
+            encryption_key: EncryptionKeyOpt {
+                encryption_key_file: None,
+                encryption_key_env: key_env.map(str::to_string),
+            },
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        std::env::set_var("TEST_BACKUP_ENCRYPTION_KEY_1", "passphrase\n");
+        let data: Vec<u8> = (0..10_000u32)
+            .flat_map(|i| (i % 17).to_le_bytes())
+            .collect();
+
+        for (compress, key_env) in [
+            (false, None),
+            (true, None),
+            (false, Some("TEST_BACKUP_ENCRYPTION_KEY_1")),
+            (true, Some("TEST_BACKUP_ENCRYPTION_KEY_1")),
+        ] {
+            let codec = encoding_opt(compress, key_env).new_codec().unwrap();
+            let encoded = codec.encode(data.clone()).unwrap();
+            assert_eq!(encoded == data, codec.encoding().is_plain());
+
+            // What a restore sees, the encoding from the manifest and the same passphrase.
+            let encoding: FileEncoding =
+                serde_json::from_slice(&serde_json::to_vec(codec.encoding()).unwrap()).unwrap();
+            let codec = FileCodec::new(encoding, Some(&b"passphrase"[..])).unwrap();
+            assert_eq!(codec.decode(encoded).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_wrong_or_missing_key() {
+        std::env::set_var("TEST_BACKUP_ENCRYPTION_KEY_2", "passphrase");
+        let codec = encoding_opt(true, Some("TEST_BACKUP_ENCRYPTION_KEY_2"))
+            .new_codec()
+            .unwrap();
+        let encoded = codec.encode(vec![7u8; 1024]).unwrap();
+
+        assert!(FileCodec::new(codec.encoding().clone(), None).is_err());
+        let wrong_codec = FileCodec::new(codec.encoding().clone(), Some(&b"wrong"[..])).unwrap();
+        assert!(wrong_codec.decode(encoded).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod backup_service_client;
 pub(crate) mod error_notes;
+pub mod file_encoding;
 pub mod read_record_bytes;
 pub mod storage_ext;
 pub(crate) mod stream;
@@ -10,6 +11,7 @@ pub(crate) mod stream;
 #[cfg(test)]
 pub mod test_utils;
 
+use crate::utils::file_encoding::{EncryptionKeyOpt, FileEncodingOpt};
 use anyhow::{anyhow, Result};
 use aptos_config::config::{RocksdbConfig, NO_OP_STORAGE_PRUNER_CONFIG};
 use aptos_crypto::HashValue;
@@ -42,6 +44,9 @@ pub struct GlobalBackupOpt {
         help = "Maximum chunk file size in bytes."
     )]
     pub max_chunk_size: usize,
+
+    #[structopt(flatten)]
+    pub file_encoding: FileEncodingOpt,
 }
 
 #[derive(Clone, StructOpt)]
@@ -98,6 +103,9 @@ pub struct GlobalRestoreOpt {
 
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+
+    #[structopt(flatten)]
+    pub encryption_key: EncryptionKeyOpt,
 }
 
 pub enum RestoreRunMode {
@@ -160,6 +168,8 @@ pub struct GlobalRestoreOptions {
     pub trusted_waypoints: Arc<HashMap<Version, Waypoint>>,
     pub run_mode: Arc<RestoreRunMode>,
     pub concurrent_downloads: usize,
+    /// Passphrase to decrypt encrypted backups with.
+    pub encryption_key: Option<Arc<Vec<u8>>>,
 }
 
 impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
@@ -185,6 +195,7 @@ impl TryFrom<GlobalRestoreOpt> for GlobalRestoreOptions {
             trusted_waypoints: Arc::new(opt.trusted_waypoints.verify()?),
             run_mode: Arc::new(run_mode),
             concurrent_downloads,
+            encryption_key: opt.encryption_key.load()?.map(Arc::new),
         })
     }
 }
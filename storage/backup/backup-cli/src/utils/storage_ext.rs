@@ -1,19 +1,42 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::storage::{BackupHandle, BackupStorage, FileHandleRef};
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+    },
+    utils::file_encoding::FileCodec,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use rand::random;
 use serde::de::DeserializeOwned;
 use std::{convert::TryInto, sync::Arc};
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[async_trait]
 pub trait BackupStorageExt {
     async fn read_all(&self, file_handle: &FileHandleRef) -> Result<Vec<u8>>;
     async fn load_json_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
-    async fn load_bcs_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T>;
+    /// Reads a data file of a backup, and decodes it.
+    async fn read_all_decoded(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &FileCodec,
+    ) -> Result<Vec<u8>>;
+    async fn load_bcs_file<T: DeserializeOwned>(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &FileCodec,
+    ) -> Result<T>;
+    /// Encodes a data file of a backup, and writes it.
+    async fn write_encoded(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+        bytes: Vec<u8>,
+        codec: &FileCodec,
+    ) -> Result<FileHandle>;
     /// Adds a random suffix ".XXXX" to the backup name, so a retry won't pass a same backup name to
     /// the storage.
     async fn create_backup_with_random_suffix(&self, name: &str) -> Result<BackupHandle>;
@@ -28,8 +51,35 @@ impl BackupStorageExt for Arc<dyn BackupStorage> {
         Ok(bytes)
     }
 
-    async fn load_bcs_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T> {
-        Ok(bcs::from_bytes(&self.read_all(file_handle).await?)?)
+    async fn read_all_decoded(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &FileCodec,
+    ) -> Result<Vec<u8>> {
+        codec.decode(self.read_all(file_handle).await?)
+    }
+
+    async fn load_bcs_file<T: DeserializeOwned>(
+        &self,
+        file_handle: &FileHandleRef,
+        codec: &FileCodec,
+    ) -> Result<T> {
+        Ok(bcs::from_bytes(
+            &self.read_all_decoded(file_handle, codec).await?,
+        )?)
+    }
+
+    async fn write_encoded(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+        bytes: Vec<u8>,
+        codec: &FileCodec,
+    ) -> Result<FileHandle> {
+        let (file_handle, mut file) = self.create_for_write(backup_handle, name).await?;
+        file.write_all(&codec.encode(bytes)?).await?;
+        file.shutdown().await?;
+        Ok(file_handle)
     }
 
     async fn load_json_file<T: DeserializeOwned>(&self, file_handle: &FileHandleRef) -> Result<T> {
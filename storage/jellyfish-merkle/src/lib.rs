@@ -85,7 +85,10 @@ use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_types::{
     nibble::{nibble_path::NibblePath, Nibble, ROOT_NIBBLE_HEIGHT},
     proof::{SparseMerkleProof, SparseMerkleRangeProof},
-    state_store::{state_key::StateKey, state_value::StateValue},
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateSnapshotProgress, StateValue},
+    },
     transaction::Version,
 };
 use node_type::{Child, Children, InternalNode, LeafNode, Node, NodeKey, NodeType};
@@ -130,6 +133,17 @@ pub trait TreeWriter<K>: Send + Sync {
     /// Writes a node batch into storage.
     fn write_node_batch(&self, node_batch: &NodeBatch<K>) -> Result<()>;
 
+    /// Writes a node batch into storage, together with the progress of the state snapshot
+    /// restore that produced it (or clears the progress if `None`). Stores that do not support
+    /// resuming an interrupted restore can simply ignore the progress.
+    fn write_node_batch_with_progress(
+        &self,
+        node_batch: &NodeBatch<K>,
+        _progress: Option<&StateSnapshotProgress>,
+    ) -> Result<()> {
+        self.write_node_batch(node_batch)
+    }
+
     /// Inform underlying store that a latest version is complete and readable.
     fn finish_version(&self, version: Version, root_hash: HashValue);
 }
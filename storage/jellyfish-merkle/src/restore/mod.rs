@@ -25,6 +25,7 @@ use aptos_types::{
         Nibble,
     },
     proof::{SparseMerkleInternalNode, SparseMerkleLeafNode, SparseMerkleRangeProof},
+    state_store::state_value::StateSnapshotProgress,
     transaction::Version,
 };
 use mirai_annotations::*;
@@ -157,6 +158,12 @@ struct JellyfishMerkleRestore<K> {
     /// The number of keys we have received since the most recent restart.
     num_keys_received: u64,
 
+    /// The number of leaves that have been frozen so far (including those persisted before the
+    /// most recent restart), and the hashed key of the rightmost one. This is persisted with the
+    /// frozen nodes so that an interrupted restore can be resumed (see `new_from_progress`).
+    num_frozen_leaves: u64,
+    last_frozen_leaf_key: Option<HashValue>,
+
     /// When the restoration process finishes, we expect the tree to have this root hash.
     expected_root_hash: HashValue,
 }
@@ -186,6 +193,8 @@ where
                     None,
                 )
             };
+        let num_frozen_leaves = Self::count_frozen_leaves(&partial_nodes)?;
+        let last_frozen_leaf_key = previous_leaf.as_ref().map(|leaf| leaf.account_key());
 
         Ok(Self {
             store,
@@ -194,6 +203,8 @@ where
             frozen_nodes: NodeBatch::new(),
             previous_leaf,
             num_keys_received: 0,
+            num_frozen_leaves,
+            last_frozen_leaf_key,
             expected_root_hash,
         })
     }
@@ -210,10 +221,102 @@ where
             frozen_nodes: NodeBatch::new(),
             previous_leaf: None,
             num_keys_received: 0,
+            num_frozen_leaves: 0,
+            last_frozen_leaf_key: None,
             expected_root_hash,
         })
     }
 
+    /// Resumes a restore that was interrupted, using the progress persisted alongside the frozen
+    /// nodes. Unlike `new`, this does not assume that the store only contains the nodes of this
+    /// restore: the rightmost persisted leaf is located using the key in the progress.
+    pub fn new_from_progress<D: 'static + TreeReader<K> + TreeWriter<K>>(
+        store: Arc<D>,
+        progress: &StateSnapshotProgress,
+    ) -> Result<Self> {
+        let version = progress.version;
+        let (leaf_node_key, leaf_node) =
+            Self::find_leaf(store.as_ref(), version, progress.last_leaf_key_hash)?;
+        let partial_nodes = Self::recover_partial_nodes(store.as_ref(), version, leaf_node_key)?;
+
+        Ok(Self {
+            store,
+            version,
+            partial_nodes,
+            frozen_nodes: NodeBatch::new(),
+            previous_leaf: Some(leaf_node),
+            num_keys_received: 0,
+            num_frozen_leaves: progress.num_leaves,
+            last_frozen_leaf_key: Some(progress.last_leaf_key_hash),
+            expected_root_hash: progress.expected_root_hash,
+        })
+    }
+
+    /// Finds the persisted leaf with the given hashed key at the given version by walking down
+    /// the nibble path of the key until the leaf is found.
+    fn find_leaf(
+        store: &dyn TreeReader<K>,
+        version: Version,
+        leaf_key: HashValue,
+    ) -> Result<(NodeKey, LeafNode<K>)> {
+        let nibble_path = NibblePath::new_even(leaf_key.to_vec());
+        let mut nibbles = nibble_path.nibbles();
+
+        for _ in 0..ROOT_NIBBLE_HEIGHT {
+            nibbles.next().expect("This nibble must exist.");
+            let node_key = NodeKey::new(version, nibbles.visited_nibbles().collect());
+            match store.get_node_option(&node_key)? {
+                Some(Node::Internal(_)) => continue,
+                Some(Node::Leaf(leaf_node)) => {
+                    ensure!(
+                        leaf_node.account_key() == leaf_key,
+                        "Found an unexpected leaf on the path of the restored leaf. Expected: {}, \
+                         found: {}.",
+                        leaf_key,
+                        leaf_node.account_key(),
+                    );
+                    return Ok((node_key, leaf_node));
+                }
+                Some(Node::Null) => bail!("Null node should not appear in storage."),
+                None => break,
+            }
+        }
+        bail!(
+            "The restored leaf {} at version {} was not found in storage.",
+            leaf_key,
+            version
+        )
+    }
+
+    /// Counts the leaves under the recovered partial nodes, i.e., all the leaves that have been
+    /// persisted to storage. A partial child (without a hash yet) is skipped, since its persisted
+    /// leaves are counted under its own entry in `partial_nodes`. Fails if the leaf count of a
+    /// persisted child is unknown, rather than persisting a wrong count with the progress.
+    fn count_frozen_leaves(partial_nodes: &[InternalInfo<K>]) -> Result<u64> {
+        let mut num_leaves = 0;
+        for child_info in partial_nodes
+            .iter()
+            .flat_map(|internal_info| internal_info.children.iter().flatten())
+        {
+            num_leaves += match child_info {
+                ChildInfo::Internal { hash: None, .. } => 0,
+                ChildInfo::Internal {
+                    leaf_count: Some(leaf_count),
+                    ..
+                } => *leaf_count as u64,
+                ChildInfo::Internal {
+                    hash: Some(hash),
+                    leaf_count: None,
+                } => bail!(
+                    "Unknown leaf count of the persisted node {}, unable to resume the restore.",
+                    hash
+                ),
+                ChildInfo::Leaf(_) => 1,
+            };
+        }
+        Ok(num_leaves)
+    }
+
     /// Recovers partial nodes from storage. We do this by looking at all the ancestors of the
     /// rightmost leaf. The ones do not exist in storage are the partial nodes.
     fn recover_partial_nodes(
@@ -289,9 +392,9 @@ where
     }
 
     /// Restores a chunk of accounts. This function will verify that the given chunk is correct
-    /// using the proof and root hash, then write things to storage. If the chunk is invalid, an
-    /// error will be returned and nothing will be written to storage.
-    fn add_chunk_impl(
+    /// using the proof and root hash. If the chunk is invalid, an error will be returned. Nothing
+    /// is written to storage until `write_frozen_nodes` is called.
+    fn add_and_verify_chunk(
         &mut self,
         chunk: Vec<(&K, HashValue)>,
         proof: SparseMerkleRangeProof,
@@ -317,9 +420,21 @@ where
 
         // Verify what we have added so far is all correct.
         self.verify(proof)?;
+        Ok(())
+    }
 
-        // Write the frozen nodes to storage.
-        self.store.write_node_batch(&self.frozen_nodes)?;
+    /// Writes the frozen nodes to storage, together with the current restore progress.
+    fn write_frozen_nodes(&mut self) -> Result<()> {
+        let progress = self.last_frozen_leaf_key.map(|last_leaf_key_hash| {
+            StateSnapshotProgress::new(
+                self.version,
+                self.expected_root_hash,
+                last_leaf_key_hash,
+                self.num_frozen_leaves,
+            )
+        });
+        self.store
+            .write_node_batch_with_progress(&self.frozen_nodes, progress.as_ref())?;
         self.frozen_nodes.clear();
         Ok(())
     }
@@ -491,6 +606,8 @@ where
                 let child_node_key = last_node
                     .node_key
                     .gen_child_node_key(self.version, (rightmost_child_index as u8).into());
+                self.num_frozen_leaves += 1;
+                self.last_frozen_leaf_key = Some(node.account_key());
                 self.frozen_nodes
                     .insert(child_node_key, node.clone().into());
             }
@@ -672,14 +789,17 @@ where
                     let node_key = NodeKey::new_empty_path(self.version);
                     assert!(self.frozen_nodes.is_empty());
                     self.frozen_nodes.insert(node_key, node.into());
-                    self.store.write_node_batch(&self.frozen_nodes)?;
+                    self.store
+                        .write_node_batch_with_progress(&self.frozen_nodes, None)?;
                     return Ok(());
                 }
             }
         }
 
         self.freeze(0);
-        self.store.write_node_batch(&self.frozen_nodes)?;
+        // The restore is complete, so the progress is no longer needed.
+        self.store
+            .write_node_batch_with_progress(&self.frozen_nodes, None)?;
         self.store
             .finish_version(self.version, self.expected_root_hash);
         Ok(())
@@ -740,16 +860,31 @@ impl<K: crate::Key + CryptoHash + Hash + Eq, V: crate::Value> StateSnapshotResto
             kv_restore: StateValueRestore::new(store, version),
         })
     }
+
+    pub fn new_from_progress<
+        D: 'static + TreeReader<K> + TreeWriter<K> + StateValueWriter<K, V>,
+    >(
+        store: Arc<D>,
+        progress: &StateSnapshotProgress,
+    ) -> Result<Self> {
+        Ok(Self {
+            tree_restore: JellyfishMerkleRestore::new_from_progress(Arc::clone(&store), progress)?,
+            kv_restore: StateValueRestore::new(store, progress.version),
+        })
+    }
 }
 
 impl<K: crate::Key + CryptoHash + Hash + Eq, V: crate::Value> StateSnapshotReceiver<K, V>
     for StateSnapshotRestore<K, V>
 {
     fn add_chunk(&mut self, chunk: Vec<(K, V)>, proof: SparseMerkleRangeProof) -> Result<()> {
+        // Verify the chunk first, then write the values before the tree nodes. The nodes are
+        // persisted together with the restore progress, so the progress never covers values
+        // that have not been written.
         self.tree_restore
-            .add_chunk_impl(chunk.iter().map(|(k, v)| (k, v.hash())).collect(), proof)?;
+            .add_and_verify_chunk(chunk.iter().map(|(k, v)| (k, v.hash())).collect(), proof)?;
         self.kv_restore.add_chunk(chunk)?;
-        Ok(())
+        self.tree_restore.write_frozen_nodes()
     }
 
     fn finish(self) -> Result<()> {
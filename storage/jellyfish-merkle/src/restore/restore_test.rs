@@ -12,7 +12,7 @@ use crate::{
 use anyhow::Result;
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_infallible::RwLock;
-use aptos_types::transaction::Version;
+use aptos_types::{state_store::state_value::StateSnapshotProgress, transaction::Version};
 use proptest::{collection::btree_map, prelude::*};
 use std::{collections::BTreeMap, sync::Arc};
 use storage_interface::StateSnapshotReceiver;
@@ -21,6 +21,7 @@ use storage_interface::StateSnapshotReceiver;
 struct MockSnapshotStore<K: TestKey, V: TestValue> {
     tree_store: MockTreeStore<K>,
     kv_store: RwLock<BTreeMap<(K, Version), V>>,
+    progress: RwLock<Option<StateSnapshotProgress>>,
 }
 
 impl<K, V> MockSnapshotStore<K, V>
@@ -32,12 +33,17 @@ where
         Self {
             tree_store: MockTreeStore::new(overwrite),
             kv_store: RwLock::new(BTreeMap::default()),
+            progress: RwLock::new(None),
         }
     }
 
     fn get_value_at_version(&self, k: &(K, Version)) -> Option<V> {
         self.kv_store.read().get(k).cloned()
     }
+
+    fn get_progress(&self) -> Option<StateSnapshotProgress> {
+        self.progress.read().clone()
+    }
 }
 
 impl<K, V> StateValueWriter<K, V> for MockSnapshotStore<K, V>
@@ -76,6 +82,16 @@ where
         self.tree_store.write_node_batch(node_batch)
     }
 
+    fn write_node_batch_with_progress(
+        &self,
+        node_batch: &NodeBatch<K>,
+        progress: Option<&StateSnapshotProgress>,
+    ) -> Result<()> {
+        self.tree_store.write_node_batch(node_batch)?;
+        *self.progress.write() = progress.cloned();
+        Ok(())
+    }
+
     fn finish_version(&self, _version: Version, _root_hash: HashValue) {}
 }
 
@@ -98,6 +114,7 @@ where
         MockSnapshotStore {
             tree_store,
             kv_store: RwLock::new(kv_store),
+            progress: RwLock::new(None),
         },
         version,
     )
@@ -178,6 +195,68 @@ proptest! {
         assert_success(&restore_db, expected_root_hash, &all, version);
     }
 
+    #[test]
+    fn test_restore_with_interruption_from_progress(
+        (all, batch1_size) in arb_btree_map(2)
+            .prop_flat_map(|btree| {
+                let len = btree.len();
+                (Just(btree), 1..len)
+            })
+    ) {
+        let (db, version) = init_mock_store(&all.clone().into_iter().map(|(_, kv)| kv).collect());
+        let tree = JellyfishMerkleTree::new(&db);
+        let expected_root_hash = tree.get_root_hash(version).unwrap();
+        let batch1: Vec<_> = all.clone().into_iter().take(batch1_size).collect();
+
+        let restore_db = Arc::new(MockSnapshotStore::default());
+        {
+            let mut restore =
+                StateSnapshotRestore::new_overwrite(Arc::clone(&restore_db), version, expected_root_hash).unwrap();
+            let proof = tree
+                .get_range_proof(batch1.last().map(|(key, _value)| *key).unwrap(), version)
+                .unwrap();
+            restore.add_chunk(batch1.into_iter().map(|(_, kv)| kv).collect(), proof).unwrap();
+            // Do not call `finish`.
+        }
+
+        {
+            let progress = match restore_db.get_progress() {
+                None => {
+                    // Sometimes the batch is too small so nothing is written to DB.
+                    return Ok(());
+                }
+                Some(progress) => progress,
+            };
+            prop_assert_eq!(progress.version, version);
+            prop_assert_eq!(progress.expected_root_hash, expected_root_hash);
+            prop_assert_eq!(
+                progress.num_leaves as usize,
+                all.range(..=progress.last_leaf_key_hash).count()
+            );
+
+            // Resume from the next account after the last persisted leaf.
+            let remaining_accounts: Vec<_> = all
+                .clone()
+                .into_iter()
+                .skip(progress.num_leaves as usize)
+                .collect();
+
+            let mut restore =
+                StateSnapshotRestore::new_from_progress(Arc::clone(&restore_db), &progress).unwrap();
+            let proof = tree
+                .get_range_proof(
+                    remaining_accounts.last().map(|(h, _)| *h).unwrap(),
+                    version,
+                )
+                .unwrap();
+            restore.add_chunk(remaining_accounts.into_iter().map(|(_, kv)| kv).collect(), proof).unwrap();
+            restore.finish().unwrap();
+        }
+
+        assert_success(&restore_db, expected_root_hash, &all, version);
+        prop_assert_eq!(restore_db.get_progress(), None);
+    }
+
     #[test]
     fn test_overwrite(
         btree1 in arb_btree_map(1),
@@ -26,7 +26,7 @@ use aptos_types::{
     state_store::{
         state_key::StateKey,
         state_key_prefix::StateKeyPrefix,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateSnapshotProgress, StateValue, StateValueChunkWithProof},
     },
     transaction::{
         AccountTransactionsWithProof, TransactionInfo, TransactionListWithProof,
@@ -270,6 +270,13 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// See [AptosDB::get_first_ledger_info_epoch].
+    ///
+    /// [AptosDB::get_first_ledger_info_epoch]: ../aptosdb/struct.AptosDB.html#method.get_first_ledger_info_epoch
+    fn get_first_ledger_info_epoch(&self) -> Result<Option<u64>> {
+        unimplemented!()
+    }
+
     /// See [AptosDB::get_first_write_set_version].
     ///
     /// [AptosDB::get_first_write_set_version]: ../aptosdb/struct.AptosDB.html#method.get_first_write_set_version
@@ -549,6 +556,12 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Get the progress of an interrupted state snapshot restore (i.e., a restore that has
+    /// started but not yet finished), if any.
+    fn get_state_snapshot_progress(&self) -> Result<Option<StateSnapshotProgress>> {
+        unimplemented!()
+    }
+
     /// Get the state prune window config value.
     fn get_state_prune_window(&self) -> Result<Option<usize>> {
         unimplemented!()
@@ -558,6 +571,18 @@ pub trait DbReader: Send + Sync {
     fn get_ledger_prune_window(&self) -> Result<Option<usize>> {
         unimplemented!()
     }
+
+    /// Get the min version of the state store that has not been pruned yet, or None if pruning
+    /// is disabled.
+    fn get_state_min_readable_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
+    /// Get the min version of the ledger (transactions, events, etc.) that has not been pruned
+    /// yet, or None if pruning is disabled.
+    fn get_ledger_min_readable_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
 }
 
 impl MoveStorage for &dyn DbReader {
@@ -699,6 +724,16 @@ pub trait DbWriter: Send + Sync {
     fn delete_genesis(&self) -> Result<()> {
         unimplemented!()
     }
+
+    /// Changes the state prune window at runtime. Fails if pruning is disabled.
+    fn set_state_prune_window(&self, window: usize) -> Result<()> {
+        unimplemented!()
+    }
+
+    /// Changes the ledger prune window at runtime. Fails if pruning is disabled.
+    fn set_ledger_prune_window(&self, window: usize) -> Result<()> {
+        unimplemented!()
+    }
 }
 
 #[derive(Clone)]
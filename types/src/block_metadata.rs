@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_config::aptos_root_address,
     event::{EventHandle, EventKey},
@@ -90,6 +91,40 @@ impl BlockMetadata {
     }
 }
 
+/// A `BlockMetadata` that also records the proposers of the rounds that failed between the
+/// parent block and this block.
+///
+/// It is only produced for blocks whose proposal records failed authors, which the on-chain
+/// consensus config enables (see `OnChainConsensusConfig::max_failed_authors_to_store`), and is
+/// executed by `Block::block_prologue_v2`. All other blocks keep using `BlockMetadata`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockMetadataV2 {
+    metadata: BlockMetadata,
+    /// Indices (in the validator set) of the proposers of the failed rounds, in round order.
+    failed_proposer_indices: Vec<u32>,
+}
+
+impl BlockMetadataV2 {
+    pub fn new(metadata: BlockMetadata, failed_proposer_indices: Vec<u32>) -> Self {
+        Self {
+            metadata,
+            failed_proposer_indices,
+        }
+    }
+
+    pub fn metadata(&self) -> &BlockMetadata {
+        &self.metadata
+    }
+
+    pub fn failed_proposer_indices(&self) -> &Vec<u32> {
+        &self.failed_proposer_indices
+    }
+
+    pub fn into_inner(self) -> (BlockMetadata, Vec<u32>) {
+        (self.metadata, self.failed_proposer_indices)
+    }
+}
+
 pub fn new_block_event_key() -> EventKey {
     EventKey::new_from_address(&aptos_root_address(), 6)
 }
@@ -125,6 +160,32 @@ impl MoveStructType for BlockResource {
 
 impl MoveResource for BlockResource {}
 
+/// Holds the handle of the `NewBlockEventV2`s. It is published at genesis, or later by
+/// `Block::initialize_new_block_events_v2` on older chains, so the key of its handle has to be
+/// read from the resource.
+#[derive(Deserialize, Serialize)]
+pub struct NewBlockEventsV2Resource {
+    new_block_events: EventHandle,
+}
+
+impl NewBlockEventsV2Resource {
+    pub fn new_block_events(&self) -> &EventHandle {
+        &self.new_block_events
+    }
+
+    /// The access path of the resource under the Association account.
+    pub fn access_path() -> AccessPath {
+        AccessPath::new(aptos_root_address(), Self::resource_path())
+    }
+}
+
+impl MoveStructType for NewBlockEventsV2Resource {
+    const MODULE_NAME: &'static IdentStr = ident_str!("Block");
+    const STRUCT_NAME: &'static IdentStr = ident_str!("NewBlockEventsV2");
+}
+
+impl MoveResource for NewBlockEventsV2Resource {}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct NewBlockEvent {
     epoch: u64,
@@ -167,3 +228,57 @@ impl NewBlockEvent {
         self.proposer
     }
 }
+
+/// A `NewBlockEvent` that also records the proposers of the rounds that failed since the
+/// previous block. Emitted by `Block::block_prologue_v2`, alongside the `NewBlockEvent`.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct NewBlockEventV2 {
+    event: NewBlockEvent,
+    failed_proposer_indices: Vec<u64>,
+}
+
+impl NewBlockEventV2 {
+    pub fn new(
+        epoch: u64,
+        round: u64,
+        previous_block_votes: Vec<bool>,
+        proposer: AccountAddress,
+        failed_proposer_indices: Vec<u64>,
+        timestamp: u64,
+    ) -> Self {
+        Self {
+            event: NewBlockEvent::new(epoch, round, previous_block_votes, proposer, timestamp),
+            failed_proposer_indices,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.event.epoch()
+    }
+
+    pub fn round(&self) -> u64 {
+        self.event.round()
+    }
+
+    pub fn previous_block_votes(&self) -> &Vec<bool> {
+        self.event.previous_block_votes()
+    }
+
+    pub fn proposer(&self) -> AccountAddress {
+        self.event.proposer()
+    }
+
+    pub fn failed_proposer_indices(&self) -> &Vec<u64> {
+        &self.failed_proposer_indices
+    }
+}
+
+/// Events emitted before failed proposers were recorded don't have any
+impl From<NewBlockEvent> for NewBlockEventV2 {
+    fn from(event: NewBlockEvent) -> Self {
+        Self {
+            event,
+            failed_proposer_indices: vec![],
+        }
+    }
+}
@@ -9,6 +9,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum OnChainConsensusConfig {
     V1(ConsensusConfigV1),
+    V2(ConsensusConfigV2),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -17,6 +18,7 @@ impl OnChainConsensusConfig {
     pub fn leader_reputation_exclude_round(&self) -> u64 {
         match &self {
             OnChainConsensusConfig::V1(config) => config.exclude_round,
+            OnChainConsensusConfig::V2(config) => config.exclude_round,
         }
     }
 
@@ -24,6 +26,7 @@ impl OnChainConsensusConfig {
     pub fn decoupled_execution(&self) -> bool {
         match &self {
             OnChainConsensusConfig::V1(config) => config.decoupled_execution,
+            OnChainConsensusConfig::V2(config) => config.decoupled_execution,
         }
     }
 
@@ -36,6 +39,16 @@ impl OnChainConsensusConfig {
         }
         match &self {
             OnChainConsensusConfig::V1(config) => config.back_pressure_limit,
+            OnChainConsensusConfig::V2(config) => config.back_pressure_limit,
+        }
+    }
+
+    /// The max number of failed proposers recorded in a proposal (the most recent ones are kept),
+    /// or None if proposals don't record failed proposers.
+    pub fn max_failed_authors_to_store(&self) -> Option<usize> {
+        match &self {
+            OnChainConsensusConfig::V1(_) => None,
+            OnChainConsensusConfig::V2(config) => Some(config.max_failed_authors_to_store),
         }
     }
 }
@@ -64,6 +77,32 @@ impl Default for ConsensusConfigV1 {
     }
 }
 
+/// Extends `ConsensusConfigV1` with recording failed proposers in proposals and block metadata,
+/// which all validators need to support before it is enabled.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConsensusConfigV2 {
+    pub decoupled_execution: bool,
+    pub back_pressure_limit: u64,
+    pub exclude_round: u64,
+    pub max_failed_authors_to_store: usize,
+}
+
+impl Default for ConsensusConfigV2 {
+    fn default() -> Self {
+        let ConsensusConfigV1 {
+            decoupled_execution,
+            back_pressure_limit,
+            exclude_round,
+        } = ConsensusConfigV1::default();
+        Self {
+            decoupled_execution,
+            back_pressure_limit,
+            exclude_round,
+            max_failed_authors_to_store: 10,
+        }
+    }
+}
+
 impl OnChainConfig for OnChainConsensusConfig {
     const IDENTIFIER: &'static str = "ConsensusConfig";
 
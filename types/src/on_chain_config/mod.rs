@@ -28,7 +28,7 @@ pub use self::{
     aptos_version::{
         Version, APTOS_MAX_KNOWN_VERSION, APTOS_VERSION_2, APTOS_VERSION_3, APTOS_VERSION_4,
     },
-    consensus_config::{ConsensusConfigV1, OnChainConsensusConfig},
+    consensus_config::{ConsensusConfigV1, ConsensusConfigV2, OnChainConsensusConfig},
     registered_currencies::RegisteredCurrencies,
     validator_set::ValidatorSet,
     vm_config::VMConfig,
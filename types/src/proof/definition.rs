@@ -470,6 +470,27 @@ where
             return Ok(());
         }
 
+        let root_hash = self.compute_root_hash(
+            first_leaf_index.expect("first_leaf_index should not be None."),
+            leaf_hashes,
+        )?;
+        ensure!(
+            root_hash == expected_root_hash,
+            "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
+            root_hash,
+            expected_root_hash,
+        );
+
+        Ok(())
+    }
+
+    /// Computes the root hash of the accumulator that the proof and the (non-empty) list of leaves
+    /// starting at `first_leaf_index` imply, i.e., the only root hash `verify()` succeeds with.
+    pub fn compute_root_hash(
+        &self,
+        first_leaf_index: u64,
+        leaf_hashes: &[HashValue],
+    ) -> Result<HashValue> {
         ensure!(
             self.left_siblings.len() <= MAX_ACCUMULATOR_PROOF_DEPTH,
             "Proof has more than {} ({}) left siblings.",
@@ -490,9 +511,7 @@ where
         let mut left_sibling_iter = self.left_siblings.iter().peekable();
         let mut right_sibling_iter = self.right_siblings.iter().peekable();
 
-        let mut first_pos = Position::from_leaf_index(
-            first_leaf_index.expect("first_leaf_index should not be None."),
-        );
+        let mut first_pos = Position::from_leaf_index(first_leaf_index);
         let mut current_hashes = leaf_hashes.to_vec();
         let mut parent_hashes = vec![];
 
@@ -539,14 +558,7 @@ where
             std::mem::swap(&mut current_hashes, &mut parent_hashes);
         }
 
-        ensure!(
-            current_hashes[0] == expected_root_hash,
-            "Root hashes do not match. Actual root hash: {:x}. Expected root hash: {:x}.",
-            current_hashes[0],
-            expected_root_hash,
-        );
-
-        Ok(())
+        Ok(current_hashes[0])
     }
 }
 
@@ -7,7 +7,7 @@ use crate::{
     account_config::{AccountResource, CoinStoreResource},
     account_state::AccountState,
     block_info::{BlockInfo, Round},
-    block_metadata::BlockMetadata,
+    block_metadata::{BlockMetadata, BlockMetadataV2},
     chain_id::ChainId,
     contract_event::ContractEvent,
     epoch_state::EpochState,
@@ -922,6 +922,22 @@ impl Arbitrary for BlockMetadata {
     type Strategy = BoxedStrategy<Self>;
 }
 
+impl Arbitrary for BlockMetadataV2 {
+    type Parameters = SizeRange;
+    fn arbitrary_with(num_validators_range: Self::Parameters) -> Self::Strategy {
+        (
+            any_with::<BlockMetadata>(num_validators_range.clone()),
+            prop::collection::vec(any::<u32>(), num_validators_range),
+        )
+            .prop_map(|(metadata, failed_proposer_indices)| {
+                BlockMetadataV2::new(metadata, failed_proposer_indices)
+            })
+            .boxed()
+    }
+
+    type Strategy = BoxedStrategy<Self>;
+}
+
 #[derive(Debug)]
 struct ValidatorSetGen {
     validators: Vec<Index>,
@@ -1,7 +1,9 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{proof::SparseMerkleRangeProof, state_store::state_key::StateKey};
+use crate::{
+    proof::SparseMerkleRangeProof, state_store::state_key::StateKey, transaction::Version,
+};
 use aptos_crypto::{
     hash::{CryptoHash, CryptoHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
     HashValue,
@@ -88,6 +90,34 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// The progress of a state snapshot restore that has not yet completed. This
+/// is persisted alongside the restored tree nodes so that an interrupted
+/// restore (e.g., due to a crash or node upgrade) can resume where it left off.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct StateSnapshotProgress {
+    pub version: Version,              // The version of the snapshot being restored
+    pub expected_root_hash: HashValue, // The expected root hash of the snapshot
+    pub last_leaf_key_hash: HashValue, // The hashed key of the last persisted leaf
+    pub num_leaves: u64,               // The number of leaves persisted so far
+}
+
+impl StateSnapshotProgress {
+    pub fn new(
+        version: Version,
+        expected_root_hash: HashValue,
+        last_leaf_key_hash: HashValue,
+        num_leaves: u64,
+    ) -> Self {
+        Self {
+            version,
+            expected_root_hash,
+            last_leaf_key_hash,
+            num_leaves,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::state_store::state_value::StateValue;
@@ -3,7 +3,7 @@
 
 use crate::{
     account_address::AccountAddress,
-    block_metadata::BlockMetadata,
+    block_metadata::{BlockMetadata, BlockMetadataV2},
     chain_id::ChainId,
     contract_event::ContractEvent,
     ledger_info::LedgerInfo,
@@ -18,7 +18,7 @@ use crate::{
 use anyhow::{ensure, format_err, Error, Result};
 use aptos_crypto::{
     ed25519::*,
-    hash::{CryptoHash, EventAccumulatorHasher},
+    hash::{CryptoHash, CryptoHasher, EventAccumulatorHasher},
     multi_ed25519::{MultiEd25519PublicKey, MultiEd25519Signature},
     traits::{signing_message, SigningKey},
     HashValue,
@@ -649,8 +649,18 @@ impl SignedTransaction {
     }
 
     /// Returns the hash when the transaction is commited onchain.
-    pub fn committed_hash(self) -> HashValue {
-        Transaction::UserTransaction(self).hash()
+    pub fn committed_hash(&self) -> HashValue {
+        // Serializes exactly like `Transaction::UserTransaction`, without moving the transaction
+        #[derive(Serialize)]
+        #[serde(rename = "Transaction")]
+        enum TransactionRef<'a> {
+            UserTransaction(&'a SignedTransaction),
+        }
+
+        let mut state = TransactionHasher::default();
+        bcs::serialize_into(&mut state, &TransactionRef::UserTransaction(self))
+            .expect("Serialization of a transaction should not fail");
+        state.finish()
     }
 }
 
@@ -1496,6 +1506,10 @@ pub enum Transaction {
     /// Transaction to let the executor update the global state tree and record the root hash
     /// in the TransactionInfo
     StateCheckpoint,
+
+    /// Transaction to update the block metadata resource at the beginning of a block whose
+    /// proposal records failed proposers.
+    BlockMetadataV2(BlockMetadataV2),
 }
 
 impl Transaction {
@@ -1517,6 +1531,8 @@ impl Transaction {
             Transaction::BlockMetadata(_block_metadata) => String::from("block_metadata"),
             // TODO: display proper information for client
             Transaction::StateCheckpoint => String::from("state_checkpoint"),
+            // TODO: display proper information for client
+            Transaction::BlockMetadataV2(_block_metadata) => String::from("block_metadata"),
         }
     }
 }
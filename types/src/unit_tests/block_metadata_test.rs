@@ -1,7 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::block_metadata::BlockMetadata;
+use crate::{
+    account_address::AccountAddress,
+    block_metadata::{BlockMetadata, BlockMetadataV2, NewBlockEvent, NewBlockEventV2},
+};
 use bcs::test_helpers::assert_canonical_encode_decode;
 use proptest::prelude::*;
 
@@ -12,4 +15,26 @@ proptest! {
     fn test_block_metadata_canonical_serialization(data in any::<BlockMetadata>()) {
         assert_canonical_encode_decode(data);
     }
+
+    #[test]
+    fn test_block_metadata_v2_canonical_serialization(data in any::<BlockMetadataV2>()) {
+        assert_canonical_encode_decode(data);
+    }
+}
+
+#[test]
+fn test_new_block_event_v2_layout() {
+    // Matches `Block::NewBlockEventV2`: the `NewBlockEvent` fields followed by the failed proposers
+    let proposer = AccountAddress::random();
+    let event = NewBlockEvent::new(1, 5, vec![true, false], proposer, 100);
+    let event_v2 = NewBlockEventV2::new(1, 5, vec![true, false], proposer, vec![0, 1], 100);
+    let mut expected_bytes = bcs::to_bytes(&event).unwrap();
+    expected_bytes.extend(bcs::to_bytes(&vec![0u64, 1]).unwrap());
+    assert_eq!(bcs::to_bytes(&event_v2).unwrap(), expected_bytes);
+
+    // Events that predate failed proposers convert to events without any
+    let event_v2 = NewBlockEventV2::from(event);
+    assert_eq!(event_v2.round(), 5);
+    assert_eq!(event_v2.proposer(), proposer);
+    assert!(event_v2.failed_proposer_indices().is_empty());
 }
@@ -11,6 +11,7 @@ use crate::{
 };
 use aptos_crypto::{
     ed25519::{self, Ed25519PrivateKey, Ed25519Signature},
+    hash::CryptoHash,
     PrivateKey, Uniform,
 };
 use bcs::test_helpers::assert_canonical_encode_decode;
@@ -44,6 +45,14 @@ proptest! {
         assert!(signed_txn.check_signature().is_ok());
     }
 
+    #[test]
+    fn test_committed_hash(signed_txn in any::<SignedTransaction>()) {
+        prop_assert_eq!(
+            signed_txn.committed_hash(),
+            Transaction::UserTransaction(signed_txn).hash()
+        );
+    }
+
     #[test]
     fn transaction_payload_bcs_roundtrip(txn_payload in any::<TransactionPayload>()) {
         assert_canonical_encode_decode(txn_payload);